@@ -10,7 +10,7 @@ use std::ffi::OsStr;
 use std::fs::create_dir_all;
 use std::path::{Path, PathBuf};
 use wiz_utils::topological_sort::topological_sort;
-use wizc_cli::{BuildType, Config, ConfigBuilder, MessageFormat};
+use wizc_cli::{BuildType, Config, ConfigBuilder, MessageFormat, Profile};
 use wizc_message::{Message, MessageKind, MessageParser};
 
 pub(crate) struct BuildCommand;
@@ -29,6 +29,7 @@ pub(crate) struct Options<'ops> {
     std: Option<&'ops str>,
     target_dir: Option<&'ops str>,
     target_triple: Option<&'ops str>,
+    profile: Option<Profile>,
     test: bool,
 }
 
@@ -38,6 +39,7 @@ impl<'ops> Options<'ops> {
         std: Option<&'ops str>,
         target_dir: Option<&'ops str>,
         target_triple: Option<&'ops str>,
+        profile: Option<Profile>,
         test: bool,
     ) -> Self {
         Self {
@@ -45,6 +47,7 @@ impl<'ops> Options<'ops> {
             std,
             target_dir,
             target_triple,
+            profile,
             test,
         }
     }
@@ -57,11 +60,18 @@ impl<'ops> From<&'ops ArgMatches> for Options<'ops> {
             args.get_one::<String>("std").map(|i| i.as_str()),
             args.get_one::<String>("target-dir").map(|i| i.as_str()),
             args.get_one::<String>("target-triple").map(|i| i.as_str()),
+            profile(args),
             args.get_flag("tests"),
         )
     }
 }
 
+/// `--profile` given to `args`, which is passed through to wizc.
+pub(crate) fn profile(args: &ArgMatches) -> Option<Profile> {
+    args.get_one::<String>("profile")
+        .map(|p| Profile::from(p.as_str()))
+}
+
 pub(crate) fn command(_: &str, options: Options) -> Result<PathBuf> {
     let ws = load_project(options.manifest_path)?;
 
@@ -85,7 +95,8 @@ pub(crate) fn command(_: &str, options: Options) -> Result<PathBuf> {
     };
     create_dir_all(&target_dir)?;
 
-    let wlib_paths = compile_dependencies(&ws, resolved_dependencies, &target_dir)?;
+    let wlib_paths =
+        compile_dependencies(&ws, resolved_dependencies, &target_dir, options.profile)?;
 
     let input_path = {
         let src_dir = ws.cws.join("src");
@@ -114,6 +125,12 @@ pub(crate) fn command(_: &str, options: Options) -> Result<PathBuf> {
         config
     };
 
+    config = if let Some(profile) = options.profile {
+        config.profile(profile)
+    } else {
+        config
+    };
+
     let output = super::subcommand::output("wizc", config.as_args())?;
     let exit_code = output.status.code();
     let error = if let Some(0) = exit_code {
@@ -180,6 +197,7 @@ fn compile_dependencies(
     ws: &Workspace,
     dependencies: ResolvedDependencyTree,
     target_dir: &Path,
+    profile: Option<Profile>,
 ) -> Result<BTreeSet<PathBuf>> {
     let message_parser = MessageParser::new();
     let mut wlib_paths = BTreeSet::new();
@@ -197,17 +215,19 @@ fn compile_dependencies(
             })
             .collect::<Vec<_>>();
         let input = dep.src_path.to_string_lossy().to_string();
-        let output = super::subcommand::output(
-            "wizc",
-            Config::default()
-                .input(&input)
-                .out_dir(target_dir)
-                .name(&dep.name)
-                .type_(BuildType::Library)
-                .libraries(&dep_wlib_paths)
-                .message_format(MessageFormat::Json)
-                .as_args(),
-        )?;
+        let config = Config::default()
+            .input(&input)
+            .out_dir(target_dir)
+            .name(&dep.name)
+            .type_(BuildType::Library)
+            .libraries(&dep_wlib_paths)
+            .message_format(MessageFormat::Json);
+        let config = if let Some(profile) = profile {
+            config.profile(profile)
+        } else {
+            config
+        };
+        let output = super::subcommand::output("wizc", config.as_args())?;
         for line in String::from_utf8_lossy(&output.stdout).split_terminator('\n') {
             match message_parser.parse(line) {
                 Ok(message) => println!("{}", message),
@@ -15,8 +15,10 @@ use crate::run::RunCommand;
 use crate::subcommand::CleanCommand;
 use crate::test::TestCommand;
 use ansi_term::Color;
+use clap::builder::PossibleValuesParser;
 use clap::{crate_version, value_parser, Arg, ArgAction, Command};
 use std::process::exit;
+use wizc_cli::Profile;
 
 fn arg_target_triple() -> Arg {
     Arg::new("target-triple")
@@ -39,6 +41,14 @@ fn arg_std() -> Arg {
         .help("Use another std library")
 }
 
+fn arg_profile() -> Arg {
+    Arg::new("profile")
+        .long("profile")
+        .num_args(1)
+        .value_parser(PossibleValuesParser::new(Profile::all_str()))
+        .help("`debug` emits runtime bounds and overflow checks")
+}
+
 fn cli() -> Result<()> {
     let app = Command::new("wiz")
         .version(crate_version!())
@@ -68,6 +78,7 @@ fn cli() -> Result<()> {
                 .arg(arg_target_triple())
                 .arg(arg_manifest_path())
                 .arg(arg_std())
+                .arg(arg_profile())
                 .arg(Arg::new("tests").action(ArgAction::SetTrue).long("tests")),
         )
         .subcommand(
@@ -88,14 +99,16 @@ fn cli() -> Result<()> {
                 .arg(Arg::new("target-dir").help("Directory for all generated artifacts"))
                 .arg(arg_target_triple())
                 .arg(arg_manifest_path())
-                .arg(arg_std()),
+                .arg(arg_std())
+                .arg(arg_profile()),
         )
         .subcommand(
             Command::new(TestCommand::NAME)
                 .about("Run the tests")
                 .arg(Arg::new("target-dir").help("Directory for all generated artifacts"))
                 .arg(arg_manifest_path())
-                .arg(arg_std()),
+                .arg(arg_std())
+                .arg(arg_profile()),
         )
         .subcommand(CleanCommand::command())
         .arg(
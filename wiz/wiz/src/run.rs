@@ -15,6 +15,7 @@ impl Cmd for RunCommand {
             args.get_one::<String>("std").map(|i| i.as_str()),
             args.get_one::<String>("target-dir").map(|i| i.as_str()),
             None,
+            build::profile(args),
             false,
         );
         let output = build::command("", build_options)?;
@@ -1,23 +1,27 @@
 use crate::declaration::{DeclarationItem, DeclarationItemKind};
 use crate::declaration_id::{DeclarationId, DeclarationIdGenerator};
 pub use function::ArenaFunction;
-pub use r#struct::{ArenaStruct, StructKind};
-use std::collections::HashMap;
+pub use r#struct::{ArenaStruct, ArenaSubscript, StructKind};
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter, Write};
+pub use type_alias::ArenaTypeAlias;
 use wiz_constants::annotation::BUILTIN;
 use wiz_data_structure::annotation::Annotations;
 use wiz_hir::typed_decl::TypedFunBody;
-use wiz_hir::typed_expr::TypedBinaryOperator;
+use wiz_hir::typed_expr::{TypedBinaryOperator, TypedExpr};
 use wiz_hir::typed_type::{TypedType, TypedTypeParam, TypedValueType};
 
 mod function;
 mod r#struct;
+mod type_alias;
 
 #[derive(Debug, Clone)]
 pub struct Arena {
     declaration_id_generator: DeclarationIdGenerator,
     declarations: HashMap<DeclarationId, DeclarationItem>,
     binary_operators: HashMap<(TypedBinaryOperator, TypedType, TypedType), TypedType>,
+    /// Values of variables declared with `const`.
+    constants: HashMap<DeclarationId, TypedExpr>,
 }
 
 impl Default for Arena {
@@ -37,6 +41,7 @@ impl Default for Arena {
             declaration_id_generator: DeclarationIdGenerator::new(0),
             declarations,
             binary_operators: Default::default(),
+            constants: Default::default(),
         };
 
         for t in TypedType::builtin_types() {
@@ -50,9 +55,11 @@ impl Default for Arena {
                         );
                     }
                     TypedValueType::Array(_, _) => {}
+                    TypedValueType::Slice(_) => {}
                     TypedValueType::Tuple(_) => {}
                     TypedValueType::Pointer(_) => {}
                     TypedValueType::Reference(_) => {}
+                    TypedValueType::Function(_) => {}
                 },
                 _ => {}
             };
@@ -69,7 +76,7 @@ impl Arena {
         declaration: DeclarationItem,
     ) -> Option<DeclarationId> {
         let d = self.declarations.get_mut(namespace)?;
-        if !declaration.is_value() && d.get_child(name).is_some() {
+        if !declaration.is_value() && d.children().contains_key(name) {
             return None;
         }
         let id = self.declaration_id_generator.generate();
@@ -138,6 +145,16 @@ impl Arena {
         self.resolve_declaration_id(DeclarationId::ROOT, fqn)
     }
 
+    /// All declarations named by `fqn`, which are several for overloaded functions.
+    pub fn resolve_declaration_ids_from_root<T: ToString>(
+        &self,
+        fqn: &[T],
+    ) -> Option<&HashSet<DeclarationId>> {
+        let (name, namespace) = fqn.split_last()?;
+        let namespace_id = self.resolve_declaration_id_from_root(namespace)?;
+        self.get_by_id(&namespace_id)?.get_child(&name.to_string())
+    }
+
     pub fn get<T: ToString>(&self, namespace: &[T], name: &str) -> Option<&DeclarationItem> {
         let id = self.resolve_declaration_id_from_root(
             &namespace
@@ -184,12 +201,60 @@ impl Arena {
         self.get_mut_by_id(&id)
     }
 
+    /// Find the function which has type `ty` from functions named `name` in `namespace`.
+    pub fn get_function<T: ToString>(
+        &self,
+        namespace: &[T],
+        name: &str,
+        ty: &TypedType,
+    ) -> Option<&ArenaFunction> {
+        let parent_id = self.resolve_declaration_id_from_root(namespace)?;
+        self.get_by_id(&parent_id)?
+            .get_child(name)?
+            .iter()
+            .find_map(|id| match &self.get_by_id(id)?.kind {
+                DeclarationItemKind::Function(rf) if rf.ty == *ty => Some(rf),
+                _ => None,
+            })
+    }
+
+    pub fn get_function_mut<T: ToString>(
+        &mut self,
+        namespace: &[T],
+        name: &str,
+        ty: &TypedType,
+    ) -> Option<&mut ArenaFunction> {
+        let parent_id = self.resolve_declaration_id_from_root(namespace)?;
+        let id = *self
+            .get_by_id(&parent_id)?
+            .get_child(name)?
+            .iter()
+            .find(|id| {
+                matches!(
+                    self.get_by_id(id).map(|i| &i.kind),
+                    Some(DeclarationItemKind::Function(rf)) if rf.ty == *ty
+                )
+            })?;
+        match &mut self.get_mut_by_id(&id)?.kind {
+            DeclarationItemKind::Function(rf) => Some(rf),
+            _ => None,
+        }
+    }
+
     pub fn get_type_by_id(&self, id: &DeclarationId) -> Option<&ArenaStruct> {
         match &self.get_by_id(id)?.kind {
             DeclarationItemKind::Type(rs) => Some(rs),
             DeclarationItemKind::Namespace
             | DeclarationItemKind::Variable(_)
-            | DeclarationItemKind::Function(..) => None,
+            | DeclarationItemKind::Function(..)
+            | DeclarationItemKind::TypeAlias(_) => None,
+        }
+    }
+
+    pub fn get_type_alias_by_id(&self, id: &DeclarationId) -> Option<&ArenaTypeAlias> {
+        match &self.get_by_id(id)?.kind {
+            DeclarationItemKind::TypeAlias(ta) => Some(ta),
+            _ => None,
         }
     }
 
@@ -202,6 +267,24 @@ impl Arena {
         self.register_type(namespace, name, annotation, StructKind::Struct)
     }
 
+    pub fn register_union(
+        &mut self,
+        namespace: &DeclarationId,
+        name: &str, /* type_parameters */
+        annotation: Annotations,
+    ) -> Option<DeclarationId> {
+        self.register_type(namespace, name, annotation, StructKind::Union)
+    }
+
+    pub fn register_class(
+        &mut self,
+        namespace: &DeclarationId,
+        name: &str, /* type_parameters */
+        annotation: Annotations,
+    ) -> Option<DeclarationId> {
+        self.register_type(namespace, name, annotation, StructKind::Class)
+    }
+
     pub fn register_type_parameter(
         &mut self,
         namespace: &DeclarationId,
@@ -211,6 +294,23 @@ impl Arena {
         self.register_type(namespace, name, annotation, StructKind::TypeParameter)
     }
 
+    /// Register associated type `name` of type `owner` as a placeholder type declared in `owner`.
+    pub fn register_associated_type(
+        &mut self,
+        owner: &DeclarationId,
+        name: &str,
+    ) -> Option<DeclarationId> {
+        let id = self.register_type_parameter(owner, name, Default::default())?;
+        let placeholder = self.get_type_by_id(&id)?.self_type();
+        match &mut self.get_mut_by_id(owner)?.kind {
+            DeclarationItemKind::Type(rs) => {
+                rs.associated_types.insert(name.to_string(), placeholder);
+                Some(id)
+            }
+            _ => None,
+        }
+    }
+
     pub fn register_protocol(
         &mut self,
         namespace: &DeclarationId,
@@ -247,6 +347,7 @@ impl Arena {
             DeclarationItemKind::Type(t) => Some(t),
             DeclarationItemKind::Variable(v) => panic!("V:{:?}", v),
             DeclarationItemKind::Function(v) => panic!("F:{:?}", v),
+            DeclarationItemKind::TypeAlias(v) => panic!("T:{:?}", v),
         }
     }
 
@@ -260,6 +361,7 @@ impl Arena {
             DeclarationItemKind::Type(t) => Some(t),
             DeclarationItemKind::Variable(v) => panic!("V:{:?}", v),
             DeclarationItemKind::Function(v) => panic!("F:{:?}", v),
+            DeclarationItemKind::TypeAlias(v) => panic!("T:{:?}", v),
         }
     }
 
@@ -284,6 +386,50 @@ impl Arena {
         )
     }
 
+    /// Register type alias `name`, whose type is resolved later by [Self::resolve_type_alias].
+    pub fn register_type_alias(
+        &mut self,
+        namespace: &DeclarationId,
+        name: &str,
+        type_parameters: Option<Vec<TypedTypeParam>>,
+        ty: TypedType,
+        annotation: Annotations,
+    ) -> Option<DeclarationId> {
+        self.register(
+            namespace,
+            name,
+            DeclarationItem::new(
+                annotation,
+                name,
+                DeclarationItemKind::TypeAlias(ArenaTypeAlias::new(type_parameters, ty)),
+                Some(*namespace),
+            ),
+        )
+    }
+
+    /// Replace type of type alias `id` by the type whose names are resolved.
+    pub fn resolve_type_alias(&mut self, id: &DeclarationId, ty: TypedType) -> Option<()> {
+        match &mut self.get_mut_by_id(id)?.kind {
+            DeclarationItemKind::TypeAlias(ta) => {
+                ta.type_ = ty;
+                ta.is_resolved = true;
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    /// Makes `ids` declared in other namespaces visible as `name` in `namespace`.
+    pub fn register_re_export(
+        &mut self,
+        namespace: &DeclarationId,
+        name: &str,
+        ids: HashSet<DeclarationId>,
+    ) -> Option<()> {
+        self.get_mut_by_id(namespace)?.add_re_export(name, ids);
+        Some(())
+    }
+
     pub fn register_value(
         &mut self,
         namespace: &DeclarationId,
@@ -303,6 +449,36 @@ impl Arena {
         )
     }
 
+    pub fn register_constant(
+        &mut self,
+        namespace: &DeclarationId,
+        name: &str,
+        ty: TypedType,
+        value: TypedExpr,
+        annotation: Annotations,
+    ) -> Option<DeclarationId> {
+        let id = self.register_value(namespace, name, ty, annotation)?;
+        self.constants.insert(id, value);
+        Some(id)
+    }
+
+    pub fn get_constant(&self, id: &DeclarationId) -> Option<&TypedExpr> {
+        self.constants.get(id)
+    }
+
+    /// Replace type and value of constant `id`, e.g. by its evaluated value.
+    pub fn update_constant(
+        &mut self,
+        id: &DeclarationId,
+        ty: TypedType,
+        value: TypedExpr,
+    ) -> Option<()> {
+        let constant = self.constants.get_mut(id)?;
+        *constant = value;
+        self.get_mut_by_id(id)?.kind = DeclarationItemKind::Variable(ty);
+        Some(())
+    }
+
     pub fn resolve_binary_operator(
         &self,
         key: &(TypedBinaryOperator, TypedType, TypedType),
@@ -377,6 +553,7 @@ mod tests {
     use crate::declaration::DeclarationItem;
     use crate::declaration::DeclarationItemKind;
     use crate::declaration_id::DeclarationId;
+    use std::collections::HashSet;
     use wiz_hir::typed_type::{TypedArgType, TypedFunctionType, TypedType};
 
     #[test]
@@ -577,4 +754,37 @@ mod tests {
             ["std", "collections"]
         )
     }
+
+    #[test]
+    fn register_re_export() {
+        let mut arena = Arena::default();
+        let std_id = arena
+            .register_namespace(&DeclarationId::ROOT, "std", Default::default())
+            .unwrap();
+        let string_id = arena
+            .register_namespace(&std_id, "string", Default::default())
+            .unwrap();
+        let prelude_id = arena
+            .register_namespace(&std_id, "prelude", Default::default())
+            .unwrap();
+        let struct_id = arena
+            .register_struct(&string_id, "String", Default::default())
+            .unwrap();
+
+        arena.register_re_export(&prelude_id, "String", HashSet::from([struct_id]));
+
+        assert_eq!(
+            arena.resolve_declaration_id_from_root(&["std", "prelude", "String"]),
+            Some(struct_id)
+        );
+        assert_eq!(
+            arena.resolve_fully_qualified_name(&struct_id),
+            ["std", "string", "String"]
+        );
+        assert!(arena
+            .get_by_id(&prelude_id)
+            .unwrap()
+            .visible_children()
+            .contains_key("String"));
+    }
 }
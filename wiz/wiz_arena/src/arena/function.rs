@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use wiz_hir::typed_decl::TypedFunBody;
+use wiz_hir::typed_expr::TypedExpr;
 use wiz_hir::typed_type::{TypedType, TypedTypeParam};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -7,6 +8,10 @@ pub struct ArenaFunction {
     pub ty: TypedType,
     pub type_parameters: Option<Vec<TypedTypeParam>>,
     pub body: Option<TypedFunBody>,
+    /// Names of arguments, in the order of arguments.
+    pub arg_names: Vec<String>,
+    /// Default values of arguments, in the order of arguments.
+    pub arg_defaults: Vec<Option<TypedExpr>>,
     pub used: Vec<HashMap<TypedTypeParam, TypedType>>,
 }
 
@@ -20,6 +25,8 @@ impl ArenaFunction {
             ty,
             type_parameters,
             body,
+            arg_names: Default::default(),
+            arg_defaults: Default::default(),
             used: Default::default(),
         }
     }
@@ -1,15 +1,14 @@
 use std::collections::{HashMap, HashSet};
-use wiz_hir::typed_type::{Package, TypedNamedValueType, TypedPackage, TypedType, TypedValueType};
-
-#[derive(Debug, Eq, PartialEq, Clone)]
-pub struct ArenaTypeParam {
-    type_constraints: Vec<String>,
-    type_params: Option<HashMap<String, ArenaTypeParam>>,
-}
+use wiz_hir::typed_type::{
+    Package, TypedFunctionType, TypedNamedValueType, TypedPackage, TypedType, TypedTypeParam,
+    TypedValueType,
+};
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum StructKind {
     Struct,
+    Union,
+    Class,
     Protocol,
     TypeParameter,
 }
@@ -19,6 +18,14 @@ impl StructKind {
         matches!(self, Self::Struct)
     }
 
+    pub fn is_union(&self) -> bool {
+        matches!(self, Self::Union)
+    }
+
+    pub fn is_class(&self) -> bool {
+        matches!(self, Self::Class)
+    }
+
     pub fn is_protocol(&self) -> bool {
         matches!(self, Self::Protocol)
     }
@@ -28,16 +35,46 @@ impl StructKind {
     }
 }
 
+/// Function types of the accessors of a `subscript`.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ArenaSubscript {
+    pub getter: TypedFunctionType,
+    pub setter: Option<TypedFunctionType>,
+}
+
+impl ArenaSubscript {
+    /// Argument types of the getter except `self`.
+    pub fn index_types(&self) -> impl Iterator<Item = &TypedType> {
+        self.getter.arguments.iter().skip(1).map(|a| &a.typ)
+    }
+
+    pub fn element_type(&self) -> &TypedType {
+        &self.getter.return_type
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct ArenaStruct {
     pub namespace: Vec<String>,
     name: String,
     pub kind: StructKind,
     pub stored_properties: HashMap<String, TypedType>,
+    /// Names of stored properties in declaration order, once they are resolved.
+    pub stored_property_order: Option<Vec<String>>,
+    /// Names of stored properties declared with `var`.
+    pub mutable_properties: HashSet<String>,
     pub computed_properties: HashMap<String, TypedType>,
+    /// Function types of `init`s in declaration order.
+    pub initializers: Vec<TypedType>,
+    pub subscripts: Vec<ArenaSubscript>,
     pub member_functions: HashMap<String, TypedType>,
     pub conformed_protocols: HashSet<String>,
-    pub type_parameters: Option<HashMap<String, ArenaTypeParam>>,
+    /// Associated types of protocols.
+    /// For protocols and type parameters, values are placeholder types declared in itself.
+    /// For conformed types, values are bound types.
+    pub associated_types: HashMap<String, TypedType>,
+    /// Type parameters in declaration order, whose constraints are conformed protocols of them.
+    pub type_parameters: Option<Vec<TypedTypeParam>>,
 }
 
 impl ArenaStruct {
@@ -47,10 +84,15 @@ impl ArenaStruct {
             name: name.to_owned(),
             kind,
             stored_properties: Default::default(),
+            stored_property_order: None,
+            mutable_properties: Default::default(),
             computed_properties: Default::default(),
+            initializers: Default::default(),
+            subscripts: Default::default(),
             member_functions: Default::default(),
             conformed_protocols: Default::default(),
-            type_parameters: None, // TODO: fill type params
+            associated_types: Default::default(),
+            type_parameters: None,
         }
     }
 
@@ -66,6 +108,13 @@ impl ArenaStruct {
         }
     }
 
+    /// Subscript whose indexes are exactly of `index_types`.
+    pub fn get_subscript(&self, index_types: &[TypedType]) -> Option<&ArenaSubscript> {
+        self.subscripts
+            .iter()
+            .find(|s| s.index_types().eq(index_types.iter()))
+    }
+
     pub fn self_type(&self) -> TypedType {
         TypedType::Value(TypedValueType::Value(TypedNamedValueType {
             package: TypedPackage::Resolved(Package::from(&self.namespace)),
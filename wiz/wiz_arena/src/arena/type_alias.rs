@@ -0,0 +1,24 @@
+use wiz_hir::typed_type::{TypedType, TypedTypeParam};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ArenaTypeAlias {
+    pub type_parameters: Option<Vec<TypedTypeParam>>,
+    /// Aliased type, whose type parameters are declared in the type alias.
+    pub type_: TypedType,
+    /// Whether names in `type_` are resolved, which is not until the type resolver loads them.
+    pub is_resolved: bool,
+}
+
+impl ArenaTypeAlias {
+    pub fn new(type_parameters: Option<Vec<TypedTypeParam>>, type_: TypedType) -> Self {
+        Self {
+            type_parameters,
+            type_,
+            is_resolved: false,
+        }
+    }
+
+    pub fn is_generic(&self) -> bool {
+        self.type_parameters.is_some()
+    }
+}
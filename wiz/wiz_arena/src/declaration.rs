@@ -1,4 +1,4 @@
-use crate::arena::{ArenaFunction, ArenaStruct};
+use crate::arena::{ArenaFunction, ArenaStruct, ArenaTypeAlias};
 use crate::declaration_id::DeclarationId;
 use std::borrow::Borrow;
 use std::collections::{HashMap, HashSet};
@@ -13,6 +13,8 @@ pub struct DeclarationItem {
     pub kind: DeclarationItemKind,
     parent: Option<DeclarationId>,
     children: HashMap<String, HashSet<DeclarationId>>,
+    /// Declarations of other namespaces made visible by `public use`.
+    re_exports: HashMap<String, HashSet<DeclarationId>>,
 }
 
 impl DeclarationItem {
@@ -27,6 +29,7 @@ impl DeclarationItem {
             name: name.to_string(),
             kind,
             children: Default::default(),
+            re_exports: Default::default(),
             parent,
         }
     }
@@ -40,18 +43,36 @@ impl DeclarationItem {
         entry.insert(id);
     }
 
+    pub fn add_re_export(&mut self, name: &str, ids: HashSet<DeclarationId>) {
+        self.re_exports.insert(name.to_string(), ids);
+    }
+
+    /// Declarations named `name`, which are declared in or re-exported from this.
     pub fn get_child<Q: ?Sized>(&self, name: &Q) -> Option<&HashSet<DeclarationId>>
     where
         String: Borrow<Q>,
         Q: Hash + Eq,
     {
-        self.children.get(name)
+        self.children
+            .get(name)
+            .or_else(|| self.re_exports.get(name))
     }
 
     pub fn children(&self) -> &HashMap<String, HashSet<DeclarationId>> {
         &self.children
     }
 
+    pub fn re_exports(&self) -> &HashMap<String, HashSet<DeclarationId>> {
+        &self.re_exports
+    }
+
+    /// Children and re-exports, which are imported by `use [namespace]::*`.
+    pub fn visible_children(&self) -> HashMap<String, HashSet<DeclarationId>> {
+        let mut children = self.re_exports.clone();
+        children.extend(self.children.clone());
+        children
+    }
+
     pub fn parent(&self) -> Option<DeclarationId> {
         self.parent
     }
@@ -61,7 +82,14 @@ impl DeclarationItem {
     }
 
     pub fn is_type(&self) -> bool {
-        matches!(self.kind, DeclarationItemKind::Type(_))
+        matches!(
+            self.kind,
+            DeclarationItemKind::Type(_) | DeclarationItemKind::TypeAlias(_)
+        )
+    }
+
+    pub fn is_type_alias(&self) -> bool {
+        matches!(self.kind, DeclarationItemKind::TypeAlias(_))
     }
 
     pub fn is_value(&self) -> bool {
@@ -83,4 +111,5 @@ pub enum DeclarationItemKind {
     Type(ArenaStruct),
     Variable(TypedType),
     Function(ArenaFunction),
+    TypeAlias(ArenaTypeAlias),
 }
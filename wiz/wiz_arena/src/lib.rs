@@ -2,6 +2,6 @@ mod arena;
 mod declaration;
 mod declaration_id;
 
-pub use arena::{Arena, ArenaFunction, ArenaStruct, StructKind};
+pub use arena::{Arena, ArenaFunction, ArenaStruct, ArenaSubscript, ArenaTypeAlias, StructKind};
 pub use declaration::{DeclarationItem, DeclarationItemKind};
 pub use declaration_id::{DeclarationId, DeclarationIdGenerator};
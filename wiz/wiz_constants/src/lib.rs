@@ -25,7 +25,9 @@ pub const STRING: &str = "str";
 
 pub mod annotation {
     pub const BUILTIN: &str = "builtin";
+    pub const DERIVE: &str = "derive";
     pub const ENTRY: &str = "entry";
     pub const NO_MANGLE: &str = "no_mangle";
     pub const TEST: &str = "test";
+    pub const THREAD_LOCAL: &str = "thread_local";
 }
@@ -2,27 +2,41 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Default, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Annotations {
-    annotations: Vec<String>,
+    annotations: Vec<Annotation>,
 }
 
 impl Annotations {
     pub fn has_annotate<T: ToString>(&self, a: T) -> bool {
-        self.annotations.contains(&a.to_string())
+        self.get(&a.to_string()).is_some()
+    }
+
+    /// The first annotation named `name`.
+    pub fn get(&self, name: &str) -> Option<&Annotation> {
+        self.annotations.iter().find(|a| a.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Annotation> {
+        self.annotations.iter()
+    }
+
+    pub fn push(&mut self, annotation: Annotation) {
+        self.annotations.push(annotation);
     }
 }
 
-impl<T: ToString> From<Vec<T>> for Annotations {
-    fn from(annotations: Vec<T>) -> Self {
-        Self {
-            annotations: annotations.iter().map(T::to_string).collect(),
-        }
+impl From<Vec<Annotation>> for Annotations {
+    fn from(annotations: Vec<Annotation>) -> Self {
+        Self { annotations }
     }
 }
 
 impl<T: ToString> From<&[T]> for Annotations {
     fn from(annotations: &[T]) -> Self {
         Self {
-            annotations: annotations.iter().map(T::to_string).collect(),
+            annotations: annotations
+                .iter()
+                .map(|a| Annotation::new(a.to_string()))
+                .collect(),
         }
     }
 }
@@ -30,7 +44,140 @@ impl<T: ToString> From<&[T]> for Annotations {
 impl<T: ToString, const N: usize> From<&[T; N]> for Annotations {
     fn from(annotations: &[T; N]) -> Self {
         Self {
-            annotations: annotations.iter().map(T::to_string).collect(),
+            annotations: annotations
+                .iter()
+                .map(|a| Annotation::new(a.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// `name` or `name(arguments)` of `#[..]`.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    pub name: String,
+    pub arguments: Vec<AnnotationArgument>,
+}
+
+impl Annotation {
+    pub fn new<T: ToString>(name: T) -> Self {
+        Self {
+            name: name.to_string(),
+            arguments: vec![],
+        }
+    }
+
+    /// Value of the argument labeled `label`, if it is a `T`.
+    pub fn argument<T: FromAnnotationValue>(&self, label: &str) -> Option<T> {
+        self.arguments
+            .iter()
+            .find(|a| a.label.as_deref() == Some(label))
+            .and_then(|a| T::from_annotation_value(&a.value))
+    }
+
+    /// Value of the `index`th argument without label, if it is a `T`.
+    pub fn positional_argument<T: FromAnnotationValue>(&self, index: usize) -> Option<T> {
+        self.arguments
+            .iter()
+            .filter(|a| a.label.is_none())
+            .nth(index)
+            .and_then(|a| T::from_annotation_value(&a.value))
+    }
+}
+
+/// `value` or `label: value`.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct AnnotationArgument {
+    pub label: Option<String>,
+    pub value: AnnotationValue,
+}
+
+/// Literals keep their source text like `TypedLiteralKind`.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub enum AnnotationValue {
+    /// e.g. `Clone` of `derive(Clone)`
+    Name(String),
+    Integer(String),
+    FloatingPoint(String),
+    String(String),
+    Boolean(bool),
+}
+
+impl AnnotationValue {
+    pub fn as_name(&self) -> Option<&str> {
+        match self {
+            AnnotationValue::Name(n) => Some(n),
+            _ => None,
         }
     }
 }
+
+pub trait FromAnnotationValue: Sized {
+    fn from_annotation_value(value: &AnnotationValue) -> Option<Self>;
+}
+
+impl FromAnnotationValue for String {
+    fn from_annotation_value(value: &AnnotationValue) -> Option<Self> {
+        match value {
+            AnnotationValue::String(s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FromAnnotationValue for bool {
+    fn from_annotation_value(value: &AnnotationValue) -> Option<Self> {
+        match value {
+            AnnotationValue::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+impl FromAnnotationValue for i64 {
+    fn from_annotation_value(value: &AnnotationValue) -> Option<Self> {
+        match value {
+            AnnotationValue::Integer(i) => i.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromAnnotationValue for f64 {
+    fn from_annotation_value(value: &AnnotationValue) -> Option<Self> {
+        match value {
+            AnnotationValue::Integer(f) | AnnotationValue::FloatingPoint(f) => f.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Annotation, AnnotationArgument, AnnotationValue, Annotations};
+
+    #[test]
+    fn argument() {
+        let annotations = Annotations::from(vec![Annotation {
+            name: "link".to_string(),
+            arguments: vec![
+                AnnotationArgument {
+                    label: None,
+                    value: AnnotationValue::Integer("3".to_string()),
+                },
+                AnnotationArgument {
+                    label: Some("name".to_string()),
+                    value: AnnotationValue::String("m".to_string()),
+                },
+            ],
+        }]);
+        let link = annotations.get("link").unwrap();
+
+        assert!(annotations.has_annotate("link"));
+        assert_eq!(link.argument::<String>("name"), Some("m".to_string()));
+        assert_eq!(link.argument::<bool>("name"), None);
+        assert_eq!(link.positional_argument::<i64>(0), Some(3));
+        assert_eq!(link.positional_argument::<f64>(0), Some(3.0));
+        assert_eq!(link.positional_argument::<i64>(1), None);
+    }
+}
@@ -17,12 +17,18 @@ pub struct TypedTopLevelDecl {
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub enum TypedDeclKind {
     Var(TypedVar),
+    Const(TypedVar),
+    ExternVar(TypedExternVar),
     Fun(TypedFun),
     Struct(TypedStruct),
+    Union(TypedStruct),
+    /// `class`, whose instances are reference counted on the heap.
+    Class(TypedStruct),
     Module(TypedModule),
     Enum,
     Protocol(TypedProtocol),
     Extension(TypedExtension),
+    TypeAlias(TypedTypeAlias),
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
@@ -33,6 +39,22 @@ pub struct TypedVar {
     pub value: TypedExpr,
 }
 
+/// Global variable declared in an `extern` block, which is defined outside of wiz.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TypedExternVar {
+    pub is_mut: bool,
+    pub name: String,
+    pub type_: TypedType,
+}
+
+/// `typealias name<type_params> = type_`, which is expanded wherever it is used.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TypedTypeAlias {
+    pub name: String,
+    pub type_params: Option<Vec<TypedTypeParam>>,
+    pub type_: TypedType,
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub struct TypedFun {
     pub name: String,
@@ -59,11 +81,12 @@ impl TypedFun {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Hash, Serialize, Deserialize)]
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub struct TypedArgDef {
     pub label: String,
     pub name: String,
     pub type_: TypedType,
+    pub default: Option<TypedExpr>,
 }
 
 impl TypedArgDef {
@@ -85,13 +108,34 @@ pub enum TypedFunBody {
 pub struct TypedStruct {
     pub name: String,
     pub type_params: Option<Vec<TypedTypeParam>>,
+    pub type_constraints: Option<Vec<TypedTypeConstraint>>,
     pub stored_properties: Vec<TypedStoredProperty>,
     pub computed_properties: Vec<TypedComputedProperty>,
+    pub initializers: Vec<TypedInitializer>,
+    pub subscripts: Vec<TypedSubscriptDecl>,
     pub member_functions: Vec<TypedFun>,
 }
 
+/// `init(..) { .. }`, which assigns every stored property of `self`.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TypedInitializer {
+    pub arg_defs: Vec<TypedArgDef>,
+    pub body: TypedFunBody,
+}
+
+/// `subscript(index: T): E { get { .. } set { .. } }`.
+///
+/// Accessors are member functions which take `&self` followed by the indexes.
+/// The setter additionally takes the assigned value as `newValue`.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TypedSubscriptDecl {
+    pub getter: TypedFun,
+    pub setter: Option<TypedFun>,
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub struct TypedStoredProperty {
+    pub is_mut: bool,
     pub name: String,
     pub type_: TypedType,
 }
@@ -102,10 +146,18 @@ pub struct TypedComputedProperty {
     pub type_: TypedType,
 }
 
+/// `type Element` in protocol, `type Element = Int64` in extension.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TypedAssociatedType {
+    pub name: String,
+    pub type_: Option<TypedType>,
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub struct TypedExtension {
     pub name: TypedType,
     pub protocol: Option<TypedType>,
+    pub associated_types: Vec<TypedAssociatedType>,
     pub computed_properties: Vec<TypedComputedProperty>,
     pub member_functions: Vec<TypedFun>,
 }
@@ -114,16 +166,25 @@ pub struct TypedExtension {
 pub struct TypedProtocol {
     pub name: String,
     pub type_params: Option<Vec<TypedTypeParam>>,
+    pub associated_types: Vec<TypedAssociatedType>,
     pub computed_properties: Vec<TypedComputedProperty>,
     pub member_functions: Vec<TypedFun>,
 }
 
 impl TypedFun {
     pub fn type_(&self) -> TypedType {
-        TypedType::Function(Box::new(TypedFunctionType {
+        TypedType::Function(Box::new(self.function_type()))
+    }
+
+    pub fn function_type(&self) -> TypedFunctionType {
+        TypedFunctionType {
             arguments: self.arg_defs.iter().map(|a| a.to_arg_type()).collect(),
             return_type: self.return_type.clone(),
-        }))
+        }
+    }
+
+    pub fn arg_names(&self) -> Vec<String> {
+        self.arg_defs.iter().map(|a| a.name.clone()).collect()
     }
 
     pub fn is_generic(&self) -> bool {
@@ -131,4 +192,86 @@ impl TypedFun {
     }
 }
 
+impl TypedInitializer {
+    /// Name of initializers in the namespace of the struct.
+    pub const NAME: &'static str = "init";
+
+    pub fn type_(&self) -> TypedType {
+        TypedType::Function(Box::new(TypedFunctionType {
+            arguments: self.arg_defs.iter().map(|a| a.to_arg_type()).collect(),
+            return_type: TypedType::Self_,
+        }))
+    }
+
+    pub fn arg_names(&self) -> Vec<String> {
+        self.arg_defs.iter().map(|a| a.name.clone()).collect()
+    }
+}
+
+impl TypedStruct {
+    /// Name of the member function `deinit` is lowered to.
+    pub const DEINIT: &'static str = "#deinit";
+
+    pub fn deinitializer(body: TypedFunBody) -> TypedFun {
+        TypedFun {
+            name: Self::DEINIT.to_string(),
+            type_params: None,
+            type_constraints: None,
+            arg_defs: vec![TypedArgDef {
+                label: "_".to_string(),
+                name: "self".to_string(),
+                type_: TypedType::reference(TypedType::Self_),
+                default: None,
+            }],
+            body: Some(body),
+            return_type: TypedType::unit(),
+        }
+    }
+}
+
+impl TypedSubscriptDecl {
+    pub const GETTER: &'static str = "subscript#get";
+    pub const SETTER: &'static str = "subscript#set";
+    pub const NEW_VALUE: &'static str = "newValue";
+
+    pub fn new(
+        arg_defs: Vec<TypedArgDef>,
+        element_type: TypedType,
+        getter: TypedFunBody,
+        setter: Option<TypedFunBody>,
+    ) -> Self {
+        let accessor = |name: &str, arg_defs: Vec<TypedArgDef>, return_type, body| {
+            let mut args = vec![TypedArgDef {
+                label: "_".to_string(),
+                name: "self".to_string(),
+                type_: TypedType::reference(TypedType::Self_),
+                default: None,
+            }];
+            args.extend(arg_defs);
+            TypedFun {
+                name: name.to_string(),
+                type_params: None,
+                type_constraints: None,
+                arg_defs: args,
+                body: Some(body),
+                return_type,
+            }
+        };
+        let setter = setter.map(|body| {
+            let mut arg_defs = arg_defs.clone();
+            arg_defs.push(TypedArgDef {
+                label: "_".to_string(),
+                name: Self::NEW_VALUE.to_string(),
+                type_: element_type.clone(),
+                default: None,
+            });
+            accessor(Self::SETTER, arg_defs, TypedType::unit(), body)
+        });
+        Self {
+            getter: accessor(Self::GETTER, arg_defs, element_type, getter),
+            setter,
+        }
+    }
+}
+
 pub type TypedModule = TypedSpellBook;
@@ -51,6 +51,7 @@ pub struct TypedArray {
 pub struct TypedSubscript {
     pub target: Box<TypedExpr>,
     pub indexes: Vec<TypedExpr>,
+    pub location: Option<TypedLocation>,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
@@ -60,6 +61,25 @@ pub enum TypedLiteralKind {
     String(String),
     Boolean(String),
     NullLiteral,
+    /// `#file`, `#line` or `#column` written at `location`.
+    ///
+    /// As a default argument value it takes the location of the caller instead.
+    SourceLocation(TypedSourceLocationKind, TypedLocation),
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum TypedSourceLocationKind {
+    File,
+    Line,
+    Column,
+}
+
+/// Position in a source file.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TypedLocation {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
@@ -67,6 +87,7 @@ pub struct TypedBinOp {
     pub left: Box<TypedExpr>,
     pub operator: TypedBinaryOperator,
     pub right: Box<TypedExpr>,
+    pub location: Option<TypedLocation>,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Hash, Serialize, Deserialize)]
@@ -112,6 +133,7 @@ pub enum TypedPrefixUnaryOperator {
 pub struct TypedPostfixUnaryOp {
     pub target: Box<TypedExpr>,
     pub operator: TypedPostfixUnaryOperator,
+    pub location: Option<TypedLocation>,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
@@ -123,6 +145,7 @@ pub enum TypedPostfixUnaryOperator {
 pub struct TypedCall {
     pub target: Box<TypedExpr>,
     pub args: Vec<TypedCallArg>,
+    pub location: Option<TypedLocation>,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
@@ -169,6 +192,7 @@ pub struct TypedTypeCast {
     pub target: Box<TypedExpr>,
     pub is_safe: bool,
     pub type_: TypedType,
+    pub location: Option<TypedLocation>,
 }
 
 impl TypedLiteralKind {
@@ -9,6 +9,8 @@ pub enum TypedStmt {
     Decl(TypedTopLevelDecl),
     Assignment(TypedAssignmentStmt),
     Loop(TypedLoopStmt),
+    /// `defer { .. }`, which runs when the enclosing block exits.
+    Defer(TypedBlock),
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use wiz_constants as constants;
 
 #[derive(Debug, Eq, PartialEq, Clone, Hash, Serialize, Deserialize)]
@@ -44,9 +45,12 @@ impl TypedType {
 pub enum TypedValueType {
     Value(TypedNamedValueType), // Primitive | Struct | Union | Enum
     Array(Box<TypedType>, usize),
+    /// Pointer to the first element and the number of elements.
+    Slice(Box<TypedType>),
     Tuple(Vec<TypedType>),
     Pointer(Box<TypedType>),
     Reference(Box<TypedType>),
+    Function(Box<TypedFunctionType>),
 }
 
 impl TypedValueType {
@@ -122,12 +126,21 @@ impl TypedValueType {
         matches!(self, Self::Array(_, _))
     }
 
+    pub fn is_slice(&self) -> bool {
+        matches!(self, Self::Slice(_))
+    }
+
+    pub fn is_function_pointer(&self) -> bool {
+        matches!(self, Self::Function(_))
+    }
+
     pub fn package(&self) -> TypedPackage {
         match self {
             TypedValueType::Value(v) => v.package.clone(),
-            TypedValueType::Array(_, _) | TypedValueType::Tuple(_) => {
-                TypedPackage::Resolved(Package::global())
-            }
+            TypedValueType::Array(_, _)
+            | TypedValueType::Slice(_)
+            | TypedValueType::Tuple(_)
+            | TypedValueType::Function(_) => TypedPackage::Resolved(Package::global()),
             TypedValueType::Pointer(v) | TypedValueType::Reference(v) => v.package(),
         }
     }
@@ -136,8 +149,10 @@ impl TypedValueType {
         match self {
             TypedValueType::Value(v) => v.name.clone(),
             TypedValueType::Array(_, _) => todo!(),
+            TypedValueType::Slice(t) => format!("[{}]", t.name()),
             TypedValueType::Tuple(_) => todo!(),
             TypedValueType::Pointer(v) | TypedValueType::Reference(v) => v.name(),
+            TypedValueType::Function(f) => f.to_string(),
         }
     }
 }
@@ -149,6 +164,9 @@ impl ToString for TypedValueType {
             TypedValueType::Array(t, len) => {
                 format!("[{};{}]", t.to_string(), len)
             }
+            TypedValueType::Slice(t) => {
+                format!("[{}]", t.to_string())
+            }
             TypedValueType::Tuple(_) => {
                 todo!()
             }
@@ -158,6 +176,7 @@ impl ToString for TypedValueType {
             TypedValueType::Reference(v) => {
                 format!("&{}", v.to_string())
             }
+            TypedValueType::Function(f) => f.to_string(),
         }
     }
 }
@@ -168,6 +187,37 @@ pub struct TypedFunctionType {
     pub return_type: TypedType,
 }
 
+impl TypedFunctionType {
+    pub fn replace(&self, from: &TypedType, to: &TypedType) -> Self {
+        Self {
+            arguments: self
+                .arguments
+                .iter()
+                .map(|a| TypedArgType {
+                    label: a.label.clone(),
+                    typ: a.typ.replace(from, to),
+                })
+                .collect(),
+            return_type: self.return_type.replace(from, to),
+        }
+    }
+}
+
+impl fmt::Display for TypedFunctionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "({}) -> {}",
+            self.arguments
+                .iter()
+                .map(|a| a.typ.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.return_type.to_string()
+        )
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Hash, Serialize, Deserialize)]
 pub struct TypedArgType {
     pub label: String,
@@ -328,6 +378,29 @@ impl TypedNamedValueType {
     pub fn is_generic(&self) -> bool {
         self.type_args.is_some()
     }
+
+    /// `Atomic<T>` declared in `core::builtin`.
+    pub fn atomic(typ: TypedType) -> Self {
+        Self {
+            package: TypedPackage::Resolved(Package::from(&["core", "builtin"])),
+            name: "Atomic".to_string(),
+            type_args: Some(vec![typ]),
+        }
+    }
+
+    /// Whether this is `Atomic` declared in `core::builtin`, with or without its type argument.
+    pub fn is_atomic(&self) -> bool {
+        self.package == TypedPackage::Resolved(Package::from(&["core", "builtin"]))
+            && self.name == "Atomic"
+    }
+
+    /// `T` of `Atomic<T>`.
+    pub fn atomic_value_type(&self) -> Option<&TypedType> {
+        match self.type_args.as_deref() {
+            Some([t]) if self.is_atomic() => Some(t),
+            _ => None,
+        }
+    }
 }
 
 impl ToString for TypedNamedValueType {
@@ -431,6 +504,47 @@ impl TypedType {
         Self::Value(TypedValueType::Reference(Box::new(typ)))
     }
 
+    pub fn slice(typ: TypedType) -> Self {
+        Self::Value(TypedValueType::Slice(Box::new(typ)))
+    }
+
+    /// `Range` declared in `core::builtin`, the type of `a..b` and `a..<b`.
+    pub fn range() -> Self {
+        Self::Value(TypedValueType::Value(TypedNamedValueType {
+            package: TypedPackage::Resolved(Package::from(&["core", "builtin"])),
+            name: "Range".to_string(),
+            type_args: None,
+        }))
+    }
+
+    /// `Atomic<T>` declared in `core::builtin`, which has the same layout as `T`.
+    pub fn atomic(typ: TypedType) -> Self {
+        Self::Value(TypedValueType::Value(TypedNamedValueType::atomic(typ)))
+    }
+
+    pub fn function_pointer(typ: TypedFunctionType) -> Self {
+        Self::Value(TypedValueType::Function(Box::new(typ)))
+    }
+
+    /// Convert function designator into function pointer value type.
+    /// Argument labels are not a part of function pointer type.
+    pub fn decay(self) -> Self {
+        match self {
+            TypedType::Function(f) => Self::function_pointer(TypedFunctionType {
+                arguments: f
+                    .arguments
+                    .into_iter()
+                    .map(|a| TypedArgType {
+                        label: "_".to_string(),
+                        typ: a.typ,
+                    })
+                    .collect(),
+                return_type: f.return_type,
+            }),
+            t => t,
+        }
+    }
+
     pub fn signed_integer_types() -> Vec<TypedType> {
         vec![
             Self::int8(),
@@ -513,6 +627,58 @@ impl TypedType {
         }
     }
 
+    pub fn is_slice_type(&self) -> bool {
+        match self {
+            TypedType::Value(v) => v.is_slice(),
+            _ => false,
+        }
+    }
+
+    /// Replace all occurrences of `from` in this type with `to`.
+    pub fn replace(&self, from: &TypedType, to: &TypedType) -> TypedType {
+        if self == from {
+            return to.clone();
+        }
+        match self {
+            TypedType::Self_ => TypedType::Self_,
+            TypedType::Value(v) => TypedType::Value(match v {
+                TypedValueType::Value(n) => TypedValueType::Value(TypedNamedValueType {
+                    package: n.package.clone(),
+                    name: n.name.clone(),
+                    type_args: n
+                        .type_args
+                        .as_ref()
+                        .map(|a| a.iter().map(|t| t.replace(from, to)).collect()),
+                }),
+                TypedValueType::Array(t, len) => {
+                    TypedValueType::Array(Box::new(t.replace(from, to)), *len)
+                }
+                TypedValueType::Slice(t) => TypedValueType::Slice(Box::new(t.replace(from, to))),
+                TypedValueType::Tuple(t) => {
+                    TypedValueType::Tuple(t.iter().map(|t| t.replace(from, to)).collect())
+                }
+                TypedValueType::Pointer(t) => {
+                    TypedValueType::Pointer(Box::new(t.replace(from, to)))
+                }
+                TypedValueType::Reference(t) => {
+                    TypedValueType::Reference(Box::new(t.replace(from, to)))
+                }
+                TypedValueType::Function(f) => {
+                    TypedValueType::Function(Box::new(f.replace(from, to)))
+                }
+            }),
+            TypedType::Function(f) => TypedType::Function(Box::new(f.replace(from, to))),
+            TypedType::Type(t) => TypedType::Type(Box::new(t.replace(from, to))),
+        }
+    }
+
+    pub fn is_function_pointer_type(&self) -> bool {
+        match self {
+            TypedType::Value(v) => v.is_function_pointer(),
+            _ => false,
+        }
+    }
+
     pub fn is_boolean(&self) -> bool {
         Self::bool().eq(self)
     }
@@ -524,13 +690,25 @@ impl TypedType {
     pub fn is_string_ref(&self) -> bool {
         Self::string_ref().eq(self)
     }
+
+    pub fn is_range(&self) -> bool {
+        Self::range().eq(self)
+    }
+
+    /// `T` of `Atomic<T>`.
+    pub fn atomic_value_type(&self) -> Option<&TypedType> {
+        match self {
+            TypedType::Value(TypedValueType::Value(v)) => v.atomic_value_type(),
+            _ => None,
+        }
+    }
 }
 
 impl ToString for TypedType {
     fn to_string(&self) -> String {
         match self {
             TypedType::Value(t) => t.to_string(),
-            TypedType::Function(f) => todo!("{:?}", f),
+            TypedType::Function(f) => f.to_string(),
             TypedType::Self_ => todo!(),
             TypedType::Type(t) => {
                 format!("Type<{}>", t.to_string())
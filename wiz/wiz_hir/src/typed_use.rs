@@ -7,6 +7,22 @@ pub struct TypedUse {
     pub annotations: Annotations,
     pub package: Package,
     pub alias: Option<String>,
+    /// `public use`, which re-exports the used names from the namespace declaring it.
+    pub is_public: bool,
+}
+
+impl TypedUse {
+    /// `use a::*`
+    pub fn is_glob(&self) -> bool {
+        self.package.names.last().is_some_and(|n| n == "*")
+    }
+
+    /// Name introduced by `use a::b` or `use a::b as c`.
+    pub fn name(&self) -> Option<&str> {
+        self.alias
+            .as_deref()
+            .or_else(|| self.package.names.last().map(String::as_str))
+    }
 }
 
 impl<T: ToString> From<Vec<T>> for TypedUse {
@@ -15,6 +31,7 @@ impl<T: ToString> From<Vec<T>> for TypedUse {
             annotations: Default::default(),
             package: Package::from(&vec),
             alias: None,
+            is_public: false,
         }
     }
 }
@@ -5,7 +5,7 @@ pub use self::error::BuilderError;
 pub use self::fun_builder::FunBuilder;
 use crate::builder::error::BResult;
 use crate::expr::{MLBlock, MLExpr};
-use crate::ml_decl::{MLArgDef, MLDecl, MLField, MLStruct, MLVar};
+use crate::ml_decl::{MLArgDef, MLDecl, MLField, MLGlobalVar, MLStruct, MLUnion, MLVar};
 use crate::ml_file::MLFile;
 use crate::ml_type::MLValueType;
 use crate::statement::{MLAssignmentStmt, MLLoopStmt, MLReturn, MLStmt};
@@ -14,8 +14,9 @@ use linked_hash_map::LinkedHashMap;
 #[derive(Clone, Debug, Default)]
 pub struct MLIRModule {
     functions: LinkedHashMap<String, FunBuilder>,
-    variables: LinkedHashMap<String, MLVar>,
+    variables: LinkedHashMap<String, MLGlobalVar>,
     structs: LinkedHashMap<String, MLStruct>,
+    unions: LinkedHashMap<String, MLUnion>,
     current_function: Option<String>,
 }
 
@@ -53,22 +54,33 @@ impl MLIRModule {
         self.structs.get_mut(name)
     }
 
-    pub fn create_global_var(&mut self, name: String, value: MLExpr) -> Option<&mut MLVar> {
-        self.add_global_var(MLVar {
+    pub fn add_union(&mut self, u: MLUnion) -> Option<&mut MLUnion> {
+        let name = u.name.clone();
+        self.unions.insert(name.clone(), u)?;
+        self.get_union(&name)
+    }
+
+    pub fn get_union(&mut self, name: &str) -> Option<&mut MLUnion> {
+        self.unions.get_mut(name)
+    }
+
+    pub fn create_global_var(&mut self, name: String, value: MLExpr) -> Option<&mut MLGlobalVar> {
+        self.add_global_var(MLGlobalVar {
             is_mute: false,
+            is_thread_local: false,
             name,
             type_: value.type_(),
-            value,
+            value: Some(value),
         })
     }
 
-    pub fn add_global_var(&mut self, var: MLVar) -> Option<&mut MLVar> {
+    pub fn add_global_var(&mut self, var: MLGlobalVar) -> Option<&mut MLGlobalVar> {
         let name = var.name.clone();
         self.variables.insert(name.clone(), var)?;
         self.get_global_var(&name)
     }
 
-    pub fn get_global_var(&mut self, name: &str) -> Option<&mut MLVar> {
+    pub fn get_global_var(&mut self, name: &str) -> Option<&mut MLGlobalVar> {
         self.variables.get_mut(name)
     }
 
@@ -87,6 +99,7 @@ impl MLIRModule {
                 .structs
                 .iter()
                 .map(|(_, v)| MLDecl::Struct(v.clone()))
+                .chain(self.unions.iter().map(|(_, v)| MLDecl::Union(v.clone())))
                 .chain(self.variables.iter().map(|(_, v)| MLDecl::Var(v.clone())))
                 .chain(forward_declarations)
                 .chain(declarations.into_iter().flatten())
@@ -2,12 +2,14 @@ mod array;
 mod block;
 mod call;
 mod if_expr;
+mod intrinsic;
 mod literal;
 
 pub use self::array::MLArray;
 pub use self::block::MLBlock;
 pub use self::call::{MLCall, MLCallArg};
 pub use self::if_expr::MLIf;
+pub use self::intrinsic::MLIntrinsic;
 pub use self::literal::{MLLiteral, MLLiteralKind};
 use crate::format::Formatter;
 use crate::ml_node::MLNode;
@@ -32,6 +34,9 @@ pub enum MLExpr {
     PrimitiveTypeCast(MLTypeCast),
     Block(MLBlock),
     SizeOf(MLType),
+    Slice(MLSlice),
+    Unwrap(MLUnwrap),
+    Intrinsic(MLIntrinsic),
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -46,6 +51,7 @@ pub struct MLBinOp {
     pub kind: MLBinOpKind,
     pub right: Box<MLExpr>,
     pub type_: MLValueType,
+    pub location: Option<MLLocation>,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -86,6 +92,33 @@ pub struct MLSubscript {
     pub target: Box<MLExpr>,
     pub index: Box<MLExpr>,
     pub type_: MLValueType,
+    pub location: Option<MLLocation>,
+}
+
+/// Elements `start..<end` of an array, a slice or a string as a slice.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct MLSlice {
+    pub target: Box<MLExpr>,
+    pub start: Box<MLExpr>,
+    pub end: Box<MLExpr>,
+    pub type_: MLValueType,
+    pub location: Option<MLLocation>,
+}
+
+/// `target!!`, panics when `target` is null.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct MLUnwrap {
+    pub target: Box<MLExpr>,
+    pub type_: MLValueType,
+    pub location: Option<MLLocation>,
+}
+
+/// Source position reported when a runtime check fails.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct MLLocation {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -99,6 +132,7 @@ pub struct MLMember {
 pub struct MLTypeCast {
     pub target: Box<MLExpr>,
     pub type_: MLValueType,
+    pub location: Option<MLLocation>,
 }
 
 impl MLExpr {
@@ -118,6 +152,9 @@ impl MLExpr {
             MLExpr::PrimitiveTypeCast(t) => MLType::Value(t.type_.clone()),
             MLExpr::Block(b) => b.r#type(),
             MLExpr::SizeOf(_) => MLType::Value(MLValueType::Primitive(MLPrimitiveType::USize)),
+            MLExpr::Slice(s) => MLType::Value(s.type_.clone()),
+            MLExpr::Unwrap(u) => MLType::Value(u.type_.clone()),
+            MLExpr::Intrinsic(i) => MLType::Value(i.type_.clone()),
         }
     }
 
@@ -149,6 +186,9 @@ impl MLNode for MLExpr {
                 f.write_str("sizeof ")?;
                 t.fmt(f)
             }
+            MLExpr::Slice(s) => s.fmt(f),
+            MLExpr::Unwrap(u) => u.fmt(f),
+            MLExpr::Intrinsic(i) => i.fmt(f),
         }
     }
 }
@@ -205,6 +245,24 @@ impl MLNode for MLSubscript {
     }
 }
 
+impl MLNode for MLSlice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.target.fmt(f)?;
+        f.write_char('[')?;
+        self.start.fmt(f)?;
+        f.write_str("..<")?;
+        self.end.fmt(f)?;
+        f.write_char(']')
+    }
+}
+
+impl MLNode for MLUnwrap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.target.fmt(f)?;
+        f.write_str("!!")
+    }
+}
+
 impl MLNode for MLMember {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         self.target.fmt(f)?;
@@ -0,0 +1,40 @@
+use crate::expr::MLCallArg;
+use crate::format::Formatter;
+use crate::ml_node::MLNode;
+use crate::ml_type::MLValueType;
+use std::fmt;
+use std::fmt::Write;
+
+/// Call of a function declared with `#[builtin]`, expanded by the code generator.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct MLIntrinsic {
+    pub name: String,
+    pub type_args: Vec<MLValueType>,
+    pub args: Vec<MLCallArg>,
+    pub type_: MLValueType,
+}
+
+impl MLNode for MLIntrinsic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_char('@')?;
+        f.write_str(&self.name)?;
+        if !self.type_args.is_empty() {
+            f.write_char('<')?;
+            for (c, type_arg) in self.type_args.iter().enumerate() {
+                if c != 0 {
+                    f.write_str(", ")?;
+                }
+                type_arg.fmt(f)?;
+            }
+            f.write_char('>')?;
+        }
+        f.write_char('(')?;
+        for (c, arg) in self.args.iter().enumerate() {
+            if c != 0 {
+                f.write_str(", ")?;
+            }
+            arg.fmt(f)?;
+        }
+        f.write_char(')')
+    }
+}
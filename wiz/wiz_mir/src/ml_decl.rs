@@ -8,9 +8,10 @@ use std::fmt::Write;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum MLDecl {
-    Var(MLVar),
+    Var(MLGlobalVar),
     Fun(MLFun),
     Struct(MLStruct),
+    Union(MLUnion),
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -21,6 +22,16 @@ pub struct MLVar {
     pub value: MLExpr,
 }
 
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct MLGlobalVar {
+    pub is_mute: bool,
+    pub is_thread_local: bool,
+    pub name: String,
+    pub type_: MLType,
+    /// `None` for variables defined outside of the module.
+    pub value: Option<MLExpr>,
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct MLFun {
     pub name: String,
@@ -46,6 +57,12 @@ pub struct MLStruct {
     pub fields: Vec<MLField>,
 }
 
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct MLUnion {
+    pub name: String,
+    pub fields: Vec<MLField>,
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct MLField {
     pub name: String,
@@ -58,6 +75,7 @@ impl MLNode for MLDecl {
             MLDecl::Var(v) => v.fmt(f),
             MLDecl::Fun(fun) => fun.fmt(f),
             MLDecl::Struct(s) => s.fmt(f),
+            MLDecl::Union(u) => u.fmt(f),
         }
     }
 }
@@ -74,6 +92,26 @@ impl MLNode for MLVar {
     }
 }
 
+impl MLNode for MLGlobalVar {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_thread_local {
+            f.write_str("thread_local ")?;
+        }
+        f.write_str(if self.is_mute { "var" } else { "val" })?;
+        f.write_char(' ')?;
+        f.write_str(&*self.name)?;
+        f.write_char(':')?;
+        self.type_.fmt(f)?;
+        match &self.value {
+            Some(value) => {
+                f.write_str(" = ")?;
+                value.fmt(f)
+            }
+            None => f.write_char(';'),
+        }
+    }
+}
+
 impl MLNode for MLFun {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.write_str("fun ")?;
@@ -134,6 +172,21 @@ impl MLNode for MLStruct {
     }
 }
 
+impl MLNode for MLUnion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("union ")?;
+        f.write_str(&self.name)?;
+        f.write_str(" {\n")?;
+        f.indent_level_up();
+        for field in self.fields.iter() {
+            field.fmt(f)?;
+            f.write_str(",\n")?;
+        }
+        f.indent_level_down();
+        f.write_str("};")
+    }
+}
+
 impl MLNode for MLField {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.write_str(&*self.name)?;
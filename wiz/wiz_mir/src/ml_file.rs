@@ -32,7 +32,7 @@ impl MLNode for MLFile {
 #[cfg(test)]
 mod tests {
     use crate::expr::{MLExpr, MLName};
-    use crate::ml_decl::{MLArgDef, MLDecl, MLField, MLFun, MLFunBody, MLStruct};
+    use crate::ml_decl::{MLArgDef, MLDecl, MLField, MLFun, MLFunBody, MLStruct, MLUnion};
     use crate::ml_file::MLFile;
     use crate::ml_type::{MLPrimitiveType, MLType, MLValueType};
     use crate::statement::{MLReturn, MLStmt};
@@ -76,6 +76,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ml_file_to_string_union() {
+        let ml_file = MLFile {
+            name: "test".to_string(),
+            body: vec![MLDecl::Union(MLUnion {
+                name: "U".to_string(),
+                fields: vec![
+                    MLField {
+                        name: "i".to_string(),
+                        type_: MLValueType::Primitive(MLPrimitiveType::Int64),
+                    },
+                    MLField {
+                        name: "f".to_string(),
+                        type_: MLValueType::Primitive(MLPrimitiveType::Double),
+                    },
+                ],
+            })],
+        };
+        assert_eq!(
+            ml_file.to_string(),
+            String::from("union U {\n    i:i64,\n    f:f64,\n};\n")
+        );
+    }
+
     #[test]
     fn test_ml_file_to_string_function_no_body() {
         let ml_file = MLFile {
@@ -26,6 +26,8 @@ pub enum MLValueType {
     Pointer(Box<MLType>),
     Reference(Box<MLType>),
     Array(Box<MLValueType>, usize),
+    /// `{ *T, usize }`
+    Slice(Box<MLValueType>),
 }
 
 impl MLValueType {
@@ -36,6 +38,7 @@ impl MLValueType {
             MLValueType::Pointer(p) => format!("*{}", p.name()),
             MLValueType::Reference(r) => format!("&{}", r.name()),
             MLValueType::Array(a, size) => format!("[{};{}]", a.name(), size),
+            MLValueType::Slice(a) => format!("[{}]", a.name()),
         }
     }
 
@@ -49,6 +52,17 @@ impl MLValueType {
             _ => false,
         }
     }
+
+    pub fn is_unsigned_integer(&self) -> bool {
+        match self {
+            Self::Primitive(p) => p.is_unsigned_integer(),
+            _ => false,
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.is_signed_integer() || self.is_unsigned_integer()
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
@@ -59,7 +73,15 @@ pub struct MLFunctionType {
 
 impl MLFunctionType {
     fn name(&self) -> String {
-        todo!()
+        format!(
+            "({}) -> {}",
+            self.arguments
+                .iter()
+                .map(|a| a.name())
+                .collect::<Vec<_>>()
+                .join(","),
+            self.return_type.name()
+        )
     }
 }
 
@@ -92,6 +114,13 @@ impl MLPrimitiveType {
             Self::Int8 | Self::Int16 | Self::Int32 | Self::Int64 | Self::Int128 | Self::Size
         )
     }
+
+    pub fn is_unsigned_integer(&self) -> bool {
+        matches!(
+            self,
+            Self::UInt8 | Self::UInt16 | Self::UInt32 | Self::UInt64 | Self::UInt128 | Self::USize
+        )
+    }
 }
 
 impl ToString for MLPrimitiveType {
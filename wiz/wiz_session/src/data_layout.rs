@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+
+/// Sizes and ABI alignments of primitive types on the target.
+///
+/// Read from an LLVM data layout string such as
+/// `e-m:e-p:32:32-f64:32:64-f80:32-n8:16:32-S128`, so that layouts computed
+/// before code generation agree with the ones LLVM uses. Sizes and alignments
+/// are in bytes, and keys are sizes in bits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataLayout {
+    pub pointer_size: u64,
+    pub pointer_align: u64,
+    integers: BTreeMap<u64, u64>,
+    floats: BTreeMap<u64, u64>,
+}
+
+impl DataLayout {
+    /// Layout with LLVM's defaults for what `layout` does not specify.
+    pub fn parse(layout: &str) -> Self {
+        let mut data_layout = Self {
+            pointer_size: 8,
+            pointer_align: 8,
+            integers: BTreeMap::from([(1, 1), (8, 1), (16, 2), (32, 4), (64, 4)]),
+            floats: BTreeMap::from([(16, 2), (32, 4), (64, 8), (128, 16)]),
+        };
+        for spec in layout.split('-') {
+            let mut fields = spec.split(':');
+            let name = fields.next().unwrap_or_default();
+            let bits = fields
+                .filter_map(|f| f.parse::<u64>().ok())
+                .collect::<Vec<_>>();
+            match (name.split_at(name.len().min(1)), bits.as_slice()) {
+                (("p", "" | "0"), [size, align, ..]) => {
+                    data_layout.pointer_size = size / 8;
+                    data_layout.pointer_align = align / 8;
+                }
+                (("i", size), [align, ..]) => {
+                    if let Ok(size) = size.parse() {
+                        data_layout.integers.insert(size, align / 8);
+                    }
+                }
+                (("f", size), [align, ..]) => {
+                    if let Ok(size) = size.parse() {
+                        data_layout.floats.insert(size, align / 8);
+                    }
+                }
+                _ => {}
+            }
+        }
+        data_layout
+    }
+
+    /// Layout where every primitive is aligned to its size, for when the data
+    /// layout of the target is not known.
+    pub fn natural(pointer_size: u64) -> Self {
+        let sizes = [8, 16, 32, 64, 128];
+        Self {
+            pointer_size,
+            pointer_align: pointer_size,
+            integers: sizes.iter().map(|bits| (*bits, bits / 8)).collect(),
+            floats: sizes.iter().map(|bits| (*bits, bits / 8)).collect(),
+        }
+    }
+
+    /// Alignment of integers of `bits` bits, which is the one of the smallest
+    /// specified integer at least as large, or else of the largest one, as LLVM does.
+    pub fn integer_align(&self, bits: u64) -> u64 {
+        self.integers
+            .range(bits..)
+            .next()
+            .or_else(|| self.integers.iter().next_back())
+            .map_or(bits / 8, |(_, align)| *align)
+    }
+
+    /// Alignment of floating point numbers of `bits` bits.
+    pub fn float_align(&self, bits: u64) -> u64 {
+        self.floats.get(&bits).copied().unwrap_or(bits / 8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DataLayout;
+
+    #[test]
+    fn test_parse_x86_64() {
+        let layout = DataLayout::parse(
+            "e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-f80:128-n8:16:32:64-S128",
+        );
+        assert_eq!((layout.pointer_size, layout.pointer_align), (8, 8));
+        assert_eq!(layout.integer_align(8), 1);
+        assert_eq!(layout.integer_align(32), 4);
+        assert_eq!(layout.integer_align(64), 8);
+        assert_eq!(layout.integer_align(128), 8);
+        assert_eq!(layout.float_align(64), 8);
+    }
+
+    #[test]
+    fn test_parse_i686() {
+        let layout = DataLayout::parse(
+            "e-m:e-p:32:32-p270:32:32-p271:32:32-p272:64:64-f64:32:64-f80:32-n8:16:32-S128",
+        );
+        assert_eq!((layout.pointer_size, layout.pointer_align), (4, 4));
+        assert_eq!(layout.integer_align(64), 4);
+        assert_eq!(layout.float_align(64), 4);
+        assert_eq!(layout.float_align(32), 4);
+    }
+}
@@ -1,6 +1,8 @@
+mod data_layout;
 mod out_stream;
 mod parse;
 
+pub use data_layout::DataLayout;
 use out_stream::OutStream;
 pub use parse::ParseSession;
 use std::collections::BTreeMap;
@@ -16,6 +18,8 @@ pub struct Session {
     pub parse_session: ParseSession,
     timers: BTreeMap<String, (Instant, Option<Duration>)>,
     errors: Vec<Box<dyn Error>>,
+    warnings: Vec<Box<dyn Error>>,
+    data_layout: Option<DataLayout>,
     pub out_stream: OutStream,
 }
 
@@ -25,6 +29,8 @@ impl Session {
             parse_session: Default::default(),
             timers: Default::default(),
             errors: Default::default(),
+            warnings: Default::default(),
+            data_layout: None,
             out_stream: if config.quiet() {
                 OutStream::void()
             } else {
@@ -74,6 +80,54 @@ impl Session {
         !self.errors.is_empty()
     }
 
+    pub fn errors(&self) -> &[Box<dyn Error>] {
+        &self.errors
+    }
+
+    pub fn emit_warning<E: 'static + Error>(&mut self, warning: E) {
+        self.warnings.push(Box::new(warning))
+    }
+
+    pub fn has_warning(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+
+    pub fn warnings(&self) -> &[Box<dyn Error>] {
+        &self.warnings
+    }
+
+    /// Size of pointers in bytes on the target, which is the host unless a target triple is given.
+    pub fn pointer_size(&self) -> u64 {
+        if let Some(data_layout) = &self.data_layout {
+            return data_layout.pointer_size;
+        }
+        match self.config.target_triple() {
+            Some(target_triple) => {
+                let arch = target_triple.split('-').next().unwrap_or_default();
+                match arch {
+                    "avr" | "msp430" => 2,
+                    "aarch64_32" | "arm64_32" => 4,
+                    _ if arch.contains("64") || arch == "s390x" => 8,
+                    _ => 4,
+                }
+            }
+            None => std::mem::size_of::<usize>() as u64,
+        }
+    }
+
+    pub fn set_data_layout(&mut self, data_layout: DataLayout) {
+        self.data_layout = Some(data_layout)
+    }
+
+    /// Data layout of the target given by the code generator, or natural
+    /// alignments when it is not given.
+    pub fn data_layout(&self) -> DataLayout {
+        match &self.data_layout {
+            Some(data_layout) => data_layout.clone(),
+            None => DataLayout::natural(self.pointer_size()),
+        }
+    }
+
     pub fn local_spell_book_root(&self) -> &Path {
         let p = self.config.input();
         if p.is_dir() {
@@ -88,6 +142,7 @@ impl Session {
 mod tests {
     use std::error::Error;
     use std::fmt::{Debug, Display, Formatter};
+    use wizc_cli::{Config, ConfigBuilder};
 
     #[test]
     fn test_start_stop() {
@@ -118,4 +173,32 @@ mod tests {
         session.emit_error(E {});
         assert_eq!(session.has_error(), true);
     }
+
+    #[test]
+    fn test_has_warnings() {
+        #[derive(Debug)]
+        struct W;
+        impl Display for W {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                f.write_str("")
+            }
+        }
+        impl Error for W {}
+        let mut session = super::Session::default();
+        session.emit_warning(W {});
+        assert!(session.has_warning());
+        assert!(!session.has_error());
+    }
+
+    #[test]
+    fn test_pointer_size() {
+        let session = super::Session::new(Config::default().target_triple("x86_64-apple-darwin"));
+        assert_eq!(session.pointer_size(), 8);
+        let session =
+            super::Session::new(Config::default().target_triple("i686-unknown-linux-gnu"));
+        assert_eq!(session.pointer_size(), 4);
+        let session =
+            super::Session::new(Config::default().target_triple("wasm32-unknown-unknown"));
+        assert_eq!(session.pointer_size(), 4);
+    }
 }
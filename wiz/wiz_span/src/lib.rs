@@ -2,13 +2,27 @@
 pub struct Location {
     offset: usize,
     line: u32,
+    /// 1-based, `0` when unknown.
+    column: u32,
 }
 
 impl Location {
     pub const DUMMY: Self = Self::new(0, 0);
 
     pub const fn new(offset: usize, line: u32) -> Self {
-        Self { offset, line }
+        Self {
+            offset,
+            line,
+            column: 0,
+        }
+    }
+
+    pub const fn with_column(self, column: u32) -> Self {
+        Self {
+            offset: self.offset,
+            line: self.line,
+            column,
+        }
     }
 
     pub fn offset(&self) -> usize {
@@ -18,6 +32,10 @@ impl Location {
     pub fn line(&self) -> u32 {
         self.line
     }
+
+    pub fn column(&self) -> u32 {
+        self.column
+    }
 }
 
 pub fn get_line_offset(s: &str, location: &Location) -> usize {
@@ -40,6 +58,8 @@ fn test_get_location() {
     let location = Location::new(1, 0);
     assert_eq!(location.offset(), 1);
     assert_eq!(location.line(), 0);
+    assert_eq!(location.column(), 0);
+    assert_eq!(location.with_column(3).column(), 3);
 }
 
 #[cfg(test)]
@@ -1,10 +1,84 @@
+use crate::syntax::expression::{ArgLabelSyntax, Expr};
 use crate::syntax::list::{ElementSyntax, ListSyntax};
 use crate::syntax::token::TokenSyntax;
+use crate::syntax::trivia::Trivia;
+use crate::syntax::Syntax;
 
 pub trait Annotatable {
     fn with_annotation(self, a: AnnotationsSyntax) -> Self;
 }
 
-pub type AnnotationsSyntax = ListSyntax<TokenSyntax>;
+pub type AnnotationsSyntax = ListSyntax<AnnotationSyntax>;
 
-pub type Annotation = ElementSyntax<TokenSyntax>;
+pub type Annotation = ElementSyntax<AnnotationSyntax>;
+
+/// Arguments of an annotation, e.g. `(Equals, Clone)` of `derive(Equals, Clone)`.
+pub type AnnotationArgumentsSyntax = ListSyntax<AnnotationArgumentSyntax>;
+
+pub type AnnotationArgument = ElementSyntax<AnnotationArgumentSyntax>;
+
+/// A literal or a name with an optional label, e.g. `name: "m"` of `link(name: "m")`.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct AnnotationArgumentSyntax {
+    pub label: Option<ArgLabelSyntax>,
+    pub value: Expr,
+}
+
+impl Syntax for AnnotationArgumentSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        match self.label {
+            None => Self {
+                label: None,
+                value: self.value.with_leading_trivia(trivia),
+            },
+            Some(label) => Self {
+                label: Some(label.with_leading_trivia(trivia)),
+                value: self.value,
+            },
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            label: self.label,
+            value: self.value.with_trailing_trivia(trivia),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct AnnotationSyntax {
+    pub name: TokenSyntax,
+    pub arguments: Option<AnnotationArgumentsSyntax>,
+}
+
+impl Syntax for AnnotationSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            name: self.name.with_leading_trivia(trivia),
+            arguments: self.arguments,
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        match self.arguments {
+            None => Self {
+                name: self.name.with_trailing_trivia(trivia),
+                arguments: None,
+            },
+            Some(arguments) => Self {
+                name: self.name,
+                arguments: Some(arguments.with_trailing_trivia(trivia)),
+            },
+        }
+    }
+}
+
+impl<T: ToString> From<T> for AnnotationSyntax {
+    fn from(name: T) -> Self {
+        Self {
+            name: TokenSyntax::from(name),
+            arguments: None,
+        }
+    }
+}
@@ -1,12 +1,19 @@
 use crate::syntax::annotation::AnnotationsSyntax;
 pub use crate::syntax::declaration::extension_syntax::{ExtensionSyntax, ProtocolConformSyntax};
+pub use crate::syntax::declaration::extern_syntax::{
+    ExternCSyntax, ExternDeclKind, ExternDeclarationSyntax,
+};
 use crate::syntax::declaration::fun_syntax::FunSyntax;
 pub use crate::syntax::declaration::properties_syntax::{
-    DeinitializerSyntax, StoredPropertySyntax, StructBodySyntax, StructPropertySyntax,
+    AssociatedTypeSyntax, AssociatedTypeValueSyntax, DeinitializerSyntax, InitializerSyntax,
+    StoredPropertySyntax, StructBodySyntax, StructPropertySyntax, SubscriptAccessorSyntax,
+    SubscriptBodySyntax, SubscriptDeclarationSyntax,
 };
 pub use crate::syntax::declaration::struct_syntax::StructSyntax;
+pub use crate::syntax::declaration::type_alias_syntax::TypeAliasSyntax;
 pub use crate::syntax::declaration::use_syntax::{
-    AliasSyntax, PackageName, PackageNameElement, UseSyntax,
+    AliasSyntax, PackageName, PackageNameElement, UseGroupElementSyntax, UseGroupSyntax,
+    UseNameSyntax, UseSyntax, UsedNameSyntax,
 };
 pub use crate::syntax::declaration::var_syntax::VarSyntax;
 use crate::syntax::file::FileSyntax;
@@ -16,9 +23,11 @@ use crate::syntax::type_name::TypeName;
 use crate::syntax::Syntax;
 
 mod extension_syntax;
+mod extern_syntax;
 pub mod fun_syntax;
 mod properties_syntax;
 mod struct_syntax;
+mod type_alias_syntax;
 mod use_syntax;
 mod var_syntax;
 
@@ -62,6 +71,7 @@ pub enum DeclKind {
     Module(ModuleSyntax),
     Extension(ExtensionSyntax),
     Use(UseSyntax),
+    TypeAlias(TypeAliasSyntax),
 }
 
 impl Syntax for DeclKind {
@@ -70,15 +80,14 @@ impl Syntax for DeclKind {
             DeclKind::Var(v) => DeclKind::Var(v.with_leading_trivia(trivia)),
             DeclKind::Fun(f) => DeclKind::Fun(f.with_leading_trivia(trivia)),
             DeclKind::Struct(s) => DeclKind::Struct(s.with_leading_trivia(trivia)),
-            DeclKind::ExternC(_) => {
-                todo!()
-            }
+            DeclKind::ExternC(e) => DeclKind::ExternC(e.with_leading_trivia(trivia)),
             DeclKind::Enum { .. } => {
                 todo!()
             }
             DeclKind::Extension(e) => DeclKind::Extension(e.with_leading_trivia(trivia)),
             DeclKind::Use(u) => DeclKind::Use(u.with_leading_trivia(trivia)),
             DeclKind::Module(m) => DeclKind::Module(m.with_leading_trivia(trivia)),
+            DeclKind::TypeAlias(t) => DeclKind::TypeAlias(t.with_leading_trivia(trivia)),
         }
     }
 
@@ -87,15 +96,14 @@ impl Syntax for DeclKind {
             DeclKind::Var(v) => DeclKind::Var(v.with_trailing_trivia(trivia)),
             DeclKind::Fun(f) => DeclKind::Fun(f.with_trailing_trivia(trivia)),
             DeclKind::Struct(s) => DeclKind::Struct(s.with_trailing_trivia(trivia)),
-            DeclKind::ExternC(_) => {
-                todo!()
-            }
+            DeclKind::ExternC(e) => DeclKind::ExternC(e.with_trailing_trivia(trivia)),
             DeclKind::Enum { .. } => {
                 todo!()
             }
             DeclKind::Extension(e) => DeclKind::Extension(e.with_trailing_trivia(trivia)),
             DeclKind::Use(u) => DeclKind::Use(u.with_trailing_trivia(trivia)),
             DeclKind::Module(m) => DeclKind::Module(m.with_trailing_trivia(trivia)),
+            DeclKind::TypeAlias(t) => DeclKind::TypeAlias(t.with_trailing_trivia(trivia)),
         }
     }
 }
@@ -112,14 +120,6 @@ impl Syntax for ModuleSyntax {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone)]
-pub struct ExternCSyntax {
-    pub extern_keyword: TokenSyntax,
-    pub left_brace: TokenSyntax,
-    pub declarations: Vec<DeclKind>,
-    pub right_brace: TokenSyntax,
-}
-
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct TypeAnnotationSyntax {
     pub colon: TokenSyntax,
@@ -0,0 +1,85 @@
+use crate::syntax::annotation::AnnotationsSyntax;
+use crate::syntax::declaration::fun_syntax::FunSyntax;
+use crate::syntax::declaration::StoredPropertySyntax;
+use crate::syntax::token::TokenSyntax;
+use crate::syntax::trivia::Trivia;
+use crate::syntax::Syntax;
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ExternCSyntax {
+    pub extern_keyword: TokenSyntax,
+    pub left_brace: TokenSyntax,
+    pub declarations: Vec<ExternDeclarationSyntax>,
+    pub right_brace: TokenSyntax,
+}
+
+impl Syntax for ExternCSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            extern_keyword: self.extern_keyword.with_leading_trivia(trivia),
+            left_brace: self.left_brace,
+            declarations: self.declarations,
+            right_brace: self.right_brace,
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            extern_keyword: self.extern_keyword,
+            left_brace: self.left_brace,
+            declarations: self.declarations,
+            right_brace: self.right_brace.with_trailing_trivia(trivia),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ExternDeclarationSyntax {
+    pub annotations: Option<AnnotationsSyntax>,
+    pub kind: ExternDeclKind,
+}
+
+impl Syntax for ExternDeclarationSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        match self.annotations {
+            None => Self {
+                annotations: None,
+                kind: self.kind.with_leading_trivia(trivia),
+            },
+            Some(annotations) => Self {
+                annotations: Some(annotations.with_leading_trivia(trivia)),
+                kind: self.kind,
+            },
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            annotations: self.annotations,
+            kind: self.kind.with_trailing_trivia(trivia),
+        }
+    }
+}
+
+/// Declarations allowed in an `extern` block, which are defined outside of wiz.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum ExternDeclKind {
+    Var(Box<StoredPropertySyntax>),
+    Fun(Box<FunSyntax>),
+}
+
+impl Syntax for ExternDeclKind {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        match self {
+            ExternDeclKind::Var(v) => ExternDeclKind::Var(Box::new(v.with_leading_trivia(trivia))),
+            ExternDeclKind::Fun(f) => ExternDeclKind::Fun(Box::new(f.with_leading_trivia(trivia))),
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        match self {
+            ExternDeclKind::Var(v) => ExternDeclKind::Var(Box::new(v.with_trailing_trivia(trivia))),
+            ExternDeclKind::Fun(f) => ExternDeclKind::Fun(Box::new(f.with_trailing_trivia(trivia))),
+        }
+    }
+}
@@ -1,5 +1,5 @@
 pub use crate::syntax::declaration::fun_syntax::arg_def::{
-    ArgDef, ArgDefElementSyntax, ArgDefListSyntax, SelfArgDefSyntax, ValueArgDef,
+    ArgDef, ArgDefElementSyntax, ArgDefListSyntax, ArgDefaultSyntax, SelfArgDefSyntax, ValueArgDef,
 };
 pub use crate::syntax::declaration::fun_syntax::body_def::{ExprFunBodySyntax, FunBody};
 use crate::syntax::declaration::TypeAnnotationSyntax;
@@ -1,3 +1,4 @@
+use crate::syntax::expression::Expr;
 use crate::syntax::list::{ElementSyntax, ListSyntax};
 use crate::syntax::token::TokenSyntax;
 use crate::syntax::trivia::Trivia;
@@ -32,6 +33,7 @@ pub struct ValueArgDef {
     pub name: TokenSyntax,
     pub colon: TokenSyntax,
     pub type_name: TypeName,
+    pub default: Option<Box<ArgDefaultSyntax>>,
 }
 
 impl Syntax for ValueArgDef {
@@ -42,22 +44,56 @@ impl Syntax for ValueArgDef {
                 name: self.name,
                 colon: self.colon,
                 type_name: self.type_name,
+                default: self.default,
             },
             None => Self {
                 label: None,
                 name: self.name.with_leading_trivia(trivia),
                 colon: self.colon,
                 type_name: self.type_name,
+                default: self.default,
             },
         }
     }
 
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        match self.default {
+            None => Self {
+                label: self.label,
+                name: self.name,
+                colon: self.colon,
+                type_name: self.type_name.with_trailing_trivia(trivia),
+                default: None,
+            },
+            Some(default) => Self {
+                label: self.label,
+                name: self.name,
+                colon: self.colon,
+                type_name: self.type_name,
+                default: Some(Box::new(default.with_trailing_trivia(trivia))),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ArgDefaultSyntax {
+    pub equal: TokenSyntax,
+    pub value: Expr,
+}
+
+impl Syntax for ArgDefaultSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            equal: self.equal.with_leading_trivia(trivia),
+            value: self.value,
+        }
+    }
+
     fn with_trailing_trivia(self, trivia: Trivia) -> Self {
         Self {
-            label: self.label,
-            name: self.name,
-            colon: self.colon,
-            type_name: self.type_name.with_trailing_trivia(trivia),
+            equal: self.equal,
+            value: self.value.with_trailing_trivia(trivia),
         }
     }
 }
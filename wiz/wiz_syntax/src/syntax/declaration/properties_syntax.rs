@@ -1,7 +1,8 @@
-use crate::syntax::declaration::fun_syntax::{FunBody, FunSyntax};
+use crate::syntax::declaration::fun_syntax::{ArgDefListSyntax, FunBody, FunSyntax};
 use crate::syntax::declaration::TypeAnnotationSyntax;
 use crate::syntax::token::TokenSyntax;
 use crate::syntax::trivia::Trivia;
+use crate::syntax::type_name::TypeName;
 use crate::syntax::Syntax;
 
 #[derive(Debug, Eq, PartialEq, Clone, Default)]
@@ -33,7 +34,10 @@ impl Syntax for StructBodySyntax {
 pub enum StructPropertySyntax {
     StoredProperty(StoredPropertySyntax),
     ComputedProperty,
+    Init(InitializerSyntax),
+    Subscript(SubscriptDeclarationSyntax),
     Deinit(DeinitializerSyntax),
+    AssociatedType(AssociatedTypeSyntax),
     Method(FunSyntax),
 }
 
@@ -44,9 +48,18 @@ impl Syntax for StructPropertySyntax {
                 StructPropertySyntax::StoredProperty(s.with_leading_trivia(trivia))
             }
             StructPropertySyntax::ComputedProperty => StructPropertySyntax::ComputedProperty,
+            StructPropertySyntax::Init(i) => {
+                StructPropertySyntax::Init(i.with_leading_trivia(trivia))
+            }
+            StructPropertySyntax::Subscript(s) => {
+                StructPropertySyntax::Subscript(s.with_leading_trivia(trivia))
+            }
             StructPropertySyntax::Deinit(d) => {
                 StructPropertySyntax::Deinit(d.with_leading_trivia(trivia))
             }
+            StructPropertySyntax::AssociatedType(a) => {
+                StructPropertySyntax::AssociatedType(a.with_leading_trivia(trivia))
+            }
             StructPropertySyntax::Method(m) => {
                 StructPropertySyntax::Method(m.with_leading_trivia(trivia))
             }
@@ -59,9 +72,18 @@ impl Syntax for StructPropertySyntax {
                 StructPropertySyntax::StoredProperty(s.with_trailing_trivia(trivia))
             }
             StructPropertySyntax::ComputedProperty => StructPropertySyntax::ComputedProperty,
+            StructPropertySyntax::Init(i) => {
+                StructPropertySyntax::Init(i.with_trailing_trivia(trivia))
+            }
+            StructPropertySyntax::Subscript(s) => {
+                StructPropertySyntax::Subscript(s.with_trailing_trivia(trivia))
+            }
             StructPropertySyntax::Deinit(d) => {
                 StructPropertySyntax::Deinit(d.with_trailing_trivia(trivia))
             }
+            StructPropertySyntax::AssociatedType(a) => {
+                StructPropertySyntax::AssociatedType(a.with_trailing_trivia(trivia))
+            }
             StructPropertySyntax::Method(m) => {
                 StructPropertySyntax::Method(m.with_trailing_trivia(trivia))
             }
@@ -94,6 +116,107 @@ impl Syntax for StoredPropertySyntax {
     }
 }
 
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct InitializerSyntax {
+    pub init_keyword: TokenSyntax,
+    pub arg_defs: ArgDefListSyntax,
+    pub body: FunBody,
+}
+
+impl Syntax for InitializerSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            init_keyword: self.init_keyword.with_leading_trivia(trivia),
+            arg_defs: self.arg_defs,
+            body: self.body,
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            init_keyword: self.init_keyword,
+            arg_defs: self.arg_defs,
+            body: self.body.with_trailing_trivia(trivia),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SubscriptDeclarationSyntax {
+    pub subscript_keyword: TokenSyntax,
+    pub arg_defs: ArgDefListSyntax,
+    pub return_type: TypeAnnotationSyntax,
+    pub body: SubscriptBodySyntax,
+}
+
+impl Syntax for SubscriptDeclarationSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            subscript_keyword: self.subscript_keyword.with_leading_trivia(trivia),
+            arg_defs: self.arg_defs,
+            return_type: self.return_type,
+            body: self.body,
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            subscript_keyword: self.subscript_keyword,
+            arg_defs: self.arg_defs,
+            return_type: self.return_type,
+            body: self.body.with_trailing_trivia(trivia),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SubscriptBodySyntax {
+    pub open: TokenSyntax,
+    pub accessors: Vec<SubscriptAccessorSyntax>,
+    pub close: TokenSyntax,
+}
+
+impl Syntax for SubscriptBodySyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            open: self.open.with_leading_trivia(trivia),
+            accessors: self.accessors,
+            close: self.close,
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            open: self.open,
+            accessors: self.accessors,
+            close: self.close.with_trailing_trivia(trivia),
+        }
+    }
+}
+
+/// `get { .. }` or `set { .. }` of a subscript.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SubscriptAccessorSyntax {
+    pub keyword: TokenSyntax,
+    pub body: FunBody,
+}
+
+impl Syntax for SubscriptAccessorSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            keyword: self.keyword.with_leading_trivia(trivia),
+            body: self.body,
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            keyword: self.keyword,
+            body: self.body.with_trailing_trivia(trivia),
+        }
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct DeinitializerSyntax {
     pub deinit_keyword: TokenSyntax,
@@ -115,3 +238,57 @@ impl Syntax for DeinitializerSyntax {
         }
     }
 }
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct AssociatedTypeSyntax {
+    pub type_keyword: TokenSyntax,
+    pub name: TokenSyntax,
+    pub value: Option<AssociatedTypeValueSyntax>,
+}
+
+impl Syntax for AssociatedTypeSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            type_keyword: self.type_keyword.with_leading_trivia(trivia),
+            name: self.name,
+            value: self.value,
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        match self.value {
+            None => Self {
+                type_keyword: self.type_keyword,
+                name: self.name.with_trailing_trivia(trivia),
+                value: None,
+            },
+            Some(value) => Self {
+                type_keyword: self.type_keyword,
+                name: self.name,
+                value: Some(value.with_trailing_trivia(trivia)),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct AssociatedTypeValueSyntax {
+    pub equal: TokenSyntax,
+    pub type_: TypeName,
+}
+
+impl Syntax for AssociatedTypeValueSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            equal: self.equal.with_leading_trivia(trivia),
+            type_: self.type_,
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            equal: self.equal,
+            type_: self.type_.with_trailing_trivia(trivia),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::syntax::token::TokenSyntax;
+use crate::syntax::trivia::Trivia;
+use crate::syntax::type_name::{TypeName, TypeParameterListSyntax};
+use crate::syntax::Syntax;
+
+/// `typealias Name<T> = Type`
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct TypeAliasSyntax {
+    pub typealias_keyword: TokenSyntax,
+    pub name: TokenSyntax,
+    pub type_params: Option<TypeParameterListSyntax>,
+    pub equal: TokenSyntax,
+    pub type_: TypeName,
+}
+
+impl Syntax for TypeAliasSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            typealias_keyword: self.typealias_keyword.with_leading_trivia(trivia),
+            name: self.name,
+            type_params: self.type_params,
+            equal: self.equal,
+            type_: self.type_,
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            typealias_keyword: self.typealias_keyword,
+            name: self.name,
+            type_params: self.type_params,
+            equal: self.equal,
+            type_: self.type_.with_trailing_trivia(trivia),
+        }
+    }
+}
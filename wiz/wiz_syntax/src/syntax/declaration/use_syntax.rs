@@ -1,21 +1,81 @@
+use crate::syntax::list::{ElementSyntax, ListSyntax};
 use crate::syntax::token::TokenSyntax;
 use crate::syntax::trivia::Trivia;
 use crate::syntax::Syntax;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct UseSyntax {
+    pub public_keyword: Option<TokenSyntax>,
     pub use_keyword: TokenSyntax,
     pub package_name: Option<PackageName>,
-    pub used_name: TokenSyntax,
-    pub alias: Option<AliasSyntax>,
+    pub used_name: UsedNameSyntax,
 }
 
 impl Syntax for UseSyntax {
     fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        match self.public_keyword {
+            None => Self {
+                public_keyword: None,
+                use_keyword: self.use_keyword.with_leading_trivia(trivia),
+                package_name: self.package_name,
+                used_name: self.used_name,
+            },
+            Some(public_keyword) => Self {
+                public_keyword: Some(public_keyword.with_leading_trivia(trivia)),
+                use_keyword: self.use_keyword,
+                package_name: self.package_name,
+                used_name: self.used_name,
+            },
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
         Self {
-            use_keyword: self.use_keyword.with_leading_trivia(trivia),
+            public_keyword: self.public_keyword,
+            use_keyword: self.use_keyword,
             package_name: self.package_name,
-            used_name: self.used_name,
+            used_name: self.used_name.with_trailing_trivia(trivia),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum UsedNameSyntax {
+    Name(UseNameSyntax),
+    Group(UseGroupSyntax),
+}
+
+impl Syntax for UsedNameSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        match self {
+            UsedNameSyntax::Name(n) => UsedNameSyntax::Name(n.with_leading_trivia(trivia)),
+            UsedNameSyntax::Group(g) => UsedNameSyntax::Group(g.with_leading_trivia(trivia)),
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        match self {
+            UsedNameSyntax::Name(n) => UsedNameSyntax::Name(n.with_trailing_trivia(trivia)),
+            UsedNameSyntax::Group(g) => UsedNameSyntax::Group(g.with_trailing_trivia(trivia)),
+        }
+    }
+}
+
+/// `{ b, c as d }` of `use a::{ b, c as d }`
+pub type UseGroupSyntax = ListSyntax<UseNameSyntax>;
+pub type UseGroupElementSyntax = ElementSyntax<UseNameSyntax>;
+
+/// `name`, `*` or `name as alias`
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct UseNameSyntax {
+    pub name: TokenSyntax,
+    pub alias: Option<AliasSyntax>,
+}
+
+impl Syntax for UseNameSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            name: self.name.with_leading_trivia(trivia),
             alias: self.alias,
         }
     }
@@ -23,15 +83,11 @@ impl Syntax for UseSyntax {
     fn with_trailing_trivia(self, trivia: Trivia) -> Self {
         match self.alias {
             None => Self {
-                use_keyword: self.use_keyword,
-                package_name: self.package_name,
-                used_name: self.used_name.with_trailing_trivia(trivia),
+                name: self.name.with_trailing_trivia(trivia),
                 alias: None,
             },
             Some(alias) => Self {
-                use_keyword: self.use_keyword,
-                package_name: self.package_name,
-                used_name: self.used_name,
+                name: self.name,
                 alias: Some(alias.with_trailing_trivia(trivia)),
             },
         }
@@ -2,12 +2,14 @@ use crate::syntax::expression::Expr;
 use crate::syntax::token::TokenSyntax;
 use crate::syntax::trivia::Trivia;
 use crate::syntax::Syntax;
+use wiz_span::Location;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct BinaryOperationSyntax {
     pub left: Box<Expr>,
     pub operator: TokenSyntax,
     pub right: Box<Expr>,
+    pub span: Location,
 }
 
 impl Syntax for BinaryOperationSyntax {
@@ -16,6 +18,7 @@ impl Syntax for BinaryOperationSyntax {
             left: Box::new(self.left.with_leading_trivia(trivia)),
             operator: self.operator,
             right: self.right,
+            span: self.span,
         }
     }
 
@@ -24,6 +27,11 @@ impl Syntax for BinaryOperationSyntax {
             left: self.left,
             operator: self.operator,
             right: Box::new(self.right.with_trailing_trivia(trivia)),
+            span: self.span,
         }
     }
+
+    fn span(&self) -> Location {
+        self.span.clone()
+    }
 }
@@ -4,12 +4,14 @@ use crate::syntax::statement::Stmt;
 use crate::syntax::token::TokenSyntax;
 use crate::syntax::trivia::Trivia;
 use crate::syntax::Syntax;
+use wiz_span::Location;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct CallExprSyntax {
     pub target: Box<Expr>,
     pub args: Option<CallArgListSyntax>,
     pub tailing_lambda: Option<LambdaSyntax>,
+    pub span: Location,
 }
 
 impl Syntax for CallExprSyntax {
@@ -18,6 +20,7 @@ impl Syntax for CallExprSyntax {
             target: Box::new(self.target.with_leading_trivia(trivia)),
             args: self.args,
             tailing_lambda: self.tailing_lambda,
+            span: self.span,
         }
     }
 
@@ -28,20 +31,27 @@ impl Syntax for CallExprSyntax {
                     target: Box::new(self.target.with_trailing_trivia(trivia)),
                     args: None,
                     tailing_lambda: None,
+                    span: self.span,
                 },
                 Some(args) => Self {
                     target: self.target,
                     args: Some(args.with_trailing_trivia(trivia)),
                     tailing_lambda: None,
+                    span: self.span,
                 },
             },
             Some(tailing_lambda) => Self {
                 target: self.target,
                 args: self.args,
                 tailing_lambda: Some(tailing_lambda.with_trailing_trivia(trivia)),
+                span: self.span,
             },
         }
     }
+
+    fn span(&self) -> Location {
+        self.span.clone()
+    }
 }
 
 pub type CallArgListSyntax = ListSyntax<CallArg>;
@@ -3,11 +3,13 @@ use crate::syntax::list::{ElementSyntax, ListSyntax};
 use crate::syntax::token::TokenSyntax;
 use crate::syntax::trivia::Trivia;
 use crate::syntax::Syntax;
+use wiz_span::Location;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct SubscriptSyntax {
     pub target: Box<Expr>,
     pub idx_or_keys: SubscriptIndexListSyntax,
+    pub span: Location,
 }
 
 impl Syntax for SubscriptSyntax {
@@ -15,6 +17,7 @@ impl Syntax for SubscriptSyntax {
         Self {
             target: Box::new(self.target.with_leading_trivia(trivia)),
             idx_or_keys: self.idx_or_keys,
+            span: self.span,
         }
     }
 
@@ -22,8 +25,13 @@ impl Syntax for SubscriptSyntax {
         Self {
             target: self.target,
             idx_or_keys: self.idx_or_keys.with_trailing_trivia(trivia),
+            span: self.span,
         }
     }
+
+    fn span(&self) -> Location {
+        self.span.clone()
+    }
 }
 
 pub type SubscriptIndexListSyntax = ListSyntax<Expr>;
@@ -3,12 +3,14 @@ use crate::syntax::token::TokenSyntax;
 use crate::syntax::trivia::Trivia;
 use crate::syntax::type_name::TypeName;
 use crate::syntax::Syntax;
+use wiz_span::Location;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct TypeCastSyntax {
     pub target: Box<Expr>,
     pub operator: TokenSyntax,
     pub type_: TypeName,
+    pub span: Location,
 }
 
 impl Syntax for TypeCastSyntax {
@@ -17,6 +19,7 @@ impl Syntax for TypeCastSyntax {
             target: Box::new(self.target.with_leading_trivia(trivia)),
             operator: self.operator,
             type_: self.type_,
+            span: self.span,
         }
     }
 
@@ -25,6 +28,11 @@ impl Syntax for TypeCastSyntax {
             target: self.target,
             operator: self.operator,
             type_: self.type_.with_trailing_trivia(trivia),
+            span: self.span,
         }
     }
+
+    fn span(&self) -> Location {
+        self.span.clone()
+    }
 }
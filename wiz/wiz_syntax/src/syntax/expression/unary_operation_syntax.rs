@@ -2,6 +2,7 @@ use crate::syntax::expression::Expr;
 use crate::syntax::token::TokenSyntax;
 use crate::syntax::trivia::Trivia;
 use crate::syntax::Syntax;
+use wiz_span::Location;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum UnaryOperationSyntax {
@@ -59,6 +60,7 @@ impl Syntax for PrefixUnaryOperationSyntax {
 pub struct PostfixUnaryOperationSyntax {
     pub target: Box<Expr>,
     pub operator: TokenSyntax,
+    pub span: Location,
 }
 
 impl Syntax for PostfixUnaryOperationSyntax {
@@ -66,6 +68,7 @@ impl Syntax for PostfixUnaryOperationSyntax {
         Self {
             target: Box::new(self.target.with_leading_trivia(trivia)),
             operator: self.operator,
+            span: self.span,
         }
     }
 
@@ -73,6 +76,11 @@ impl Syntax for PostfixUnaryOperationSyntax {
         Self {
             target: self.target,
             operator: self.operator.with_trailing_trivia(trivia),
+            span: self.span,
         }
     }
+
+    fn span(&self) -> Location {
+        self.span.clone()
+    }
 }
@@ -1,10 +1,13 @@
 use crate::syntax::declaration::DeclarationSyntax;
 use crate::syntax::trivia::Trivia;
 use crate::syntax::Syntax;
+use std::path::PathBuf;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct WizFile {
     pub name: String,
+    /// Source file, `None` for inline modules and sources not read from a file.
+    pub path: Option<PathBuf>,
     pub syntax: FileSyntax,
 }
 
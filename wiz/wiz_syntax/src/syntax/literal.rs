@@ -1,6 +1,7 @@
 use crate::syntax::token::TokenSyntax;
 use crate::syntax::trivia::Trivia;
 use crate::syntax::Syntax;
+use wiz_span::Location;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum LiteralSyntax {
@@ -13,6 +14,11 @@ pub enum LiteralSyntax {
     },
     Boolean(TokenSyntax),
     Null,
+    /// `#file`, `#line` or `#column`
+    SourceLocation {
+        token: TokenSyntax,
+        span: Location,
+    },
 }
 
 impl Syntax for LiteralSyntax {
@@ -35,6 +41,10 @@ impl Syntax for LiteralSyntax {
             LiteralSyntax::Null => {
                 todo!()
             }
+            LiteralSyntax::SourceLocation { token, span } => LiteralSyntax::SourceLocation {
+                token: token.with_leading_trivia(trivia),
+                span,
+            },
         }
     }
 
@@ -57,6 +67,10 @@ impl Syntax for LiteralSyntax {
             LiteralSyntax::Null => {
                 todo!()
             }
+            LiteralSyntax::SourceLocation { token, span } => LiteralSyntax::SourceLocation {
+                token: token.with_trailing_trivia(trivia),
+                span,
+            },
         }
     }
 }
@@ -1,4 +1,5 @@
 mod assignment_syntax;
+mod defer_syntax;
 mod for_loop_syntax;
 mod while_loop_syntax;
 
@@ -7,6 +8,7 @@ use crate::syntax::expression::Expr;
 pub use crate::syntax::statement::assignment_syntax::{
     AssignmentAndOperatorSyntax, AssignmentStmt, AssignmentSyntax,
 };
+pub use crate::syntax::statement::defer_syntax::DeferSyntax;
 pub use crate::syntax::statement::for_loop_syntax::ForLoopSyntax;
 pub use crate::syntax::statement::while_loop_syntax::WhileLoopSyntax;
 use crate::syntax::trivia::Trivia;
@@ -18,6 +20,7 @@ pub enum Stmt {
     Expr(Expr),
     Assignment(AssignmentStmt),
     Loop(LoopStmt),
+    Defer(DeferSyntax),
 }
 
 impl Syntax for Stmt {
@@ -27,6 +30,7 @@ impl Syntax for Stmt {
             Stmt::Expr(e) => Stmt::Expr(e.with_leading_trivia(trivia)),
             Stmt::Assignment(a) => Stmt::Assignment(a.with_leading_trivia(trivia)),
             Stmt::Loop(l) => Stmt::Loop(l.with_leading_trivia(trivia)),
+            Stmt::Defer(d) => Stmt::Defer(d.with_leading_trivia(trivia)),
         }
     }
 
@@ -36,6 +40,7 @@ impl Syntax for Stmt {
             Stmt::Expr(e) => Stmt::Expr(e.with_trailing_trivia(trivia)),
             Stmt::Assignment(a) => Stmt::Assignment(a.with_trailing_trivia(trivia)),
             Stmt::Loop(l) => Stmt::Loop(l.with_trailing_trivia(trivia)),
+            Stmt::Defer(d) => Stmt::Defer(d.with_trailing_trivia(trivia)),
         }
     }
 }
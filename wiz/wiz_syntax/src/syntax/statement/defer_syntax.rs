@@ -0,0 +1,26 @@
+use crate::syntax::block::BlockSyntax;
+use crate::syntax::token::TokenSyntax;
+use crate::syntax::trivia::Trivia;
+use crate::syntax::Syntax;
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct DeferSyntax {
+    pub defer_keyword: TokenSyntax,
+    pub block: BlockSyntax,
+}
+
+impl Syntax for DeferSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            defer_keyword: self.defer_keyword.with_leading_trivia(trivia),
+            block: self.block,
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            defer_keyword: self.defer_keyword,
+            block: self.block.with_trailing_trivia(trivia),
+        }
+    }
+}
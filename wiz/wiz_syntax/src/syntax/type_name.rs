@@ -1,3 +1,4 @@
+use crate::syntax::expression::Expr;
 use crate::syntax::list::{ElementSyntax, ListSyntax};
 use crate::syntax::token::TokenSyntax;
 use crate::syntax::trivia::Trivia;
@@ -10,6 +11,8 @@ pub enum TypeName {
     Decorated(Box<DecoratedTypeName>),
     Parenthesized(ParenthesizedTypeName),
     Array(Box<ArrayTypeSyntax>),
+    Slice(Box<SliceTypeSyntax>),
+    Function(Box<FunctionTypeSyntax>),
 }
 
 impl Syntax for TypeName {
@@ -22,6 +25,8 @@ impl Syntax for TypeName {
             TypeName::Decorated(d) => TypeName::Decorated(Box::new(d.with_leading_trivia(trivia))),
             TypeName::Parenthesized(p) => TypeName::Parenthesized(p.with_leading_trivia(trivia)),
             TypeName::Array(a) => TypeName::Array(Box::new(a.with_leading_trivia(trivia))),
+            TypeName::Slice(a) => TypeName::Slice(Box::new(a.with_leading_trivia(trivia))),
+            TypeName::Function(f) => TypeName::Function(Box::new(f.with_leading_trivia(trivia))),
         }
     }
 
@@ -34,6 +39,8 @@ impl Syntax for TypeName {
             TypeName::Decorated(d) => TypeName::Decorated(Box::new(d.with_trailing_trivia(trivia))),
             TypeName::Parenthesized(p) => TypeName::Parenthesized(p.with_trailing_trivia(trivia)),
             TypeName::Array(a) => TypeName::Array(Box::new(a.with_trailing_trivia(trivia))),
+            TypeName::Slice(a) => TypeName::Slice(Box::new(a.with_trailing_trivia(trivia))),
+            TypeName::Function(f) => TypeName::Function(Box::new(f.with_trailing_trivia(trivia))),
         }
     }
 }
@@ -136,7 +143,7 @@ impl Syntax for SimpleTypeName {
     fn with_leading_trivia(self, trivia: Trivia) -> Self {
         Self {
             name: self.name.with_leading_trivia(trivia),
-            type_args: None,
+            type_args: self.type_args,
         }
     }
 
@@ -296,7 +303,7 @@ pub struct ArrayTypeSyntax {
     pub open: TokenSyntax,
     pub type_: TypeName,
     pub semicolon: TokenSyntax,
-    pub size: TokenSyntax,
+    pub size: Expr,
     pub close: TokenSyntax,
 }
 
@@ -321,3 +328,53 @@ impl Syntax for ArrayTypeSyntax {
         }
     }
 }
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SliceTypeSyntax {
+    pub open: TokenSyntax,
+    pub type_: TypeName,
+    pub close: TokenSyntax,
+}
+
+impl Syntax for SliceTypeSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            open: self.open.with_leading_trivia(trivia),
+            type_: self.type_,
+            close: self.close,
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            open: self.open,
+            type_: self.type_,
+            close: self.close.with_trailing_trivia(trivia),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct FunctionTypeSyntax {
+    pub arguments: TypeArgumentListSyntax,
+    pub arrow: TokenSyntax,
+    pub return_type: TypeName,
+}
+
+impl Syntax for FunctionTypeSyntax {
+    fn with_leading_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            arguments: self.arguments.with_leading_trivia(trivia),
+            arrow: self.arrow,
+            return_type: self.return_type,
+        }
+    }
+
+    fn with_trailing_trivia(self, trivia: Trivia) -> Self {
+        Self {
+            arguments: self.arguments,
+            arrow: self.arrow,
+            return_type: self.return_type.with_trailing_trivia(trivia),
+        }
+    }
+}
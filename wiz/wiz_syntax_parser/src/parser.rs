@@ -1,4 +1,5 @@
 use nom_locate::LocatedSpan;
+use wiz_span::Location;
 
 pub mod error;
 #[cfg(test)]
@@ -6,3 +7,21 @@ mod tests;
 pub mod wiz;
 
 pub type Span<'a> = LocatedSpan<&'a str>;
+
+/// Parser input that knows where it is in the source file.
+pub trait Located {
+    fn location(&self) -> Location;
+}
+
+impl Located for &str {
+    fn location(&self) -> Location {
+        Location::DUMMY
+    }
+}
+
+impl Located for Span<'_> {
+    fn location(&self) -> Location {
+        Location::new(self.location_offset(), self.location_line())
+            .with_column(self.get_utf8_column() as u32)
+    }
+}
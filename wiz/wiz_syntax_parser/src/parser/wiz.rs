@@ -35,6 +35,7 @@ pub fn parse_from_string<P: AsRef<Path>>(
             } else {
                 Ok(WizFile {
                     name: name.unwrap_or("<unknown source>").to_string(),
+                    path: src_path.map(|p| p.as_ref().to_path_buf()),
                     syntax: f,
                 })
             }
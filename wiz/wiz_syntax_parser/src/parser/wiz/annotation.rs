@@ -1,32 +1,46 @@
 use crate::parser::wiz::character::comma;
+use crate::parser::wiz::expression::{arg_label_syntax, literal_expr, name_expr};
 use crate::parser::wiz::lexical_structure::{identifier, token, whitespace0};
+use crate::parser::Located;
+use nom::branch::alt;
 use nom::combinator::{map, opt};
 use nom::multi::many0;
 use nom::sequence::tuple;
-use nom::{AsChar, Compare, FindSubstring, IResult, InputIter, InputLength, InputTake, Slice};
+use nom::{
+    AsChar, Compare, ExtendInto, FindSubstring, IResult, InputIter, InputLength, InputTake,
+    InputTakeAtPosition, Offset, Slice,
+};
 use std::ops::{Range, RangeFrom};
-use wiz_syntax::syntax::annotation::{Annotation, AnnotationsSyntax};
+use wiz_syntax::syntax::annotation::{
+    Annotation, AnnotationArgument, AnnotationArgumentSyntax, AnnotationArgumentsSyntax,
+    AnnotationSyntax, AnnotationsSyntax,
+};
 use wiz_syntax::syntax::token::TokenSyntax;
 use wiz_syntax::syntax::Syntax;
 
 pub fn annotations_syntax<I>(s: I) -> IResult<I, AnnotationsSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
         + InputLength
         + ToString
         + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(
         tuple((
             token("#["),
-            many0(tuple((whitespace0, identifier, whitespace0, comma))),
-            opt(tuple((whitespace0, identifier))),
+            many0(tuple((whitespace0, annotation_syntax, whitespace0, comma))),
+            opt(tuple((whitespace0, annotation_syntax))),
             whitespace0,
             token("]"),
         )),
@@ -34,14 +48,14 @@ where
             let mut annotations: Vec<_> = v
                 .into_iter()
                 .map(|(lws, a, rws, cma)| Annotation {
-                    element: TokenSyntax::from(a).with_leading_trivia(lws),
+                    element: a.with_leading_trivia(lws),
                     trailing_comma: Some(cma.with_leading_trivia(rws)),
                 })
                 .collect();
 
             if let Some((ws, p)) = a {
                 annotations.push(Annotation {
-                    element: TokenSyntax::from(p).with_leading_trivia(ws),
+                    element: p.with_leading_trivia(ws),
                     trailing_comma: None,
                 });
             }
@@ -55,12 +69,144 @@ where
     )(s)
 }
 
+// <annotation> ::= <identifier> <annotation_arguments>?
+pub fn annotation_syntax<I>(s: I) -> IResult<I, AnnotationSyntax>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(
+        tuple((
+            identifier,
+            opt(tuple((whitespace0, annotation_arguments_syntax))),
+        )),
+        |(name, arguments)| AnnotationSyntax {
+            name: TokenSyntax::from(name),
+            arguments: arguments.map(|(ws, a)| a.with_leading_trivia(ws)),
+        },
+    )(s)
+}
+
+// <annotation_arguments> ::= "(" (<annotation_argument> ("," <annotation_argument>)* ","?)? ")"
+pub fn annotation_arguments_syntax<I>(s: I) -> IResult<I, AnnotationArgumentsSyntax>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(
+        tuple((
+            token("("),
+            many0(tuple((
+                whitespace0,
+                annotation_argument_syntax,
+                whitespace0,
+                comma,
+            ))),
+            opt(tuple((whitespace0, annotation_argument_syntax))),
+            whitespace0,
+            token(")"),
+        )),
+        |(open, v, a, tws, close)| {
+            let mut arguments: Vec<_> = v
+                .into_iter()
+                .map(|(lws, a, rws, cma)| AnnotationArgument {
+                    element: a.with_leading_trivia(lws),
+                    trailing_comma: Some(cma.with_leading_trivia(rws)),
+                })
+                .collect();
+
+            if let Some((ws, p)) = a {
+                arguments.push(AnnotationArgument {
+                    element: p.with_leading_trivia(ws),
+                    trailing_comma: None,
+                });
+            }
+
+            AnnotationArgumentsSyntax {
+                open,
+                elements: arguments,
+                close: close.with_leading_trivia(tws),
+            }
+        },
+    )(s)
+}
+
+// <annotation_argument> ::= <arg_label>? (<literal> | <name_expr>)
+pub fn annotation_argument_syntax<I>(s: I) -> IResult<I, AnnotationArgumentSyntax>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    alt((
+        map(
+            tuple((
+                arg_label_syntax,
+                whitespace0,
+                alt((literal_expr, name_expr)),
+            )),
+            |(label, ws, value)| AnnotationArgumentSyntax {
+                label: Some(label),
+                value: value.with_leading_trivia(ws),
+            },
+        ),
+        map(alt((literal_expr, name_expr)), |value| {
+            AnnotationArgumentSyntax { label: None, value }
+        }),
+    ))(s)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::parser::tests::check;
     use crate::parser::wiz::annotation::annotations_syntax;
-    use wiz_syntax::syntax::annotation::{Annotation, AnnotationsSyntax};
+    use wiz_syntax::syntax::annotation::{
+        Annotation, AnnotationArgument, AnnotationArgumentSyntax, AnnotationArgumentsSyntax,
+        AnnotationSyntax, AnnotationsSyntax,
+    };
+    use wiz_syntax::syntax::expression::{ArgLabelSyntax, Expr, NameExprSyntax};
+    use wiz_syntax::syntax::literal::LiteralSyntax;
     use wiz_syntax::syntax::token::TokenSyntax;
+    use wiz_syntax::syntax::trivia::{Trivia, TriviaPiece};
+    use wiz_syntax::syntax::Syntax;
 
     #[test]
     fn test_annotations() {
@@ -70,11 +216,122 @@ mod tests {
             AnnotationsSyntax {
                 open: TokenSyntax::from("#["),
                 elements: vec![Annotation {
-                    element: TokenSyntax::from("no_mangle"),
+                    element: AnnotationSyntax::from("no_mangle"),
                     trailing_comma: None,
                 }],
                 close: TokenSyntax::from("]"),
             },
         );
     }
+
+    #[test]
+    fn test_annotations_with_arguments() {
+        check(
+            "#[test, derive(Equals, Clone)]",
+            annotations_syntax,
+            AnnotationsSyntax {
+                open: TokenSyntax::from("#["),
+                elements: vec![
+                    Annotation {
+                        element: AnnotationSyntax::from("test"),
+                        trailing_comma: Some(TokenSyntax::from(",")),
+                    },
+                    Annotation {
+                        element: AnnotationSyntax {
+                            name: TokenSyntax::from("derive"),
+                            arguments: Some(AnnotationArgumentsSyntax {
+                                open: TokenSyntax::from("("),
+                                elements: vec![
+                                    AnnotationArgument {
+                                        element: AnnotationArgumentSyntax {
+                                            label: None,
+                                            value: Expr::Name(NameExprSyntax::simple(
+                                                TokenSyntax::from("Equals"),
+                                            )),
+                                        },
+                                        trailing_comma: Some(TokenSyntax::from(",")),
+                                    },
+                                    AnnotationArgument {
+                                        element: AnnotationArgumentSyntax {
+                                            label: None,
+                                            value: Expr::Name(NameExprSyntax::simple(
+                                                TokenSyntax::from("Clone"),
+                                            )),
+                                        }
+                                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                                        trailing_comma: None,
+                                    },
+                                ],
+                                close: TokenSyntax::from(")"),
+                            }),
+                        }
+                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                        trailing_comma: None,
+                    },
+                ],
+                close: TokenSyntax::from("]"),
+            },
+        );
+    }
+
+    #[test]
+    fn test_annotations_with_literal_arguments() {
+        check(
+            r#"#[deprecated("use X"), link(name: "m")]"#,
+            annotations_syntax,
+            AnnotationsSyntax {
+                open: TokenSyntax::from("#["),
+                elements: vec![
+                    Annotation {
+                        element: AnnotationSyntax {
+                            name: TokenSyntax::from("deprecated"),
+                            arguments: Some(AnnotationArgumentsSyntax {
+                                open: TokenSyntax::from("("),
+                                elements: vec![AnnotationArgument {
+                                    element: AnnotationArgumentSyntax {
+                                        label: None,
+                                        value: Expr::Literal(LiteralSyntax::String {
+                                            open_quote: TokenSyntax::from("\""),
+                                            value: "use X".to_string(),
+                                            close_quote: TokenSyntax::from("\""),
+                                        }),
+                                    },
+                                    trailing_comma: None,
+                                }],
+                                close: TokenSyntax::from(")"),
+                            }),
+                        },
+                        trailing_comma: Some(TokenSyntax::from(",")),
+                    },
+                    Annotation {
+                        element: AnnotationSyntax {
+                            name: TokenSyntax::from("link"),
+                            arguments: Some(AnnotationArgumentsSyntax {
+                                open: TokenSyntax::from("("),
+                                elements: vec![AnnotationArgument {
+                                    element: AnnotationArgumentSyntax {
+                                        label: Some(ArgLabelSyntax {
+                                            label: TokenSyntax::from("name"),
+                                            colon: TokenSyntax::from(":"),
+                                        }),
+                                        value: Expr::Literal(LiteralSyntax::String {
+                                            open_quote: TokenSyntax::from("\""),
+                                            value: "m".to_string(),
+                                            close_quote: TokenSyntax::from("\""),
+                                        })
+                                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                                    },
+                                    trailing_comma: None,
+                                }],
+                                close: TokenSyntax::from(")"),
+                            }),
+                        }
+                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                        trailing_comma: None,
+                    },
+                ],
+                close: TokenSyntax::from("]"),
+            },
+        );
+    }
 }
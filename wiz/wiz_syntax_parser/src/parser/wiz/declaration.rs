@@ -2,13 +2,15 @@ use crate::parser::wiz::annotation::annotations_syntax;
 use crate::parser::wiz::character::{ampersand, comma};
 use crate::parser::wiz::expression::expr;
 use crate::parser::wiz::keywords::{
-    as_keyword, deinit_keyword, extension_keyword, fun_keyword, namespace_keyword,
-    protocol_keyword, self_keyword, struct_keyword, use_keyword, val_keyword, var_keyword,
-    where_keyword,
+    as_keyword, class_keyword, const_keyword, deinit_keyword, extension_keyword, extern_keyword,
+    fun_keyword, get_keyword, init_keyword, namespace_keyword, protocol_keyword, public_keyword,
+    self_keyword, set_keyword, struct_keyword, subscript_keyword, type_keyword, typealias_keyword,
+    union_keyword, use_keyword, val_keyword, var_keyword, where_keyword,
 };
 use crate::parser::wiz::lexical_structure::{identifier, token, whitespace0, whitespace1};
 use crate::parser::wiz::statement::stmt;
 use crate::parser::wiz::type_::{type_, type_parameter, type_parameters};
+use crate::parser::Located;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::combinator::{map, opt};
@@ -21,22 +23,26 @@ use nom::{
 use std::ops::{Range, RangeFrom};
 use wiz_syntax::syntax::block::BlockSyntax;
 use wiz_syntax::syntax::declaration::fun_syntax::{
-    ArgDef, ArgDefElementSyntax, ArgDefListSyntax, ExprFunBodySyntax, FunBody, FunSyntax,
-    SelfArgDefSyntax, ValueArgDef,
+    ArgDef, ArgDefElementSyntax, ArgDefListSyntax, ArgDefaultSyntax, ExprFunBodySyntax, FunBody,
+    FunSyntax, SelfArgDefSyntax, ValueArgDef,
 };
 use wiz_syntax::syntax::declaration::{
-    AliasSyntax, DeclKind, DeclarationSyntax, DeinitializerSyntax, ExtensionSyntax, ModuleSyntax,
-    PackageName, ProtocolConformSyntax, StoredPropertySyntax, StructBodySyntax,
-    StructPropertySyntax, StructSyntax, TypeAnnotationSyntax, UseSyntax,
+    AliasSyntax, AssociatedTypeSyntax, AssociatedTypeValueSyntax, DeclKind, DeclarationSyntax,
+    DeinitializerSyntax, ExtensionSyntax, ExternCSyntax, ExternDeclKind, ExternDeclarationSyntax,
+    InitializerSyntax, ModuleSyntax, PackageName, ProtocolConformSyntax, StoredPropertySyntax,
+    StructBodySyntax, StructPropertySyntax, StructSyntax, SubscriptAccessorSyntax,
+    SubscriptBodySyntax, SubscriptDeclarationSyntax, TypeAliasSyntax, TypeAnnotationSyntax,
+    UseGroupElementSyntax, UseGroupSyntax, UseNameSyntax, UseSyntax, UsedNameSyntax,
 };
 use wiz_syntax::syntax::declaration::{PackageNameElement, VarSyntax};
 use wiz_syntax::syntax::token::TokenSyntax;
 use wiz_syntax::syntax::type_name::{TypeConstraintElementSyntax, TypeConstraintsSyntax};
-use wiz_syntax::syntax::Syntax;
+use wiz_syntax::syntax::{FileSyntax, Syntax};
 
 pub fn decl<I>(s: I) -> IResult<I, DeclarationSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -62,6 +68,8 @@ where
                 function_decl,
                 var_decl,
                 extension_decl,
+                extern_decl,
+                type_alias_decl,
             )),
         )),
         |(a, ws, d)| DeclarationSyntax {
@@ -73,16 +81,21 @@ where
 
 pub fn type_annotation_syntax<I>(s: I) -> IResult<I, TypeAnnotationSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
+        + InputLength
         + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(tuple((token(":"), whitespace0, type_)), |(c, ws, t)| {
         TypeAnnotationSyntax {
@@ -96,7 +109,8 @@ where
 
 pub fn struct_decl<I>(s: I) -> IResult<I, DeclKind>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -114,10 +128,11 @@ where
     map(struct_syntax, DeclKind::Struct)(s)
 }
 
-// <struct_decl> ::= "struct" <identifier> <type_parameters>? <struct_body>
+// <struct_decl> ::= ("struct" | "class" | "protocol" | "union") <identifier> <type_parameters>? <struct_body>
 pub fn struct_syntax<I>(s: I) -> IResult<I, StructSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -134,7 +149,12 @@ where
 {
     map(
         tuple((
-            alt((struct_keyword, protocol_keyword)),
+            alt((
+                struct_keyword,
+                class_keyword,
+                protocol_keyword,
+                union_keyword,
+            )),
             whitespace1,
             identifier,
             opt(tuple((whitespace0, type_parameters))),
@@ -161,7 +181,8 @@ where
 // <struct_properties> ::= (<struct_property> ("\n" <struct_property>)* "\n"?)?
 pub fn struct_body_syntax<I>(s: I) -> IResult<I, StructBodySyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -202,11 +223,14 @@ where
 }
 
 // <struct_property> ::= <stored_property>
+//                     | <initializer>
+//                     | <subscript>
 //                     | <deinitializer>
 //                     | <member_function>
 pub fn struct_property<I>(s: I) -> IResult<I, StructPropertySyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -221,22 +245,34 @@ where
     <I as InputIter>::Item: AsChar + Copy,
     <I as InputTakeAtPosition>::Item: AsChar,
 {
-    alt((stored_property, deinitializer, member_function))(s)
+    alt((
+        stored_property,
+        initializer,
+        subscript,
+        deinitializer,
+        associated_type,
+        member_function,
+    ))(s)
 }
 
 // <stored_property> ::= ("var" | "val") <identifier> ":" <type>
 pub fn stored_property<I>(s: I) -> IResult<I, StructPropertySyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
+        + InputLength
         + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(stored_property_syntax, StructPropertySyntax::StoredProperty)(s)
 }
@@ -244,16 +280,21 @@ where
 // <stored_property> ::= ("var" | "val") <identifier> ":" <type>
 pub fn stored_property_syntax<I>(s: I) -> IResult<I, StoredPropertySyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
+        + InputLength
         + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(
         tuple((
@@ -271,10 +312,126 @@ where
     )(s)
 }
 
+// <initializer> =:: "init" <function_value_parameters> <function_body>
+pub fn initializer<I>(s: I) -> IResult<I, StructPropertySyntax>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(
+        tuple((
+            init_keyword,
+            whitespace0,
+            function_value_parameters,
+            whitespace0,
+            function_body,
+        )),
+        |(init, ws, args, bws, body)| {
+            StructPropertySyntax::Init(InitializerSyntax {
+                init_keyword: init,
+                arg_defs: args.with_leading_trivia(ws),
+                body: body.with_leading_trivia(bws),
+            })
+        },
+    )(s)
+}
+
 // <deinitializer> =:: "deinit" <function_body>
+// <subscript> ::= "subscript" <function_value_parameters> ":" <type> "{" <subscript_accessor>+ "}"
+pub fn subscript<I>(s: I) -> IResult<I, StructPropertySyntax>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(
+        tuple((
+            subscript_keyword,
+            whitespace0,
+            function_value_parameters,
+            whitespace0,
+            type_annotation_syntax,
+            whitespace0,
+            token("{"),
+            many1(tuple((whitespace0, subscript_accessor))),
+            whitespace0,
+            token("}"),
+        )),
+        |(subscript, ws, args, tws, return_type, ows, open, accessors, cws, close)| {
+            StructPropertySyntax::Subscript(SubscriptDeclarationSyntax {
+                subscript_keyword: subscript,
+                arg_defs: args.with_leading_trivia(ws),
+                return_type: return_type.with_leading_trivia(tws),
+                body: SubscriptBodySyntax {
+                    open: open.with_leading_trivia(ows),
+                    accessors: accessors
+                        .into_iter()
+                        .map(|(ws, a)| a.with_leading_trivia(ws))
+                        .collect(),
+                    close: close.with_leading_trivia(cws),
+                },
+            })
+        },
+    )(s)
+}
+
+// <subscript_accessor> ::= ("get" | "set") <function_body>
+pub fn subscript_accessor<I>(s: I) -> IResult<I, SubscriptAccessorSyntax>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(
+        tuple((alt((get_keyword, set_keyword)), whitespace0, function_body)),
+        |(keyword, ws, body)| SubscriptAccessorSyntax {
+            keyword,
+            body: body.with_leading_trivia(ws),
+        },
+    )(s)
+}
+
 pub fn deinitializer<I>(s: I) -> IResult<I, StructPropertySyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -300,10 +457,50 @@ where
     )(s)
 }
 
+// <associated_type> ::= "type" <identifier> ("=" <type>)?
+pub fn associated_type<I>(s: I) -> IResult<I, StructPropertySyntax>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(
+        tuple((
+            type_keyword,
+            whitespace1,
+            identifier,
+            opt(tuple((whitespace0, token("="), whitespace0, type_))),
+        )),
+        |(type_keyword, ws, name, value)| {
+            StructPropertySyntax::AssociatedType(AssociatedTypeSyntax {
+                type_keyword,
+                name: TokenSyntax::from(name).with_leading_trivia(ws),
+                value: value.map(|(lws, equal, tws, type_)| AssociatedTypeValueSyntax {
+                    equal: equal.with_leading_trivia(lws),
+                    type_: type_.with_leading_trivia(tws),
+                }),
+            })
+        },
+    )(s)
+}
+
 // <member_function> =:: <modifiers>? "fun" <identifier> <type_parameters>? <function_value_parameters> (":" <type>)? <type_constraints>? <function_body>?
 pub fn member_function<I>(s: I) -> IResult<I, StructPropertySyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -327,7 +524,8 @@ where
 
 pub fn function_decl<I>(s: I) -> IResult<I, DeclKind>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -347,7 +545,8 @@ where
 
 pub fn function_syntax<I>(s: I) -> IResult<I, FunSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -387,16 +586,21 @@ where
 
 pub fn function_value_parameters<I>(s: I) -> IResult<I, ArgDefListSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
         + InputLength
         + ToString
         + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(
         tuple((
@@ -438,16 +642,21 @@ where
 // <function_value_parameter> ::= (<function_value_label> <function_value_name> ":" <type> ("=" <expr>)?) | "self"
 pub fn function_value_parameter<I>(s: I) -> IResult<I, ArgDef>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
         + InputLength
         + ToString
         + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     alt((
         map(
@@ -458,20 +667,29 @@ where
                 token(":"),
                 whitespace0,
                 type_,
+                opt(tuple((whitespace0, token("="), whitespace0, expr))),
             )),
-            |(label, name, cws, colon, ws, typ)| {
+            |(label, name, cws, colon, ws, typ, default)| {
+                let default = default.map(|(lws, equal, rws, value)| {
+                    Box::new(ArgDefaultSyntax {
+                        equal: equal.with_leading_trivia(lws),
+                        value: value.with_leading_trivia(rws),
+                    })
+                });
                 ArgDef::Value(match label {
                     None => ValueArgDef {
                         label: None,
                         name: TokenSyntax::from(name),
                         colon: colon.with_leading_trivia(cws),
                         type_name: typ.with_leading_trivia(ws),
+                        default,
                     },
                     Some((label, lws)) => ValueArgDef {
                         label: Some(TokenSyntax::from(label)),
                         name: TokenSyntax::from(name).with_leading_trivia(lws),
                         colon: colon.with_leading_trivia(cws),
                         type_name: typ.with_leading_trivia(ws),
+                        default,
                     },
                 })
             },
@@ -506,16 +724,21 @@ where
 
 pub fn type_constraints<I>(s: I) -> IResult<I, TypeConstraintsSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
         + InputLength
         + ToString
         + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(
         tuple((
@@ -552,7 +775,8 @@ where
 
 pub fn function_body<I>(s: I) -> IResult<I, FunBody>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -580,7 +804,8 @@ where
 
 pub fn block<I>(s: I) -> IResult<I, BlockSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -619,7 +844,8 @@ where
 
 pub fn var_decl<I>(s: I) -> IResult<I, DeclKind>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -639,7 +865,8 @@ where
 
 pub fn var_syntax<I>(s: I) -> IResult<I, VarSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -656,7 +883,7 @@ where
 {
     map(
         tuple((
-            alt((var_keyword, val_keyword)),
+            alt((var_keyword, val_keyword, const_keyword)),
             whitespace1,
             identifier,
             opt(tuple((whitespace0, type_annotation_syntax))),
@@ -677,104 +904,337 @@ where
 
 //endregion
 
-//region use
-pub fn use_decl<I>(s: I) -> IResult<I, DeclKind>
+//region extern
+
+pub fn extern_decl<I>(s: I) -> IResult<I, DeclKind>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
+        + InputLength
         + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
-    map(use_syntax, DeclKind::Use)(s)
+    map(extern_syntax, DeclKind::ExternC)(s)
 }
 
-// <use> ::= "use" <package_name> ("as" <identifier>)?
-pub fn use_syntax<I>(s: I) -> IResult<I, UseSyntax>
+// <extern_decl> ::= "extern" "{" <extern_declaration>* "}"
+pub fn extern_syntax<I>(s: I) -> IResult<I, ExternCSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
+        + InputLength
         + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(
         tuple((
-            use_keyword,
-            whitespace1,
-            opt(package_name),
-            alt((identifier, map(tag("*"), |i: I| i.to_string()))),
-            opt(tuple((whitespace1, as_keyword, whitespace1, identifier))),
+            extern_keyword,
+            whitespace0,
+            token("{"),
+            many0(tuple((whitespace0, extern_declaration))),
+            whitespace0,
+            token("}"),
         )),
-        |(u, ws, pkg, n, alias)| match pkg {
-            None => UseSyntax {
-                use_keyword: TokenSyntax::from(u),
-                package_name: None,
-                used_name: TokenSyntax::from(n).with_leading_trivia(ws),
-                alias: alias.map(|(lws, a, rws, n)| AliasSyntax {
-                    as_keyword: TokenSyntax::from(a).with_leading_trivia(lws),
-                    name: TokenSyntax::from(n).with_leading_trivia(rws),
-                }),
-            },
-            Some(pkg) => UseSyntax {
-                use_keyword: TokenSyntax::from(u),
-                package_name: Some(pkg.with_leading_trivia(ws)),
-                used_name: TokenSyntax::from(n),
-                alias: alias.map(|(lws, a, rws, n)| AliasSyntax {
-                    as_keyword: TokenSyntax::from(a).with_leading_trivia(lws),
-                    name: TokenSyntax::from(n).with_leading_trivia(rws),
-                }),
-            },
+        |(kw, ws, open, declarations, cws, close)| ExternCSyntax {
+            extern_keyword: kw,
+            left_brace: open.with_leading_trivia(ws),
+            declarations: declarations
+                .into_iter()
+                .map(|(ws, d)| d.with_leading_trivia(ws))
+                .collect(),
+            right_brace: close.with_leading_trivia(cws),
         },
     )(s)
 }
 
-pub fn module_decl<I>(s: I) -> IResult<I, DeclKind>
+// <extern_declaration> ::= <annotations>? (<stored_property> | <fun_decl>)
+pub fn extern_declaration<I>(s: I) -> IResult<I, ExternDeclarationSyntax>
 where
-    I: InputTake
-        + Compare<&'static str>
-        + ToString
-        + FindSubstring<&'static str>
+    I: Located
         + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
+        + Clone
         + InputLength
-        + Clone,
-    <I as InputIter>::Item: AsChar + Copy,
-{
-    map(module_syntax, DeclKind::Module)(s)
-}
-
-// <namespace> ::= "namespace" <identifier>
-pub fn module_syntax<I>(s: I) -> IResult<I, ModuleSyntax>
-where
-    I: InputTake
-        + Compare<&'static str>
         + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
-        + Slice<RangeFrom<usize>>
-        + Slice<Range<usize>>
-        + InputIter
-        + InputLength
-        + Clone,
+        + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(
-        tuple((namespace_keyword, whitespace1, identifier)),
-        |(kw, ws, id)| (id, None),
-    )(s)
-}
-
+        tuple((
+            opt(annotations_syntax),
+            whitespace0,
+            alt((
+                map(stored_property_syntax, |v| ExternDeclKind::Var(Box::new(v))),
+                map(function_syntax, |f| ExternDeclKind::Fun(Box::new(f))),
+            )),
+        )),
+        |(a, ws, d)| ExternDeclarationSyntax {
+            annotations: a,
+            kind: d.with_leading_trivia(ws),
+        },
+    )(s)
+}
+
+//endregion
+
+//region use
+pub fn use_decl<I>(s: I) -> IResult<I, DeclKind>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(use_syntax, DeclKind::Use)(s)
+}
+
+// <use> ::= "public"? "use" <package_name>? (<use_name> | <use_group>)
+pub fn use_syntax<I>(s: I) -> IResult<I, UseSyntax>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(
+        tuple((
+            opt(tuple((public_keyword, whitespace1))),
+            use_keyword,
+            whitespace1,
+            opt(package_name),
+            alt((
+                map(use_group, UsedNameSyntax::Group),
+                map(use_name, UsedNameSyntax::Name),
+            )),
+        )),
+        |(public, u, ws, pkg, n)| {
+            let (public_keyword, use_keyword) = match public {
+                None => (None, TokenSyntax::from(u)),
+                Some((p, pws)) => (Some(p), TokenSyntax::from(u).with_leading_trivia(pws)),
+            };
+            match pkg {
+                None => UseSyntax {
+                    public_keyword,
+                    use_keyword,
+                    package_name: None,
+                    used_name: n.with_leading_trivia(ws),
+                },
+                Some(pkg) => UseSyntax {
+                    public_keyword,
+                    use_keyword,
+                    package_name: Some(pkg.with_leading_trivia(ws)),
+                    used_name: n,
+                },
+            }
+        },
+    )(s)
+}
+
+// <use_group> ::= "{" (<use_name> ("," <use_name>)* ","?)? "}"
+pub fn use_group<I>(s: I) -> IResult<I, UseGroupSyntax>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(
+        tuple((
+            token("{"),
+            many0(tuple((whitespace0, use_name, whitespace0, comma))),
+            opt(tuple((whitespace0, use_name))),
+            whitespace0,
+            token("}"),
+        )),
+        |(open, names, name, tws, close)| {
+            let mut elements: Vec<_> = names
+                .into_iter()
+                .map(|(lws, n, rws, com)| UseGroupElementSyntax {
+                    element: n.with_leading_trivia(lws),
+                    trailing_comma: Some(com.with_leading_trivia(rws)),
+                })
+                .collect();
+            if let Some((ws, n)) = name {
+                elements.push(UseGroupElementSyntax {
+                    element: n.with_leading_trivia(ws),
+                    trailing_comma: None,
+                });
+            };
+            UseGroupSyntax {
+                open,
+                elements,
+                close: close.with_leading_trivia(tws),
+            }
+        },
+    )(s)
+}
+
+// <use_name> ::= (<identifier> | "*") ("as" <identifier>)?
+pub fn use_name<I>(s: I) -> IResult<I, UseNameSyntax>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(
+        tuple((
+            alt((identifier, map(tag("*"), |i: I| i.to_string()))),
+            opt(tuple((whitespace1, as_keyword, whitespace1, identifier))),
+        )),
+        |(n, alias)| UseNameSyntax {
+            name: TokenSyntax::from(n),
+            alias: alias.map(|(lws, a, rws, n)| AliasSyntax {
+                as_keyword: TokenSyntax::from(a).with_leading_trivia(lws),
+                name: TokenSyntax::from(n).with_leading_trivia(rws),
+            }),
+        },
+    )(s)
+}
+
+pub fn module_decl<I>(s: I) -> IResult<I, DeclKind>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(module_syntax, DeclKind::Module)(s)
+}
+
+// <namespace> ::= "namespace" <identifier> ("{" <decl>* "}")?
+pub fn module_syntax<I>(s: I) -> IResult<I, ModuleSyntax>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(
+        tuple((
+            namespace_keyword,
+            whitespace1,
+            identifier,
+            opt(tuple((
+                whitespace0,
+                token("{"),
+                whitespace0,
+                many0(tuple((whitespace0, decl))),
+                whitespace0,
+                token("}"),
+            ))),
+        )),
+        |(_, _, id, body)| {
+            (
+                id,
+                body.map(
+                    |(_, _, leading_trivia, decls, trailing_trivia, _)| FileSyntax {
+                        leading_trivia,
+                        body: decls
+                            .into_iter()
+                            .map(|(t, d)| d.with_leading_trivia(t))
+                            .collect(),
+                        trailing_trivia,
+                    },
+                ),
+            )
+        },
+    )(s)
+}
+
 // <package_name> ::= (<identifier> "::")*
 pub fn package_name<I>(s: I) -> IResult<I, PackageName>
 where
@@ -803,7 +1263,8 @@ where
 //region extension
 pub fn extension_decl<I>(s: I) -> IResult<I, DeclKind>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -823,7 +1284,8 @@ where
 
 pub fn extension_syntax<I>(s: I) -> IResult<I, ExtensionSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -864,21 +1326,92 @@ where
 }
 //endregion
 
+//region typealias
+pub fn type_alias_decl<I>(s: I) -> IResult<I, DeclKind>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(type_alias_syntax, DeclKind::TypeAlias)(s)
+}
+
+// <type_alias_decl> ::= "typealias" <identifier> <type_parameters>? "=" <type>
+pub fn type_alias_syntax<I>(s: I) -> IResult<I, TypeAliasSyntax>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(
+        tuple((
+            typealias_keyword,
+            whitespace1,
+            identifier,
+            opt(tuple((whitespace0, type_parameters))),
+            whitespace0,
+            token("="),
+            whitespace0,
+            type_,
+        )),
+        |(typealias_keyword, nws, name, params, ews, equal, tws, type_)| TypeAliasSyntax {
+            typealias_keyword,
+            name: TokenSyntax::from(name).with_leading_trivia(nws),
+            type_params: params.map(|(ws, p)| p.with_leading_trivia(ws)),
+            equal: equal.with_leading_trivia(ews),
+            type_: type_.with_leading_trivia(tws),
+        },
+    )(s)
+}
+//endregion
+
 #[cfg(test)]
 mod tests {
     use crate::parser::tests::check;
     use crate::parser::wiz::declaration::{
-        block, function_body, function_decl, member_function, package_name, stored_property,
-        struct_syntax, type_constraints, use_syntax, var_decl,
+        associated_type, block, extern_syntax, function_body, function_decl,
+        function_value_parameter, initializer, member_function, module_syntax, package_name,
+        stored_property, struct_syntax, subscript, type_alias_syntax, type_constraints, use_syntax,
+        var_decl,
     };
+    use wiz_span::Location;
+    use wiz_syntax::syntax::annotation::{Annotation, AnnotationSyntax, AnnotationsSyntax};
     use wiz_syntax::syntax::block::BlockSyntax;
     use wiz_syntax::syntax::declaration::fun_syntax::{
-        ArgDef, ArgDefElementSyntax, ArgDefListSyntax, ExprFunBodySyntax, FunBody, FunSyntax,
-        ValueArgDef,
+        ArgDef, ArgDefElementSyntax, ArgDefListSyntax, ArgDefaultSyntax, ExprFunBodySyntax,
+        FunBody, FunSyntax, ValueArgDef,
     };
     use wiz_syntax::syntax::declaration::{
-        AliasSyntax, DeclKind, PackageName, StoredPropertySyntax, StructBodySyntax,
-        StructPropertySyntax, StructSyntax, TypeAnnotationSyntax, UseSyntax,
+        AliasSyntax, AssociatedTypeSyntax, AssociatedTypeValueSyntax, DeclKind, DeclarationSyntax,
+        ExternCSyntax, ExternDeclKind, ExternDeclarationSyntax, InitializerSyntax, PackageName,
+        StoredPropertySyntax, StructBodySyntax, StructPropertySyntax, StructSyntax,
+        SubscriptAccessorSyntax, SubscriptBodySyntax, SubscriptDeclarationSyntax, TypeAliasSyntax,
+        TypeAnnotationSyntax, UseGroupElementSyntax, UseGroupSyntax, UseNameSyntax, UseSyntax,
+        UsedNameSyntax,
     };
     use wiz_syntax::syntax::declaration::{PackageNameElement, VarSyntax};
     use wiz_syntax::syntax::expression::{BinaryOperationSyntax, Expr, NameExprSyntax};
@@ -887,10 +1420,11 @@ mod tests {
     use wiz_syntax::syntax::token::TokenSyntax;
     use wiz_syntax::syntax::trivia::{Trivia, TriviaPiece};
     use wiz_syntax::syntax::type_name::{
-        SimpleTypeName, TypeConstraintElementSyntax, TypeConstraintSyntax, TypeConstraintsSyntax,
-        TypeName, TypeParam,
+        DecoratedTypeName, SimpleTypeName, TypeArgumentElementSyntax, TypeArgumentListSyntax,
+        TypeConstraintElementSyntax, TypeConstraintSyntax, TypeConstraintsSyntax, TypeName,
+        TypeParam, TypeParameterElementSyntax, TypeParameterListSyntax,
     };
-    use wiz_syntax::syntax::Syntax;
+    use wiz_syntax::syntax::{FileSyntax, Syntax};
 
     #[test]
     fn test_stored_property() {
@@ -962,6 +1496,102 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_union_syntax() {
+        check(
+            r"union A {var a: Int32}",
+            struct_syntax,
+            StructSyntax {
+                struct_keyword: TokenSyntax::from("union"),
+                name: TokenSyntax::from("A")
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                type_params: None,
+                body: StructBodySyntax {
+                    open: TokenSyntax::from("{")
+                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                    properties: vec![StructPropertySyntax::StoredProperty(StoredPropertySyntax {
+                        mutability_keyword: TokenSyntax::from("var"),
+                        name: TokenSyntax::from("a")
+                            .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                        type_: TypeAnnotationSyntax {
+                            colon: TokenSyntax::from(":"),
+                            type_: TypeName::Simple(SimpleTypeName {
+                                name: TokenSyntax::from("Int32"),
+                                type_args: None,
+                            })
+                            .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                        },
+                    })],
+                    close: TokenSyntax::from("}"),
+                },
+            },
+        );
+    }
+
+    #[test]
+    fn test_class_syntax() {
+        check(
+            r"class A {var a: Int32}",
+            struct_syntax,
+            StructSyntax {
+                struct_keyword: TokenSyntax::from("class"),
+                name: TokenSyntax::from("A")
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                type_params: None,
+                body: StructBodySyntax {
+                    open: TokenSyntax::from("{")
+                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                    properties: vec![StructPropertySyntax::StoredProperty(StoredPropertySyntax {
+                        mutability_keyword: TokenSyntax::from("var"),
+                        name: TokenSyntax::from("a")
+                            .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                        type_: TypeAnnotationSyntax {
+                            colon: TokenSyntax::from(":"),
+                            type_: TypeName::Simple(SimpleTypeName {
+                                name: TokenSyntax::from("Int32"),
+                                type_args: None,
+                            })
+                            .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                        },
+                    })],
+                    close: TokenSyntax::from("}"),
+                },
+            },
+        );
+    }
+
+    #[test]
+    fn test_associated_type() {
+        check(
+            "type Element",
+            associated_type,
+            StructPropertySyntax::AssociatedType(AssociatedTypeSyntax {
+                type_keyword: TokenSyntax::from("type"),
+                name: TokenSyntax::from("Element")
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                value: None,
+            }),
+        );
+        check(
+            "type Element = Int64",
+            associated_type,
+            StructPropertySyntax::AssociatedType(AssociatedTypeSyntax {
+                type_keyword: TokenSyntax::from("type"),
+                name: TokenSyntax::from("Element")
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                value: Some(AssociatedTypeValueSyntax {
+                    equal: TokenSyntax::from("=")
+                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                    type_: TypeName::Simple(SimpleTypeName {
+                        name: TokenSyntax::from("Int64"),
+                        type_args: None,
+                    })
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                }),
+            }),
+        );
+    }
+
     #[test]
     fn test_member_function() {
         check(
@@ -985,6 +1615,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_initializer() {
+        check(
+            "init() {}",
+            initializer,
+            StructPropertySyntax::Init(InitializerSyntax {
+                init_keyword: TokenSyntax::from("init"),
+                arg_defs: ArgDefListSyntax::default(),
+                body: FunBody::Block(BlockSyntax {
+                    open: TokenSyntax::from("{")
+                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                    body: vec![],
+                    close: TokenSyntax::from("}"),
+                }),
+            }),
+        );
+    }
+
+    #[test]
+    fn test_subscript() {
+        check(
+            "subscript(): Int64 { get {} set {} }",
+            subscript,
+            StructPropertySyntax::Subscript(SubscriptDeclarationSyntax {
+                subscript_keyword: TokenSyntax::from("subscript"),
+                arg_defs: ArgDefListSyntax::default(),
+                return_type: TypeAnnotationSyntax {
+                    colon: TokenSyntax::from(":"),
+                    type_: TypeName::Simple(SimpleTypeName {
+                        name: TokenSyntax::from("Int64"),
+                        type_args: None,
+                    })
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                },
+                body: SubscriptBodySyntax {
+                    open: TokenSyntax::from("{")
+                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                    accessors: ["get", "set"]
+                        .into_iter()
+                        .map(|keyword| SubscriptAccessorSyntax {
+                            keyword: TokenSyntax::from(keyword)
+                                .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                            body: FunBody::Block(BlockSyntax {
+                                open: TokenSyntax::from("{")
+                                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                                body: vec![],
+                                close: TokenSyntax::from("}"),
+                            }),
+                        })
+                        .collect(),
+                    close: TokenSyntax::from("}")
+                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                },
+            }),
+        );
+    }
+
     #[test]
     fn test_empty_block() {
         assert_eq!(
@@ -1033,6 +1720,7 @@ mod tests {
                         right: Box::new(Expr::Literal(LiteralSyntax::Integer(TokenSyntax::from(
                             "1"
                         )))),
+                        span: Location::DUMMY,
                     }))],
                     close: TokenSyntax::from("}")
                 }
@@ -1138,6 +1826,7 @@ mod tests {
                                     .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
                                 type_args: None,
                             }),
+                            default: None,
                         }),
                         trailing_comma: None,
                     }],
@@ -1157,6 +1846,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_function_value_parameter_default() {
+        check(
+            "mode: Int32 = 0",
+            function_value_parameter,
+            ArgDef::Value(ValueArgDef {
+                label: None,
+                name: TokenSyntax::from("mode"),
+                colon: TokenSyntax::from(":"),
+                type_name: TypeName::Simple(SimpleTypeName {
+                    name: TokenSyntax::from("Int32")
+                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                    type_args: None,
+                }),
+                default: Some(Box::new(ArgDefaultSyntax {
+                    equal: TokenSyntax::from("=")
+                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                    value: Expr::Literal(LiteralSyntax::Integer(
+                        TokenSyntax::from("0")
+                            .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                    )),
+                })),
+            }),
+        );
+    }
+
     #[test]
     fn test_function_short_label() {
         check(
@@ -1179,6 +1894,7 @@ mod tests {
                                     .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
                                 type_args: None,
                             }),
+                            default: None,
                         }),
                         trailing_comma: None,
                     }],
@@ -1223,6 +1939,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_var_decl_with_type_arguments() {
+        check(
+            "val a: A<B> = 1",
+            var_decl,
+            DeclKind::Var(VarSyntax {
+                mutability_keyword: TokenSyntax::from("val"),
+                name: TokenSyntax::from("a")
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                type_annotation: Some(TypeAnnotationSyntax {
+                    colon: TokenSyntax::from(":"),
+                    type_: TypeName::Simple(SimpleTypeName {
+                        name: TokenSyntax::from("A")
+                            .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                        type_args: Some(TypeArgumentListSyntax {
+                            open: TokenSyntax::from("<"),
+                            elements: vec![TypeArgumentElementSyntax {
+                                element: TypeName::Simple(SimpleTypeName::from("B")),
+                                trailing_comma: None,
+                            }],
+                            close: TokenSyntax::from(">"),
+                        }),
+                    }),
+                }),
+                equal: TokenSyntax::from("=")
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                value: Expr::Literal(LiteralSyntax::Integer(TokenSyntax::from("1")))
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+            }),
+        );
+    }
+
+    #[test]
+    fn test_extern_syntax() {
+        check(
+            "extern {#[thread_local] var errno: Int32}",
+            extern_syntax,
+            ExternCSyntax {
+                extern_keyword: TokenSyntax::from("extern"),
+                left_brace: TokenSyntax::from("{")
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                declarations: vec![ExternDeclarationSyntax {
+                    annotations: Some(AnnotationsSyntax {
+                        open: TokenSyntax::from("#["),
+                        elements: vec![Annotation {
+                            element: AnnotationSyntax::from("thread_local"),
+                            trailing_comma: None,
+                        }],
+                        close: TokenSyntax::from("]"),
+                    }),
+                    kind: ExternDeclKind::Var(Box::new(StoredPropertySyntax {
+                        mutability_keyword: TokenSyntax::from("var")
+                            .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                        name: TokenSyntax::from("errno")
+                            .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                        type_: TypeAnnotationSyntax {
+                            colon: TokenSyntax::from(":"),
+                            type_: TypeName::Simple(SimpleTypeName::from("Int32"))
+                                .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                        },
+                    })),
+                }],
+                right_brace: TokenSyntax::from("}"),
+            },
+        );
+    }
+
     #[test]
     fn test_var_decl_without_type() {
         check(
@@ -1385,28 +2168,153 @@ mod tests {
             "use abc",
             use_syntax,
             UseSyntax {
+                public_keyword: None,
                 use_keyword: TokenSyntax::from("use"),
                 package_name: None,
-                used_name: TokenSyntax::from("abc")
-                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
-                alias: None,
+                used_name: UsedNameSyntax::Name(UseNameSyntax {
+                    name: TokenSyntax::from("abc")
+                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                    alias: None,
+                }),
             },
         );
         check(
             "use abc as def",
             use_syntax,
             UseSyntax {
+                public_keyword: None,
                 use_keyword: TokenSyntax::from("use"),
                 package_name: None,
-                used_name: TokenSyntax::from("abc")
-                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
-                alias: Some(AliasSyntax {
-                    as_keyword: TokenSyntax::from("as")
-                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
-                    name: TokenSyntax::from("def")
+                used_name: UsedNameSyntax::Name(UseNameSyntax {
+                    name: TokenSyntax::from("abc")
                         .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                    alias: Some(AliasSyntax {
+                        as_keyword: TokenSyntax::from("as")
+                            .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                        name: TokenSyntax::from("def")
+                            .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                    }),
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn test_use_group() {
+        check(
+            "public use abc::{def, ghi as jkl,}",
+            use_syntax,
+            UseSyntax {
+                public_keyword: Some(TokenSyntax::from("public")),
+                use_keyword: TokenSyntax::from("use")
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                package_name: Some(
+                    PackageName {
+                        names: vec![PackageNameElement {
+                            name: TokenSyntax::from("abc"),
+                            sep: TokenSyntax::from("::"),
+                        }],
+                    }
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                ),
+                used_name: UsedNameSyntax::Group(UseGroupSyntax {
+                    open: TokenSyntax::from("{"),
+                    elements: vec![
+                        UseGroupElementSyntax {
+                            element: UseNameSyntax {
+                                name: TokenSyntax::from("def"),
+                                alias: None,
+                            },
+                            trailing_comma: Some(TokenSyntax::from(",")),
+                        },
+                        UseGroupElementSyntax {
+                            element: UseNameSyntax {
+                                name: TokenSyntax::from("ghi")
+                                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                                alias: Some(AliasSyntax {
+                                    as_keyword: TokenSyntax::from("as")
+                                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                                    name: TokenSyntax::from("jkl")
+                                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                                }),
+                            },
+                            trailing_comma: Some(TokenSyntax::from(",")),
+                        },
+                    ],
+                    close: TokenSyntax::from("}"),
                 }),
             },
         );
     }
+
+    #[test]
+    fn test_type_alias() {
+        check(
+            "typealias Fd = Int32",
+            type_alias_syntax,
+            TypeAliasSyntax {
+                typealias_keyword: TokenSyntax::from("typealias"),
+                name: TokenSyntax::from("Fd")
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                type_params: None,
+                equal: TokenSyntax::from("=")
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                type_: TypeName::Simple(SimpleTypeName {
+                    name: TokenSyntax::from("Int32"),
+                    type_args: None,
+                })
+                .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+            },
+        );
+        check(
+            "typealias Ptr<T> = *T",
+            type_alias_syntax,
+            TypeAliasSyntax {
+                typealias_keyword: TokenSyntax::from("typealias"),
+                name: TokenSyntax::from("Ptr")
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                type_params: Some(TypeParameterListSyntax {
+                    open: TokenSyntax::from("<"),
+                    elements: vec![TypeParameterElementSyntax {
+                        element: TypeParam {
+                            name: TokenSyntax::from("T"),
+                            type_constraint: None,
+                        },
+                        trailing_comma: None,
+                    }],
+                    close: TokenSyntax::from(">"),
+                }),
+                equal: TokenSyntax::from("=")
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                type_: TypeName::Decorated(Box::new(DecoratedTypeName {
+                    decoration: TokenSyntax::from("*"),
+                    type_: TypeName::Simple(SimpleTypeName {
+                        name: TokenSyntax::from("T"),
+                        type_args: None,
+                    }),
+                }))
+                .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+            },
+        );
+    }
+
+    #[test]
+    fn test_module() {
+        check("namespace abc", module_syntax, (String::from("abc"), None));
+        check(
+            "namespace abc { namespace def }",
+            module_syntax,
+            (
+                String::from("abc"),
+                Some(FileSyntax {
+                    leading_trivia: Trivia::from(TriviaPiece::Spaces(1)),
+                    body: vec![DeclarationSyntax {
+                        annotations: None,
+                        kind: DeclKind::Module((String::from("def"), None)),
+                    }],
+                    trailing_trivia: Trivia::from(TriviaPiece::Spaces(1)),
+                }),
+            ),
+        );
+    }
 }
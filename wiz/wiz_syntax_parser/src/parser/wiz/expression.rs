@@ -4,7 +4,7 @@ use crate::parser::wiz::keywords::{
     else_keyword, false_keyword, if_keyword, return_keyword, true_keyword,
 };
 use crate::parser::wiz::lexical_structure::{
-    identifier, token, whitespace0, whitespace1, whitespace_without_eol0,
+    identifier, location, token, whitespace0, whitespace1, whitespace_without_eol0,
 };
 use crate::parser::wiz::name_space::name_space;
 use crate::parser::wiz::operators::{
@@ -15,8 +15,9 @@ use crate::parser::wiz::operators::{
 };
 use crate::parser::wiz::statement::stmt;
 use crate::parser::wiz::type_::{type_, type_arguments};
+use crate::parser::Located;
 use nom::branch::{alt, permutation};
-use nom::bytes::complete::{escaped_transform, take_until, take_while_m_n};
+use nom::bytes::complete::{escaped_transform, tag, take_until, take_while_m_n};
 use nom::character::complete::{char, digit1};
 use nom::combinator::{map, opt, value};
 use nom::multi::many0;
@@ -27,6 +28,7 @@ use nom::{
 };
 use std::char::{decode_utf16, REPLACEMENT_CHARACTER};
 use std::ops::{Range, RangeFrom};
+use wiz_span::Location;
 use wiz_syntax::syntax::block::BlockSyntax;
 use wiz_syntax::syntax::expression::{
     ArgLabelSyntax, ArrayElementSyntax, ArraySyntax, BinaryOperationSyntax, CallArg,
@@ -141,9 +143,26 @@ where
     map(alt((true_keyword, false_keyword)), LiteralSyntax::Boolean)(s)
 }
 
+/*
+<source_location_literal> ::= "#file" | "#line" | "#column"
+*/
+pub fn source_location_literal<I>(s: I) -> IResult<I, LiteralSyntax>
+where
+    I: InputTake + Compare<&'static str> + Clone + ToString + Located,
+{
+    map(
+        tuple((location, alt((tag("#file"), tag("#line"), tag("#column"))))),
+        |(span, token)| LiteralSyntax::SourceLocation {
+            token: TokenSyntax::from(token),
+            span,
+        },
+    )(s)
+}
+
 pub fn literal_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Clone
+    I: Located
+        + Clone
         + Offset
         + InputLength
         + InputTake
@@ -164,6 +183,7 @@ where
             integer_literal,
             string_literal,
             raw_string_literal,
+            source_location_literal,
         )),
         Expr::Literal,
     )(s)
@@ -171,16 +191,21 @@ where
 
 pub fn name_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
+        + InputLength
         + ToString
-        + Compare<&'static str>
-        + Slice<Range<usize>>
-        + FindSubstring<&'static str>,
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(
         tuple((opt(name_space), identifier, opt(type_arguments))),
@@ -196,7 +221,8 @@ where
 
 pub fn parenthesized_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -225,7 +251,8 @@ where
 
 pub fn return_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -253,7 +280,8 @@ where
 
 pub fn array_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -301,7 +329,8 @@ where
 
 pub fn primary_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -330,7 +359,8 @@ where
 */
 pub fn if_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -388,7 +418,8 @@ where
 */
 pub fn postfix_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -404,8 +435,8 @@ where
     <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(
-        tuple((primary_expr, many0(postfix_suffix))),
-        |(e, suffixes)| {
+        tuple((location, primary_expr, many0(postfix_suffix))),
+        |(span, e, suffixes)| {
             let mut e = e;
             for suffix in suffixes {
                 e = match suffix {
@@ -413,6 +444,7 @@ where
                         Expr::UnaryOp(UnaryOperationSyntax::Postfix(PostfixUnaryOperationSyntax {
                             target: Box::new(e),
                             operator: TokenSyntax::from(kind),
+                            span: span.clone(),
                         }))
                     }
                     PostfixSuffix::TypeArgumentSuffix(t) => panic!("type argument suffix {:?}", t),
@@ -423,10 +455,12 @@ where
                         target: Box::new(e),
                         args,
                         tailing_lambda,
+                        span: span.clone(),
                     }),
                     PostfixSuffix::IndexingSuffix(indexes) => Expr::Subscript(SubscriptSyntax {
                         target: Box::new(e),
                         idx_or_keys: indexes,
+                        span: span.clone(),
                     }),
                     PostfixSuffix::NavigationSuffix { navigation, name } => {
                         Expr::Member(MemberSyntax {
@@ -451,7 +485,8 @@ where
 */
 pub fn postfix_suffix<I>(s: I) -> IResult<I, PostfixSuffix>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -505,7 +540,8 @@ where
 // <indexing_suffix> ::= "[" <expr> ("," <expr>)* ","? "]"
 pub fn indexing_suffix<I>(s: I) -> IResult<I, PostfixSuffix>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -553,7 +589,8 @@ where
 
 pub fn prefix_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -580,16 +617,17 @@ where
     )(s)
 }
 
-fn _binop<T>(e: Expr, v: Vec<(Trivia, T, Trivia, Expr)>) -> Expr
+fn _binop<T>(e: Expr, v: Vec<(Trivia, Location, T, Trivia, Expr)>) -> Expr
 where
     T: ToString,
 {
     let mut bin_op = e;
-    for (lws, op, rws, ex) in v {
+    for (lws, span, op, rws, ex) in v {
         bin_op = Expr::BinOp(BinaryOperationSyntax {
             left: Box::new(bin_op),
             operator: TokenSyntax::from(op).with_leading_trivia(lws),
             right: Box::new(ex.with_leading_trivia(rws)),
+            span,
         })
     }
     bin_op
@@ -600,7 +638,8 @@ where
 */
 pub fn conjunction_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -620,6 +659,7 @@ where
             equality_expr,
             many0(tuple((
                 whitespace_without_eol0,
+                location,
                 conjunction_operator,
                 whitespace0,
                 equality_expr,
@@ -633,7 +673,8 @@ where
 */
 pub fn equality_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -653,6 +694,7 @@ where
             comparison_expr,
             many0(tuple((
                 whitespace_without_eol0,
+                location,
                 equality_operator,
                 whitespace0,
                 comparison_expr,
@@ -667,7 +709,8 @@ where
 */
 pub fn comparison_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -687,6 +730,7 @@ where
             generic_call_like_comparison_expr,
             many0(tuple((
                 whitespace_without_eol0,
+                location,
                 comparison_operator,
                 whitespace0,
                 generic_call_like_comparison_expr,
@@ -701,7 +745,8 @@ where
 */
 pub fn call_suffix<I>(s: I) -> IResult<I, PostfixSuffix>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -743,7 +788,8 @@ where
 */
 pub fn value_arguments<I>(s: I) -> IResult<I, CallArgListSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -793,7 +839,8 @@ where
 */
 pub fn value_argument<I>(s: I) -> IResult<I, CallArg>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -857,7 +904,8 @@ where
 */
 pub fn annotated_lambda<I>(s: I) -> IResult<I, LambdaSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -877,7 +925,8 @@ where
 
 pub fn lambda_literal<I>(s: I) -> IResult<I, LambdaSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -907,7 +956,8 @@ where
 */
 pub fn generic_call_like_comparison_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -936,7 +986,8 @@ where
 */
 pub fn infix_operation_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -952,25 +1003,35 @@ where
     <I as InputTakeAtPosition>::Item: AsChar,
 {
     enum P {
-        IN { op: TokenSyntax, expr: Expr },
-        IS { op: TokenSyntax, type_: TypeName },
+        IN {
+            op: TokenSyntax,
+            expr: Expr,
+            span: Location,
+        },
+        IS {
+            op: TokenSyntax,
+            type_: TypeName,
+            span: Location,
+        },
     }
     map(
         tuple((
             elvis_expr,
             many0(alt((
                 map(
-                    tuple((whitespace1, in_operator, whitespace1, elvis_expr)),
-                    |(ows, op, ews, expr): (_, I, _, _)| P::IN {
+                    tuple((whitespace1, location, in_operator, whitespace1, elvis_expr)),
+                    |(ows, span, op, ews, expr): (_, _, I, _, _)| P::IN {
                         op: TokenSyntax::from(op).with_leading_trivia(ows),
                         expr: expr.with_leading_trivia(ews),
+                        span,
                     },
                 ),
                 map(
-                    tuple((whitespace1, is_operator, whitespace1, type_)),
-                    |(ows, op, ews, type_)| P::IS {
+                    tuple((whitespace1, location, is_operator, whitespace1, type_)),
+                    |(ows, span, op, ews, type_)| P::IS {
                         op: op.with_leading_trivia(ows),
                         type_: type_.with_leading_trivia(ews),
+                        span,
                     },
                 ),
             ))),
@@ -979,19 +1040,21 @@ where
             let mut bin_op = op;
             for p in v {
                 match p {
-                    P::IS { op, type_ } => {
+                    P::IS { op, type_, span } => {
                         // TODO introduce type check syntax
                         bin_op = Expr::TypeCast(TypeCastSyntax {
                             target: Box::new(bin_op),
                             operator: op,
                             type_,
+                            span,
                         })
                     }
-                    P::IN { op, expr } => {
+                    P::IN { op, expr, span } => {
                         bin_op = Expr::BinOp(BinaryOperationSyntax {
                             left: Box::new(bin_op),
                             operator: op,
                             right: Box::new(expr),
+                            span,
                         })
                     }
                 }
@@ -1006,7 +1069,8 @@ where
 */
 pub fn elvis_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -1026,6 +1090,7 @@ where
             infix_function_call_expr,
             many0(tuple((
                 whitespace_without_eol0,
+                location,
                 elvis_operator,
                 whitespace0,
                 infix_function_call_expr,
@@ -1040,7 +1105,8 @@ where
 */
 pub fn infix_function_call_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -1060,6 +1126,7 @@ where
             range_expr,
             many0(tuple((
                 whitespace_without_eol0,
+                location,
                 identifier,
                 whitespace0,
                 range_expr,
@@ -1074,7 +1141,8 @@ where
 */
 pub fn range_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -1094,6 +1162,7 @@ where
             additive_expr,
             many0(tuple((
                 whitespace_without_eol0,
+                location,
                 range_operator,
                 whitespace0,
                 additive_expr,
@@ -1108,7 +1177,8 @@ where
 */
 pub fn additive_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -1128,6 +1198,7 @@ where
             multiplicative_expr,
             many0(tuple((
                 whitespace_without_eol0,
+                location,
                 additive_operator,
                 whitespace0,
                 multiplicative_expr,
@@ -1142,7 +1213,8 @@ where
 */
 pub fn multiplicative_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -1162,6 +1234,7 @@ where
             as_expr,
             many0(tuple((
                 whitespace_without_eol0,
+                location,
                 multiplicative_operator,
                 whitespace0,
                 as_expr,
@@ -1176,7 +1249,8 @@ where
 */
 pub fn as_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -1199,10 +1273,12 @@ where
         |(e, v): (_, Vec<(_, I, _, _)>)| {
             let mut bin_op = e;
             for (ws, op, tws, typ) in v {
+                let span = op.location();
                 bin_op = Expr::TypeCast(TypeCastSyntax {
                     target: Box::new(bin_op),
                     operator: TokenSyntax::from(op).with_leading_trivia(ws),
                     type_: typ.with_leading_trivia(tws),
+                    span,
                 })
             }
             bin_op
@@ -1212,7 +1288,8 @@ where
 
 pub fn disjunction_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -1232,6 +1309,7 @@ where
             conjunction_expr,
             many0(tuple((
                 whitespace_without_eol0,
+                location,
                 disjunction_operator,
                 whitespace0,
                 conjunction_expr,
@@ -1243,7 +1321,8 @@ where
 
 pub fn expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -1267,9 +1346,10 @@ mod tests {
     use crate::parser::wiz::expression::{
         array_expr, boolean_literal, conjunction_expr, disjunction_expr, equality_expr, expr,
         floating_point_literal, if_expr, indexing_suffix, integer_literal, literal_expr, name_expr,
-        postfix_suffix, primary_expr, raw_string_literal, return_expr, string_literal,
-        value_arguments,
+        postfix_suffix, primary_expr, raw_string_literal, return_expr, source_location_literal,
+        string_literal, value_arguments,
     };
+    use wiz_span::Location;
     use wiz_syntax::syntax::block::BlockSyntax;
     use wiz_syntax::syntax::declaration::VarSyntax;
     use wiz_syntax::syntax::declaration::{DeclKind, DeclarationSyntax};
@@ -1379,6 +1459,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_source_location_literal() {
+        check(
+            "#line",
+            source_location_literal,
+            LiteralSyntax::SourceLocation {
+                token: TokenSyntax::from("#line"),
+                span: Location::new(0, 1).with_column(1),
+            },
+        );
+        check(
+            "#column",
+            literal_expr,
+            Expr::Literal(LiteralSyntax::SourceLocation {
+                token: TokenSyntax::from("#column"),
+                span: Location::new(0, 1).with_column(1),
+            }),
+        );
+    }
+
     #[test]
     fn test_boolean_literal() {
         assert_eq!(
@@ -1496,6 +1596,7 @@ mod tests {
                     right: Box::from(Expr::Literal(LiteralSyntax::Integer(TokenSyntax::from(
                         "2",
                     )))),
+                    span: Location::new(1, 1).with_column(2),
                 })),
                 operator: TokenSyntax::from("||")
                     .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
@@ -1503,6 +1604,7 @@ mod tests {
                     Expr::Literal(LiteralSyntax::Integer(TokenSyntax::from("3")))
                         .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
                 ),
+                span: Location::new(5, 1).with_column(6),
             }),
         );
     }
@@ -1527,6 +1629,7 @@ mod tests {
                                 TriviaPiece::Spaces(12),
                             ])),
                     ),
+                    span: Location::new(2, 1).with_column(3),
                 })),
                 operator: TokenSyntax::from("&&")
                     .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
@@ -1534,6 +1637,7 @@ mod tests {
                     Expr::Literal(LiteralSyntax::Integer(TokenSyntax::from("3")))
                         .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
                 ),
+                span: Location::new(19, 2).with_column(15),
             }),
         );
     }
@@ -1608,6 +1712,7 @@ mod tests {
                 )))),
                 args: Some(CallArgListSyntax::new()),
                 tailing_lambda: None,
+                span: Location::new(0, 1).with_column(1),
             }),
         );
     }
@@ -1638,6 +1743,7 @@ mod tests {
                     close: TokenSyntax::from(")"),
                 }),
                 tailing_lambda: None,
+                span: Location::new(0, 1).with_column(1),
             }),
         );
     }
@@ -1674,6 +1780,7 @@ mod tests {
                     close: TokenSyntax::from(")"),
                 }),
                 tailing_lambda: None,
+                span: Location::new(0, 1).with_column(1),
             }),
         );
     }
@@ -1721,6 +1828,7 @@ mod tests {
                             Expr::Name(NameExprSyntax::simple(TokenSyntax::from("length")))
                                 .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
                         ),
+                        span: Location::new(12, 1).with_column(13),
                     })
                     .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
                 ),
@@ -1754,6 +1862,7 @@ mod tests {
                                                 TriviaPiece::Spaces(1),
                                             )),
                                         ),
+                                        span: Location::new(66, 2).with_column(43),
                                     })
                                     .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
                                 ),
@@ -1789,6 +1898,7 @@ mod tests {
                                                         TriviaPiece::Spaces(1),
                                                     )),
                                                 ),
+                                                span: Location::new(93, 2).with_column(70),
                                             },
                                         ))
                                         .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1)))],
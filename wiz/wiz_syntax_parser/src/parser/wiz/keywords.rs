@@ -38,6 +38,13 @@ where
     token("val")(s)
 }
 
+pub fn const_keyword<I>(s: I) -> IResult<I, TokenSyntax>
+where
+    I: InputTake + Compare<&'static str> + ToString,
+{
+    token("const")(s)
+}
+
 pub fn extension_keyword<I>(s: I) -> IResult<I, TokenSyntax>
 where
     I: InputTake + Compare<&'static str> + ToString,
@@ -52,6 +59,20 @@ where
     token("protocol")(s)
 }
 
+pub fn class_keyword<I>(s: I) -> IResult<I, TokenSyntax>
+where
+    I: InputTake + Compare<&'static str> + ToString,
+{
+    token("class")(s)
+}
+
+pub fn union_keyword<I>(s: I) -> IResult<I, TokenSyntax>
+where
+    I: InputTake + Compare<&'static str> + ToString,
+{
+    token("union")(s)
+}
+
 pub fn while_keyword<I>(s: I) -> IResult<I, TokenSyntax>
 where
     I: InputTake + Compare<&'static str> + ToString,
@@ -59,6 +80,13 @@ where
     token("while")(s)
 }
 
+pub fn defer_keyword<I>(s: I) -> IResult<I, TokenSyntax>
+where
+    I: InputTake + Compare<&'static str> + ToString,
+{
+    token("defer")(s)
+}
+
 pub fn for_keyword<I>(s: I) -> IResult<I, I>
 where
     I: InputTake + Compare<&'static str>,
@@ -87,6 +115,13 @@ where
     token("return")(s)
 }
 
+pub fn init_keyword<I>(s: I) -> IResult<I, TokenSyntax>
+where
+    I: InputTake + Compare<&'static str> + ToString,
+{
+    token("init")(s)
+}
+
 pub fn deinit_keyword<I>(s: I) -> IResult<I, TokenSyntax>
 where
     I: InputTake + Compare<&'static str> + ToString,
@@ -94,6 +129,48 @@ where
     token("deinit")(s)
 }
 
+pub fn subscript_keyword<I>(s: I) -> IResult<I, TokenSyntax>
+where
+    I: InputTake + Compare<&'static str> + ToString,
+{
+    token("subscript")(s)
+}
+
+pub fn get_keyword<I>(s: I) -> IResult<I, TokenSyntax>
+where
+    I: InputTake + Compare<&'static str> + ToString,
+{
+    token("get")(s)
+}
+
+pub fn set_keyword<I>(s: I) -> IResult<I, TokenSyntax>
+where
+    I: InputTake + Compare<&'static str> + ToString,
+{
+    token("set")(s)
+}
+
+pub fn type_keyword<I>(s: I) -> IResult<I, TokenSyntax>
+where
+    I: InputTake + Compare<&'static str> + ToString,
+{
+    token("type")(s)
+}
+
+pub fn typealias_keyword<I>(s: I) -> IResult<I, TokenSyntax>
+where
+    I: InputTake + Compare<&'static str> + ToString,
+{
+    token("typealias")(s)
+}
+
+pub fn public_keyword<I>(s: I) -> IResult<I, TokenSyntax>
+where
+    I: InputTake + Compare<&'static str> + ToString,
+{
+    token("public")(s)
+}
+
 pub fn use_keyword<I>(s: I) -> IResult<I, I>
 where
     I: InputTake + Compare<&'static str>,
@@ -154,10 +231,12 @@ where
 mod tests {
     use crate::parser::tests::check;
     use crate::parser::wiz::keywords::{
-        as_keyword, deinit_keyword, else_keyword, extension_keyword, extern_keyword, false_keyword,
-        for_keyword, fun_keyword, if_keyword, in_keyword, protocol_keyword, return_keyword,
-        self_keyword, struct_keyword, true_keyword, use_keyword, val_keyword, var_keyword,
-        where_keyword, while_keyword,
+        as_keyword, class_keyword, const_keyword, defer_keyword, deinit_keyword, else_keyword,
+        extension_keyword, extern_keyword, false_keyword, for_keyword, fun_keyword, get_keyword,
+        if_keyword, in_keyword, init_keyword, protocol_keyword, public_keyword, return_keyword,
+        self_keyword, set_keyword, struct_keyword, subscript_keyword, true_keyword, type_keyword,
+        typealias_keyword, union_keyword, use_keyword, val_keyword, var_keyword, where_keyword,
+        while_keyword,
     };
     use wiz_syntax::syntax::token::TokenSyntax;
 
@@ -186,6 +265,11 @@ mod tests {
         check("val", val_keyword, TokenSyntax::from("val"));
     }
 
+    #[test]
+    fn test_const_keyword() {
+        check("const", const_keyword, TokenSyntax::from("const"));
+    }
+
     #[test]
     fn test_extension_keyword() {
         check(
@@ -200,11 +284,26 @@ mod tests {
         check("protocol", protocol_keyword, TokenSyntax::from("protocol"));
     }
 
+    #[test]
+    fn test_class_keyword() {
+        check("class", class_keyword, TokenSyntax::from("class"));
+    }
+
+    #[test]
+    fn test_union_keyword() {
+        check("union", union_keyword, TokenSyntax::from("union"));
+    }
+
     #[test]
     fn test_while_keyword() {
         check("while", while_keyword, TokenSyntax::from("while"));
     }
 
+    #[test]
+    fn test_defer_keyword() {
+        check("defer", defer_keyword, TokenSyntax::from("defer"));
+    }
+
     #[test]
     fn test_for_keyword() {
         assert_eq!(for_keyword("for"), Ok(("", "for")))
@@ -225,11 +324,54 @@ mod tests {
         check("return", return_keyword, TokenSyntax::from("return"));
     }
 
+    #[test]
+    fn test_init_keyword() {
+        check("init", init_keyword, TokenSyntax::from("init"));
+    }
+
     #[test]
     fn test_deinit_keyword() {
         check("deinit", deinit_keyword, TokenSyntax::from("deinit"));
     }
 
+    #[test]
+    fn test_subscript_keyword() {
+        check(
+            "subscript",
+            subscript_keyword,
+            TokenSyntax::from("subscript"),
+        );
+    }
+
+    #[test]
+    fn test_get_keyword() {
+        check("get", get_keyword, TokenSyntax::from("get"));
+    }
+
+    #[test]
+    fn test_set_keyword() {
+        check("set", set_keyword, TokenSyntax::from("set"));
+    }
+
+    #[test]
+    fn test_type_keyword() {
+        check("type", type_keyword, TokenSyntax::from("type"));
+    }
+
+    #[test]
+    fn test_typealias_keyword() {
+        check(
+            "typealias",
+            typealias_keyword,
+            TokenSyntax::from("typealias"),
+        );
+    }
+
+    #[test]
+    fn test_public_keyword() {
+        check("public", public_keyword, TokenSyntax::from("public"));
+    }
+
     #[test]
     fn test_use_keyword() {
         assert_eq!(use_keyword("use"), Ok(("", "use")))
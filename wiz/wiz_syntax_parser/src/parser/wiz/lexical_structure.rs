@@ -1,6 +1,7 @@
 use crate::parser::wiz::character::{
     alphabet, backticks, carriage_return, digit, form_feed, space, under_score, vertical_tab,
 };
+use crate::parser::Located;
 use nom::branch::{alt, permutation};
 use nom::bytes::complete::{tag, take_until, take_while_m_n};
 use nom::character::complete::{crlf, newline, tab};
@@ -11,6 +12,7 @@ use nom::multi::{many0, many1};
 use nom::sequence::tuple;
 use nom::{AsChar, Compare, FindSubstring, IResult, InputIter, InputLength, InputTake, Slice};
 use std::iter::FromIterator;
+use wiz_span::Location;
 use wiz_syntax::syntax::token::TokenSyntax;
 use wiz_syntax::syntax::trivia::{Trivia, TriviaPiece};
 
@@ -24,6 +26,15 @@ where
     map(tag(tkn), TokenSyntax::from)
 }
 
+/// Location of the input, consuming nothing.
+pub fn location<I>(s: I) -> IResult<I, Location>
+where
+    I: Located,
+{
+    let location = s.location();
+    Ok((s, location))
+}
+
 pub fn whitespace0<I>(s: I) -> IResult<I, Trivia>
 where
     I: Slice<RangeFrom<usize>>
@@ -89,13 +89,13 @@ where
 }
 
 /*
-<range_operator> ::= "..." || "..<"
+<range_operator> ::= ".." | "..<"
 */
 pub fn range_operator<I>(s: I) -> IResult<I, I>
 where
     I: InputTake + Compare<&'static str> + Clone,
 {
-    alt((tag("..."), tag("..<")))(s)
+    alt((tag("..<"), tag("..")))(s)
 }
 
 /*
@@ -217,7 +217,7 @@ mod tests {
 
     #[test]
     fn test_range_operator() {
-        assert_eq!(range_operator("..."), Ok(("", "...")));
+        assert_eq!(range_operator(".."), Ok(("", "..")));
         assert_eq!(range_operator("..<"), Ok(("", "..<")));
     }
 
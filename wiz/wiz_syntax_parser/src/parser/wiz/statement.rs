@@ -1,8 +1,9 @@
 use crate::parser::wiz::declaration::{block, decl};
 use crate::parser::wiz::expression::{expr, postfix_expr, prefix_expr};
-use crate::parser::wiz::keywords::{for_keyword, in_keyword, while_keyword};
+use crate::parser::wiz::keywords::{defer_keyword, for_keyword, in_keyword, while_keyword};
 use crate::parser::wiz::lexical_structure::{identifier, token, whitespace0, whitespace1};
 use crate::parser::wiz::operators::{assignment_and_operator, assignment_operator};
+use crate::parser::Located;
 use crate::parser::Span;
 use nom::branch::alt;
 use nom::combinator::map;
@@ -17,8 +18,8 @@ use std::ops::{Range, RangeFrom};
 use wiz_session::ParseSession;
 use wiz_syntax::syntax::expression::{Expr, NameExprSyntax, ParenthesizedExprSyntax};
 use wiz_syntax::syntax::statement::{
-    AssignmentAndOperatorSyntax, AssignmentStmt, AssignmentSyntax, ForLoopSyntax, LoopStmt, Stmt,
-    WhileLoopSyntax,
+    AssignmentAndOperatorSyntax, AssignmentStmt, AssignmentSyntax, DeferSyntax, ForLoopSyntax,
+    LoopStmt, Stmt, WhileLoopSyntax,
 };
 use wiz_syntax::syntax::token::TokenSyntax;
 use wiz_syntax::syntax::FileSyntax;
@@ -26,7 +27,8 @@ use wiz_syntax::syntax::Syntax;
 
 pub fn decl_stmt<I>(s: I) -> IResult<I, Stmt>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -46,7 +48,8 @@ where
 
 pub fn expr_stmt<I>(s: I) -> IResult<I, Stmt>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -69,7 +72,8 @@ where
 */
 pub fn assignment_stmt<I>(s: I) -> IResult<I, Stmt>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -119,7 +123,8 @@ where
 */
 pub fn directly_assignable_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -150,7 +155,8 @@ where
 */
 fn _directly_assignable_postfix_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -179,7 +185,8 @@ where
 */
 pub fn assignable_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -201,7 +208,8 @@ where
 */
 pub fn parenthesized_assignable_expression<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -239,7 +247,8 @@ where
 */
 pub fn parenthesized_directly_assignable_expr<I>(s: I) -> IResult<I, Expr>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -274,7 +283,8 @@ where
 
 pub fn loop_stmt<I>(s: I) -> IResult<I, Stmt>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -294,7 +304,8 @@ where
 
 pub fn while_stmt<I>(s: I) -> IResult<I, LoopStmt>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -323,7 +334,8 @@ where
 
 pub fn for_stmt<I>(s: I) -> IResult<I, LoopStmt>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -362,9 +374,37 @@ where
     )(s)
 }
 
+// <defer_stmt> ::= "defer" <block>
+pub fn defer_stmt<I>(s: I) -> IResult<I, Stmt>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(tuple((defer_keyword, whitespace0, block)), |(d, ws, b)| {
+        Stmt::Defer(DeferSyntax {
+            defer_keyword: d,
+            block: b.with_leading_trivia(ws),
+        })
+    })(s)
+}
+
 pub fn stmt<I>(s: I) -> IResult<I, Stmt>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
         + Clone
@@ -379,7 +419,7 @@ where
     <I as InputIter>::Item: AsChar + Copy,
     <I as InputTakeAtPosition>::Item: AsChar,
 {
-    alt((decl_stmt, assignment_stmt, loop_stmt, expr_stmt))(s)
+    alt((decl_stmt, defer_stmt, assignment_stmt, loop_stmt, expr_stmt))(s)
 }
 
 pub fn file<'sess, 's>(session: &'sess ParseSession, s: Span<'s>) -> IResult<Span<'s>, FileSyntax> {
@@ -403,8 +443,10 @@ pub fn file<'sess, 's>(session: &'sess ParseSession, s: Span<'s>) -> IResult<Spa
 mod tests {
     use crate::parser::tests::{check, check_with_session};
     use crate::parser::wiz::statement::{
-        assignable_expr, assignment_stmt, directly_assignable_expr, file, stmt, while_stmt,
+        assignable_expr, assignment_stmt, defer_stmt, directly_assignable_expr, file, stmt,
+        while_stmt,
     };
+    use wiz_span::Location;
     use wiz_syntax::syntax::block::BlockSyntax;
     use wiz_syntax::syntax::expression::{
         BinaryOperationSyntax, CallArgListSyntax, CallExprSyntax, Expr, MemberSyntax,
@@ -412,7 +454,7 @@ mod tests {
     };
     use wiz_syntax::syntax::literal::LiteralSyntax;
     use wiz_syntax::syntax::statement::{
-        AssignmentAndOperatorSyntax, AssignmentStmt, AssignmentSyntax, LoopStmt, Stmt,
+        AssignmentAndOperatorSyntax, AssignmentStmt, AssignmentSyntax, DeferSyntax, LoopStmt, Stmt,
         WhileLoopSyntax,
     };
     use wiz_syntax::syntax::token::TokenSyntax;
@@ -431,6 +473,7 @@ mod tests {
                 )))),
                 args: Some(CallArgListSyntax::new()),
                 tailing_lambda: None,
+                span: Location::new(0, 1).with_column(1),
             })),
         )
     }
@@ -455,6 +498,7 @@ mod tests {
                             Expr::Name(NameExprSyntax::simple(TokenSyntax::from("b")))
                                 .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
                         ),
+                        span: Location::new(9, 1).with_column(10),
                     })),
                     close_paren: TokenSyntax::from(")"),
                 }),
@@ -476,6 +520,7 @@ mod tests {
                                     Expr::Literal(LiteralSyntax::Integer(TokenSyntax::from("1")))
                                         .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
                                 ),
+                                span: Location::new(34, 2).with_column(19),
                             })
                             .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
                         }))
@@ -493,6 +538,40 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_defer_stmt() {
+        check(
+            r"defer {
+            a = 1
+        }",
+            defer_stmt,
+            Stmt::Defer(DeferSyntax {
+                defer_keyword: TokenSyntax::from("defer"),
+                block: BlockSyntax {
+                    open: TokenSyntax::from("{")
+                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                    body: vec![Stmt::Assignment(AssignmentStmt::Assignment(
+                        AssignmentSyntax {
+                            target: Expr::Name(NameExprSyntax::simple(TokenSyntax::from("a")))
+                                .with_leading_trivia(Trivia::from(vec![
+                                    TriviaPiece::Newlines(1),
+                                    TriviaPiece::Spaces(12),
+                                ])),
+                            operator: TokenSyntax::from("=")
+                                .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                            value: Expr::Literal(LiteralSyntax::Integer(TokenSyntax::from("1")))
+                                .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                        },
+                    ))],
+                    close: TokenSyntax::from("}").with_leading_trivia(Trivia::from(vec![
+                        TriviaPiece::Newlines(1),
+                        TriviaPiece::Spaces(8),
+                    ])),
+                },
+            }),
+        );
+    }
+
     #[test]
     fn test_while_stmt() {
         check(
@@ -519,6 +598,7 @@ mod tests {
                         Expr::Name(NameExprSyntax::simple(TokenSyntax::from("b")))
                             .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
                     ),
+                    span: Location::new(10, 1).with_column(11),
                 }),
                 block: BlockSyntax {
                     open: TokenSyntax::from("{")
@@ -538,6 +618,7 @@ mod tests {
                                     Expr::Literal(LiteralSyntax::Integer(TokenSyntax::from("1")))
                                         .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
                                 ),
+                                span: Location::new(34, 2).with_column(19),
                             })
                             .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
                         }))
@@ -1,55 +1,70 @@
 use crate::parser::wiz::character::{ampersand, comma};
+use crate::parser::wiz::expression::expr;
 use crate::parser::wiz::lexical_structure::{identifier, token, whitespace0};
+use crate::parser::Located;
 use nom::branch::alt;
-use nom::character::complete::char;
 use nom::combinator::{map, opt};
-use nom::multi::{many0, many1};
+use nom::multi::many0;
 use nom::sequence::tuple;
-use nom::{AsChar, Compare, FindSubstring, IResult, InputIter, InputLength, InputTake, Slice};
+use nom::{
+    AsChar, Compare, ExtendInto, FindSubstring, IResult, InputIter, InputLength, InputTake,
+    InputTakeAtPosition, Offset, Slice,
+};
 use std::ops::{Range, RangeFrom};
 use wiz_syntax::syntax::token::TokenSyntax;
 use wiz_syntax::syntax::type_name::{
-    ArrayTypeSyntax, DecoratedTypeName, ParenthesizedTypeName, SimpleTypeName,
-    TypeArgumentElementSyntax, TypeArgumentListSyntax, TypeConstraintSyntax, TypeName,
-    TypeNameSpaceElementSyntax, TypeParam, TypeParameterElementSyntax, TypeParameterListSyntax,
-    UserTypeName,
+    ArrayTypeSyntax, DecoratedTypeName, FunctionTypeSyntax, ParenthesizedTypeName, SimpleTypeName,
+    SliceTypeSyntax, TypeArgumentElementSyntax, TypeArgumentListSyntax, TypeConstraintSyntax,
+    TypeName, TypeNameSpaceElementSyntax, TypeParam, TypeParameterElementSyntax,
+    TypeParameterListSyntax, UserTypeName,
 };
 use wiz_syntax::syntax::Syntax;
 
 pub fn type_<I>(s: I) -> IResult<I, TypeName>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
+        + InputLength
         + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     alt((
+        map(function_type, |f| TypeName::Function(Box::new(f))),
         parenthesized_type,
         map(decorated_type, |t| TypeName::Decorated(Box::new(t))),
         type_reference,
         map(array_type_syntax, |a| TypeName::Array(Box::new(a))),
-        // function_type,
+        map(slice_type_syntax, |a| TypeName::Slice(Box::new(a))),
     ))(s)
 }
 
 pub fn parenthesized_type<I>(s: I) -> IResult<I, TypeName>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
+        + InputLength
         + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(
         tuple((token("("), whitespace0, type_, whitespace0, token(")"))),
@@ -65,16 +80,21 @@ where
 
 pub fn decorated_type<I>(s: I) -> IResult<I, DecoratedTypeName>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
+        + InputLength
         + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(
         tuple((
@@ -90,32 +110,42 @@ where
 
 pub fn type_reference<I>(s: I) -> IResult<I, TypeName>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
+        + InputLength
         + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     user_type(s)
 }
 
 pub fn user_type<I>(s: I) -> IResult<I, TypeName>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
+        + InputLength
         + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(
         tuple((
@@ -140,16 +170,21 @@ where
 
 pub fn simple_user_type<I>(s: I) -> IResult<I, SimpleTypeName>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
+        + InputLength
         + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(tuple((identifier, opt(type_arguments))), |(name, args)| {
         SimpleTypeName {
@@ -159,22 +194,81 @@ where
     })(s)
 }
 
-// pub fn function_type(s: &str) -> IResult<&str, TypeName> {
-//
-// }
+// <function_type> ::= "(" (<type> ("," <type>)* ","?)? ")" "->" <type>
+pub fn function_type<I>(s: I) -> IResult<I, FunctionTypeSyntax>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(
+        tuple((
+            token("("),
+            many0(tuple((whitespace0, type_, whitespace0, comma))),
+            opt(tuple((whitespace0, type_))),
+            whitespace0,
+            token(")"),
+            whitespace0,
+            token("->"),
+            whitespace0,
+            type_,
+        )),
+        |(open, t, typ, tws, close, aws, arrow, rws, return_type)| {
+            let mut elements: Vec<_> = t
+                .into_iter()
+                .map(|(lws, tp, rws, com)| TypeArgumentElementSyntax {
+                    element: tp.with_leading_trivia(lws),
+                    trailing_comma: Some(com.with_leading_trivia(rws)),
+                })
+                .collect();
+            if let Some((ws, p)) = typ {
+                elements.push(TypeArgumentElementSyntax {
+                    element: p.with_leading_trivia(ws),
+                    trailing_comma: None,
+                });
+            };
+            FunctionTypeSyntax {
+                arguments: TypeArgumentListSyntax {
+                    open,
+                    elements,
+                    close: close.with_leading_trivia(tws),
+                },
+                arrow: arrow.with_leading_trivia(aws),
+                return_type: return_type.with_leading_trivia(rws),
+            }
+        },
+    )(s)
+}
 
 pub fn type_arguments<I>(s: I) -> IResult<I, TypeArgumentListSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
+        + InputLength
         + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(
         tuple((
@@ -210,16 +304,21 @@ where
 // <type_parameters> ::= "<" <type_parameter> ("," <type_parameter>)* ","? ">"
 pub fn type_parameters<I>(s: I) -> IResult<I, TypeParameterListSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
-        + Compare<&'static str>
-        + FindSubstring<&'static str>
+        + InputLength
         + ToString
-        + Slice<Range<usize>>,
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(
         tuple((
@@ -255,16 +354,21 @@ where
 // <type_parameter> ::= <identifier> <type_constraint>?
 pub fn type_parameter<I>(s: I) -> IResult<I, TypeParam>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
-        + Compare<&'static str>
-        + FindSubstring<&'static str>
+        + InputLength
         + ToString
-        + Slice<Range<usize>>,
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(
         tuple((
@@ -281,16 +385,21 @@ where
 // <type_constraint> ::= ":" <type>
 pub fn type_constraint_syntax<I>(s: I) -> IResult<I, TypeConstraintSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
-        + Compare<&'static str>
-        + FindSubstring<&'static str>
+        + InputLength
         + ToString
-        + Slice<Range<usize>>,
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(tuple((token(":"), whitespace0, type_)), |(sep, lws, t)| {
         TypeConstraintSyntax {
@@ -302,16 +411,21 @@ where
 
 pub fn array_type_syntax<I>(s: I) -> IResult<I, ArrayTypeSyntax>
 where
-    I: Slice<RangeFrom<usize>>
+    I: Located
+        + Slice<RangeFrom<usize>>
         + Slice<Range<usize>>
         + InputIter
-        + InputTake
-        + InputLength
         + Clone
+        + InputLength
         + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
         + FindSubstring<&'static str>
         + Compare<&'static str>,
     <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
 {
     map(
         tuple((
@@ -321,18 +435,7 @@ where
             whitespace0,
             token(";"),
             whitespace0,
-            many1(alt((
-                char('0'),
-                char('1'),
-                char('2'),
-                char('3'),
-                char('4'),
-                char('5'),
-                char('6'),
-                char('7'),
-                char('8'),
-                char('9'),
-            ))),
+            expr,
             whitespace0,
             token("]"),
         )),
@@ -340,22 +443,55 @@ where
             open,
             type_: typ.with_leading_trivia(ws1),
             semicolon: semi.with_leading_trivia(ws2),
-            size: TokenSyntax::from(size.into_iter().collect::<String>()).with_leading_trivia(ws3),
+            size: size.with_leading_trivia(ws3),
             close: close.with_leading_trivia(ws4),
         },
     )(s)
 }
 
+pub fn slice_type_syntax<I>(s: I) -> IResult<I, SliceTypeSyntax>
+where
+    I: Located
+        + Slice<RangeFrom<usize>>
+        + Slice<Range<usize>>
+        + InputIter
+        + Clone
+        + InputLength
+        + ToString
+        + InputTake
+        + Offset
+        + InputTakeAtPosition
+        + ExtendInto<Item = char, Extender = String>
+        + FindSubstring<&'static str>
+        + Compare<&'static str>,
+    <I as InputIter>::Item: AsChar + Copy,
+    <I as InputTakeAtPosition>::Item: AsChar,
+{
+    map(
+        tuple((token("["), whitespace0, type_, whitespace0, token("]"))),
+        |(open, ws1, typ, ws2, close)| SliceTypeSyntax {
+            open,
+            type_: typ.with_leading_trivia(ws1),
+            close: close.with_leading_trivia(ws2),
+        },
+    )(s)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::parser::tests::check;
     use crate::parser::wiz::type_::{
-        array_type_syntax, decorated_type, type_parameter, type_parameters, user_type,
+        array_type_syntax, decorated_type, function_type, slice_type_syntax, type_parameter,
+        type_parameters, user_type,
     };
+    use wiz_span::Location;
+    use wiz_syntax::syntax::expression::{BinaryOperationSyntax, Expr, NameExprSyntax};
+    use wiz_syntax::syntax::literal::LiteralSyntax;
     use wiz_syntax::syntax::token::TokenSyntax;
     use wiz_syntax::syntax::trivia::{Trivia, TriviaPiece};
     use wiz_syntax::syntax::type_name::{
-        ArrayTypeSyntax, DecoratedTypeName, SimpleTypeName, TypeConstraintSyntax, TypeName,
+        ArrayTypeSyntax, DecoratedTypeName, FunctionTypeSyntax, SimpleTypeName, SliceTypeSyntax,
+        TypeArgumentElementSyntax, TypeArgumentListSyntax, TypeConstraintSyntax, TypeName,
         TypeNameSpaceElementSyntax, TypeParam, TypeParameterElementSyntax, TypeParameterListSyntax,
         UserTypeName,
     };
@@ -644,9 +780,78 @@ mod tests {
                 open: TokenSyntax::from("["),
                 type_: TypeName::Simple(SimpleTypeName::from("Int64")),
                 semicolon: TokenSyntax::from(";"),
-                size: TokenSyntax::from("12"),
+                size: Expr::Literal(LiteralSyntax::Integer(TokenSyntax::from("12"))),
                 close: TokenSyntax::from("]"),
             },
         );
     }
+
+    #[test]
+    fn test_array_type_syntax_with_constant_expression() {
+        check(
+            "[Int64; N * 2]",
+            array_type_syntax,
+            ArrayTypeSyntax {
+                open: TokenSyntax::from("["),
+                type_: TypeName::Simple(SimpleTypeName::from("Int64")),
+                semicolon: TokenSyntax::from(";"),
+                size: Expr::BinOp(BinaryOperationSyntax {
+                    left: Box::new(Expr::Name(NameExprSyntax::simple(
+                        TokenSyntax::from("N")
+                            .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                    ))),
+                    operator: TokenSyntax::from("*")
+                        .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                    right: Box::new(Expr::Literal(LiteralSyntax::Integer(
+                        TokenSyntax::from("2")
+                            .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                    ))),
+                    span: Location::new(10, 1).with_column(11),
+                }),
+                close: TokenSyntax::from("]"),
+            },
+        );
+    }
+
+    #[test]
+    fn test_slice_type_syntax() {
+        check(
+            "[UInt8]",
+            slice_type_syntax,
+            SliceTypeSyntax {
+                open: TokenSyntax::from("["),
+                type_: TypeName::Simple(SimpleTypeName::from("UInt8")),
+                close: TokenSyntax::from("]"),
+            },
+        );
+    }
+
+    #[test]
+    fn test_function_type() {
+        check(
+            "(Int64, Int64) -> Int64",
+            function_type,
+            FunctionTypeSyntax {
+                arguments: TypeArgumentListSyntax {
+                    open: TokenSyntax::from("("),
+                    elements: vec![
+                        TypeArgumentElementSyntax {
+                            element: TypeName::Simple(SimpleTypeName::from("Int64")),
+                            trailing_comma: Some(TokenSyntax::from(",")),
+                        },
+                        TypeArgumentElementSyntax {
+                            element: TypeName::Simple(SimpleTypeName::from("Int64"))
+                                .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                            trailing_comma: None,
+                        },
+                    ],
+                    close: TokenSyntax::from(")"),
+                },
+                arrow: TokenSyntax::from("->")
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+                return_type: TypeName::Simple(SimpleTypeName::from("Int64"))
+                    .with_leading_trivia(Trivia::from(TriviaPiece::Spaces(1))),
+            },
+        );
+    }
 }
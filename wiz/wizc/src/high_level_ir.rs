@@ -1,17 +1,25 @@
+use crate::high_level_ir::const_evaluator::ConstEvaluator;
+use crate::high_level_ir::derive::Derive;
 use crate::high_level_ir::node_id::ModuleId;
+use crate::high_level_ir::re_export::ReExports;
+use crate::high_level_ir::type_resolver::error::ResolverError;
 use crate::high_level_ir::type_resolver::TypeResolver;
 use std::collections::HashMap;
-use wiz_arena::{Arena, DeclarationId};
-use wiz_data_structure::annotation::Annotations;
+use wiz_arena::{Arena, DeclarationId, DeclarationItemKind};
+use wiz_constants::annotation::{DERIVE, NO_MANGLE};
+use wiz_data_structure::annotation::{
+    Annotation, AnnotationArgument, AnnotationValue, Annotations,
+};
 use wiz_hir::typed_decl::{
-    TypedArgDef, TypedComputedProperty, TypedDeclKind, TypedExtension, TypedFun, TypedFunBody,
-    TypedProtocol, TypedStoredProperty, TypedStruct, TypedTopLevelDecl, TypedVar,
+    TypedArgDef, TypedAssociatedType, TypedComputedProperty, TypedDeclKind, TypedExtension,
+    TypedExternVar, TypedFun, TypedFunBody, TypedInitializer, TypedProtocol, TypedStoredProperty,
+    TypedStruct, TypedSubscriptDecl, TypedTopLevelDecl, TypedTypeAlias, TypedVar,
 };
 use wiz_hir::typed_expr::{
     TypedArray, TypedBinOp, TypedBinaryOperator, TypedCall, TypedCallArg, TypedExpr, TypedExprKind,
-    TypedIf, TypedInstanceMember, TypedLambda, TypedLiteralKind, TypedName, TypedPostfixUnaryOp,
-    TypedPostfixUnaryOperator, TypedPrefixUnaryOp, TypedPrefixUnaryOperator, TypedReturn,
-    TypedSubscript, TypedTypeCast, TypedUnaryOp,
+    TypedIf, TypedInstanceMember, TypedLambda, TypedLiteralKind, TypedLocation, TypedName,
+    TypedPostfixUnaryOp, TypedPostfixUnaryOperator, TypedPrefixUnaryOp, TypedPrefixUnaryOperator,
+    TypedReturn, TypedSourceLocationKind, TypedSubscript, TypedTypeCast, TypedUnaryOp,
 };
 use wiz_hir::typed_file::TypedSpellBook;
 use wiz_hir::typed_stmt::{
@@ -19,17 +27,21 @@ use wiz_hir::typed_stmt::{
     TypedBlock, TypedForStmt, TypedLoopStmt, TypedStmt, TypedWhileLoopStmt,
 };
 use wiz_hir::typed_type::{
-    Package, TypedNamedValueType, TypedPackage, TypedType, TypedTypeParam, TypedValueType,
+    Package, TypedArgType, TypedFunctionType, TypedNamedValueType, TypedPackage, TypedType,
+    TypedTypeParam, TypedValueType,
 };
 use wiz_hir::typed_type_constraint::TypedTypeConstraint;
 use wiz_hir::typed_use::TypedUse;
 use wiz_result::Result;
 use wiz_session::Session;
-use wiz_syntax::syntax::annotation::AnnotationsSyntax;
+use wiz_span::Location;
+use wiz_syntax::syntax::annotation::{AnnotationSyntax, AnnotationsSyntax};
 use wiz_syntax::syntax::block::BlockSyntax;
 use wiz_syntax::syntax::declaration::fun_syntax::{ArgDef, FunBody, FunSyntax};
 use wiz_syntax::syntax::declaration::{
-    DeclKind, StoredPropertySyntax, StructPropertySyntax, StructSyntax, UseSyntax,
+    AssociatedTypeSyntax, DeclKind, ExternCSyntax, ExternDeclKind, InitializerSyntax,
+    StoredPropertySyntax, StructPropertySyntax, StructSyntax, SubscriptDeclarationSyntax,
+    TypeAliasSyntax, UseSyntax, UsedNameSyntax,
 };
 use wiz_syntax::syntax::declaration::{ExtensionSyntax, VarSyntax};
 use wiz_syntax::syntax::expression::{
@@ -42,11 +54,14 @@ use wiz_syntax::syntax::statement::{
     AssignmentStmt, ForLoopSyntax, LoopStmt, Stmt, WhileLoopSyntax,
 };
 use wiz_syntax::syntax::type_name::{TypeName, TypeParam, UserTypeName};
-use wiz_syntax::syntax::WizFile;
+use wiz_syntax::syntax::{FileSyntax, WizFile};
 use wiz_syntax_parser::parser::wiz::parse_from_file_path;
 use wiz_utils::utils::path_string_to_page_name;
 
+mod const_evaluator;
+mod derive;
 pub mod node_id;
+mod re_export;
 pub mod type_checker;
 pub mod type_resolver;
 pub mod wlib;
@@ -55,6 +70,9 @@ pub struct AstLowering<'a> {
     session: &'a mut Session,
     arena: &'a mut Arena,
     namespace_id: DeclarationId,
+    errors: Vec<ResolverError>,
+    /// Path of the file being lowered, reported by source locations.
+    source_file: String,
 }
 
 pub fn ast2hlir(
@@ -73,6 +91,20 @@ impl<'a> AstLowering<'a> {
             session,
             arena,
             namespace_id: DeclarationId::ROOT,
+            errors: vec![],
+            source_file: String::from("<unknown source>"),
+        }
+    }
+
+    fn location(&self, span: Location) -> Option<TypedLocation> {
+        if span == Location::DUMMY {
+            None
+        } else {
+            Some(TypedLocation {
+                file: self.source_file.clone(),
+                line: span.line(),
+                column: span.column(),
+            })
         }
     }
 
@@ -96,34 +128,44 @@ impl<'a> AstLowering<'a> {
 
     pub fn lowing(&mut self, s: WizFile, module_id: ModuleId) -> Result<TypedSpellBook> {
         let file = self.file(s);
+        if !self.errors.is_empty() {
+            return Err(Box::new(self.errors.remove(0)));
+        }
+
+        ReExports::new(&file).apply_to(self.arena)?;
 
         let mut resolver = TypeResolver::new(self.session, self.arena);
 
         // NOTE: detect decl names
         resolver.preload_file(&file)?;
+        resolver.preload_arg_defaults_file(&file)?;
 
         let file = resolver.file(file)?;
         Ok(file)
     }
 
     fn file(&mut self, f: WizFile) -> TypedSpellBook {
-        let WizFile { name, syntax } = f;
+        let WizFile { name, path, syntax } = f;
 
         let name = path_string_to_page_name(&name);
 
-        self.push_namespace(name, |slf| {
-            // NOTE: Inject default uses
-            let mut uses = vec![
-                TypedUse::from(vec!["core", "builtin", "*"]),
-                TypedUse::from(vec!["std", "builtin", "*"]),
-            ];
+        let parent_source_file = self.source_file.clone();
+        if let Some(path) = path {
+            self.source_file = path.display().to_string();
+        }
+
+        let spell_book = self.push_namespace(name, |slf| {
+            let mut uses = vec![];
             let mut others = vec![];
+            let mut externs = vec![];
             for l in syntax.body.into_iter() {
                 if let DeclKind::Use(u) = l.kind {
-                    uses.push(slf.use_syntax(u, l.annotations));
+                    uses.extend(slf.use_syntax(u, l.annotations));
+                } else if let DeclKind::ExternC(e) = l.kind {
+                    externs.push(e);
                 } else if let DeclKind::Struct(s) = &l.kind {
                     let annotation = slf.annotations(&l.annotations);
-                    match s.struct_keyword.token().as_str() {
+                    let id = match s.struct_keyword.token().as_str() {
                         "struct" => slf.arena.register_struct(
                             &slf.namespace_id,
                             &s.name.token(),
@@ -134,23 +176,125 @@ impl<'a> AstLowering<'a> {
                             &s.name.token(),
                             annotation,
                         ),
+                        "union" => {
+                            slf.arena
+                                .register_union(&slf.namespace_id, &s.name.token(), annotation)
+                        }
+                        "class" => {
+                            slf.arena
+                                .register_class(&slf.namespace_id, &s.name.token(), annotation)
+                        }
                         _ => unreachable!(),
                     };
+                    if let Some(id) = id {
+                        for p in s.body.properties.iter() {
+                            if let StructPropertySyntax::AssociatedType(a) = p {
+                                slf.arena.register_associated_type(&id, &a.name.token());
+                            }
+                        }
+                    }
                     others.push(l);
                 } else {
                     others.push(l);
                 }
             }
 
+            // NOTE: constants and functions are lowered and registered first,
+            //       so that array lengths can be evaluated with them.
+            //       Type aliases are registered before the declarations using them.
+            let mut others = others.into_iter().map(Some).collect::<Vec<_>>();
+            let mut body = vec![None; others.len()];
+            let mut derived = vec![];
+            let phases: [fn(&DeclKind) -> bool; 4] = [
+                |d| matches!(d, DeclKind::Var(v) if v.mutability_keyword.token() == "const"),
+                |d| matches!(d, DeclKind::TypeAlias(_)),
+                |d| matches!(d, DeclKind::Fun(_)),
+                |_| true,
+            ];
+            for phase in phases {
+                for (i, d) in others.iter_mut().enumerate() {
+                    if d.as_ref().is_some_and(|d| phase(&d.kind)) {
+                        let d = d.take().unwrap();
+                        let decl = slf.decl(d.kind, d.annotations);
+                        let derives = slf.derives(&decl.annotations);
+                        slf.register_decl(&decl);
+                        derived.extend(slf.derive(&decl, derives));
+                        body[i] = Some(decl);
+                    }
+                }
+            }
+            let mut body: Vec<_> = body.into_iter().flatten().collect();
+            body.extend(derived);
+            for e in externs {
+                for decl in slf.extern_syntax(e) {
+                    slf.register_decl(&decl);
+                    body.push(decl);
+                }
+            }
+
             TypedSpellBook {
                 name: name.to_string(),
                 uses,
-                body: others
-                    .into_iter()
-                    .map(|d| slf.decl(d.kind, d.annotations))
-                    .collect(),
+                body,
             }
-        })
+        });
+        self.source_file = parent_source_file;
+        spell_book
+    }
+
+    /// Register constants and functions, which may be used in array lengths,
+    /// and type aliases, which are expanded by [Self::type_].
+    fn register_decl(&mut self, d: &TypedTopLevelDecl) {
+        match &d.kind {
+            TypedDeclKind::TypeAlias(t) => {
+                self.arena.register_type_alias(
+                    &self.namespace_id,
+                    &t.name,
+                    t.type_params.clone(),
+                    t.type_.clone(),
+                    d.annotations.clone(),
+                );
+            }
+            TypedDeclKind::Const(c) => {
+                self.arena.register_constant(
+                    &self.namespace_id,
+                    &c.name,
+                    c.type_.clone().unwrap_or_else(TypedType::noting),
+                    c.value.clone(),
+                    d.annotations.clone(),
+                );
+            }
+            TypedDeclKind::Fun(f) => {
+                let id = self.arena.register_function(
+                    &self.namespace_id,
+                    &f.name,
+                    TypedType::noting(),
+                    f.type_params.clone(),
+                    f.body.clone(),
+                    d.annotations.clone(),
+                );
+                if let Some(DeclarationItemKind::Function(rf)) = id
+                    .and_then(|id| self.arena.get_mut_by_id(&id))
+                    .map(|item| &mut item.kind)
+                {
+                    rf.arg_names = f.arg_defs.iter().map(|a| a.name.clone()).collect();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn array_length(&mut self, size: Expr) -> usize {
+        let size = self.expr(size);
+        ConstEvaluator::new(self.arena, self.namespace_id, self.session.data_layout())
+            .array_length(&size)
+            .unwrap_or_else(|e| {
+                self.errors.push(ResolverError::from(format!(
+                    "Array length is not a constant expression: {}",
+                    e
+                )));
+                0
+            })
     }
 
     fn annotations(&mut self, a: &Option<AnnotationsSyntax>) -> Annotations {
@@ -159,18 +303,143 @@ impl<'a> AstLowering<'a> {
             Some(a) => Annotations::from(
                 a.elements
                     .iter()
-                    .map(|a| a.element.token())
+                    .map(|a| self.annotation(&a.element))
                     .collect::<Vec<_>>(),
             ),
         }
     }
 
+    fn annotation(&mut self, a: &AnnotationSyntax) -> Annotation {
+        Annotation {
+            name: a.name.token(),
+            arguments: a
+                .arguments
+                .iter()
+                .flat_map(|a| a.elements.iter())
+                .filter_map(|a| {
+                    let value = self.annotation_value(&a.element.value)?;
+                    Some(AnnotationArgument {
+                        label: a.element.label.as_ref().map(|l| l.label.token()),
+                        value,
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    fn annotation_value(&mut self, e: &Expr) -> Option<AnnotationValue> {
+        match e {
+            Expr::Name(n) if n.name_space.is_none() && n.type_arguments.is_none() => {
+                Some(AnnotationValue::Name(n.name.token()))
+            }
+            Expr::Literal(l) => match self.literal_syntax(l.clone()) {
+                TypedLiteralKind::Integer(i) => Some(AnnotationValue::Integer(i)),
+                TypedLiteralKind::FloatingPoint(f) => Some(AnnotationValue::FloatingPoint(f)),
+                TypedLiteralKind::String(s) => Some(AnnotationValue::String(s)),
+                TypedLiteralKind::Boolean(b) => Some(AnnotationValue::Boolean(b == "true")),
+                _ => {
+                    self.errors.push(ResolverError::from(format!(
+                        "Annotation argument must be a literal or a name, but got {:?}",
+                        l
+                    )));
+                    None
+                }
+            },
+            _ => {
+                self.errors.push(ResolverError::from(format!(
+                    "Annotation argument must be a literal or a name, but got {:?}",
+                    e
+                )));
+                None
+            }
+        }
+    }
+
+    /// Protocols listed in `#[derive(..)]`.
+    fn derives(&mut self, a: &Annotations) -> Vec<Derive> {
+        let arguments = a
+            .iter()
+            .filter(|a| a.name == DERIVE)
+            .flat_map(|a| a.arguments.iter())
+            .map(|a| match a.value.as_name() {
+                Some(name) => name.to_string(),
+                None => format!("{:?}", a.value),
+            })
+            .collect::<Vec<_>>();
+        arguments
+            .into_iter()
+            .filter_map(|name| {
+                let derive = Derive::from_name(&name);
+                if derive.is_none() {
+                    self.errors.push(ResolverError::from(format!(
+                        "Cannot derive `{}`, derivable protocols are `Clone`, `Equals` and `Printable`",
+                        name
+                    )));
+                }
+                derive
+            })
+            .collect()
+    }
+
+    /// Synthesize the extensions requested by `#[derive(..)]` on `d`.
+    /// Whether the fields conform to the protocols is checked by [TypeResolver].
+    fn derive(&mut self, d: &TypedTopLevelDecl, derives: Vec<Derive>) -> Vec<TypedTopLevelDecl> {
+        if derives.is_empty() {
+            return vec![];
+        }
+        match &d.kind {
+            TypedDeclKind::Struct(s)
+                if derives.contains(&Derive::Clone)
+                    && s.member_functions
+                        .iter()
+                        .any(|f| f.name == TypedStruct::DEINIT) =>
+            {
+                self.errors.push(ResolverError::from(format!(
+                    "Cannot derive `Clone` for `{}`, because it has `deinit`",
+                    s.name
+                )));
+                vec![]
+            }
+            TypedDeclKind::Struct(s) if s.type_params.is_none() => derives
+                .into_iter()
+                .map(|derive| TypedTopLevelDecl {
+                    annotations: Annotations::from(&[DERIVE]),
+                    package: d.package.clone(),
+                    modifiers: vec![],
+                    kind: TypedDeclKind::Extension(derive.extension(s, &d.package)),
+                })
+                .collect(),
+            TypedDeclKind::Struct(s) => {
+                self.errors.push(ResolverError::from(format!(
+                    "Cannot derive for generic struct `{}`",
+                    s.name
+                )));
+                vec![]
+            }
+            _ => {
+                self.errors.push(ResolverError::from(
+                    "`derive` is only allowed on struct declarations",
+                ));
+                vec![]
+            }
+        }
+    }
+
     fn stmt(&mut self, s: Stmt) -> TypedStmt {
         match s {
-            Stmt::Decl(decl) => TypedStmt::Decl(self.decl(decl.kind, decl.annotations)),
+            Stmt::Decl(decl) => {
+                if let DeclKind::TypeAlias(t) = &decl.kind {
+                    self.errors.push(ResolverError::from(format!(
+                        "type alias `{}` must be declared at top level",
+                        t.name.token()
+                    )));
+                }
+                TypedStmt::Decl(self.decl(decl.kind, decl.annotations))
+            }
             Stmt::Expr(expr) => TypedStmt::Expr(self.expr(expr)),
             Stmt::Assignment(a) => TypedStmt::Assignment(self.assignment(a)),
             Stmt::Loop(l) => TypedStmt::Loop(self.loop_stmt(l)),
+            Stmt::Defer(d) => TypedStmt::Defer(self.block(d.block)),
         }
     }
 
@@ -227,37 +496,105 @@ impl<'a> AstLowering<'a> {
             package: Package::from(&self.arena.resolve_fully_qualified_name(&self.namespace_id)),
             modifiers: vec![],
             kind: match d {
-                DeclKind::Var(v) => TypedDeclKind::Var(self.var_syntax(v)),
+                DeclKind::Var(v) => match &*v.mutability_keyword.token() {
+                    "const" => TypedDeclKind::Const(self.var_syntax(v)),
+                    _ => TypedDeclKind::Var(self.var_syntax(v)),
+                },
                 DeclKind::Fun(f) => TypedDeclKind::Fun(self.fun_syntax(f)),
                 DeclKind::Struct(s) => match &*s.struct_keyword.token() {
                     "struct" => TypedDeclKind::Struct(self.struct_syntax(s)),
                     "protocol" => TypedDeclKind::Protocol(self.protocol_syntax(s)),
+                    "union" => TypedDeclKind::Union(self.struct_syntax(s)),
+                    "class" => TypedDeclKind::Class(self.struct_syntax(s)),
                     kw => panic!("Unknown keyword `{}`", kw),
                 },
-                DeclKind::ExternC { .. } => todo!(),
+                DeclKind::ExternC(_) => unreachable!(),
                 DeclKind::Enum { .. } => TypedDeclKind::Enum,
                 DeclKind::Extension(e) => TypedDeclKind::Extension(self.extension_syntax(e)),
                 DeclKind::Use(_) => unreachable!(),
-                DeclKind::Module(m) => {
-                    let (name, file) = m;
-                    let file = match file {
-                        Some(file) => WizFile { name, syntax: file },
-                        None => {
-                            let mut s = self.session.local_spell_book_root().to_owned();
-                            let fqn = self.arena.resolve_fully_qualified_name(&self.namespace_id);
-                            for n in &fqn[1..] {
-                                s.push(n);
+                DeclKind::TypeAlias(t) => TypedDeclKind::TypeAlias(self.type_alias(t)),
+                DeclKind::Module((name, Some(file))) => TypedDeclKind::Module(self.file(WizFile {
+                    name,
+                    path: None,
+                    syntax: file,
+                })),
+                DeclKind::Module((name, None)) => TypedDeclKind::Module(self.module_file(name)),
+            },
+        }
+    }
+
+    /// Namespace `name` declared without a body, whose declarations are in
+    /// `<name>.wiz` of the directory named after the current namespace.
+    /// e.g. `namespace ip` in `net/address.wiz` is read from `net/address/ip.wiz`.
+    fn module_file(&mut self, name: String) -> TypedSpellBook {
+        let fqn = self.arena.resolve_fully_qualified_name(&self.namespace_id);
+        let mut path = self.session.local_spell_book_root().to_owned();
+        for n in &fqn[1..] {
+            path.push(n);
+        }
+        path.push(&name);
+        path.set_extension("wiz");
+        let file = if path.is_file() {
+            match parse_from_file_path(&self.session.parse_session, &path, Some(&name)) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    self.errors.push(ResolverError::from(e.to_string()));
+                    None
+                }
+            }
+        } else {
+            self.errors.push(ResolverError::from(format!(
+                "namespace `{}::{}` is declared in `{}`, but `{}` is not found",
+                fqn.join("::"),
+                name,
+                self.source_file,
+                path.display()
+            )));
+            None
+        };
+        // NOTE: lower an empty namespace to continue reporting other errors
+        self.file(file.unwrap_or_else(|| WizFile {
+            name,
+            path: None,
+            syntax: FileSyntax {
+                leading_trivia: Default::default(),
+                body: vec![],
+                trailing_trivia: Default::default(),
+            },
+        }))
+    }
+
+    /// Declarations in an `extern` block refer to C symbols, so they are never mangled.
+    fn extern_syntax(&mut self, e: ExternCSyntax) -> Vec<TypedTopLevelDecl> {
+        let package = Package::from(&self.arena.resolve_fully_qualified_name(&self.namespace_id));
+        e.declarations
+            .into_iter()
+            .map(|d| {
+                let mut annotations = self.annotations(&d.annotations);
+                annotations.push(Annotation::new(NO_MANGLE));
+                TypedTopLevelDecl {
+                    annotations,
+                    package: package.clone(),
+                    modifiers: vec![],
+                    kind: match d.kind {
+                        ExternDeclKind::Var(v) => TypedDeclKind::ExternVar(TypedExternVar {
+                            is_mut: v.mutability_keyword.token() == "var",
+                            name: v.name.token(),
+                            type_: self.type_(v.type_.type_),
+                        }),
+                        ExternDeclKind::Fun(f) => {
+                            if f.body.is_some() {
+                                self.errors.push(ResolverError::from(format!(
+                                    "function `{}` in `extern` block must not have a body",
+                                    f.name.token()
+                                )));
                             }
-                            s.push(&name);
-                            s.set_extension("wiz");
-                            parse_from_file_path(&self.session.parse_session, s, Some(&name))
-                                .expect(&format!("try to parse {:?}{}, but failed", fqn, name))
+                            TypedDeclKind::Fun(self.fun_syntax(*f))
                         }
-                    };
-                    TypedDeclKind::Module(self.file(file))
+                    },
                 }
-            },
-        }
+            })
+            .collect()
     }
 
     pub fn var_syntax(&mut self, v: VarSyntax) -> TypedVar {
@@ -279,17 +616,20 @@ impl<'a> AstLowering<'a> {
                 },
                 name: a.name.token(),
                 type_: self.type_(a.type_name),
+                default: a.default.map(|d| self.expr(d.value)),
             },
             ArgDef::Self_(s) => match s.reference {
                 None => TypedArgDef {
                     label: "_".to_string(),
                     name: "self".to_string(),
                     type_: TypedType::Self_,
+                    default: None,
                 },
                 Some(_) => TypedArgDef {
                     label: "_".to_string(),
                     name: "self".to_string(),
-                    type_: TypedType::Self_, // TODO: Reference
+                    type_: TypedType::Value(TypedValueType::Reference(Box::new(TypedType::Self_))),
+                    default: None,
                 },
             },
         }
@@ -340,33 +680,7 @@ impl<'a> AstLowering<'a> {
             (Some(a), _) | (_, Some(a)) => Some(a),
             (_, _) => None,
         }
-        .map(|type_constraints| {
-            let mut group = HashMap::<_, Vec<_>>::new();
-            for type_constraint in type_constraints {
-                let name = type_constraint.name.token();
-                group
-                    .entry(name)
-                    .or_default()
-                    .push(type_constraint.type_constraint);
-            }
-            group
-                .into_iter()
-                .map(|(k, v)| TypedTypeConstraint {
-                    type_: TypedType::Type(Box::new(TypedType::Value(TypedValueType::Value(
-                        TypedNamedValueType {
-                            package: TypedPackage::Raw(Package::global()),
-                            name: k,
-                            type_args: None,
-                        },
-                    )))),
-                    constraints: v
-                        .into_iter()
-                        .flatten()
-                        .map(|s| self.type_(s.constraint))
-                        .collect(),
-                })
-                .collect()
-        });
+        .map(|type_constraints| self.type_constraints(type_constraints));
 
         let body = body.map(|b| self.fun_body(b));
 
@@ -395,16 +709,21 @@ impl<'a> AstLowering<'a> {
                 if stn.name.token() == "Self" {
                     TypedType::Self_
                 } else {
-                    TypedType::Value(TypedValueType::Value(TypedNamedValueType {
-                        package: TypedPackage::Raw(Package::new()),
-                        name: stn.name.token(),
-                        type_args: stn.type_args.map(|v| {
-                            v.elements
-                                .into_iter()
-                                .map(|t| self.type_(t.element))
-                                .collect()
-                        }),
-                    }))
+                    let name = stn.name.token();
+                    let type_args = stn.type_args.map(|v| {
+                        v.elements
+                            .into_iter()
+                            .map(|t| self.type_(t.element))
+                            .collect()
+                    });
+                    match self.expand_type_alias(&name, &type_args) {
+                        Some(t) => t,
+                        None => TypedType::Value(TypedValueType::Value(TypedNamedValueType {
+                            package: TypedPackage::Raw(Package::new()),
+                            name,
+                            type_args,
+                        })),
+                    }
                 }
             }
             TypeName::Decorated(d) => {
@@ -439,8 +758,75 @@ impl<'a> AstLowering<'a> {
             TypeName::Parenthesized(p) => self.type_(*p.type_name),
             TypeName::Array(a) => TypedType::Value(TypedValueType::Array(
                 Box::new(self.type_(a.type_)),
-                a.size.token().parse().unwrap(),
+                self.array_length(a.size),
             )),
+            TypeName::Slice(s) => TypedType::slice(self.type_(s.type_)),
+            TypeName::Function(f) => TypedType::function_pointer(TypedFunctionType {
+                arguments: f
+                    .arguments
+                    .elements
+                    .into_iter()
+                    .map(|a| TypedArgType {
+                        label: "_".to_string(),
+                        typ: self.type_(a.element),
+                    })
+                    .collect(),
+                return_type: self.type_(f.return_type),
+            }),
+        }
+    }
+
+    /// Expand type alias `name` declared in this file, whose type parameters are replaced with
+    /// `type_args`. Type aliases declared in other files are expanded by [TypeResolver].
+    fn expand_type_alias(
+        &mut self,
+        name: &str,
+        type_args: &Option<Vec<TypedType>>,
+    ) -> Option<TypedType> {
+        let type_alias = self
+            .arena
+            .get_by_id(&self.namespace_id)?
+            .get_child(name)?
+            .iter()
+            .find_map(|id| self.arena.get_type_alias_by_id(id))?
+            .clone();
+        let type_params = type_alias.type_parameters.unwrap_or_default();
+        let type_args = type_args.clone().unwrap_or_default();
+        if type_params.len() != type_args.len() {
+            self.errors.push(ResolverError::from(format!(
+                "`{}` required {} type arguments, but {} were given.",
+                name,
+                type_params.len(),
+                type_args.len()
+            )));
+            return Some(type_alias.type_);
+        }
+        let raw_type = |name: String| {
+            TypedType::Value(TypedValueType::Value(TypedNamedValueType {
+                package: TypedPackage::Raw(Package::new()),
+                name,
+                type_args: None,
+            }))
+        };
+        // NOTE: parameters are renamed first, so that type arguments are not replaced again.
+        let type_ = type_params.iter().fold(type_alias.type_, |t, p| {
+            t.replace(&raw_type(p.name.clone()), &raw_type(format!("#{}", p.name)))
+        });
+        Some(type_params.iter().zip(type_args).fold(type_, |t, (p, a)| {
+            t.replace(&raw_type(format!("#{}", p.name)), &a)
+        }))
+    }
+
+    fn type_alias(&mut self, t: TypeAliasSyntax) -> TypedTypeAlias {
+        TypedTypeAlias {
+            name: t.name.token(),
+            type_params: t.type_params.map(|v| {
+                v.elements
+                    .into_iter()
+                    .map(|p| self.type_param(p.element))
+                    .collect()
+            }),
+            type_: self.type_(t.type_),
         }
     }
 
@@ -450,21 +836,81 @@ impl<'a> AstLowering<'a> {
         }
     }
 
+    /// Group constraints by the name of type parameter.
+    fn type_constraints(&mut self, type_constraints: Vec<TypeParam>) -> Vec<TypedTypeConstraint> {
+        let mut group = HashMap::<_, Vec<_>>::new();
+        for type_constraint in type_constraints {
+            let name = type_constraint.name.token();
+            group
+                .entry(name)
+                .or_default()
+                .push(type_constraint.type_constraint);
+        }
+        group
+            .into_iter()
+            .map(|(k, v)| TypedTypeConstraint {
+                type_: TypedType::Type(Box::new(TypedType::Value(TypedValueType::Value(
+                    TypedNamedValueType {
+                        package: TypedPackage::Raw(Package::global()),
+                        name: k,
+                        type_args: None,
+                    },
+                )))),
+                constraints: v
+                    .into_iter()
+                    .flatten()
+                    .map(|s| self.type_(s.constraint))
+                    .collect(),
+            })
+            .collect()
+    }
+
     pub fn struct_syntax(&mut self, s: StructSyntax) -> TypedStruct {
         let mut stored_properties: Vec<TypedStoredProperty> = vec![];
         let mut computed_properties: Vec<TypedComputedProperty> = vec![];
+        let mut initializers: Vec<TypedInitializer> = vec![];
+        let mut subscripts: Vec<TypedSubscriptDecl> = vec![];
         let mut member_functions: Vec<TypedFun> = vec![];
+        let is_union = s.struct_keyword.token() == "union";
+        let mut has_deinit = false;
         for p in s.body.properties {
             match p {
                 StructPropertySyntax::StoredProperty(v) => {
                     stored_properties.push(self.stored_property_syntax(v));
                 }
                 StructPropertySyntax::ComputedProperty => {}
+                StructPropertySyntax::Init(init) => initializers.push(self.initializer(init)),
+                StructPropertySyntax::Subscript(subscript) => {
+                    subscripts.extend(self.subscript(subscript))
+                }
                 StructPropertySyntax::Method(method) => {
                     member_functions.push(self.member_function(method))
                 }
                 StructPropertySyntax::Deinit(deinit) => {
-                    todo!("deinit {:?}", deinit)
+                    if is_union {
+                        self.errors.push(ResolverError::from(format!(
+                            "`deinit` is only allowed in struct and class, but `{}` is a {}",
+                            s.name.token(),
+                            s.struct_keyword.token()
+                        )));
+                    } else if has_deinit {
+                        self.errors.push(ResolverError::from(format!(
+                            "`{}` has multiple `deinit`s",
+                            s.name.token()
+                        )));
+                    } else {
+                        has_deinit = true;
+                        member_functions
+                            .push(TypedStruct::deinitializer(self.fun_body(deinit.body)));
+                    }
+                }
+                StructPropertySyntax::AssociatedType(a) => {
+                    self.errors.push(ResolverError::from(format!(
+                        "associated type `{}` is only allowed in protocol and extension, but `{}` is a {}",
+                        a.name.token(),
+                        s.name.token(),
+                        s.struct_keyword.token()
+                    )));
                 }
             };
         }
@@ -472,22 +918,75 @@ impl<'a> AstLowering<'a> {
         // add size_of struct
         member_functions.push(TypedFun::size(TypedType::Self_));
 
+        let type_params = s.type_params.map(|v| {
+            v.elements
+                .into_iter()
+                .map(|tp| tp.element)
+                .collect::<Vec<_>>()
+        });
+
         TypedStruct {
             name: s.name.token(),
-            type_params: s.type_params.map(|v| {
-                v.elements
-                    .into_iter()
-                    .map(|tp| self.type_param(tp.element))
-                    .collect()
-            }),
+            type_params: type_params
+                .as_ref()
+                .map(|v| v.iter().cloned().map(|tp| self.type_param(tp)).collect()),
+            type_constraints: type_params.map(|v| self.type_constraints(v)),
             stored_properties,
             computed_properties,
+            initializers,
+            subscripts,
             member_functions,
         }
     }
 
+    fn initializer(&mut self, i: InitializerSyntax) -> TypedInitializer {
+        TypedInitializer {
+            arg_defs: i
+                .arg_defs
+                .elements
+                .into_iter()
+                .map(|a| self.arg_def(a.element))
+                .collect(),
+            body: self.fun_body(i.body),
+        }
+    }
+
+    fn subscript(&mut self, s: SubscriptDeclarationSyntax) -> Option<TypedSubscriptDecl> {
+        let mut getters = vec![];
+        let mut setters = vec![];
+        for a in s.body.accessors {
+            if a.keyword.token() == "get" {
+                getters.push(a.body);
+            } else {
+                setters.push(a.body);
+            }
+        }
+        if getters.len() != 1 || setters.len() > 1 {
+            self.errors.push(ResolverError::from(
+                "subscript must have exactly one `get` and at most one `set`",
+            ));
+            return None;
+        }
+        let arg_defs = s
+            .arg_defs
+            .elements
+            .into_iter()
+            .map(|a| self.arg_def(a.element))
+            .collect();
+        let element_type = self.type_(s.return_type.type_);
+        let getter = self.fun_body(getters.remove(0));
+        let setter = setters.pop().map(|b| self.fun_body(b));
+        Some(TypedSubscriptDecl::new(
+            arg_defs,
+            element_type,
+            getter,
+            setter,
+        ))
+    }
+
     pub fn stored_property_syntax(&mut self, p: StoredPropertySyntax) -> TypedStoredProperty {
         TypedStoredProperty {
+            is_mut: p.mutability_keyword.token() == "var",
             name: p.name.token(),
             type_: self.type_(p.type_.type_),
         }
@@ -527,20 +1026,53 @@ impl<'a> AstLowering<'a> {
         }
     }
 
-    pub fn use_syntax(&mut self, u: UseSyntax, annotations: Option<AnnotationsSyntax>) -> TypedUse {
-        let mut names: Vec<_> = u
+    /// `use a::{b, c}` is lowered to `use a::b` and `use a::c`.
+    pub fn use_syntax(
+        &mut self,
+        u: UseSyntax,
+        annotations: Option<AnnotationsSyntax>,
+    ) -> Vec<TypedUse> {
+        let package: Vec<_> = u
             .package_name
             .map(|pn| pn.names.into_iter().map(|i| i.name.token()).collect())
             .unwrap_or_default();
-        names.push(u.used_name.token());
-        TypedUse {
-            annotations: self.annotations(&annotations),
-            package: Package { names },
-            alias: u.alias.map(|a| a.name.token()),
+        let used_names = match u.used_name {
+            UsedNameSyntax::Name(n) => vec![n],
+            UsedNameSyntax::Group(g) => g.elements.into_iter().map(|e| e.element).collect(),
+        };
+        let annotations = self.annotations(&annotations);
+        used_names
+            .into_iter()
+            .map(|n| {
+                let mut names = package.clone();
+                names.push(n.name.token());
+                let alias = n.alias.map(|a| a.name.token());
+                if alias.is_some() && n.name.token() == "*" {
+                    self.errors.push(ResolverError::from(format!(
+                        "`{}` can not have an alias",
+                        names.join("::")
+                    )));
+                }
+                TypedUse {
+                    annotations: annotations.clone(),
+                    package: Package { names },
+                    alias,
+                    is_public: u.public_keyword.is_some(),
+                }
+            })
+            .collect()
+    }
+
+    fn associated_type(&mut self, a: AssociatedTypeSyntax) -> TypedAssociatedType {
+        TypedAssociatedType {
+            name: a.name.token(),
+            type_: a.value.map(|v| self.type_(v.type_)),
         }
     }
 
     fn extension_syntax(&mut self, e: ExtensionSyntax) -> TypedExtension {
+        let name = self.type_(e.name);
+        let mut associated_types = vec![];
         let mut computed_properties = vec![];
         let mut member_functions = vec![];
         for prop in e.body.properties {
@@ -549,19 +1081,37 @@ impl<'a> AstLowering<'a> {
                     panic!("Stored property not allowed here.")
                 }
                 StructPropertySyntax::ComputedProperty => todo!(),
+                StructPropertySyntax::Init(_) => {
+                    self.errors.push(ResolverError::from(format!(
+                        "`init` is not allowed in extension of `{}`",
+                        name.to_string()
+                    )));
+                }
+                StructPropertySyntax::Subscript(_) => {
+                    self.errors.push(ResolverError::from(format!(
+                        "`subscript` is not allowed in extension of `{}`",
+                        name.to_string()
+                    )));
+                }
                 StructPropertySyntax::Deinit(_) => panic!("Deinit is not allowed here."),
+                StructPropertySyntax::AssociatedType(a) => {
+                    associated_types.push(self.associated_type(a))
+                }
                 StructPropertySyntax::Method(m) => member_functions.push(self.member_function(m)),
             }
         }
         TypedExtension {
-            name: self.type_(e.name),
+            name,
             protocol: e.protocol_extension.map(|tps| self.type_(tps.protocol)),
+            associated_types,
             computed_properties,
             member_functions,
         }
     }
 
     fn protocol_syntax(&mut self, p: StructSyntax) -> TypedProtocol {
+        let name = p.name.token();
+        let mut associated_types: Vec<TypedAssociatedType> = vec![];
         let mut computed_properties: Vec<TypedComputedProperty> = vec![];
         let mut member_functions: Vec<TypedFun> = vec![];
         for p in p.body.properties {
@@ -573,19 +1123,35 @@ impl<'a> AstLowering<'a> {
                 StructPropertySyntax::Method(method) => {
                     member_functions.push(self.member_function(method))
                 }
+                StructPropertySyntax::Init(_) => {
+                    self.errors.push(ResolverError::from(format!(
+                        "`init` is not allowed in protocol `{}`",
+                        name
+                    )));
+                }
+                StructPropertySyntax::Subscript(_) => {
+                    self.errors.push(ResolverError::from(format!(
+                        "`subscript` is not allowed in protocol `{}`",
+                        name
+                    )));
+                }
                 StructPropertySyntax::Deinit(deinit) => {
                     panic!("protocol is not allowed deinit {:?}", deinit)
                 }
+                StructPropertySyntax::AssociatedType(a) => {
+                    associated_types.push(self.associated_type(a))
+                }
             };
         }
         TypedProtocol {
-            name: p.name.token(),
+            name,
             type_params: p.type_params.map(|v| {
                 v.elements
                     .into_iter()
                     .map(|tp| self.type_param(tp.element))
                     .collect()
             }),
+            associated_types,
             computed_properties,
             member_functions,
         }
@@ -597,6 +1163,7 @@ impl<'a> AstLowering<'a> {
             Expr::Literal(literal) => {
                 TypedExpr::new(TypedExprKind::Literal(self.literal_syntax(literal)), None)
             }
+            Expr::BinOp(b) if matches!(&*b.operator.token(), ".." | "..<") => self.range_syntax(b),
             Expr::BinOp(b) => {
                 TypedExpr::new(TypedExprKind::BinOp(self.binary_operation_syntax(b)), None)
             }
@@ -632,6 +1199,19 @@ impl<'a> AstLowering<'a> {
             } => TypedLiteralKind::String(value),
             LiteralSyntax::Boolean(syntax) => TypedLiteralKind::Boolean(syntax.token()),
             LiteralSyntax::Null => TypedLiteralKind::NullLiteral,
+            LiteralSyntax::SourceLocation { token, span } => TypedLiteralKind::SourceLocation(
+                match &*token.token() {
+                    "#file" => TypedSourceLocationKind::File,
+                    "#line" => TypedSourceLocationKind::Line,
+                    "#column" => TypedSourceLocationKind::Column,
+                    t => panic!("Unknown source location literal {}", t),
+                },
+                self.location(span).unwrap_or(TypedLocation {
+                    file: self.source_file.clone(),
+                    line: 0,
+                    column: 0,
+                }),
+            ),
         }
     }
 
@@ -667,6 +1247,7 @@ impl<'a> AstLowering<'a> {
             left,
             operator: kind,
             right,
+            span,
         } = b;
         let left = Box::new(self.expr(*left));
         let right = Box::new(self.expr(*right));
@@ -689,9 +1270,76 @@ impl<'a> AstLowering<'a> {
                 _ => TypedBinaryOperator::InfixFunctionCall(kind.token()),
             },
             right,
+            location: self.location(span),
         }
     }
 
+    /// `a..<b` is lowered to `core::builtin::Range(start: a, end: b)`, and
+    /// the closed range `a..b` ends at `b + 1`.
+    pub fn range_syntax(&mut self, b: BinaryOperationSyntax) -> TypedExpr {
+        let BinaryOperationSyntax {
+            left,
+            operator,
+            right,
+            span,
+        } = b;
+        let start = self.expr(*left);
+        let end = self.expr(*right);
+        let end = if operator.token() == ".." {
+            let succ = match &end.kind {
+                TypedExprKind::Literal(TypedLiteralKind::Integer(value)) => value
+                    .parse::<u128>()
+                    .ok()
+                    .and_then(|value| value.checked_add(1)),
+                _ => None,
+            };
+            match succ {
+                Some(value) => TypedExpr::new(
+                    TypedExprKind::Literal(TypedLiteralKind::Integer(value.to_string())),
+                    None,
+                ),
+                None => TypedExpr::new(
+                    TypedExprKind::BinOp(TypedBinOp {
+                        left: Box::new(end),
+                        operator: TypedBinaryOperator::Add,
+                        right: Box::new(TypedExpr::new(
+                            TypedExprKind::Literal(TypedLiteralKind::Integer("1".to_string())),
+                            None,
+                        )),
+                        location: self.location(span),
+                    }),
+                    None,
+                ),
+            }
+        } else {
+            end
+        };
+        let range = TypedName {
+            package: TypedPackage::Raw(Package::from(&["core", "builtin"])),
+            name: "Range".to_string(),
+            type_arguments: None,
+        };
+        TypedExpr::new(
+            TypedExprKind::Call(TypedCall {
+                target: Box::new(TypedExpr::new(TypedExprKind::Name(range), None)),
+                args: vec![
+                    TypedCallArg {
+                        label: Some("start".to_string()),
+                        arg: Box::new(start),
+                        is_vararg: false,
+                    },
+                    TypedCallArg {
+                        label: Some("end".to_string()),
+                        arg: Box::new(end),
+                        is_vararg: false,
+                    },
+                ],
+                location: None,
+            }),
+            None,
+        )
+    }
+
     pub fn unary_operation_syntax(&mut self, u: UnaryOperationSyntax) -> TypedUnaryOp {
         match u {
             UnaryOperationSyntax::Prefix(p) => {
@@ -726,7 +1374,11 @@ impl<'a> AstLowering<'a> {
         &mut self,
         p: PostfixUnaryOperationSyntax,
     ) -> TypedPostfixUnaryOp {
-        let PostfixUnaryOperationSyntax { target, operator } = p;
+        let PostfixUnaryOperationSyntax {
+            target,
+            operator,
+            span,
+        } = p;
         let target = self.expr(*target);
         TypedPostfixUnaryOp {
             target: Box::new(target),
@@ -734,6 +1386,7 @@ impl<'a> AstLowering<'a> {
                 "!!" => TypedPostfixUnaryOperator::Unwrap,
                 _ => panic!(),
             },
+            location: self.location(span),
         }
     }
 
@@ -755,7 +1408,11 @@ impl<'a> AstLowering<'a> {
             .into_iter()
             .map(|i| self.expr(i.element))
             .collect();
-        TypedSubscript { target, indexes }
+        TypedSubscript {
+            target,
+            indexes,
+            location: self.location(s.span),
+        }
     }
 
     pub fn member_syntax(&mut self, m: MemberSyntax) -> TypedInstanceMember {
@@ -777,6 +1434,7 @@ impl<'a> AstLowering<'a> {
             target,
             args,
             tailing_lambda,
+            span,
         } = c;
         let mut args: Vec<TypedCallArg> = args
             .unwrap_or_default()
@@ -804,6 +1462,7 @@ impl<'a> AstLowering<'a> {
         TypedCall {
             target: Box::new(self.expr(*target)),
             args,
+            location: self.location(span),
         }
     }
 
@@ -845,6 +1504,7 @@ impl<'a> AstLowering<'a> {
             target: Box::new(self.expr(*t.target)),
             is_safe: t.operator.token().ends_with('?'),
             type_: self.type_(t.type_),
+            location: self.location(t.span),
         }
     }
 
@@ -0,0 +1,855 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use wiz_arena::{Arena, ArenaFunction, DeclarationId, DeclarationItemKind};
+use wiz_constants as constants;
+use wiz_hir::typed_decl::{TypedDeclKind, TypedFunBody};
+use wiz_hir::typed_expr::{
+    TypedBinOp, TypedBinaryOperator, TypedCall, TypedExpr, TypedExprKind, TypedIf,
+    TypedLiteralKind, TypedName, TypedPrefixUnaryOp, TypedPrefixUnaryOperator,
+    TypedSourceLocationKind, TypedTypeCast, TypedUnaryOp,
+};
+use wiz_hir::typed_stmt::{
+    TypedAssignmentAndOperator, TypedAssignmentStmt, TypedBlock, TypedLoopStmt, TypedStmt,
+};
+use wiz_hir::typed_type::{TypedNamedValueType, TypedPackage, TypedType, TypedValueType};
+use wiz_session::DataLayout;
+
+const RECURSION_LIMIT: usize = 128;
+const STEP_LIMIT: usize = 1_000_000;
+
+/// Value of a constant expression.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ConstValue {
+    Integer(i128),
+    FloatingPoint(f64),
+    Boolean(bool),
+    String(String),
+    Unit,
+}
+
+impl ConstValue {
+    /// Literal expression of type `ty` which has this value.
+    pub(crate) fn into_expr(self, ty: Option<TypedType>) -> Result<TypedExpr, String> {
+        let is_floating_point = ty.as_ref().is_some_and(is_floating_point);
+        let kind = match self {
+            ConstValue::Integer(i) if is_floating_point => {
+                TypedLiteralKind::FloatingPoint(format!("{:?}", i as f64))
+            }
+            ConstValue::Integer(i) => {
+                if let Some(ty) = &ty {
+                    check_fits(i, ty)?;
+                }
+                TypedLiteralKind::Integer(i.to_string())
+            }
+            ConstValue::FloatingPoint(f) => TypedLiteralKind::FloatingPoint(format!("{:?}", f)),
+            ConstValue::Boolean(b) => TypedLiteralKind::Boolean(b.to_string()),
+            ConstValue::String(s) => TypedLiteralKind::String(s),
+            ConstValue::Unit => return Err("expression does not have a value".to_string()),
+        };
+        Ok(TypedExpr::new(TypedExprKind::Literal(kind), ty))
+    }
+}
+
+impl Display for ConstValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstValue::Integer(i) => write!(f, "{}", i),
+            ConstValue::FloatingPoint(v) => write!(f, "{:?}", v),
+            ConstValue::Boolean(b) => write!(f, "{}", b),
+            ConstValue::String(s) => write!(f, "\"{}\"", s),
+            ConstValue::Unit => f.write_str("()"),
+        }
+    }
+}
+
+/// Why evaluation stopped before producing a value.
+enum Unwind {
+    Return(ConstValue),
+    Error(String),
+}
+
+impl From<String> for Unwind {
+    fn from(message: String) -> Self {
+        Self::Error(message)
+    }
+}
+
+impl From<&str> for Unwind {
+    fn from(message: &str) -> Self {
+        Self::Error(message.to_string())
+    }
+}
+
+type Evaluation<T> = Result<T, Unwind>;
+
+struct Frame {
+    /// Namespace raw names are looked up from.
+    namespace: DeclarationId,
+    self_type: Option<TypedType>,
+    locals: HashMap<String, ConstValue>,
+}
+
+impl Frame {
+    fn new(namespace: DeclarationId, self_type: Option<TypedType>) -> Self {
+        Self {
+            namespace,
+            self_type,
+            locals: Default::default(),
+        }
+    }
+}
+
+/// Compile-time evaluator of HIR expressions.
+///
+/// Supports literals, arithmetic, comparisons, casts, `size()` and calls of
+/// functions whose bodies only use those, local variables, `if`, `while` and
+/// `return`. Expressions may be resolved or not; raw names are looked up from
+/// the namespace the evaluation started in.
+pub(crate) struct ConstEvaluator<'a> {
+    arena: &'a Arena,
+    /// Sizes and alignments of primitives on the target.
+    data_layout: DataLayout,
+    frames: Vec<Frame>,
+    /// Constants being evaluated, to detect cyclic definitions.
+    evaluating: Vec<DeclarationId>,
+    steps: usize,
+}
+
+impl<'a> ConstEvaluator<'a> {
+    pub(crate) fn new(arena: &'a Arena, namespace: DeclarationId, data_layout: DataLayout) -> Self {
+        Self {
+            arena,
+            data_layout,
+            frames: vec![Frame::new(namespace, None)],
+            evaluating: vec![],
+            steps: 0,
+        }
+    }
+
+    pub(crate) fn evaluate(&mut self, e: &TypedExpr) -> Result<ConstValue, String> {
+        match self.expr(e) {
+            Ok(value) => Ok(value),
+            Err(Unwind::Return(_)) => Err("`return` is not allowed here".to_string()),
+            Err(Unwind::Error(message)) => Err(message),
+        }
+    }
+
+    /// Evaluate `e` into a literal which has the same type as `e`.
+    pub(crate) fn fold(&mut self, e: &TypedExpr) -> Result<TypedExpr, String> {
+        self.evaluate(e)?.into_expr(e.ty.clone())
+    }
+
+    pub(crate) fn array_length(&mut self, e: &TypedExpr) -> Result<usize, String> {
+        match self.evaluate(e)? {
+            ConstValue::Integer(i) => usize::try_from(i)
+                .map_err(|_| format!("array length must not be negative, but it is `{}`", i)),
+            value => Err(format!(
+                "array length must be an integer, but it is `{}`",
+                value
+            )),
+        }
+    }
+
+    fn frame(&self) -> &Frame {
+        self.frames.last().unwrap()
+    }
+
+    fn frame_mut(&mut self) -> &mut Frame {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn step(&mut self) -> Evaluation<()> {
+        self.steps += 1;
+        if self.steps > STEP_LIMIT {
+            Err(format!("evaluation exceeded the limit of {} steps", STEP_LIMIT).into())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn unsupported(what: &str) -> Unwind {
+        Unwind::Error(format!("{} is not a constant expression", what))
+    }
+
+    fn expr(&mut self, e: &TypedExpr) -> Evaluation<ConstValue> {
+        self.step()?;
+        match &e.kind {
+            TypedExprKind::Name(n) => self.name(n),
+            TypedExprKind::Literal(l) => Self::literal(l),
+            TypedExprKind::BinOp(b) => self.binop(b, &e.ty),
+            TypedExprKind::UnaryOp(TypedUnaryOp::Prefix(p)) => self.prefix_unary_op(p, &e.ty),
+            TypedExprKind::UnaryOp(TypedUnaryOp::Postfix(_)) => Err(Self::unsupported("unwrap")),
+            TypedExprKind::Call(c) => self.call(c),
+            TypedExprKind::If(i) => self.if_expr(i),
+            TypedExprKind::Return(r) => {
+                let value = match &r.value {
+                    Some(value) => self.expr(value)?,
+                    None => ConstValue::Unit,
+                };
+                Err(Unwind::Return(value))
+            }
+            TypedExprKind::TypeCast(t) => self.type_cast(t),
+            TypedExprKind::SizeOf(t) => Ok(ConstValue::Integer(self.layout(t)?.0 as i128)),
+            TypedExprKind::Subscript(_) => Err(Self::unsupported("subscript")),
+            TypedExprKind::Member(_) => Err(Self::unsupported("member access")),
+            TypedExprKind::Array(_) => Err(Self::unsupported("array literal")),
+            TypedExprKind::Tuple => Err(Self::unsupported("tuple")),
+            TypedExprKind::Dict => Err(Self::unsupported("dictionary")),
+            TypedExprKind::StringBuilder => Err(Self::unsupported("string interpolation")),
+            TypedExprKind::When => Err(Self::unsupported("`when`")),
+            TypedExprKind::Lambda(_) => Err(Self::unsupported("lambda")),
+        }
+    }
+
+    fn literal(l: &TypedLiteralKind) -> Evaluation<ConstValue> {
+        Ok(match l {
+            TypedLiteralKind::Integer(value) => ConstValue::Integer(
+                value
+                    .parse()
+                    .map_err(|_| format!("integer literal `{}` is too large", value))?,
+            ),
+            TypedLiteralKind::FloatingPoint(value) => ConstValue::FloatingPoint(
+                value
+                    .parse()
+                    .map_err(|_| format!("invalid floating point literal `{}`", value))?,
+            ),
+            TypedLiteralKind::String(value) => ConstValue::String(value.clone()),
+            TypedLiteralKind::Boolean(value) => ConstValue::Boolean(value == "true"),
+            TypedLiteralKind::NullLiteral => return Err(Self::unsupported("`null`")),
+            TypedLiteralKind::SourceLocation(kind, location) => match kind {
+                TypedSourceLocationKind::File => ConstValue::String(location.file.clone()),
+                TypedSourceLocationKind::Line => ConstValue::Integer(location.line.into()),
+                TypedSourceLocationKind::Column => ConstValue::Integer(location.column.into()),
+            },
+        })
+    }
+
+    /// Declarations named `name` in `package`. Raw packages are looked up from
+    /// the current namespace outward.
+    fn declarations(&self, package: &TypedPackage, name: &str) -> Vec<DeclarationId> {
+        let (names, mut namespace) = match package {
+            TypedPackage::Raw(p) => (&p.names, Some(self.frame().namespace)),
+            TypedPackage::Resolved(p) => (&p.names, Some(DeclarationId::ROOT)),
+        };
+        while let Some(id) = namespace {
+            if let Some(ids) = self
+                .arena
+                .resolve_declaration_id(id, names)
+                .and_then(|id| self.arena.get_by_id(&id))
+                .and_then(|item| item.get_child(name))
+            {
+                return ids.iter().copied().collect();
+            }
+            namespace = match package {
+                TypedPackage::Raw(_) => self.arena.get_by_id(&id).and_then(|i| i.parent()),
+                TypedPackage::Resolved(_) => None,
+            };
+        }
+        vec![]
+    }
+
+    fn is_local(&self, n: &TypedName) -> bool {
+        let names = match &n.package {
+            TypedPackage::Raw(p) | TypedPackage::Resolved(p) => &p.names,
+        };
+        names.is_empty() && self.frame().locals.contains_key(&n.name)
+    }
+
+    fn name(&mut self, n: &TypedName) -> Evaluation<ConstValue> {
+        if self.is_local(n) {
+            return Ok(self.frame().locals[&n.name].clone());
+        }
+        let arena = self.arena;
+        let ids = self.declarations(&n.package, &n.name);
+        if let Some((id, value)) = ids
+            .iter()
+            .find_map(|id| Some((*id, arena.get_constant(id)?)))
+        {
+            return self.constant(id, &n.name, value);
+        }
+        Err(match ids.first().and_then(|id| arena.get_by_id(id)) {
+            Some(item) if item.is_function() => format!("`{}` is a function, not a value", n.name),
+            Some(item) if item.is_type() => format!("`{}` is a type, not a value", n.name),
+            Some(item) if item.is_namespace() => {
+                format!("`{}` is a namespace, not a value", n.name)
+            }
+            _ => format!("`{}` is not a constant", n.name),
+        }
+        .into())
+    }
+
+    fn constant(
+        &mut self,
+        id: DeclarationId,
+        name: &str,
+        value: &TypedExpr,
+    ) -> Evaluation<ConstValue> {
+        if self.evaluating.contains(&id) {
+            return Err(format!("`{}` is defined in terms of itself", name).into());
+        }
+        let namespace = self
+            .arena
+            .get_by_id(&id)
+            .and_then(|i| i.parent())
+            .unwrap_or(DeclarationId::ROOT);
+        self.evaluating.push(id);
+        self.frames.push(Frame::new(namespace, None));
+        let result = self.expr(value);
+        self.frames.pop();
+        self.evaluating.pop();
+        result
+    }
+
+    fn binop(&mut self, b: &TypedBinOp, ty: &Option<TypedType>) -> Evaluation<ConstValue> {
+        let left = self.expr(&b.left)?;
+        match &b.operator {
+            TypedBinaryOperator::And | TypedBinaryOperator::Or => {
+                let l = Self::boolean(left)?;
+                if l == (b.operator == TypedBinaryOperator::Or) {
+                    return Ok(ConstValue::Boolean(l));
+                }
+                let r = self.expr(&b.right)?;
+                Ok(ConstValue::Boolean(Self::boolean(r)?))
+            }
+            TypedBinaryOperator::InfixFunctionCall(name) => {
+                Err(Self::unsupported(&format!("infix call of `{}`", name)))
+            }
+            operator => {
+                let right = self.expr(&b.right)?;
+                Self::operate(operator, left, right, ty)
+            }
+        }
+    }
+
+    fn operate(
+        operator: &TypedBinaryOperator,
+        left: ConstValue,
+        right: ConstValue,
+        ty: &Option<TypedType>,
+    ) -> Evaluation<ConstValue> {
+        use TypedBinaryOperator::*;
+        let is_floating_point = ty.as_ref().is_some_and(is_floating_point);
+        let value = match (left, right) {
+            (ConstValue::Integer(l), ConstValue::Integer(r)) if !is_floating_point => {
+                let checked = match operator {
+                    Add => l.checked_add(r),
+                    Sub => l.checked_sub(r),
+                    Mul => l.checked_mul(r),
+                    Div | Mod if r == 0 => return Err("division by zero".into()),
+                    Div => l.checked_div(r),
+                    Mod => l.checked_rem(r),
+                    _ => return Ok(ConstValue::Boolean(Self::compare(operator, &l, &r))),
+                };
+                let i = checked.ok_or_else(|| "arithmetic overflow".to_string())?;
+                if let Some(ty) = ty {
+                    check_fits(i, ty)?;
+                }
+                ConstValue::Integer(i)
+            }
+            (l @ ConstValue::Integer(_), r @ ConstValue::Integer(_))
+            | (l @ ConstValue::Integer(_), r @ ConstValue::FloatingPoint(_))
+            | (l @ ConstValue::FloatingPoint(_), r @ ConstValue::Integer(_))
+            | (l @ ConstValue::FloatingPoint(_), r @ ConstValue::FloatingPoint(_)) => {
+                let (l, r) = (Self::floating_point(l), Self::floating_point(r));
+                let f = match operator {
+                    Add => l + r,
+                    Sub => l - r,
+                    Mul => l * r,
+                    Div => l / r,
+                    Mod => l % r,
+                    _ => return Ok(ConstValue::Boolean(Self::compare(operator, &l, &r))),
+                };
+                ConstValue::FloatingPoint(round_to(f, ty))
+            }
+            (ConstValue::Boolean(l), ConstValue::Boolean(r))
+                if matches!(operator, Equal | NotEqual) =>
+            {
+                ConstValue::Boolean(Self::compare(operator, &l, &r))
+            }
+            (ConstValue::String(l), ConstValue::String(r))
+                if matches!(operator, Equal | NotEqual) =>
+            {
+                ConstValue::Boolean(Self::compare(operator, &l, &r))
+            }
+            (l, r) => {
+                return Err(format!(
+                    "operator `{}` cannot be applied to `{}` and `{}`",
+                    operator_symbol(operator),
+                    l,
+                    r
+                )
+                .into())
+            }
+        };
+        Ok(value)
+    }
+
+    fn compare<T: PartialOrd>(operator: &TypedBinaryOperator, l: &T, r: &T) -> bool {
+        match operator {
+            TypedBinaryOperator::Equal => l == r,
+            TypedBinaryOperator::NotEqual => l != r,
+            TypedBinaryOperator::GrateThan => l > r,
+            TypedBinaryOperator::GrateThanEqual => l >= r,
+            TypedBinaryOperator::LessThan => l < r,
+            TypedBinaryOperator::LessThanEqual => l <= r,
+            o => unreachable!("{:?} is not a comparison", o),
+        }
+    }
+
+    fn boolean(value: ConstValue) -> Evaluation<bool> {
+        match value {
+            ConstValue::Boolean(b) => Ok(b),
+            value => Err(format!("expected `Bool`, but got `{}`", value).into()),
+        }
+    }
+
+    fn floating_point(value: ConstValue) -> f64 {
+        match value {
+            ConstValue::Integer(i) => i as f64,
+            ConstValue::FloatingPoint(f) => f,
+            value => unreachable!("{} is not a number", value),
+        }
+    }
+
+    fn prefix_unary_op(
+        &mut self,
+        p: &TypedPrefixUnaryOp,
+        ty: &Option<TypedType>,
+    ) -> Evaluation<ConstValue> {
+        let target = match p.operator {
+            TypedPrefixUnaryOperator::Reference => return Err(Self::unsupported("reference")),
+            TypedPrefixUnaryOperator::Dereference => return Err(Self::unsupported("dereference")),
+            _ => self.expr(&p.target)?,
+        };
+        match (&p.operator, target) {
+            (TypedPrefixUnaryOperator::Negative, ConstValue::Integer(i)) => {
+                let i = i
+                    .checked_neg()
+                    .ok_or_else(|| "arithmetic overflow".to_string())?;
+                if let Some(ty) = ty {
+                    check_fits(i, ty)?;
+                }
+                Ok(ConstValue::Integer(i))
+            }
+            (TypedPrefixUnaryOperator::Negative, ConstValue::FloatingPoint(f)) => {
+                Ok(ConstValue::FloatingPoint(-f))
+            }
+            (TypedPrefixUnaryOperator::Positive, v @ ConstValue::Integer(_))
+            | (TypedPrefixUnaryOperator::Positive, v @ ConstValue::FloatingPoint(_)) => Ok(v),
+            (TypedPrefixUnaryOperator::Not, ConstValue::Boolean(b)) => Ok(ConstValue::Boolean(!b)),
+            (operator, value) => Err(format!(
+                "operator `{}` cannot be applied to `{}`",
+                match operator {
+                    TypedPrefixUnaryOperator::Negative => "-",
+                    TypedPrefixUnaryOperator::Positive => "+",
+                    _ => "!",
+                },
+                value
+            )
+            .into()),
+        }
+    }
+
+    fn type_cast(&mut self, t: &TypedTypeCast) -> Evaluation<ConstValue> {
+        if t.is_safe {
+            return Err(Self::unsupported("`as?`"));
+        }
+        let value = self.expr(&t.target)?;
+        let ty = Some(t.type_.clone());
+        match (value, integer_type(&t.type_)) {
+            (ConstValue::Integer(i), Some(int)) => Ok(ConstValue::Integer(wrap(i, int))),
+            (ConstValue::FloatingPoint(f), Some(int)) if f.is_finite() => {
+                Ok(ConstValue::Integer(wrap(f as i128, int)))
+            }
+            (v @ ConstValue::Integer(_), None) | (v @ ConstValue::FloatingPoint(_), None)
+                if is_floating_point(&t.type_) =>
+            {
+                Ok(ConstValue::FloatingPoint(round_to(
+                    Self::floating_point(v),
+                    &ty,
+                )))
+            }
+            (v @ ConstValue::Boolean(_), None) if type_name(&t.type_) == Some(constants::BOOL) => {
+                Ok(v)
+            }
+            (v @ ConstValue::String(_), None) if type_name(&t.type_) == Some(constants::STRING) => {
+                Ok(v)
+            }
+            (value, _) => {
+                Err(format!("`{}` cannot be cast to `{}`", value, t.type_.to_string()).into())
+            }
+        }
+    }
+
+    fn if_expr(&mut self, i: &TypedIf) -> Evaluation<ConstValue> {
+        let condition = self.expr(&i.condition)?;
+        if Self::boolean(condition)? {
+            self.block(&i.body)
+        } else {
+            match &i.else_body {
+                Some(else_body) => self.block(else_body),
+                None => Ok(ConstValue::Unit),
+            }
+        }
+    }
+
+    /// Value of the last statement of `b` if it is an expression.
+    fn block(&mut self, b: &TypedBlock) -> Evaluation<ConstValue> {
+        let mut value = ConstValue::Unit;
+        for stmt in b.body.iter() {
+            value = ConstValue::Unit;
+            match stmt {
+                TypedStmt::Expr(e) => value = self.expr(e)?,
+                TypedStmt::Decl(d) => match &d.kind {
+                    TypedDeclKind::Var(v) | TypedDeclKind::Const(v) => {
+                        let value = self.expr(&v.value)?;
+                        self.frame_mut().locals.insert(v.name.clone(), value);
+                    }
+                    _ => return Err(Self::unsupported("local declaration")),
+                },
+                TypedStmt::Assignment(a) => self.assignment(a)?,
+                TypedStmt::Loop(TypedLoopStmt::While(w)) => loop {
+                    let condition = self.expr(&w.condition)?;
+                    if !Self::boolean(condition)? {
+                        break;
+                    }
+                    self.block(&w.block)?;
+                },
+                TypedStmt::Loop(TypedLoopStmt::For(_)) => {
+                    return Err(Self::unsupported("`for` loop"))
+                }
+                TypedStmt::Defer(_) => return Err(Self::unsupported("`defer`")),
+            }
+        }
+        Ok(value)
+    }
+
+    fn assignment(&mut self, a: &TypedAssignmentStmt) -> Evaluation<()> {
+        let (target, value) = match a {
+            TypedAssignmentStmt::Assignment(a) => (&a.target, self.expr(&a.value)?),
+            TypedAssignmentStmt::AssignmentAndOperation(a) => {
+                let current = self.expr(&a.target)?;
+                let operand = self.expr(&a.value)?;
+                let operator = match a.operator {
+                    TypedAssignmentAndOperator::Add => TypedBinaryOperator::Add,
+                    TypedAssignmentAndOperator::Sub => TypedBinaryOperator::Sub,
+                    TypedAssignmentAndOperator::Mul => TypedBinaryOperator::Mul,
+                    TypedAssignmentAndOperator::Div => TypedBinaryOperator::Div,
+                    TypedAssignmentAndOperator::Mod => TypedBinaryOperator::Mod,
+                };
+                let value = Self::operate(&operator, current, operand, &a.target.ty)?;
+                (&a.target, value)
+            }
+        };
+        match &target.kind {
+            TypedExprKind::Name(n) if self.is_local(n) => {
+                self.frame_mut().locals.insert(n.name.clone(), value);
+                Ok(())
+            }
+            _ => Err(Self::unsupported("assignment to non local variable")),
+        }
+    }
+
+    fn call(&mut self, c: &TypedCall) -> Evaluation<ConstValue> {
+        let n = match &c.target.kind {
+            TypedExprKind::Name(n) => n,
+            _ => return Err(Self::unsupported("call of this expression")),
+        };
+        let arena = self.arena;
+        let functions = self
+            .declarations(&n.package, &n.name)
+            .into_iter()
+            .filter_map(|id| match &arena.get_by_id(&id)?.kind {
+                DeclarationItemKind::Function(rf) => Some((id, rf)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if functions.is_empty() {
+            return Err(format!("`{}` is not a function", n.name).into());
+        }
+        let exact = functions
+            .iter()
+            .filter(|(_, rf)| Some(&rf.ty) == c.target.ty.as_ref())
+            .copied()
+            .collect::<Vec<_>>();
+        let candidates = if exact.len() == 1 {
+            exact
+        } else {
+            functions
+                .into_iter()
+                .filter(|(_, rf)| Self::accepts(rf, c.args.len()))
+                .collect()
+        };
+        let (id, rf) = match candidates.as_slice() {
+            [candidate] => *candidate,
+            [] => {
+                return Err(
+                    format!("`{}` does not accept {} arguments", n.name, c.args.len()).into(),
+                )
+            }
+            _ => return Err(format!("call of `{}` is ambiguous", n.name).into()),
+        };
+        let body = rf
+            .body
+            .as_ref()
+            .ok_or_else(|| format!("`{}` has no body", n.name))?;
+
+        let mut args = c
+            .args
+            .iter()
+            .map(|a| self.expr(&a.arg))
+            .collect::<Evaluation<Vec<_>>>()?;
+        for default in rf.arg_defaults.iter().skip(args.len()) {
+            match default {
+                Some(default) => args.push(self.expr(default)?),
+                None => break,
+            }
+        }
+        if args.len() != rf.arg_names.len() {
+            return Err(format!("arguments of `{}` are not known", n.name).into());
+        }
+
+        if self.frames.len() > RECURSION_LIMIT {
+            return Err(format!(
+                "recursion limit of {} exceeded while calling `{}`",
+                RECURSION_LIMIT, n.name
+            )
+            .into());
+        }
+        let parent = arena.get_by_id(&id).and_then(|i| i.parent());
+        let self_type = parent
+            .and_then(|p| arena.get_type_by_id(&p))
+            .map(|rs| rs.self_type());
+        let mut frame = Frame::new(parent.unwrap_or(DeclarationId::ROOT), self_type);
+        frame.locals = rf.arg_names.iter().cloned().zip(args).collect();
+        self.frames.push(frame);
+        let result = match body {
+            TypedFunBody::Expr(e) => self.expr(e),
+            TypedFunBody::Block(b) => self.block(b).map(|_| ConstValue::Unit),
+        };
+        self.frames.pop();
+        let value = match result {
+            Ok(value) | Err(Unwind::Return(value)) => value,
+            Err(e) => return Err(e),
+        };
+
+        if let TypedType::Function(f) = &rf.ty {
+            match &value {
+                ConstValue::Unit if f.return_type != TypedType::unit() => {
+                    return Err(format!("`{}` did not return a value", n.name).into())
+                }
+                ConstValue::Integer(i) => check_fits(*i, &f.return_type)?,
+                _ => {}
+            }
+        }
+        Ok(value)
+    }
+
+    fn accepts(rf: &ArenaFunction, count: usize) -> bool {
+        let arguments = match &rf.ty {
+            TypedType::Function(f) => f.arguments.len(),
+            _ => rf.arg_names.len(),
+        };
+        let required = if rf.arg_defaults.len() == arguments {
+            rf.arg_defaults.iter().take_while(|d| d.is_none()).count()
+        } else {
+            arguments
+        };
+        required <= count && count <= arguments
+    }
+
+    /// Size and alignment of `ty`.
+    fn layout(&mut self, ty: &TypedType) -> Evaluation<(u64, u64)> {
+        self.step()?;
+        let pointer = (
+            self.data_layout.pointer_size,
+            self.data_layout.pointer_align,
+        );
+        match ty {
+            TypedType::Self_ => match self.frame().self_type.clone() {
+                Some(t) => self.layout(&t),
+                None => Err("size of `Self` is not known here".into()),
+            },
+            TypedType::Value(v) => match v {
+                TypedValueType::Value(n) => self.named_layout(n),
+                TypedValueType::Array(t, len) => {
+                    let (size, align) = self.layout(t)?;
+                    Ok((size * *len as u64, align))
+                }
+                TypedValueType::Slice(_) => Ok((pointer.0 * 2, pointer.1)),
+                TypedValueType::Tuple(ts) => {
+                    let fields = ts
+                        .iter()
+                        .map(|t| self.layout(t))
+                        .collect::<Evaluation<Vec<_>>>()?;
+                    Ok(struct_layout(&fields))
+                }
+                TypedValueType::Pointer(_)
+                | TypedValueType::Reference(_)
+                | TypedValueType::Function(_) => Ok(pointer),
+            },
+            TypedType::Function(_) => Ok(pointer),
+            TypedType::Type(t) => Err(format!("type `{}` has no size", t.to_string()).into()),
+        }
+    }
+
+    fn named_layout(&mut self, n: &TypedNamedValueType) -> Evaluation<(u64, u64)> {
+        let is_global = match &n.package {
+            TypedPackage::Raw(p) | TypedPackage::Resolved(p) => p.names.is_empty(),
+        };
+        if is_global {
+            if let Some(layout) = primitive_layout(&n.name, &self.data_layout) {
+                return Ok(layout);
+            }
+        }
+        let arena = self.arena;
+        let rs = self
+            .declarations(&n.package, &n.name)
+            .iter()
+            .find_map(|id| arena.get_type_by_id(id))
+            .ok_or_else(|| format!("`{}` is not a type", n.name))?;
+        if rs.kind.is_class() {
+            // Values of classes are references to their instances.
+            return Ok((
+                self.data_layout.pointer_size,
+                self.data_layout.pointer_align,
+            ));
+        }
+        if !(rs.kind.is_struct() || rs.kind.is_union()) {
+            return Err(format!("size of `{}` is not known", n.name).into());
+        }
+        let order = rs.stored_property_order.as_ref().ok_or_else(|| {
+            format!(
+                "layout of `{}` is not known before its declaration is resolved",
+                n.name
+            )
+        })?;
+        let fields = order
+            .iter()
+            .map(|name| self.layout(&rs.stored_properties[name]))
+            .collect::<Evaluation<Vec<_>>>()?;
+        Ok(if rs.kind.is_union() {
+            let size = fields.iter().map(|f| f.0).max().unwrap_or_default();
+            let align = fields.iter().map(|f| f.1).max().unwrap_or(1);
+            (round_up(size, align), align)
+        } else {
+            struct_layout(&fields)
+        })
+    }
+}
+
+fn struct_layout(fields: &[(u64, u64)]) -> (u64, u64) {
+    let mut offset = 0;
+    let mut align = 1;
+    for (field_size, field_align) in fields {
+        offset = round_up(offset, *field_align) + field_size;
+        align = align.max(*field_align);
+    }
+    (round_up(offset, align), align)
+}
+
+fn round_up(size: u64, align: u64) -> u64 {
+    size.div_ceil(align) * align
+}
+
+fn primitive_layout(name: &str, data_layout: &DataLayout) -> Option<(u64, u64)> {
+    let integer = |bits: u64| (bits / 8, data_layout.integer_align(bits));
+    let float = |bits: u64| (bits / 8, data_layout.float_align(bits));
+    Some(match name {
+        constants::BOOL => (1, data_layout.integer_align(1)),
+        constants::INT8 | constants::UINT8 => integer(8),
+        constants::INT16 | constants::UINT16 => integer(16),
+        constants::INT32 | constants::UINT32 => integer(32),
+        constants::INT64 | constants::UINT64 => integer(64),
+        constants::INT128 | constants::UINT128 => integer(128),
+        constants::F32 => float(32),
+        constants::F64 => float(64),
+        constants::F128 => float(128),
+        constants::SIZE | constants::USIZE | constants::STRING => {
+            (data_layout.pointer_size, data_layout.pointer_align)
+        }
+        constants::UNIT | constants::NOTING => (0, 1),
+        _ => return None,
+    })
+}
+
+fn type_name(ty: &TypedType) -> Option<&str> {
+    match ty {
+        TypedType::Value(TypedValueType::Value(n)) => Some(&n.name),
+        _ => None,
+    }
+}
+
+/// Bit width and signedness of integer type `ty`.
+fn integer_type(ty: &TypedType) -> Option<(u32, bool)> {
+    Some(match type_name(ty)? {
+        constants::INT8 => (8, true),
+        constants::INT16 => (16, true),
+        constants::INT32 => (32, true),
+        constants::INT64 | constants::SIZE => (64, true),
+        constants::INT128 => (128, true),
+        constants::UINT8 => (8, false),
+        constants::UINT16 => (16, false),
+        constants::UINT32 => (32, false),
+        constants::UINT64 | constants::USIZE => (64, false),
+        constants::UINT128 => (128, false),
+        _ => return None,
+    })
+}
+
+fn is_floating_point(ty: &TypedType) -> bool {
+    matches!(
+        type_name(ty),
+        Some(constants::F32 | constants::F64 | constants::F128)
+    )
+}
+
+fn round_to(f: f64, ty: &Option<TypedType>) -> f64 {
+    match ty.as_ref().and_then(type_name) {
+        Some(constants::F32) => f as f32 as f64,
+        _ => f,
+    }
+}
+
+fn check_fits(i: i128, ty: &TypedType) -> Result<(), String> {
+    match integer_type(ty) {
+        Some(int) if wrap(i, int) != i || (!int.1 && i < 0) => {
+            Err(format!("`{}` does not fit in `{}`", i, ty.to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Two's complement truncation of `i` to integer type `(bits, signed)`.
+fn wrap(i: i128, (bits, signed): (u32, bool)) -> i128 {
+    if bits >= 128 {
+        return i;
+    }
+    let modulus = 1i128 << bits;
+    let u = i.rem_euclid(modulus);
+    if signed && u >= modulus / 2 {
+        u - modulus
+    } else {
+        u
+    }
+}
+
+fn operator_symbol(operator: &TypedBinaryOperator) -> &str {
+    match operator {
+        TypedBinaryOperator::Add => "+",
+        TypedBinaryOperator::Sub => "-",
+        TypedBinaryOperator::Mul => "*",
+        TypedBinaryOperator::Div => "/",
+        TypedBinaryOperator::Mod => "%",
+        TypedBinaryOperator::Equal => "==",
+        TypedBinaryOperator::GrateThanEqual => ">=",
+        TypedBinaryOperator::GrateThan => ">",
+        TypedBinaryOperator::LessThanEqual => "<=",
+        TypedBinaryOperator::LessThan => "<",
+        TypedBinaryOperator::NotEqual => "!=",
+        TypedBinaryOperator::And => "&&",
+        TypedBinaryOperator::Or => "||",
+        TypedBinaryOperator::InfixFunctionCall(name) => name,
+    }
+}
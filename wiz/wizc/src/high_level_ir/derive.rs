@@ -0,0 +1,307 @@
+use std::fmt;
+use wiz_data_structure::annotation::Annotations;
+use wiz_hir::typed_decl::{
+    TypedArgDef, TypedDeclKind, TypedExtension, TypedFun, TypedFunBody, TypedStruct,
+    TypedTopLevelDecl, TypedVar,
+};
+use wiz_hir::typed_expr::{
+    TypedBinOp, TypedBinaryOperator, TypedCall, TypedCallArg, TypedExpr, TypedExprKind,
+    TypedInstanceMember, TypedLiteralKind, TypedName, TypedPrefixUnaryOp, TypedPrefixUnaryOperator,
+    TypedReturn, TypedUnaryOp,
+};
+use wiz_hir::typed_stmt::{TypedBlock, TypedStmt};
+use wiz_hir::typed_type::{Package, TypedNamedValueType, TypedPackage, TypedType, TypedValueType};
+
+/// Protocols which implementations are synthesized by `#[derive(..)]`.
+///
+/// Each member function is built from the stored properties of the struct.
+/// Fields of primitive or pointer types are compared with `==` and copied,
+/// others must conform to the protocol themselves.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub(crate) enum Derive {
+    Clone,
+    Equals,
+    Printable,
+}
+
+impl Derive {
+    pub(crate) const ALL: [Derive; 3] = [Self::Clone, Self::Equals, Self::Printable];
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.name() == name)
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Clone => "Clone",
+            Self::Equals => "Equals",
+            Self::Printable => "Printable",
+        }
+    }
+
+    pub(crate) fn protocol(&self) -> TypedType {
+        match self {
+            Self::Clone | Self::Equals => {
+                named_type(Package::from(&["core", "builtin"]), self.name())
+            }
+            Self::Printable => named_type(Package::from(&["std", "format"]), self.name()),
+        }
+    }
+
+    /// Whether values of `t` can be compared with `==` and copied without calling
+    /// the protocol implementation.
+    pub(crate) fn is_trivial(&self, t: &TypedType) -> bool {
+        match self {
+            Self::Clone | Self::Equals => t.is_primitive() || t.is_pointer_type(),
+            Self::Printable => false,
+        }
+    }
+
+    /// Synthesize the extension of `s` conforming to this protocol.
+    /// `package` is the package `s` is declared in.
+    pub(crate) fn extension(&self, s: &TypedStruct, package: &Package) -> TypedExtension {
+        TypedExtension {
+            name: named_type(Package::new(), &s.name),
+            protocol: Some(self.protocol()),
+            associated_types: vec![],
+            computed_properties: vec![],
+            member_functions: vec![match self {
+                Self::Clone => self.clone_fun(s),
+                Self::Equals => self.equals_fun(s),
+                Self::Printable => self.description_fun(s, package),
+            }],
+        }
+    }
+
+    /// `fun clone(&self): Self { return Self(a: self.a.clone(), ..) }`
+    fn clone_fun(&self, s: &TypedStruct) -> TypedFun {
+        let args = s
+            .stored_properties
+            .iter()
+            .map(|p| {
+                let field = member(name("self"), &p.name);
+                TypedCallArg {
+                    label: Some(p.name.clone()),
+                    arg: Box::new(if self.is_trivial_field(&p.type_) {
+                        field
+                    } else {
+                        call(member(field, "clone"), vec![])
+                    }),
+                    is_vararg: false,
+                }
+            })
+            .collect();
+        fun(
+            "clone",
+            vec![],
+            TypedType::Self_,
+            vec![return_(call(name("Self"), args))],
+        )
+    }
+
+    /// `fun equals(&self, _ other: &Self): Bool { return self.a.equals(&other.a) && .. }`
+    fn equals_fun(&self, s: &TypedStruct) -> TypedFun {
+        let value = s
+            .stored_properties
+            .iter()
+            .map(|p| {
+                let field = member(name("self"), &p.name);
+                let other = member(name("other"), &p.name);
+                if self.is_trivial_field(&p.type_) {
+                    bin_op(field, TypedBinaryOperator::Equal, other)
+                } else {
+                    call(
+                        member(field, "equals"),
+                        vec![TypedCallArg {
+                            label: None,
+                            arg: Box::new(reference(other)),
+                            is_vararg: false,
+                        }],
+                    )
+                }
+            })
+            .reduce(|l, r| bin_op(l, TypedBinaryOperator::And, r))
+            .unwrap_or_else(|| {
+                expr(TypedExprKind::Literal(TypedLiteralKind::Boolean(
+                    "true".to_string(),
+                )))
+            });
+        fun(
+            "equals",
+            vec![TypedArgDef {
+                label: "_".to_string(),
+                name: "other".to_string(),
+                type_: TypedType::reference(TypedType::Self_),
+                default: None,
+            }],
+            named_type(Package::new(), "Bool"),
+            vec![return_(value)],
+        )
+    }
+
+    /// `fun description(&self): String`, which describes `A { a: 1 }` as `A(a: 1)`.
+    fn description_fun(&self, s: &TypedStruct, package: &Package) -> TypedFun {
+        let description = "description";
+        let push = |s: String| {
+            TypedStmt::Expr(call(
+                member(name(description), "push"),
+                vec![TypedCallArg {
+                    label: None,
+                    arg: Box::new(expr(TypedExprKind::Literal(TypedLiteralKind::String(s)))),
+                    is_vararg: false,
+                }],
+            ))
+        };
+        let string = named_type(Package::from(&["std", "string"]), "String");
+        let mut body = vec![TypedStmt::Decl(TypedTopLevelDecl {
+            annotations: Annotations::default(),
+            package: package.clone(),
+            modifiers: vec![],
+            kind: TypedDeclKind::Var(TypedVar {
+                is_mut: true,
+                name: description.to_string(),
+                type_: None,
+                value: call(
+                    expr(TypedExprKind::Name(TypedName {
+                        package: TypedPackage::Raw(Package::from(&["std", "string", "String"])),
+                        name: "new".to_string(),
+                        type_arguments: None,
+                    })),
+                    vec![TypedCallArg {
+                        label: None,
+                        arg: Box::new(expr(TypedExprKind::Literal(TypedLiteralKind::String(
+                            format!("{}(", s.name),
+                        )))),
+                        is_vararg: false,
+                    }],
+                ),
+            }),
+        })];
+        for (i, p) in s.stored_properties.iter().enumerate() {
+            let separator = if i == 0 { "" } else { ", " };
+            body.push(push(format!("{}{}: ", separator, p.name)));
+            body.push(TypedStmt::Expr(call(
+                member(name(description), "join"),
+                vec![TypedCallArg {
+                    label: None,
+                    arg: Box::new(call(
+                        member(member(name("self"), &p.name), "description"),
+                        vec![],
+                    )),
+                    is_vararg: false,
+                }],
+            )));
+        }
+        body.push(push(")".to_string()));
+        body.push(return_(name(description)));
+        fun("description", vec![], string, body)
+    }
+
+    /// Field types are not resolved yet, so primitives are detected by their names.
+    fn is_trivial_field(&self, t: &TypedType) -> bool {
+        let t = match t {
+            TypedType::Value(TypedValueType::Value(v))
+                if v.package == TypedPackage::Raw(Package::new()) =>
+            {
+                TypedType::Value(TypedValueType::Value(TypedNamedValueType {
+                    package: TypedPackage::Resolved(Package::global()),
+                    name: v.name.clone(),
+                    type_args: v.type_args.clone(),
+                }))
+            }
+            t => t.clone(),
+        };
+        self.is_trivial(&t)
+    }
+}
+
+impl fmt::Display for Derive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+fn named_type(package: Package, name: &str) -> TypedType {
+    TypedType::Value(TypedValueType::Value(TypedNamedValueType {
+        package: TypedPackage::Raw(package),
+        name: name.to_string(),
+        type_args: None,
+    }))
+}
+
+fn fun(
+    name: &str,
+    mut arg_defs: Vec<TypedArgDef>,
+    return_type: TypedType,
+    body: Vec<TypedStmt>,
+) -> TypedFun {
+    arg_defs.insert(
+        0,
+        TypedArgDef {
+            label: "_".to_string(),
+            name: "self".to_string(),
+            type_: TypedType::reference(TypedType::Self_),
+            default: None,
+        },
+    );
+    TypedFun {
+        name: name.to_string(),
+        type_params: None,
+        type_constraints: None,
+        arg_defs,
+        body: Some(TypedFunBody::Block(TypedBlock { body })),
+        return_type,
+    }
+}
+
+fn expr(kind: TypedExprKind) -> TypedExpr {
+    TypedExpr::new(kind, None)
+}
+
+fn name(name: &str) -> TypedExpr {
+    expr(TypedExprKind::Name(TypedName {
+        package: TypedPackage::Raw(Package::new()),
+        name: name.to_string(),
+        type_arguments: None,
+    }))
+}
+
+fn member(target: TypedExpr, name: &str) -> TypedExpr {
+    expr(TypedExprKind::Member(TypedInstanceMember {
+        target: Box::new(target),
+        name: name.to_string(),
+        is_safe: false,
+    }))
+}
+
+fn call(target: TypedExpr, args: Vec<TypedCallArg>) -> TypedExpr {
+    expr(TypedExprKind::Call(TypedCall {
+        target: Box::new(target),
+        args,
+        location: None,
+    }))
+}
+
+fn reference(target: TypedExpr) -> TypedExpr {
+    expr(TypedExprKind::UnaryOp(TypedUnaryOp::Prefix(
+        TypedPrefixUnaryOp {
+            target: Box::new(target),
+            operator: TypedPrefixUnaryOperator::Reference,
+        },
+    )))
+}
+
+fn bin_op(left: TypedExpr, operator: TypedBinaryOperator, right: TypedExpr) -> TypedExpr {
+    expr(TypedExprKind::BinOp(TypedBinOp {
+        left: Box::new(left),
+        operator,
+        right: Box::new(right),
+        location: None,
+    }))
+}
+
+fn return_(value: TypedExpr) -> TypedStmt {
+    TypedStmt::Expr(expr(TypedExprKind::Return(TypedReturn {
+        value: Some(Box::new(value)),
+    })))
+}
@@ -0,0 +1,143 @@
+use crate::high_level_ir::type_resolver::error::ResolverError;
+use crate::high_level_ir::type_resolver::result::Result;
+use std::collections::HashMap;
+use wiz_arena::Arena;
+use wiz_hir::typed_decl::TypedDeclKind;
+use wiz_hir::typed_file::TypedSpellBook;
+use wiz_hir::typed_use::TypedUse;
+
+/// `public use` declarations of a spell book and of the namespaces in it.
+///
+/// Re-exported names are visible from the namespace declaring `public use` as if they
+/// were declared in it, but keep the fully qualified names of their declarations.
+/// Declarations of the namespace shadow names re-exported by `public use a::*`.
+#[derive(Debug)]
+pub(crate) struct ReExports {
+    uses: Vec<(Vec<String>, TypedUse)>,
+}
+
+impl ReExports {
+    pub(crate) fn new(f: &TypedSpellBook) -> Self {
+        let mut uses = vec![];
+        Self::collect(vec![], f, &mut uses);
+        Self { uses }
+    }
+
+    fn collect(
+        mut namespace: Vec<String>,
+        f: &TypedSpellBook,
+        uses: &mut Vec<(Vec<String>, TypedUse)>,
+    ) {
+        namespace.push(f.name.clone());
+        uses.extend(
+            f.uses
+                .iter()
+                .filter(|u| u.is_public)
+                .map(|u| (namespace.clone(), u.clone())),
+        );
+        for d in f.body.iter() {
+            if let TypedDeclKind::Module(m) = &d.kind {
+                Self::collect(namespace.clone(), m, uses);
+            }
+        }
+    }
+
+    /// Re-exports may refer to names re-exported by others, so they are applied
+    /// until nothing changes.
+    pub(crate) fn apply_to(&self, arena: &mut Arena) -> Result<()> {
+        loop {
+            let mut changed = false;
+            let mut unresolved = None;
+            for (namespace, u) in self.uses.iter() {
+                match self.re_export(arena, namespace, u)? {
+                    Some(c) => changed |= c,
+                    None => {
+                        unresolved.get_or_insert((namespace, u));
+                    }
+                }
+            }
+            if !changed {
+                return match unresolved {
+                    None => Ok(()),
+                    Some((namespace, u)) => Err(ResolverError::from(format!(
+                        "Cannot resolve `{}` re-exported from `{}`",
+                        u.package.names.join("::"),
+                        namespace.join("::")
+                    ))),
+                };
+            }
+        }
+    }
+
+    /// Returns whether `arena` is changed, or `None` if the used names are not found.
+    fn re_export(
+        &self,
+        arena: &mut Arena,
+        namespace: &[String],
+        u: &TypedUse,
+    ) -> Result<Option<bool>> {
+        let used = if u.is_glob() {
+            let names = &u.package.names[..u.package.names.len() - 1];
+            match arena
+                .resolve_declaration_id_from_root(names)
+                .and_then(|id| arena.get_by_id(&id))
+            {
+                Some(item) => item.visible_children(),
+                None => return Ok(None),
+            }
+        } else {
+            match (
+                u.name(),
+                arena.resolve_declaration_ids_from_root(&u.package.names),
+            ) {
+                (Some(name), Some(ids)) => HashMap::from([(name.to_string(), ids.clone())]),
+                _ => return Ok(None),
+            }
+        };
+        let namespace_id = arena
+            .resolve_declaration_id_from_root(namespace)
+            .ok_or_else(|| {
+                ResolverError::from(format!("Cannot resolve `{}`", namespace.join("::")))
+            })?;
+        let item = arena.get_by_id(&namespace_id).unwrap();
+        let mut re_exports = vec![];
+        for (name, ids) in used {
+            if item.children().contains_key(&name) {
+                if u.is_glob() {
+                    continue;
+                }
+                return Err(ResolverError::from(format!(
+                    "`{}` is already declared in `{}`",
+                    name,
+                    namespace.join("::")
+                )));
+            }
+            if u.is_glob() && self.is_re_exported_by_name(namespace, &name) {
+                continue;
+            }
+            match item.re_exports().get(&name) {
+                Some(re_exported) if *re_exported == ids => {}
+                Some(_) => {
+                    return Err(ResolverError::from(format!(
+                        "`{}` is re-exported from `{}` as different declarations",
+                        name,
+                        namespace.join("::")
+                    )))
+                }
+                None => re_exports.push((name, ids)),
+            }
+        }
+        let changed = !re_exports.is_empty();
+        for (name, ids) in re_exports {
+            arena.register_re_export(&namespace_id, &name, ids);
+        }
+        Ok(Some(changed))
+    }
+
+    /// Names re-exported by `public use a::b` shadow ones by `public use c::*`.
+    fn is_re_exported_by_name(&self, namespace: &[String], name: &str) -> bool {
+        self.uses
+            .iter()
+            .any(|(n, u)| n == namespace && !u.is_glob() && u.name() == Some(name))
+    }
+}
@@ -1,6 +1,13 @@
+mod control_flow;
 mod error;
+mod initialization;
+mod moves;
+mod mutability;
+#[cfg(test)]
+mod tests;
 
 use crate::high_level_ir::type_checker::error::CheckerError;
+use crate::high_level_ir::type_checker::mutability::Binding;
 use wiz_arena::{Arena, StructKind};
 use wiz_hir::typed_decl::{
     TypedDeclKind, TypedExtension, TypedFun, TypedFunBody, TypedProtocol, TypedStruct, TypedVar,
@@ -12,56 +19,80 @@ use wiz_hir::typed_expr::{
 };
 use wiz_hir::typed_file::TypedSpellBook;
 use wiz_hir::typed_stmt::{TypedAssignmentStmt, TypedBlock, TypedLoopStmt, TypedStmt};
-use wiz_hir::typed_type::{Package, TypedType};
+use wiz_hir::typed_type::{Package, TypedType, TypedValueType};
 use wiz_session::Session;
+use wiz_utils::StackedHashMap;
 
 #[derive(Debug)]
 pub struct TypeChecker<'s> {
     session: &'s mut Session,
     arena: &'s Arena,
+    bindings: StackedHashMap<String, Binding>,
+    /// Whether the body of `defer` is being checked.
+    in_defer: bool,
 }
 
 impl<'s> TypeChecker<'s> {
     pub fn new(session: &'s mut Session, arena: &'s Arena) -> Self {
-        Self { session, arena }
+        Self {
+            session,
+            arena,
+            bindings: StackedHashMap::new(),
+            in_defer: false,
+        }
     }
 
     pub(crate) fn verify(&mut self, typed_file: &TypedSpellBook) {
+        self.push_bindings(Self::global_bindings(typed_file));
         typed_file
             .body
             .iter()
-            .for_each(|d| self.decl(&d.kind, &d.package))
+            .for_each(|d| self.decl(&d.kind, &d.package));
+        self.pop_bindings();
     }
 
     fn decl(&mut self, decl: &TypedDeclKind, package: &Package) {
         match decl {
             TypedDeclKind::Var(v) => self.variable(v),
+            TypedDeclKind::Const(c) => self.constant(c),
+            TypedDeclKind::ExternVar(_) => {}
             TypedDeclKind::Fun(f) => self.function(f),
-            TypedDeclKind::Struct(s) => self.struct_(s, package),
+            TypedDeclKind::Struct(s) | TypedDeclKind::Class(s) => self.struct_(s, package),
+            TypedDeclKind::Union(u) => self.union_(u),
             TypedDeclKind::Module(m) => self.verify(m),
             TypedDeclKind::Enum => todo!(),
             TypedDeclKind::Protocol(p) => self.protocol(p),
             TypedDeclKind::Extension(e) => self.extension(e),
+            TypedDeclKind::TypeAlias(_) => {}
         }
     }
 
     fn variable(&mut self, typed_variable: &TypedVar) {
-        if typed_variable.type_ != typed_variable.value.ty {
+        if typed_variable.type_ != typed_variable.value.ty.clone().map(TypedType::decay) {
             self.session.emit_error(CheckerError::new(format!(
                 "TypeMissMatchError: left -> {:?}, right -> {:?}",
                 typed_variable.type_, typed_variable.value.ty
             )));
         };
-        self.expression(&typed_variable.value)
+        self.expression(&typed_variable.value);
+        self.bind_variable(typed_variable);
+    }
+
+    fn constant(&mut self, typed_constant: &TypedVar) {
+        self.expression(&typed_constant.value);
+        self.bind_constant(typed_constant);
     }
 
     fn function(&mut self, typed_function: &TypedFun) {
+        self.push_bindings(Self::argument_bindings(&typed_function.arg_defs));
         if let Some(body) = &typed_function.body {
             match body {
                 TypedFunBody::Expr(e) => {
                     self.expression(e);
                     if let Some(expr_type) = &e.ty {
-                        if typed_function.return_type != *expr_type {
+                        if typed_function.return_type != *expr_type
+                            && *expr_type != TypedType::noting()
+                        {
                             self.session.emit_error(CheckerError::new(format!(
                                 "TypeMissMatchError: {:?} excepted return {:?}, but return {:?}",
                                 typed_function.name, typed_function.return_type, expr_type,
@@ -74,16 +105,34 @@ impl<'s> TypeChecker<'s> {
                 }
                 TypedFunBody::Block(b) => {
                     self.block(b);
+                    self.control_flow(&typed_function.name, &typed_function.return_type, b);
                 }
             };
+            self.moves(&typed_function.arg_defs, body);
+        }
+        self.pop_bindings();
+    }
+
+    fn member_function(&mut self, typed_function: &TypedFun) {
+        self.push_bindings(Self::argument_bindings(&typed_function.arg_defs));
+        if let Some(body) = &typed_function.body {
+            match body {
+                TypedFunBody::Expr(e) => self.expression(e),
+                TypedFunBody::Block(b) => {
+                    self.block(b);
+                    self.control_flow(&typed_function.name, &typed_function.return_type, b);
+                }
+            }
+            self.moves(&typed_function.arg_defs, body);
         }
+        self.pop_bindings();
     }
 
     fn struct_(&mut self, typed_struct: &TypedStruct, package: &Package) {
         let struct_info = self.arena.get_type(&package.names, &typed_struct.name);
 
         if let Some(struct_info) = struct_info {
-            if struct_info.kind == StructKind::Struct {
+            if struct_info.kind.is_struct() || struct_info.kind.is_class() {
                 struct_info
                     .conformed_protocols
                     .iter()
@@ -99,28 +148,37 @@ impl<'s> TypeChecker<'s> {
         };
         typed_struct.computed_properties.iter().for_each(|_| {});
         typed_struct.stored_properties.iter().for_each(|_| {});
-        typed_struct.member_functions.iter().for_each(|i| {
-            if let Some(body) = &i.body {
-                match body {
-                    TypedFunBody::Expr(e) => self.expression(e),
-                    TypedFunBody::Block(b) => self.block(b),
-                }
+        typed_struct
+            .initializers
+            .iter()
+            .for_each(|i| self.initializer(typed_struct, i));
+        typed_struct.subscripts.iter().for_each(|s| {
+            self.member_function(&s.getter);
+            if let Some(setter) = &s.setter {
+                self.member_function(setter);
             }
         });
+        typed_struct
+            .member_functions
+            .iter()
+            .for_each(|i| self.member_function(i));
+    }
+
+    fn union_(&mut self, typed_union: &TypedStruct) {
+        typed_union
+            .member_functions
+            .iter()
+            .for_each(|i| self.member_function(i));
     }
 
     fn protocol(&mut self, typed_protocol: &TypedProtocol) {}
 
     fn extension(&mut self, typed_extension: &TypedExtension) {
         typed_extension.computed_properties.iter().for_each(|_| {});
-        typed_extension.member_functions.iter().for_each(|i| {
-            if let Some(body) = &i.body {
-                match body {
-                    TypedFunBody::Expr(e) => self.expression(e),
-                    TypedFunBody::Block(b) => self.block(b),
-                }
-            }
-        })
+        typed_extension
+            .member_functions
+            .iter()
+            .for_each(|i| self.member_function(i))
     }
 
     fn statement(&mut self, typed_statement: &TypedStmt) {
@@ -129,12 +187,21 @@ impl<'s> TypeChecker<'s> {
             TypedStmt::Decl(d) => self.decl(&d.kind, &d.package),
             TypedStmt::Assignment(a) => self.assignment_statement(a),
             TypedStmt::Loop(l) => self.loop_statement(l),
+            TypedStmt::Defer(b) => {
+                let in_defer = std::mem::replace(&mut self.in_defer, true);
+                self.block(b);
+                self.in_defer = in_defer;
+            }
         }
     }
 
     fn assignment_statement(&mut self, typed_assignment: &TypedAssignmentStmt) {
         match typed_assignment {
             TypedAssignmentStmt::Assignment(a) => {
+                self.assignable(&a.target);
+                if let TypedExprKind::Member(m) = &a.target.kind {
+                    self.member(m);
+                }
                 if a.target.ty != a.value.ty {
                     self.session.emit_error(CheckerError::new(format!(
                         "TypeMissMatchError: assignment {:?}, into {:?}",
@@ -143,6 +210,10 @@ impl<'s> TypeChecker<'s> {
                 }
             }
             TypedAssignmentStmt::AssignmentAndOperation(a) => {
+                self.assignable(&a.target);
+                if let TypedExprKind::Member(m) = &a.target.kind {
+                    self.member(m);
+                }
                 if a.target.ty != a.value.ty {
                     self.session.emit_error(CheckerError::new(format!(
                         "TypeMissMatchError: assignment {:?}, into {:?}",
@@ -171,13 +242,18 @@ impl<'s> TypeChecker<'s> {
                 self.block(&w.block);
             }
             TypedLoopStmt::For(f) => {
+                self.push_bindings(Default::default());
+                f.values.iter().for_each(|v| self.bind_loop_variable(v));
                 self.block(&f.block);
+                self.pop_bindings();
             }
         }
     }
 
     fn block(&mut self, typed_block: &TypedBlock) {
-        typed_block.body.iter().for_each(|s| self.statement(s))
+        self.push_bindings(Default::default());
+        typed_block.body.iter().for_each(|s| self.statement(s));
+        self.pop_bindings();
     }
 
     fn expression(&mut self, typed_expr: &TypedExpr) {
@@ -246,7 +322,7 @@ impl<'s> TypeChecker<'s> {
             }
             TypedLiteralKind::String(value) => {
                 if let Some(typ) = ty {
-                    if !typ.is_string_ref() {
+                    if !typ.is_string_ref() && *typ != TypedType::slice(TypedType::uint8()) {
                         self.session.emit_error(CheckerError::new(format!(
                             "Invalid literal type of {:?}",
                             value
@@ -281,6 +357,7 @@ impl<'s> TypeChecker<'s> {
                     )))
                 }
             }
+            TypedLiteralKind::SourceLocation(..) => {}
         }
     }
 
@@ -306,15 +383,36 @@ impl<'s> TypeChecker<'s> {
 
     fn member(&mut self, typed_member: &TypedInstanceMember) {
         self.expression(&*typed_member.target);
+        let mut target_type = typed_member.target.ty.as_ref();
+        while let Some(TypedType::Value(TypedValueType::Reference(t))) = target_type {
+            target_type = Some(t.as_ref());
+        }
+        if let Some(TypedType::Value(TypedValueType::Value(t))) = target_type {
+            let is_union = self
+                .arena
+                .get_type(&t.package.clone().into_resolved().names, &t.name)
+                .map(|rs| rs.kind.is_union())
+                .unwrap_or_default();
+            if is_union {
+                self.session.emit_warning(CheckerError::new(format!(
+                    "UnsafeAccess: field `{}` of union `{}` is accessed without knowing which member is active",
+                    typed_member.name, t.name
+                )))
+            }
+        }
     }
 
     fn array(&mut self, typed_array: &TypedArray, ty: &Option<TypedType>) {
+        let element_type = match ty {
+            Some(TypedType::Value(TypedValueType::Array(t, _))) => Some(*t.clone()),
+            _ => None,
+        };
         typed_array.elements.iter().for_each(|e| {
             self.expression(e);
-            if e.ty != *ty {
+            if element_type.is_some() && e.ty != element_type {
                 self.session.emit_error(CheckerError::new(format!(
                     "TypeMissMatchError: Array element excepted {:?}, but {:?} found",
-                    ty, e.ty
+                    element_type, e.ty
                 )))
             }
         })
@@ -328,7 +426,7 @@ impl<'s> TypeChecker<'s> {
     }
 
     fn if_(&mut self, typed_if: &TypedIf) {
-        if typed_if.condition.ty.as_ref().unwrap().is_boolean() {
+        if !typed_if.condition.ty.as_ref().unwrap().is_boolean() {
             self.session.emit_error(CheckerError::new(format!(
                 "if condition type must be boolean, but {:?} ware given",
                 typed_if.condition.ty
@@ -343,6 +441,11 @@ impl<'s> TypeChecker<'s> {
     }
 
     fn return_(&mut self, typed_return: &TypedReturn) {
+        if self.in_defer {
+            self.session.emit_error(CheckerError::new(
+                "DeferError: `return` is not allowed in defer",
+            ))
+        }
         typed_return.value.as_ref().map(|v| self.expression(&*v));
     }
 
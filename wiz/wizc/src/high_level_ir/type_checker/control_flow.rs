@@ -0,0 +1,130 @@
+use crate::high_level_ir::type_checker::error::CheckerError;
+use crate::high_level_ir::type_checker::TypeChecker;
+use wiz_hir::typed_decl::TypedDeclKind;
+use wiz_hir::typed_expr::{TypedExpr, TypedExprKind, TypedLiteralKind, TypedUnaryOp};
+use wiz_hir::typed_stmt::{TypedAssignmentStmt, TypedBlock, TypedLoopStmt, TypedStmt};
+use wiz_hir::typed_type::TypedType;
+
+/// Control-flow analysis over function bodies.
+///
+/// A statement *diverges* when control never reaches the statement after it:
+/// `return`, calls of functions returning `Noting`, `if`/`else` whose branches
+/// both diverge and `while true` loops (there is no `break`).
+impl<'s> TypeChecker<'s> {
+    pub(super) fn control_flow(&mut self, name: &str, return_type: &TypedType, body: &TypedBlock) {
+        if self.block_diverges(name, body) {
+            return;
+        }
+        if *return_type == TypedType::noting() {
+            self.session.emit_error(CheckerError::new(format!(
+                "MissingReturn: `{}` returns `Noting`, but may reach the end of its body",
+                name
+            )))
+        } else if *return_type != TypedType::unit() {
+            self.session.emit_error(CheckerError::new(format!(
+                "MissingReturn: `{}` must return `{}`, but may reach the end of its body without returning",
+                name,
+                return_type.to_string()
+            )))
+        }
+    }
+
+    fn block_diverges(&mut self, name: &str, typed_block: &TypedBlock) -> bool {
+        let mut diverges = false;
+        for stmt in typed_block.body.iter() {
+            if diverges {
+                self.session.emit_warning(CheckerError::new(format!(
+                    "UnreachableCode: statement in `{}` will never be executed",
+                    name
+                )));
+                break;
+            }
+            diverges = self.statement_diverges(name, stmt);
+        }
+        diverges
+    }
+
+    fn statement_diverges(&mut self, name: &str, typed_statement: &TypedStmt) -> bool {
+        match typed_statement {
+            TypedStmt::Expr(e) => self.expression_diverges(name, e),
+            TypedStmt::Decl(d) => match &d.kind {
+                TypedDeclKind::Var(v) | TypedDeclKind::Const(v) => {
+                    self.expression_diverges(name, &v.value)
+                }
+                _ => false,
+            },
+            TypedStmt::Assignment(a) => match a {
+                TypedAssignmentStmt::Assignment(a) => {
+                    self.expression_diverges(name, &a.target)
+                        | self.expression_diverges(name, &a.value)
+                }
+                TypedAssignmentStmt::AssignmentAndOperation(a) => {
+                    self.expression_diverges(name, &a.target)
+                        | self.expression_diverges(name, &a.value)
+                }
+            },
+            // runs at the exit of the block, so it never prevents reaching the next statement
+            TypedStmt::Defer(_) => false,
+            TypedStmt::Loop(l) => match l {
+                TypedLoopStmt::While(w) => {
+                    let condition = self.expression_diverges(name, &w.condition);
+                    self.block_diverges(name, &w.block);
+                    condition
+                        || matches!(
+                            w.condition.kind,
+                            TypedExprKind::Literal(TypedLiteralKind::Boolean(ref b)) if b == "true"
+                        )
+                }
+                TypedLoopStmt::For(f) => {
+                    let iterator = self.expression_diverges(name, &f.iterator);
+                    self.block_diverges(name, &f.block);
+                    iterator
+                }
+            },
+        }
+    }
+
+    fn expression_diverges(&mut self, name: &str, typed_expr: &TypedExpr) -> bool {
+        match &typed_expr.kind {
+            TypedExprKind::Return(_) => true,
+            TypedExprKind::Call(c) => {
+                let target = self.expression_diverges(name, &c.target);
+                let args = c
+                    .args
+                    .iter()
+                    .fold(false, |d, a| self.expression_diverges(name, &a.arg) | d);
+                target || args || typed_expr.ty == Some(TypedType::noting())
+            }
+            TypedExprKind::If(i) => {
+                let condition = self.expression_diverges(name, &i.condition);
+                let body = self.block_diverges(name, &i.body);
+                let else_body = i
+                    .else_body
+                    .as_ref()
+                    .map(|b| self.block_diverges(name, b))
+                    .unwrap_or_default();
+                condition || (body && else_body)
+            }
+            TypedExprKind::BinOp(b) => {
+                self.expression_diverges(name, &b.left) | self.expression_diverges(name, &b.right)
+            }
+            TypedExprKind::UnaryOp(u) => match u {
+                TypedUnaryOp::Prefix(p) => self.expression_diverges(name, &p.target),
+                TypedUnaryOp::Postfix(p) => self.expression_diverges(name, &p.target),
+            },
+            TypedExprKind::Subscript(s) => s
+                .indexes
+                .iter()
+                .fold(self.expression_diverges(name, &s.target), |d, i| {
+                    self.expression_diverges(name, i) | d
+                }),
+            TypedExprKind::Member(m) => self.expression_diverges(name, &m.target),
+            TypedExprKind::Array(a) => a
+                .elements
+                .iter()
+                .fold(false, |d, e| self.expression_diverges(name, e) | d),
+            TypedExprKind::TypeCast(c) => self.expression_diverges(name, &c.target),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::high_level_ir::type_checker::error::CheckerError;
+use crate::high_level_ir::type_checker::mutability::Binding;
+use crate::high_level_ir::type_checker::TypeChecker;
+use std::collections::HashSet;
+use wiz_hir::typed_decl::{TypedDeclKind, TypedFunBody, TypedInitializer, TypedStruct};
+use wiz_hir::typed_expr::{TypedExpr, TypedExprKind, TypedUnaryOp};
+use wiz_hir::typed_stmt::{TypedAssignmentStmt, TypedBlock, TypedLoopStmt, TypedStmt};
+
+/// Definite initialization of `self` in initializers.
+///
+/// Every stored property must be assigned by `self.name = ..` before it or `self`
+/// itself is read, and before the end of the body. A property assigned in an `if`
+/// counts only when both branches assign it, and assignments in loops and `defer`
+/// do not count.
+impl<'s> TypeChecker<'s> {
+    pub(super) fn initializer(&mut self, typed_struct: &TypedStruct, i: &TypedInitializer) {
+        let mut bindings = Self::argument_bindings(&i.arg_defs);
+        bindings.insert("self".to_string(), Binding::initializing_self(typed_struct));
+        self.push_bindings(bindings);
+        match &i.body {
+            TypedFunBody::Expr(e) => self.expression(e),
+            TypedFunBody::Block(b) => self.block(b),
+        }
+        self.pop_bindings();
+        self.moves(&i.arg_defs, &i.body);
+
+        let mut assigned = HashSet::new();
+        match &i.body {
+            TypedFunBody::Expr(e) => self.initialized_expression(typed_struct, e, &mut assigned),
+            TypedFunBody::Block(b) => self.initialized_block(typed_struct, b, &mut assigned),
+        }
+        for p in typed_struct.stored_properties.iter() {
+            if !assigned.contains(&p.name) {
+                self.session.emit_error(CheckerError::new(format!(
+                    "InitializationError: `self.{}` is not initialized at the end of initializer of `{}`",
+                    p.name, typed_struct.name
+                )))
+            }
+        }
+    }
+
+    fn initialized_block(
+        &mut self,
+        s: &TypedStruct,
+        typed_block: &TypedBlock,
+        assigned: &mut HashSet<String>,
+    ) {
+        for stmt in typed_block.body.iter() {
+            self.initialized_statement(s, stmt, assigned);
+        }
+    }
+
+    fn initialized_statement(
+        &mut self,
+        s: &TypedStruct,
+        typed_statement: &TypedStmt,
+        assigned: &mut HashSet<String>,
+    ) {
+        match typed_statement {
+            TypedStmt::Expr(e) => self.initialized_expression(s, e, assigned),
+            TypedStmt::Decl(d) => match &d.kind {
+                TypedDeclKind::Var(v) | TypedDeclKind::Const(v) => {
+                    self.initialized_expression(s, &v.value, assigned)
+                }
+                _ => {}
+            },
+            TypedStmt::Assignment(TypedAssignmentStmt::Assignment(a)) => {
+                self.initialized_expression(s, &a.value, assigned);
+                match Self::self_property(s, &a.target) {
+                    Some(name) => {
+                        assigned.insert(name.to_string());
+                    }
+                    None => self.initialized_expression(s, &a.target, assigned),
+                }
+            }
+            TypedStmt::Assignment(TypedAssignmentStmt::AssignmentAndOperation(a)) => {
+                self.initialized_expression(s, &a.value, assigned);
+                self.initialized_expression(s, &a.target, assigned);
+            }
+            TypedStmt::Loop(TypedLoopStmt::While(w)) => {
+                self.initialized_expression(s, &w.condition, assigned);
+                self.initialized_block(s, &w.block, &mut assigned.clone());
+            }
+            TypedStmt::Loop(TypedLoopStmt::For(f)) => {
+                self.initialized_expression(s, &f.iterator, assigned);
+                self.initialized_block(s, &f.block, &mut assigned.clone());
+            }
+            TypedStmt::Defer(b) => self.initialized_block(s, b, &mut assigned.clone()),
+        }
+    }
+
+    fn initialized_expression(
+        &mut self,
+        s: &TypedStruct,
+        typed_expr: &TypedExpr,
+        assigned: &mut HashSet<String>,
+    ) {
+        if let Some(name) = Self::self_property(s, typed_expr) {
+            if !assigned.contains(name) {
+                self.session.emit_error(CheckerError::new(format!(
+                    "InitializationError: `self.{}` is used before being initialized",
+                    name
+                )))
+            }
+            return;
+        }
+        match &typed_expr.kind {
+            TypedExprKind::Name(n) if n.name == "self" => {
+                if let Some(p) = s
+                    .stored_properties
+                    .iter()
+                    .find(|p| !assigned.contains(&p.name))
+                {
+                    self.session.emit_error(CheckerError::new(format!(
+                        "InitializationError: `self` is used before `self.{}` is initialized",
+                        p.name
+                    )))
+                }
+            }
+            TypedExprKind::Return(_) => self.session.emit_error(CheckerError::new(
+                "InitializationError: `return` is not allowed in initializer",
+            )),
+            TypedExprKind::Call(c) => {
+                self.initialized_expression(s, &c.target, assigned);
+                for a in c.args.iter() {
+                    self.initialized_expression(s, &a.arg, assigned);
+                }
+            }
+            TypedExprKind::If(i) => {
+                self.initialized_expression(s, &i.condition, assigned);
+                let mut body = assigned.clone();
+                self.initialized_block(s, &i.body, &mut body);
+                if let Some(else_body) = &i.else_body {
+                    let mut else_assigned = assigned.clone();
+                    self.initialized_block(s, else_body, &mut else_assigned);
+                    assigned.extend(body.intersection(&else_assigned).cloned());
+                }
+            }
+            TypedExprKind::BinOp(b) => {
+                self.initialized_expression(s, &b.left, assigned);
+                self.initialized_expression(s, &b.right, assigned);
+            }
+            TypedExprKind::UnaryOp(TypedUnaryOp::Prefix(p)) => {
+                self.initialized_expression(s, &p.target, assigned)
+            }
+            TypedExprKind::UnaryOp(TypedUnaryOp::Postfix(p)) => {
+                self.initialized_expression(s, &p.target, assigned)
+            }
+            TypedExprKind::Subscript(sub) => {
+                self.initialized_expression(s, &sub.target, assigned);
+                for i in sub.indexes.iter() {
+                    self.initialized_expression(s, i, assigned);
+                }
+            }
+            TypedExprKind::Member(m) => self.initialized_expression(s, &m.target, assigned),
+            TypedExprKind::Array(a) => {
+                for e in a.elements.iter() {
+                    self.initialized_expression(s, e, assigned);
+                }
+            }
+            TypedExprKind::TypeCast(c) => self.initialized_expression(s, &c.target, assigned),
+            _ => {}
+        }
+    }
+
+    /// Name of the stored property of `s` that `typed_expr` accesses as `self.name`.
+    fn self_property<'a>(s: &TypedStruct, typed_expr: &'a TypedExpr) -> Option<&'a str> {
+        match &typed_expr.kind {
+            TypedExprKind::Member(m) => match &m.target.kind {
+                TypedExprKind::Name(n)
+                    if n.name == "self" && s.stored_properties.iter().any(|p| p.name == m.name) =>
+                {
+                    Some(&m.name)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
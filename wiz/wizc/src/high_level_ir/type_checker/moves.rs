@@ -0,0 +1,320 @@
+use crate::high_level_ir::derive::Derive;
+use crate::high_level_ir::type_checker::error::CheckerError;
+use crate::high_level_ir::type_checker::TypeChecker;
+use std::collections::{HashMap, HashSet};
+use wiz_arena::ArenaStruct;
+use wiz_hir::typed_decl::{TypedArgDef, TypedDeclKind, TypedFunBody, TypedStruct};
+use wiz_hir::typed_expr::{TypedExpr, TypedExprKind, TypedUnaryOp};
+use wiz_hir::typed_stmt::{TypedAssignmentStmt, TypedBlock, TypedLoopStmt, TypedStmt};
+use wiz_hir::typed_type::{TypedPackage, TypedType, TypedValueType};
+
+/// Local bindings of a function body and which of them are moved out.
+///
+/// Each declaration makes a new binding, so a binding shadowed by an inner block or a
+/// later declaration is tracked apart from the one shadowing it.
+#[derive(Debug, Clone, Default)]
+struct Moves {
+    /// Names of the bindings, indexed by their ids.
+    bindings: Vec<String>,
+    /// Ids of the bindings declared in each of the enclosing blocks, innermost last.
+    scopes: Vec<HashMap<String, usize>>,
+    /// Bindings moved out on some path reaching the current point.
+    moved: HashSet<usize>,
+}
+
+impl Moves {
+    fn declare(&mut self, name: &str) {
+        let id = self.bindings.len();
+        self.bindings.push(name.to_string());
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), id);
+        }
+    }
+
+    fn binding(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+    }
+
+    /// Drops the bindings of the innermost block, which are out of scope when leaving it.
+    fn pop_scope(&mut self, first: usize) {
+        self.scopes.pop();
+        self.bindings.truncate(first);
+        self.moved.retain(|id| *id < first);
+    }
+}
+
+/// Use after move of values which are not copyable.
+///
+/// A struct value is moved when a local binding of it is used as a value to be stored:
+/// the initializer of a variable, the right hand side of an assignment, an argument not
+/// taken by reference, an element of an array or the value of `return`. Any later use of the binding is an
+/// error until it is assigned again. A struct is copyable when it conforms to `Clone`
+/// and has no `deinit`; primitives, pointers, references and classes are always copyable.
+/// A field which is not copyable can not be moved out of its struct, and moves in the
+/// branches of an `if` are merged, so a binding moved on either path counts as moved.
+impl<'s> TypeChecker<'s> {
+    pub(super) fn moves(&mut self, arg_defs: &[TypedArgDef], body: &TypedFunBody) {
+        let mut moves = Moves {
+            scopes: vec![HashMap::new()],
+            ..Moves::default()
+        };
+        for a in arg_defs.iter() {
+            moves.declare(&a.name);
+        }
+        match body {
+            TypedFunBody::Expr(e) => {
+                self.moved_value(e, &mut moves);
+            }
+            TypedFunBody::Block(b) => {
+                self.moves_block(b, &mut moves);
+            }
+        }
+    }
+
+    /// Whether values of `t` are moved rather than copied.
+    pub(super) fn is_move_only(&self, t: &TypedType) -> bool {
+        self.struct_of(t).is_some_and(|rs| {
+            !rs.conformed_protocols
+                .contains(&Derive::Clone.protocol().to_string())
+                || rs.member_functions.contains_key(TypedStruct::DEINIT)
+        })
+    }
+
+    fn has_deinit(&self, t: &TypedType) -> bool {
+        self.struct_of(t)
+            .is_some_and(|rs| rs.member_functions.contains_key(TypedStruct::DEINIT))
+    }
+
+    fn struct_of(&self, t: &TypedType) -> Option<&ArenaStruct> {
+        match t {
+            t if t.is_primitive() => None,
+            TypedType::Value(TypedValueType::Value(t)) => match &t.package {
+                TypedPackage::Resolved(package) => self
+                    .arena
+                    .get_type(&package.names, &t.name)
+                    .filter(|rs| rs.kind.is_struct()),
+                TypedPackage::Raw(_) => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns whether `typed_block` diverges.
+    fn moves_block(&mut self, typed_block: &TypedBlock, moves: &mut Moves) -> bool {
+        let first = moves.bindings.len();
+        moves.scopes.push(HashMap::new());
+        let mut diverges = false;
+        for stmt in typed_block.body.iter() {
+            diverges |= self.moves_statement(stmt, moves);
+        }
+        moves.pop_scope(first);
+        diverges
+    }
+
+    fn moves_statement(&mut self, typed_statement: &TypedStmt, moves: &mut Moves) -> bool {
+        match typed_statement {
+            TypedStmt::Expr(e) => self.moves_expression(e, moves),
+            TypedStmt::Decl(d) => match &d.kind {
+                TypedDeclKind::Var(v) | TypedDeclKind::Const(v) => {
+                    let diverges = self.moved_value(&v.value, moves);
+                    moves.declare(&v.name);
+                    diverges
+                }
+                _ => false,
+            },
+            TypedStmt::Assignment(TypedAssignmentStmt::Assignment(a)) => {
+                let diverges = self.moved_value(&a.value, moves);
+                match &a.target.kind {
+                    TypedExprKind::Name(n) => match moves.binding(&n.name) {
+                        Some(id) => {
+                            moves.moved.remove(&id);
+                            diverges
+                        }
+                        None => diverges,
+                    },
+                    _ => self.moves_expression(&a.target, moves) || diverges,
+                }
+            }
+            TypedStmt::Assignment(TypedAssignmentStmt::AssignmentAndOperation(a)) => {
+                let diverges = self.moves_expression(&a.value, moves);
+                self.moves_expression(&a.target, moves) || diverges
+            }
+            TypedStmt::Loop(TypedLoopStmt::While(w)) => {
+                let diverges = self.moves_expression(&w.condition, moves);
+                self.moves_loop(&w.block, moves);
+                diverges
+            }
+            TypedStmt::Loop(TypedLoopStmt::For(f)) => {
+                let diverges = self.moves_expression(&f.iterator, moves);
+                self.moves_loop(&f.block, moves);
+                diverges
+            }
+            TypedStmt::Defer(b) => {
+                self.moves_block(b, &mut moves.clone());
+                false
+            }
+        }
+    }
+
+    /// A binding declared outside of a loop and moved in its body would be used after
+    /// being moved by the next iteration, because loops are only left by `return`.
+    fn moves_loop(&mut self, body: &TypedBlock, moves: &mut Moves) {
+        let mut in_loop = moves.clone();
+        if self.moves_block(body, &mut in_loop) {
+            return;
+        }
+        let mut moved_in_loop: Vec<_> = in_loop.moved.difference(&moves.moved).cloned().collect();
+        moved_in_loop.sort();
+        for id in moved_in_loop {
+            self.session.emit_error(CheckerError::new(format!(
+                "MoveError: `{}` is moved in a loop, so it is used after being moved by the next iteration",
+                moves.bindings[id]
+            )));
+            moves.moved.insert(id);
+        }
+    }
+
+    /// Whether a parameter of type `t` borrows its argument instead of moving it.
+    fn is_borrowed(t: &TypedType) -> bool {
+        matches!(t, TypedType::Value(TypedValueType::Reference(_)))
+    }
+
+    /// `typed_expr` used as a value to be stored, which moves the binding it names.
+    /// A field which is not copyable can not be moved out, because the struct holding
+    /// it still owns it.
+    fn moved_value(&mut self, typed_expr: &TypedExpr, moves: &mut Moves) -> bool {
+        let diverges = self.moves_expression(typed_expr, moves);
+        let t = match typed_expr.ty.as_ref().filter(|t| self.is_move_only(t)) {
+            Some(t) => t,
+            None => return diverges,
+        };
+        match &typed_expr.kind {
+            TypedExprKind::Name(n) => {
+                if let Some(id) = moves.binding(&n.name) {
+                    moves.moved.insert(id);
+                }
+            }
+            TypedExprKind::Member(m) => {
+                let reason = if self.has_deinit(t) {
+                    "has `deinit`"
+                } else {
+                    "is not copyable"
+                };
+                self.session.emit_error(CheckerError::new(format!(
+                    "MoveError: cannot move `{}` out of `{}`, because `{}` {}",
+                    Self::place_name(typed_expr),
+                    Self::place_name(&m.target),
+                    t.to_string(),
+                    reason
+                )))
+            }
+            _ => {}
+        }
+        diverges
+    }
+
+    fn moves_expression(&mut self, typed_expr: &TypedExpr, moves: &mut Moves) -> bool {
+        match &typed_expr.kind {
+            TypedExprKind::Name(n) => {
+                // reported once for each move
+                if moves
+                    .binding(&n.name)
+                    .is_some_and(|id| moves.moved.remove(&id))
+                {
+                    self.session.emit_error(CheckerError::new(format!(
+                        "MoveError: `{}` of type `{}` is used after being moved",
+                        n.name,
+                        typed_expr
+                            .ty
+                            .as_ref()
+                            .map(|t| t.to_string())
+                            .unwrap_or_default()
+                    )))
+                }
+                false
+            }
+            TypedExprKind::Return(r) => {
+                if let Some(value) = &r.value {
+                    self.moved_value(value, moves);
+                }
+                true
+            }
+            TypedExprKind::Call(c) => {
+                let (target, params) = match (&c.target.kind, &c.target.ty) {
+                    // receivers taken by value are moved as the first argument
+                    (TypedExprKind::Member(m), Some(TypedType::Function(f))) => {
+                        let target = match f.arguments.first() {
+                            Some(a) if !Self::is_borrowed(&a.typ) => {
+                                self.moved_value(&m.target, moves)
+                            }
+                            _ => self.moves_expression(&c.target, moves),
+                        };
+                        (target, f.arguments.get(1..).unwrap_or_default())
+                    }
+                    (_, Some(TypedType::Function(f))) => {
+                        (self.moves_expression(&c.target, moves), &f.arguments[..])
+                    }
+                    _ => (self.moves_expression(&c.target, moves), &[][..]),
+                };
+                let args = c.args.iter().enumerate().fold(false, |d, (i, a)| {
+                    let param = match &a.label {
+                        Some(label) => params.iter().find(|p| &p.label == label),
+                        None => params.get(i),
+                    };
+                    // arguments taken by reference are borrowed
+                    let diverges = if param.is_some_and(|p| Self::is_borrowed(&p.typ)) {
+                        self.moves_expression(&a.arg, moves)
+                    } else {
+                        self.moved_value(&a.arg, moves)
+                    };
+                    diverges | d
+                });
+                target || args || typed_expr.ty == Some(TypedType::noting())
+            }
+            TypedExprKind::If(i) => {
+                let condition = self.moves_expression(&i.condition, moves);
+                let mut body = moves.clone();
+                let body_diverges = self.moves_block(&i.body, &mut body);
+                let mut else_body = moves.clone();
+                let else_diverges = match &i.else_body {
+                    Some(b) => self.moves_block(b, &mut else_body),
+                    None => false,
+                };
+                match (body_diverges, else_diverges) {
+                    (true, true) => {}
+                    (true, false) => moves.moved = else_body.moved,
+                    (false, true) => moves.moved = body.moved,
+                    (false, false) => {
+                        moves.moved = body.moved.union(&else_body.moved).cloned().collect()
+                    }
+                }
+                condition || (body_diverges && else_diverges)
+            }
+            TypedExprKind::BinOp(b) => {
+                self.moves_expression(&b.left, moves) | self.moves_expression(&b.right, moves)
+            }
+            TypedExprKind::UnaryOp(TypedUnaryOp::Prefix(p)) => {
+                self.moves_expression(&p.target, moves)
+            }
+            TypedExprKind::UnaryOp(TypedUnaryOp::Postfix(p)) => {
+                self.moves_expression(&p.target, moves)
+            }
+            TypedExprKind::Subscript(s) => s
+                .indexes
+                .iter()
+                .fold(self.moves_expression(&s.target, moves), |d, i| {
+                    self.moves_expression(i, moves) | d
+                }),
+            TypedExprKind::Member(m) => self.moves_expression(&m.target, moves),
+            TypedExprKind::Array(a) => a
+                .elements
+                .iter()
+                .fold(false, |d, e| self.moved_value(e, moves) | d),
+            TypedExprKind::TypeCast(c) => self.moves_expression(&c.target, moves),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use crate::high_level_ir::type_checker::error::CheckerError;
+use crate::high_level_ir::type_checker::TypeChecker;
+use std::collections::HashMap;
+use wiz_hir::typed_decl::{TypedArgDef, TypedDeclKind, TypedExternVar, TypedStruct, TypedVar};
+use wiz_hir::typed_expr::{TypedExpr, TypedExprKind, TypedPrefixUnaryOperator, TypedUnaryOp};
+use wiz_hir::typed_file::TypedSpellBook;
+use wiz_hir::typed_type::{TypedType, TypedValueType};
+
+/// A name visible to the mutability pass and how it was declared.
+#[derive(Debug, Clone)]
+pub(super) struct Binding {
+    is_mut: bool,
+    name: String,
+    declaration: String,
+    /// Immutable binding a reference held by this binding was taken from.
+    borrowed_from: Option<Box<Binding>>,
+    /// `self` of an initializer, whose fields are assigned regardless of `val`.
+    is_initializing: bool,
+}
+
+impl Binding {
+    fn variable(v: &TypedVar) -> Self {
+        Self {
+            is_mut: v.is_mut,
+            name: v.name.clone(),
+            declaration: format!(
+                "`{} {}: {}`",
+                if v.is_mut { "var" } else { "val" },
+                v.name,
+                v.type_.as_ref().map(|t| t.to_string()).unwrap_or_default()
+            ),
+            borrowed_from: None,
+            is_initializing: false,
+        }
+    }
+
+    fn extern_variable(v: &TypedExternVar) -> Self {
+        Self {
+            is_mut: v.is_mut,
+            name: v.name.clone(),
+            declaration: format!(
+                "`extern {} {}: {}`",
+                if v.is_mut { "var" } else { "val" },
+                v.name,
+                v.type_.to_string()
+            ),
+            borrowed_from: None,
+            is_initializing: false,
+        }
+    }
+
+    fn constant(c: &TypedVar) -> Self {
+        Self {
+            is_mut: false,
+            name: c.name.clone(),
+            declaration: format!(
+                "`const {}: {}`",
+                c.name,
+                c.type_.as_ref().map(|t| t.to_string()).unwrap_or_default()
+            ),
+            borrowed_from: None,
+            is_initializing: false,
+        }
+    }
+
+    fn argument(a: &TypedArgDef) -> Self {
+        Self {
+            is_mut: false,
+            name: a.name.clone(),
+            declaration: if a.name == "self" {
+                format!("receiver `self: {}`", a.type_.to_string())
+            } else {
+                format!("argument `{}: {}`", a.name, a.type_.to_string())
+            },
+            borrowed_from: None,
+            is_initializing: false,
+        }
+    }
+
+    pub(super) fn initializing_self(s: &TypedStruct) -> Self {
+        Self {
+            is_mut: true,
+            name: "self".to_string(),
+            declaration: format!("`self` of initializer of `{}`", s.name),
+            borrowed_from: None,
+            is_initializing: true,
+        }
+    }
+
+    fn reason(&self) -> String {
+        let reason = format!("`{}` is declared as {}", self.name, self.declaration);
+        if self.name == "self" {
+            format!("{}; take `&self` to mutate it", reason)
+        } else {
+            reason
+        }
+    }
+}
+
+/// Mutability checking of assignments.
+///
+/// Writes are allowed through `var` bindings, references that were not taken
+/// from a `val` binding, raw pointers and instances of classes. Fields must be
+/// declared with `var`.
+impl<'s> TypeChecker<'s> {
+    pub(super) fn push_bindings(&mut self, bindings: HashMap<String, Binding>) {
+        self.bindings.push(bindings)
+    }
+
+    pub(super) fn pop_bindings(&mut self) {
+        self.bindings.pop();
+    }
+
+    pub(super) fn global_bindings(typed_file: &TypedSpellBook) -> HashMap<String, Binding> {
+        typed_file
+            .body
+            .iter()
+            .filter_map(|d| match &d.kind {
+                TypedDeclKind::Var(v) => Some((v.name.clone(), Binding::variable(v))),
+                TypedDeclKind::Const(c) => Some((c.name.clone(), Binding::constant(c))),
+                TypedDeclKind::ExternVar(v) => Some((v.name.clone(), Binding::extern_variable(v))),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub(super) fn argument_bindings(arg_defs: &[TypedArgDef]) -> HashMap<String, Binding> {
+        arg_defs
+            .iter()
+            .map(|a| (a.name.clone(), Binding::argument(a)))
+            .collect()
+    }
+
+    pub(super) fn bind_variable(&mut self, typed_variable: &TypedVar) {
+        let mut binding = Binding::variable(typed_variable);
+        binding.borrowed_from = self.borrowed_from(&typed_variable.value);
+        self.bindings.insert(typed_variable.name.clone(), binding);
+    }
+
+    pub(super) fn bind_constant(&mut self, typed_constant: &TypedVar) {
+        self.bindings.insert(
+            typed_constant.name.clone(),
+            Binding::constant(typed_constant),
+        );
+    }
+
+    pub(super) fn bind_loop_variable(&mut self, name: &str) {
+        self.bindings.insert(
+            name.to_string(),
+            Binding {
+                is_mut: false,
+                name: name.to_string(),
+                declaration: format!("loop variable `{}`", name),
+                borrowed_from: None,
+                is_initializing: false,
+            },
+        );
+    }
+
+    /// Immutable binding the reference produced by `value` points into, if any.
+    fn borrowed_from(&self, value: &TypedExpr) -> Option<Box<Binding>> {
+        match &value.kind {
+            TypedExprKind::UnaryOp(TypedUnaryOp::Prefix(p))
+                if p.operator == TypedPrefixUnaryOperator::Reference =>
+            {
+                match &p.target.kind {
+                    TypedExprKind::Name(n) => self
+                        .bindings
+                        .get(&n.name)
+                        .filter(|b| !b.is_mut && !Self::is_reference(&p.target.ty))
+                        .map(|b| Box::new(b.clone())),
+                    _ => None,
+                }
+            }
+            TypedExprKind::Name(n) => self
+                .bindings
+                .get(&n.name)
+                .and_then(|b| b.borrowed_from.clone()),
+            _ => None,
+        }
+    }
+
+    pub(super) fn assignable(&mut self, target: &TypedExpr) {
+        if let Err(reason) = self.place(target) {
+            self.session.emit_error(CheckerError::new(format!(
+                "MutabilityError: cannot assign to `{}`, because {}",
+                Self::place_name(target),
+                reason
+            )))
+        }
+    }
+
+    fn place(&self, target: &TypedExpr) -> Result<(), String> {
+        match &target.kind {
+            TypedExprKind::Name(n) => match self.bindings.get(&n.name) {
+                Some(b) if !b.is_mut => Err(b.reason()),
+                _ => Ok(()),
+            },
+            TypedExprKind::Member(m) => {
+                if let TypedExprKind::Name(n) = &m.target.kind {
+                    if self
+                        .bindings
+                        .get(&n.name)
+                        .is_some_and(|b| b.is_initializing)
+                    {
+                        return Ok(());
+                    }
+                }
+                let mut owner = m.target.ty.as_ref();
+                while let Some(TypedType::Value(TypedValueType::Reference(t))) = owner {
+                    owner = Some(t.as_ref());
+                }
+                if let Some(TypedType::Value(TypedValueType::Value(t))) = owner {
+                    if let Some(rs) = self
+                        .arena
+                        .get_type(&t.package.clone().into_resolved().names, &t.name)
+                    {
+                        if let Some(field_type) = rs.stored_properties.get(&m.name) {
+                            if !rs.mutable_properties.contains(&m.name) {
+                                return Err(format!(
+                                    "field `{}` of `{}` is declared as `val {}: {}`",
+                                    m.name,
+                                    t.name,
+                                    m.name,
+                                    field_type.to_string()
+                                ));
+                            }
+                        }
+                        // instances of classes are shared, so fields are written through any binding
+                        if rs.kind.is_class() {
+                            return Ok(());
+                        }
+                    }
+                }
+                self.container(&m.target)
+            }
+            TypedExprKind::Subscript(s) => self.container(&s.target),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the expression a field or an element is written through.
+    fn container(&self, target: &TypedExpr) -> Result<(), String> {
+        match &target.ty {
+            Some(TypedType::Value(TypedValueType::Pointer(_))) => Ok(()),
+            ty if Self::is_reference(ty) => match &target.kind {
+                TypedExprKind::Name(n) => match self
+                    .bindings
+                    .get(&n.name)
+                    .and_then(|b| b.borrowed_from.as_ref())
+                {
+                    Some(origin) => Err(format!(
+                        "`{}` refers to `{}`, which is declared as {}",
+                        n.name, origin.name, origin.declaration
+                    )),
+                    None => Ok(()),
+                },
+                _ => Ok(()),
+            },
+            _ => match &target.kind {
+                TypedExprKind::UnaryOp(TypedUnaryOp::Prefix(p))
+                    if p.operator == TypedPrefixUnaryOperator::Dereference =>
+                {
+                    Ok(())
+                }
+                _ => self.place(target),
+            },
+        }
+    }
+
+    fn is_reference(ty: &Option<TypedType>) -> bool {
+        matches!(ty, Some(TypedType::Value(TypedValueType::Reference(_))))
+    }
+
+    pub(super) fn place_name(target: &TypedExpr) -> String {
+        match &target.kind {
+            TypedExprKind::Name(n) => n.name.clone(),
+            TypedExprKind::Member(m) => format!("{}.{}", Self::place_name(&m.target), m.name),
+            TypedExprKind::Subscript(s) => format!("{}[..]", Self::place_name(&s.target)),
+            TypedExprKind::UnaryOp(TypedUnaryOp::Prefix(p)) => {
+                format!("*{}", Self::place_name(&p.target))
+            }
+            _ => "expression".to_string(),
+        }
+    }
+}
@@ -0,0 +1,582 @@
+use crate::high_level_ir::type_checker::TypeChecker;
+use crate::high_level_ir::AstLowering;
+use crate::ModuleId;
+use wiz_arena::Arena;
+use wiz_session::{ParseSession, Session};
+use wiz_syntax_parser::parser::wiz::parse_from_string;
+
+fn check(source: &str) -> Session {
+    let parse_session = ParseSession::default();
+    let ast = parse_from_string::<&str>(&parse_session, None, source, Some("test")).unwrap();
+
+    let mut session = Session::default();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    let f = ast2hlir.lowing(ast, ModuleId::DUMMY).unwrap();
+
+    let mut type_checker = TypeChecker::new(&mut session, &arena);
+    type_checker.verify(&f);
+    session
+}
+
+fn messages(session: &Session) -> (Vec<String>, Vec<String>) {
+    (
+        session.errors().iter().map(|e| e.to_string()).collect(),
+        session.warnings().iter().map(|w| w.to_string()).collect(),
+    )
+}
+
+#[test]
+fn test_missing_return() {
+    let source = r"
+        fun f(_ a: Bool): Int64 {
+            if a {
+                return 1
+            }
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert!(errors.contains(
+        &"MissingReturn: `f` must return `Int64`, but may reach the end of its body without returning"
+            .to_string()
+    ));
+}
+
+#[test]
+fn test_return_in_all_branches() {
+    let source = r"
+        fun f(_ a: Bool): Int64 {
+            if a {
+                return 1
+            } else {
+                return 2
+            }
+        }
+        ";
+
+    let (errors, warnings) = messages(&check(source));
+
+    assert!(!errors.iter().any(|e| e.starts_with("MissingReturn")));
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_unreachable_statement() {
+    let source = r"
+        fun f(): Int64 {
+            return 1
+            val a = 2
+        }
+        ";
+
+    let (errors, warnings) = messages(&check(source));
+
+    assert!(!errors.iter().any(|e| e.starts_with("MissingReturn")));
+    assert_eq!(
+        warnings,
+        vec!["UnreachableCode: statement in `f` will never be executed".to_string()]
+    );
+}
+
+#[test]
+fn test_noting_call_diverges() {
+    let source = r"
+        fun fail(): Noting {
+            fail()
+        }
+        fun f(_ a: Bool): Int64 {
+            if a {
+                return 1
+            }
+            fail()
+        }
+        fun g(): Noting {
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert!(!errors.iter().any(|e| e.contains("`f`")));
+    assert!(!errors.iter().any(|e| e.contains("`fail`")));
+    assert!(errors.contains(
+        &"MissingReturn: `g` returns `Noting`, but may reach the end of its body".to_string()
+    ));
+}
+
+#[test]
+fn test_assign_to_val() {
+    let source = r"
+        fun f(): Unit {
+            val a = 1
+            a = 2
+            var b = 1
+            b += 2
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(
+        errors,
+        vec![
+            "MutabilityError: cannot assign to `a`, because `a` is declared as `val a: Int64`"
+                .to_string()
+        ]
+    );
+}
+
+#[test]
+fn test_assign_to_global() {
+    let source = r"
+        extern {
+            val stdin: *UInt8
+            var errno: Int32
+        }
+
+        #[thread_local]
+        var count: Int64 = 0
+
+        fun f(): Unit {
+            count += 1
+            errno = errno
+            stdin = stdin
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(
+        errors,
+        vec![
+            "MutabilityError: cannot assign to `stdin`, because `stdin` is declared as `extern val stdin: *UInt8`"
+                .to_string()
+        ]
+    );
+}
+
+#[test]
+fn test_assign_to_val_field() {
+    let source = r"
+        struct A {
+            val a: Int64
+            var b: Int64
+        }
+        fun f(): Unit {
+            var x = A(a: 1, b: 2)
+            x.a = 3
+            x.b = 4
+            val y = A(a: 1, b: 2)
+            y.b = 5
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(
+        errors,
+        vec![
+            "MutabilityError: cannot assign to `x.a`, because field `a` of `A` is declared as `val a: Int64`".to_string(),
+            "MutabilityError: cannot assign to `y.b`, because `y` is declared as `val y: test::A`".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_assign_through_self() {
+    let source = r"
+        struct A {
+            var a: Int64
+
+            fun set(&self, _ a: Int64): Unit {
+                self.a = a
+            }
+
+            fun update(self, _ a: Int64): Unit {
+                self.a = a
+            }
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(
+        errors,
+        vec!["MutabilityError: cannot assign to `self.a`, because `self` is declared as receiver `self: test::A`; take `&self` to mutate it".to_string()]
+    );
+}
+
+#[test]
+fn test_assign_through_immutable_reference() {
+    let source = r"
+        struct A {
+            var a: Int64
+        }
+        fun f(): Unit {
+            val x = A(a: 1)
+            val r = &x
+            r.a = 2
+            var y = A(a: 1)
+            val s = &y
+            s.a = 2
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(
+        errors,
+        vec!["MutabilityError: cannot assign to `r.a`, because `r` refers to `x`, which is declared as `val x: test::A`".to_string()]
+    );
+}
+
+#[test]
+fn test_initializer() {
+    let source = r"
+        struct A {
+            val a: Int64
+            var b: Int64
+            init(a: Int64) {
+                self.a = a
+                if a > 0 {
+                    self.b = a
+                } else {
+                    self.b = self.a
+                }
+            }
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_initializer_not_initialized() {
+    let source = r"
+        struct A {
+            val a: Int64
+            val b: Int64
+            init(a: Int64) {
+                self.b = self.a
+                if a > 0 {
+                    self.a = a
+                }
+            }
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(
+        errors,
+        vec![
+            "InitializationError: `self.a` is used before being initialized".to_string(),
+            "InitializationError: `self.a` is not initialized at the end of initializer of `A`"
+                .to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_subscript_setter_on_immutable() {
+    let source = r"
+        struct A {
+            var a: Int64
+            subscript(index: Int64): Int64 {
+                get {
+                    return self.a
+                }
+                set {
+                    self.a = newValue
+                }
+            }
+        }
+        fun f(_ a: A) {
+            a[0] = 1
+            var b = a
+            b[0] = 1
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(
+        errors,
+        vec![
+            "MutabilityError: cannot assign to `a[..]`, because `a` is declared as argument `a: test::A`"
+                .to_string()
+        ]
+    );
+}
+
+#[test]
+fn test_return_in_defer() {
+    let source = r"
+        fun f(): Int64 {
+            defer {
+                return 1
+            }
+            return 2
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(
+        errors,
+        vec!["DeferError: `return` is not allowed in defer".to_string()]
+    );
+}
+
+#[test]
+fn test_class_field_through_immutable() {
+    let source = r"
+        class A {
+            var a: Int64
+            val b: Int64
+        }
+        fun f(_ a: A) {
+            val b = a
+            b.a = 1
+            a.a = 1
+            a.b = 1
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(
+        errors,
+        vec![
+            "MutabilityError: cannot assign to `a.b`, because field `b` of `A` is declared as `val b: Int64`"
+                .to_string()
+        ]
+    );
+}
+
+#[test]
+fn test_array_elements() {
+    let source = r"
+        fun f(): Int64 {
+            val a = [128, 256]
+            return a[0]
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(errors, Vec::<String>::new());
+}
+
+#[test]
+fn test_use_after_move() {
+    let source = r"
+        struct A {
+            val a: Int64
+        }
+        fun take(_ a: A) {
+        }
+        fun f(_ a: A, _ b: A, _ c: Bool): Int64 {
+            val x = a
+            if c {
+                take(b)
+            }
+            var y = x
+            take(y)
+            y = A(a: 1)
+            take(y)
+            return a.a + b.a
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(
+        errors,
+        vec![
+            "MoveError: `a` of type `test::A` is used after being moved".to_string(),
+            "MoveError: `b` of type `test::A` is used after being moved".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_argument_taken_by_reference_is_not_moved() {
+    let source = r"
+        struct A {
+            val a: Int64
+            fun same(&self, _ other: &A): Bool {
+                return self.a == other.a
+            }
+        }
+        fun f(_ a: A, _ b: A): Bool {
+            return a.same(b) && b.same(a)
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(errors, Vec::<String>::new());
+}
+
+#[test]
+fn test_move_in_loop() {
+    let source = r"
+        struct A {
+            val a: Int64
+        }
+        fun take(_ a: A) {
+        }
+        fun f(_ a: A, _ c: Bool) {
+            while c {
+                take(a)
+            }
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(
+        errors,
+        vec!["MoveError: `a` is moved in a loop, so it is used after being moved by the next iteration".to_string()]
+    );
+}
+
+#[test]
+fn test_copy_primitive_and_reference() {
+    let source = r"
+        struct A {
+            val a: Int64
+        }
+        fun f(_ a: &A, _ n: Int64): Int64 {
+            val b = a
+            val m = n
+            return a.a + b.a + n + m
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_move_field_with_deinit() {
+    let source = r"
+        struct A {
+            val a: Int64
+            deinit {
+            }
+        }
+        struct B {
+            val a: A
+        }
+        fun take(_ a: A) {
+        }
+        fun f(_ b: &B): Int64 {
+            take(b.a)
+            return b.a.a
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(
+        errors,
+        vec!["MoveError: cannot move `b.a` out of `b`, because `test::A` has `deinit`".to_string()]
+    );
+}
+
+#[test]
+fn test_move_shadowed_binding() {
+    let source = r"
+        struct A {
+            val a: Int64
+        }
+        fun take(_ a: A) {
+        }
+        fun f(_ a: A, _ c: Bool): Int64 {
+            if c {
+                val a = A(a: 1)
+                take(a)
+            }
+            val b = A(a: 2)
+            while c {
+                val b = A(a: 3)
+                take(b)
+            }
+            return a.a + b.a
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_move_reassigned_in_branches() {
+    let source = r"
+        struct A {
+            val a: Int64
+        }
+        fun take(_ a: A) {
+        }
+        fun f(_ c: Bool): Int64 {
+            var x = A(a: 1)
+            var y = A(a: 1)
+            take(x)
+            take(y)
+            if c {
+                x = A(a: 2)
+                y = A(a: 2)
+            } else {
+                y = A(a: 3)
+            }
+            return x.a + y.a
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(
+        errors,
+        vec!["MoveError: `x` of type `test::A` is used after being moved".to_string()]
+    );
+}
+
+#[test]
+fn test_move_field_not_copyable() {
+    let source = r"
+        struct A {
+            val a: Int64
+        }
+        struct B {
+            val a: A
+            val n: Int64
+        }
+        fun take(_ a: A) {
+        }
+        fun f(_ b: B): Int64 {
+            take(b.a)
+            val n = b.n
+            return n
+        }
+        ";
+
+    let (errors, _) = messages(&check(source));
+
+    assert_eq!(
+        errors,
+        vec![
+            "MoveError: cannot move `b.a` out of `b`, because `test::A` is not copyable"
+                .to_string()
+        ]
+    );
+}
@@ -4,27 +4,39 @@ mod name_environment;
 pub mod result;
 #[cfg(test)]
 mod tests;
+mod type_parameter;
 
+use crate::high_level_ir::const_evaluator::ConstEvaluator;
+use crate::high_level_ir::derive::Derive;
 use crate::high_level_ir::type_resolver::context::ResolverContext;
 use crate::high_level_ir::type_resolver::error::ResolverError;
 use crate::high_level_ir::type_resolver::result::Result;
+use crate::high_level_ir::type_resolver::type_parameter::TypeParameterSubstitution;
+use crate::intrinsic::{verify_atomic_value_type, Intrinsic, MemoryOrdering};
 use std::fmt::Write;
-use wiz_arena::{Arena, DeclarationId, DeclarationItemKind};
+use wiz_arena::{Arena, ArenaSubscript, DeclarationId, DeclarationItemKind};
+use wiz_constants::annotation::{BUILTIN, DERIVE};
+use wiz_data_structure::annotation::Annotations;
 use wiz_hir::typed_decl::{
-    TypedArgDef, TypedDeclKind, TypedExtension, TypedFun, TypedFunBody, TypedProtocol,
-    TypedStoredProperty, TypedStruct, TypedTopLevelDecl, TypedVar,
+    TypedArgDef, TypedAssociatedType, TypedDeclKind, TypedExtension, TypedExternVar, TypedFun,
+    TypedFunBody, TypedInitializer, TypedProtocol, TypedStoredProperty, TypedStruct,
+    TypedSubscriptDecl, TypedTopLevelDecl, TypedTypeAlias, TypedVar,
 };
 use wiz_hir::typed_expr::{
     TypedArray, TypedBinOp, TypedCall, TypedCallArg, TypedExpr, TypedExprKind, TypedIf,
-    TypedInstanceMember, TypedLiteralKind, TypedName, TypedPostfixUnaryOp, TypedPrefixUnaryOp,
-    TypedPrefixUnaryOperator, TypedReturn, TypedSubscript, TypedTypeCast, TypedUnaryOp,
+    TypedInstanceMember, TypedLiteralKind, TypedLocation, TypedName, TypedPostfixUnaryOp,
+    TypedPrefixUnaryOp, TypedPrefixUnaryOperator, TypedReturn, TypedSourceLocationKind,
+    TypedSubscript, TypedTypeCast, TypedUnaryOp,
 };
 use wiz_hir::typed_file::TypedSpellBook;
 use wiz_hir::typed_stmt::{
     TypedAssignment, TypedAssignmentAndOperation, TypedAssignmentStmt, TypedBlock, TypedForStmt,
     TypedLoopStmt, TypedStmt, TypedWhileLoopStmt,
 };
-use wiz_hir::typed_type::{Package, TypedArgType, TypedFunctionType, TypedType, TypedValueType};
+use wiz_hir::typed_type::{
+    Package, TypedArgType, TypedFunctionType, TypedPackage, TypedType, TypedTypeParam,
+    TypedValueType,
+};
 use wiz_hir::typed_type_constraint::TypedTypeConstraint;
 use wiz_session::Session;
 
@@ -45,18 +57,152 @@ impl<'s> TypeResolver<'s> {
     pub fn preload_file(&mut self, f: &TypedSpellBook) -> Result<()> {
         self.context.push_name_space(&f.name);
         for u in f.uses.iter() {
-            self.context.use_name_space(u.package.names.clone());
+            self.context.use_name_space(u.clone());
         }
+        // NOTE: type aliases may be used by any declarations in this file
+        self.preload_type_aliases(f)?;
         for d in f.body.iter() {
             self.preload_decl(d)?;
         }
         for u in f.uses.iter() {
-            self.context.unuse_name_space(&u.package.names);
+            self.context.unuse_name_space(u);
         }
         self.context.pop_name_space();
         Ok(())
     }
 
+    /// Resolve default values of arguments after [`Self::preload_file`],
+    /// because they may refer any names declared in the spell book.
+    pub fn preload_arg_defaults_file(&mut self, f: &TypedSpellBook) -> Result<()> {
+        self.context.push_name_space(&f.name);
+        for u in f.uses.iter() {
+            self.context.use_name_space(u.clone());
+        }
+        let result = f.body.iter().try_for_each(|d| self.preload_arg_defaults(d));
+        for u in f.uses.iter() {
+            self.context.unuse_name_space(u);
+        }
+        self.context.pop_name_space();
+        result
+    }
+
+    fn preload_arg_defaults(&mut self, d: &TypedTopLevelDecl) -> Result<()> {
+        match &d.kind {
+            TypedDeclKind::Fun(f) => {
+                let namespace = self.context.current_namespace();
+                self.context.push_name_space(&f.name);
+                let result = self.preload_fun_arg_defaults(f, &namespace);
+                self.context.pop_name_space();
+                result?;
+            }
+            TypedDeclKind::Struct(s) => {
+                self.context.push_name_space(&s.name);
+                let namespace = self.context.current_namespace();
+                for member_function in s.member_functions.iter() {
+                    self.preload_fun_arg_defaults(member_function, &namespace)?;
+                }
+                self.context.pop_name_space();
+            }
+            TypedDeclKind::Extension(e) => {
+                let tmp_ns_id = self.context.get_current_namespace_id();
+                let this_type = self.context.full_type_name(&e.name)?;
+                let env = self.context.get_current_name_environment();
+                let mut namespace = this_type.package().into_resolved().names;
+                let type_id = env.get_type_id(&namespace, &this_type.name()).unwrap();
+                namespace.push(this_type.name());
+                self.context.set_current_namespace_id_force(type_id);
+                let result = e
+                    .member_functions
+                    .iter()
+                    .try_for_each(|m| self.preload_fun_arg_defaults(m, &namespace));
+                self.context.set_current_namespace_id_force(tmp_ns_id);
+                result?;
+            }
+            TypedDeclKind::Module(m) => {
+                self.preload_arg_defaults_file(m)?;
+            }
+            TypedDeclKind::Var(_)
+            | TypedDeclKind::Const(_)
+            | TypedDeclKind::ExternVar(_)
+            | TypedDeclKind::Union(_)
+            | TypedDeclKind::Class(_)
+            | TypedDeclKind::Enum
+            | TypedDeclKind::Protocol(_)
+            | TypedDeclKind::TypeAlias(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Resolve type aliases declared in `f`, which may refer to each other in any order.
+    fn preload_type_aliases(&mut self, f: &TypedSpellBook) -> Result<()> {
+        let mut type_aliases = f
+            .body
+            .iter()
+            .filter_map(|d| match &d.kind {
+                TypedDeclKind::TypeAlias(t) => Some((t, &d.annotations)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        while !type_aliases.is_empty() {
+            let mut unresolved = vec![];
+            let mut error = None;
+            for (t, annotations) in type_aliases.iter().copied() {
+                if let Err(e) = self.preload_type_alias(t, annotations) {
+                    error.get_or_insert(e);
+                    unresolved.push((t, annotations));
+                }
+            }
+            if unresolved.len() == type_aliases.len() {
+                return Err(error.unwrap());
+            }
+            type_aliases = unresolved;
+        }
+        Ok(())
+    }
+
+    fn preload_type_alias(&mut self, t: &TypedTypeAlias, annotations: &Annotations) -> Result<()> {
+        let id = self
+            .context
+            .register_type_alias(
+                &t.name,
+                t.type_params.clone(),
+                t.type_.clone(),
+                annotations.clone(),
+            )
+            .ok_or_else(|| ResolverError::from(format!("`{}` is already declared", t.name)))?;
+        let t = self.typed_type_alias(t.clone())?;
+        self.context.arena_mut().resolve_type_alias(&id, t.type_);
+        Ok(())
+    }
+
+    /// Resolve default values of arguments of `f` and store them to the function in `namespace`.
+    fn preload_fun_arg_defaults(&mut self, f: &TypedFun, namespace: &[String]) -> Result<()> {
+        if f.arg_defs.iter().all(|a| a.default.is_none()) {
+            return Ok(());
+        }
+        let ty = self.context.full_type_name(&f.type_())?;
+        let arg_defaults = f
+            .arg_defs
+            .iter()
+            .map(|a| match &a.default {
+                Some(_) => Ok(self.typed_arg_def(a.clone())?.default),
+                None => Ok(None),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let rf = self
+            .context
+            .arena_mut()
+            .get_function_mut(namespace, &f.name, &ty)
+            .ok_or_else(|| {
+                ResolverError::from(format!(
+                    "Function `{}` not exist. Maybe before preload",
+                    f.name
+                ))
+            })?;
+        rf.arg_defaults = arg_defaults;
+        Ok(())
+    }
+
     fn preload_decl(&mut self, d: &TypedTopLevelDecl) -> Result<()> {
         match &d.kind {
             TypedDeclKind::Var(v) => {
@@ -68,21 +214,45 @@ impl<'s> TypeResolver<'s> {
                     d.annotations.clone(),
                 );
             }
+            TypedDeclKind::ExternVar(v) => {
+                let v = self.typed_extern_var(v.clone())?;
+                self.context
+                    .register_value(&v.name, v.type_, d.annotations.clone());
+            }
+            TypedDeclKind::Const(c) => {
+                let c = self.typed_const(c.clone())?;
+                self.context.register_constant(
+                    &c.name,
+                    c.type_
+                        .ok_or_else(|| ResolverError::from("Cannot resolve constant type"))?,
+                    c.value,
+                    d.annotations.clone(),
+                );
+            }
             TypedDeclKind::Fun(f) => {
-                let id = self
-                    .context
-                    .register_function(
-                        &f.name,
-                        TypedType::noting(),
-                        f.type_params.clone(),
-                        f.body.clone(),
-                        d.annotations.clone(),
-                    )
-                    .unwrap();
+                let arg_names = f.arg_names();
+                // NOTE: functions are registered by ast lowering, to be called in array lengths
+                let id = match self.context.lowered_function(&f.name, &arg_names, &f.body) {
+                    Some(id) => id,
+                    None => self
+                        .context
+                        .register_function(
+                            &f.name,
+                            TypedType::noting(),
+                            f.type_params.clone(),
+                            f.body.clone(),
+                            d.annotations.clone(),
+                        )
+                        .unwrap(),
+                };
+                self.context.update_arg_names(&id, arg_names).unwrap();
                 let fun = self.preload_fun(f)?;
+                if d.annotations.has_annotate(BUILTIN) {
+                    Self::verify_intrinsic(&fun, f.body.is_some())?;
+                }
                 self.context.update_function(&id, fun.type_()).unwrap();
             }
-            TypedDeclKind::Struct(s) => {
+            TypedDeclKind::Struct(s) | TypedDeclKind::Union(s) | TypedDeclKind::Class(s) => {
                 self.preload_struct(s)?;
             }
             TypedDeclKind::Module(m) => {
@@ -95,6 +265,7 @@ impl<'s> TypeResolver<'s> {
             TypedDeclKind::Extension(e) => {
                 self.preload_extension(e)?;
             }
+            TypedDeclKind::TypeAlias(_) => {}
         }
         Ok(())
     }
@@ -102,16 +273,16 @@ impl<'s> TypeResolver<'s> {
     fn preload_fun(&mut self, f: &TypedFun) -> Result<TypedFun> {
         self.context.push_name_space(&f.name);
         self.context.push_local_stack();
-        if let Some(type_params) = &f.type_params {
-            for type_param in type_params {
-                self.context
-                    .register_type_parameter(&type_param.name, Default::default());
-            }
-        }
+        self.preload_type_parameters(&f.name, &f.type_params, &f.type_constraints)?;
         let arg_defs = f
             .arg_defs
             .iter()
-            .map(|a| self.typed_arg_def(a.clone()))
+            .map(|a| {
+                Ok(TypedArgDef {
+                    type_: self.context.full_type_name(&a.type_)?,
+                    ..a.clone()
+                })
+            })
             .collect::<Result<Vec<_>>>()?;
         let return_type = self.context.full_type_name(&f.return_type)?;
         let fun = TypedFun {
@@ -127,15 +298,89 @@ impl<'s> TypeResolver<'s> {
         Ok(fun)
     }
 
+    /// Register type parameters of `owner` in the current namespace.
+    /// Protocols they are constrained to are registered as conformed ones.
+    fn preload_type_parameters(
+        &mut self,
+        owner: &str,
+        type_params: &Option<Vec<TypedTypeParam>>,
+        type_constraints: &Option<Vec<TypedTypeConstraint>>,
+    ) -> Result<()> {
+        if let Some(type_params) = type_params {
+            for type_param in type_params {
+                self.context
+                    .register_type_parameter(&type_param.name, Default::default());
+            }
+            for tc in type_constraints.iter().flatten() {
+                let vec_current_namespace = self.context.current_namespace();
+                let constraints = tc
+                    .constraints
+                    .iter()
+                    .map(|c| self.context.full_type_name(c))
+                    .collect::<Result<Vec<_>>>()?;
+                let ne = self.context.get_current_name_environment();
+                let associated_types = constraints
+                    .iter()
+                    .filter_map(|c| ne.get_type_by_typed_type(c.clone()))
+                    .flat_map(|crs| crs.associated_types.keys().cloned())
+                    .collect::<Vec<_>>();
+                let constraints = constraints.iter().map(TypedType::to_string);
+                let rs = self
+                    .context
+                    .arena_mut()
+                    .get_type_mut(&vec_current_namespace, &tc.type_.name())
+                    .ok_or_else(|| {
+                        ResolverError::from(format!(
+                            "`{}` is not a type parameter of `{}`",
+                            tc.type_.name(),
+                            owner
+                        ))
+                    })?;
+                rs.conformed_protocols.extend(constraints);
+                let mut fqn = vec_current_namespace;
+                fqn.push(tc.type_.name());
+                let arena = self.context.arena_mut();
+                let type_param_id = arena.resolve_declaration_id_from_root(&fqn).unwrap();
+                for associated_type in associated_types {
+                    arena.register_associated_type(&type_param_id, &associated_type);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Functions declared with `#[builtin]` must be intrinsics with matching signatures.
+    fn verify_intrinsic(f: &TypedFun, has_body: bool) -> Result<()> {
+        let intrinsic = Intrinsic::from_name(&f.name).ok_or_else(|| {
+            ResolverError::from(format!("`{}` is not a compiler intrinsic", f.name))
+        })?;
+        if has_body {
+            return Err(ResolverError::from(format!(
+                "intrinsic `{}` must not have a body",
+                f.name
+            )));
+        }
+        intrinsic.verify(f).map_err(ResolverError::from)
+    }
+
     fn preload_struct(&mut self, s: &TypedStruct) -> Result<()> {
         let TypedStruct {
             name,
-            type_params: _,
+            type_params,
+            type_constraints,
             stored_properties,
             computed_properties,
+            initializers,
+            subscripts,
             member_functions,
         } = s;
         self.context.push_name_space(name);
+        self.preload_type_parameters(name, type_params, type_constraints)?;
+        let rs = self.context.current_type_mut().ok_or_else(|| {
+            ResolverError::from(format!("Struct {:?} not exist. Maybe before preload", name))
+        })?;
+        rs.type_parameters = type_params.clone();
+        rs.stored_property_order = Some(stored_properties.iter().map(|p| p.name.clone()).collect());
         for stored_property in stored_properties.iter() {
             let type_ = self.context.full_type_name(&stored_property.type_)?;
             let rs = self.context.current_type_mut().ok_or_else(|| {
@@ -143,6 +388,9 @@ impl<'s> TypeResolver<'s> {
             })?;
             rs.stored_properties
                 .insert(stored_property.name.clone(), type_);
+            if stored_property.is_mut {
+                rs.mutable_properties.insert(stored_property.name.clone());
+            }
         }
         for computed_property in computed_properties.iter() {
             let type_ = self.context.full_type_name(&computed_property.type_)?;
@@ -155,27 +403,96 @@ impl<'s> TypeResolver<'s> {
 
         for member_function in member_functions.iter() {
             let type_ = self.context.full_type_name(&member_function.type_())?;
-            self.context.register_function(
+            if let Some(id) = self.context.register_function(
                 &member_function.name,
                 type_.clone(),
                 member_function.type_params.clone(),
                 member_function.body.clone(),
                 Default::default(),
-            );
+            ) {
+                self.context
+                    .update_arg_names(&id, member_function.arg_names());
+            }
             let rs = self.context.current_type_mut().ok_or_else(|| {
                 ResolverError::from(format!("Struct {:?} not exist. Maybe before preload", name))
             })?;
             rs.member_functions
                 .insert(member_function.name.clone(), type_);
         }
+        for initializer in initializers.iter() {
+            let type_ = self.context.full_type_name(&initializer.type_())?;
+            if let Some(id) = self.context.register_function(
+                TypedInitializer::NAME,
+                type_.clone(),
+                None,
+                Some(initializer.body.clone()),
+                Default::default(),
+            ) {
+                self.context.update_arg_names(&id, initializer.arg_names());
+            }
+            let rs = self.context.current_type_mut().ok_or_else(|| {
+                ResolverError::from(format!("Struct {:?} not exist. Maybe before preload", name))
+            })?;
+            if rs
+                .initializers
+                .iter()
+                .any(|i| Self::argument_labels(i) == Self::argument_labels(&type_))
+            {
+                return Err(ResolverError::from(format!(
+                    "`{}` has multiple initializers with the same argument labels",
+                    name
+                )));
+            }
+            rs.initializers.push(type_);
+        }
+        for subscript in subscripts.iter() {
+            let getter = self.accessor_type(&subscript.getter)?;
+            let setter = match &subscript.setter {
+                Some(setter) => Some(self.accessor_type(setter)?),
+                None => None,
+            };
+            let subscript = ArenaSubscript { getter, setter };
+            let rs = self.context.current_type_mut().ok_or_else(|| {
+                ResolverError::from(format!("Struct {:?} not exist. Maybe before preload", name))
+            })?;
+            if rs
+                .subscripts
+                .iter()
+                .any(|s| s.index_types().eq(subscript.index_types()))
+            {
+                return Err(ResolverError::from(format!(
+                    "`{}` has multiple subscripts with the same index types",
+                    name
+                )));
+            }
+            rs.subscripts.push(subscript);
+        }
         self.context.pop_name_space();
         Ok(())
     }
 
+    fn accessor_type(&mut self, accessor: &TypedFun) -> Result<TypedFunctionType> {
+        match self.context.full_type_name(&accessor.type_())? {
+            TypedType::Function(f) => Ok(*f),
+            t => Err(ResolverError::from(format!(
+                "accessor must be a function, but {:?}",
+                t
+            ))),
+        }
+    }
+
+    fn argument_labels(t: &TypedType) -> Vec<&str> {
+        match t {
+            TypedType::Function(f) => f.arguments.iter().map(|a| a.label.as_str()).collect(),
+            _ => vec![],
+        }
+    }
+
     fn preload_extension(&mut self, e: &TypedExtension) -> Result<()> {
         let TypedExtension {
             name,
             protocol,
+            associated_types,
             computed_properties,
             member_functions,
         } = e;
@@ -189,6 +506,48 @@ impl<'s> TypeResolver<'s> {
             )
             .unwrap();
         self.context.set_current_namespace_id_force(type_id);
+        if let Some(protocol) = protocol {
+            let protocol = self.context.full_type_name(protocol)?;
+            let rs = self
+                .context
+                .arena_mut()
+                .get_type_mut(
+                    &this_type.package().into_resolved().names,
+                    &this_type.name(),
+                )
+                .ok_or_else(|| {
+                    ResolverError::from(format!(
+                        "Struct {:?} not exist. Maybe before preload",
+                        this_type
+                    ))
+                })?;
+            rs.conformed_protocols.insert(protocol.to_string());
+        }
+        for associated_type in associated_types {
+            let type_ = associated_type.type_.as_ref().ok_or_else(|| {
+                ResolverError::from(format!(
+                    "Associated type `{}` of `{}` must be bound to a type.",
+                    associated_type.name,
+                    this_type.to_string()
+                ))
+            })?;
+            let type_ = self.context.full_type_name(type_)?;
+            let rs = self
+                .context
+                .arena_mut()
+                .get_type_mut(
+                    &this_type.package().into_resolved().names,
+                    &this_type.name(),
+                )
+                .ok_or_else(|| {
+                    ResolverError::from(format!(
+                        "Struct {:?} not exist. Maybe before preload",
+                        this_type
+                    ))
+                })?;
+            rs.associated_types
+                .insert(associated_type.name.clone(), type_);
+        }
         for computed_property in computed_properties {
             let type_ = self.context.full_type_name(&computed_property.type_)?;
             let rs = self
@@ -209,13 +568,16 @@ impl<'s> TypeResolver<'s> {
         }
         for member_function in member_functions {
             let type_ = self.context.full_type_name(&member_function.type_())?;
-            self.context.register_function(
+            if let Some(id) = self.context.register_function(
                 &member_function.name,
                 type_.clone(),
                 member_function.type_params.clone(),
                 member_function.body.clone(),
                 Default::default(),
-            );
+            ) {
+                self.context
+                    .update_arg_names(&id, member_function.arg_names());
+            }
             let rs = self
                 .context
                 .arena_mut()
@@ -240,6 +602,7 @@ impl<'s> TypeResolver<'s> {
         let TypedProtocol {
             name,
             type_params: _,
+            associated_types: _, // NOTE: registered with protocol name
             computed_properties,
             member_functions,
         } = p;
@@ -267,11 +630,12 @@ impl<'s> TypeResolver<'s> {
     pub fn file(&mut self, f: TypedSpellBook) -> Result<TypedSpellBook> {
         self.context.push_name_space(&f.name);
         for u in f.uses.iter() {
-            self.context.use_name_space(u.package.names.clone());
+            self.context.use_name_space(u.clone());
         }
         let result = Ok(TypedSpellBook {
             name: f.name,
-            uses: vec![],
+            // NOTE: re-exports are applied again when loaded as a library
+            uses: f.uses.iter().filter(|u| u.is_public).cloned().collect(),
             body: f
                 .body
                 .into_iter()
@@ -279,29 +643,52 @@ impl<'s> TypeResolver<'s> {
                 .collect::<Result<Vec<_>>>()?,
         });
         for u in f.uses.iter() {
-            self.context.unuse_name_space(&u.package.names);
+            self.context.unuse_name_space(u);
         }
         self.context.pop_name_space();
         result
     }
 
     pub fn decl(&mut self, d: TypedTopLevelDecl) -> Result<TypedTopLevelDecl> {
+        let is_derived = d.annotations.has_annotate(DERIVE);
         Ok(TypedTopLevelDecl {
             annotations: d.annotations,
             package: d.package,
             modifiers: d.modifiers,
             kind: match d.kind {
-                TypedDeclKind::Var(v) => TypedDeclKind::Var(self.typed_var(v)?),
+                TypedDeclKind::Var(v) => TypedDeclKind::Var(self.typed_global_var(v)?),
+                TypedDeclKind::Const(c) => TypedDeclKind::Const(self.typed_const(c)?),
+                TypedDeclKind::ExternVar(v) => TypedDeclKind::ExternVar(self.typed_extern_var(v)?),
                 TypedDeclKind::Fun(f) => TypedDeclKind::Fun(self.typed_fun(f)?),
                 TypedDeclKind::Struct(s) => TypedDeclKind::Struct(self.typed_struct(s)?),
+                TypedDeclKind::Union(u) => TypedDeclKind::Union(self.typed_struct(u)?),
+                TypedDeclKind::Class(c) => TypedDeclKind::Class(self.typed_struct(c)?),
                 TypedDeclKind::Module(m) => TypedDeclKind::Module(self.file(m)?),
                 TypedDeclKind::Enum => TypedDeclKind::Enum,
                 TypedDeclKind::Protocol(p) => TypedDeclKind::Protocol(self.typed_protocol(p)?),
-                TypedDeclKind::Extension(e) => TypedDeclKind::Extension(self.typed_extension(e)?),
+                TypedDeclKind::TypeAlias(t) => TypedDeclKind::TypeAlias(self.typed_type_alias(t)?),
+                TypedDeclKind::Extension(e) => {
+                    if is_derived {
+                        self.check_derivable(&e)?;
+                    }
+                    TypedDeclKind::Extension(self.typed_extension(e)?)
+                }
             },
         })
     }
 
+    /// Resolve the aliased type, in which type parameters of the type alias are declared.
+    pub fn typed_type_alias(&mut self, t: TypedTypeAlias) -> Result<TypedTypeAlias> {
+        self.context.push_name_space(&t.name);
+        for type_param in t.type_params.iter().flatten() {
+            self.context
+                .register_type_parameter(&type_param.name, Default::default());
+        }
+        let type_ = self.context.full_type_name(&t.type_);
+        self.context.pop_name_space();
+        Ok(TypedTypeAlias { type_: type_?, ..t })
+    }
+
     pub fn typed_var(&mut self, t: TypedVar) -> Result<TypedVar> {
         let TypedVar {
             is_mut,
@@ -309,27 +696,89 @@ impl<'s> TypeResolver<'s> {
             type_,
             value,
         } = t;
-        let value = self.expr(
-            value,
-            match type_ {
-                Some(type_) => Some(self.context.full_type_name(&type_)?),
-                None => None,
-            },
-        )?;
+        let type_ = match type_ {
+            Some(type_) => Some(self.typed_type_annotation(&type_)?),
+            None => None,
+        };
+        let value = self.expr(value, type_)?;
         let v = TypedVar {
             is_mut,
             name,
-            type_: value.ty.clone(),
+            type_: value.ty.clone().map(TypedType::decay),
             value,
         };
         Ok(v)
     }
 
+    /// Resolve global variable `v`, whose initializer must be a constant expression.
+    fn typed_global_var(&mut self, v: TypedVar) -> Result<TypedVar> {
+        let mut v = self.typed_var(v)?;
+        v.value = self.fold_constant(&v.value).map_err(|e| {
+            ResolverError::from(format!(
+                "Initializer of global `{}` is not a constant expression: {}",
+                v.name, e
+            ))
+        })?;
+        Ok(v)
+    }
+
+    fn typed_extern_var(&mut self, v: TypedExternVar) -> Result<TypedExternVar> {
+        Ok(TypedExternVar {
+            is_mut: v.is_mut,
+            name: v.name,
+            type_: self.typed_type_annotation(&v.type_)?,
+        })
+    }
+
+    fn typed_const(&mut self, c: TypedVar) -> Result<TypedVar> {
+        let mut c = self.typed_var(c)?;
+        c.value = self.fold_constant(&c.value).map_err(|e| {
+            ResolverError::from(format!(
+                "Value of constant `{}` is not a constant expression: {}",
+                c.name, e
+            ))
+        })?;
+        Ok(c)
+    }
+
+    fn fold_constant(&self, e: &TypedExpr) -> std::result::Result<TypedExpr, String> {
+        ConstEvaluator::new(
+            self.context.arena(),
+            self.context.get_current_namespace_id(),
+            self.session.data_layout(),
+        )
+        .fold(e)
+    }
+
+    /// Resolve type annotation `t` whose type arguments must satisfy constraints.
+    fn typed_type_annotation(&mut self, t: &TypedType) -> Result<TypedType> {
+        let t = self.context.full_type_name(t)?;
+        self.check_type_arguments(&t)?;
+        Ok(t)
+    }
+
     fn typed_arg_def(&mut self, a: TypedArgDef) -> Result<TypedArgDef> {
+        let type_ = self.typed_type_annotation(&a.type_)?;
+        let default = match a.default {
+            Some(d) => {
+                let d = self.expr(d, Some(type_.clone()))?;
+                if d.ty.as_ref() != Some(&type_) {
+                    return Err(ResolverError::from(format!(
+                        "Default value of `{}` has type `{}`, but `{}` is expected.",
+                        a.name,
+                        d.ty.map(|t| t.to_string()).unwrap_or_default(),
+                        type_.to_string()
+                    )));
+                }
+                Some(d)
+            }
+            None => None,
+        };
         Ok(TypedArgDef {
             label: a.label,
             name: a.name,
-            type_: self.context.full_type_name(&a.type_)?,
+            type_,
+            default,
         })
     }
 
@@ -345,14 +794,38 @@ impl<'s> TypeResolver<'s> {
                         for c in con.constraints.iter() {
                             let c = self.context.full_type_name(c)?;
                             let ne = self.context.get_current_name_environment();
-                            let crs = ne.get_type_by_typed_type(c).unwrap();
+                            let crs = ne.get_type_by_typed_type(c.clone()).ok_or_else(|| {
+                                ResolverError::from(format!(
+                                    "Cannot resolve protocol `{}`",
+                                    c.to_string()
+                                ))
+                            })?;
                             let members = crs.member_functions.clone();
+                            let protocol_associated_types = crs.associated_types.clone();
                             let rs = self
                                 .context
                                 .arena_mut()
                                 .get_type_mut(&vec_current_namespace, &type_param.name)
                                 .unwrap();
-                            rs.member_functions.extend(members);
+                            // NOTE: `Self` of protocol methods is the type parameter itself
+                            let param_type = rs.self_type();
+                            // NOTE: associated types of protocol are ones of the type parameter
+                            let associated_types = protocol_associated_types
+                                .iter()
+                                .filter_map(|(name, placeholder)| {
+                                    Some((
+                                        placeholder.clone(),
+                                        rs.associated_types.get(name)?.clone(),
+                                    ))
+                                })
+                                .collect::<Vec<_>>();
+                            rs.member_functions
+                                .extend(members.into_iter().map(|(name, t)| {
+                                    let t = associated_types
+                                        .iter()
+                                        .fold(t, |t, (from, to)| t.replace(from, to));
+                                    (name, t.replace(&c, &param_type))
+                                }));
                         }
                     }
                 };
@@ -368,7 +841,7 @@ impl<'s> TypeResolver<'s> {
                 Ok(a)
             })
             .collect::<Result<Vec<_>>>()?;
-        let return_type = self.context.full_type_name(&f.return_type)?;
+        let return_type = self.typed_type_annotation(&f.return_type)?;
         let fun = TypedFun {
             name: f.name,
             type_params: f.type_params,
@@ -392,8 +865,11 @@ impl<'s> TypeResolver<'s> {
         let TypedStruct {
             name,
             type_params,
+            type_constraints,
             stored_properties,
             computed_properties, // TODO
+            initializers,
+            subscripts,
             member_functions,
         } = s;
         self.context.push_name_space(&name);
@@ -402,28 +878,79 @@ impl<'s> TypeResolver<'s> {
             .map(|s| self.typed_stored_property(s))
             .collect::<Result<Vec<_>>>()?;
         let computed_properties = computed_properties.into_iter().collect();
+        let initializers = initializers
+            .into_iter()
+            .map(|i| self.typed_initializer(i))
+            .collect::<Result<Vec<_>>>()?;
+        let subscripts = subscripts
+            .into_iter()
+            .map(|s| {
+                Ok(TypedSubscriptDecl {
+                    getter: self.typed_member_function(s.getter)?,
+                    setter: match s.setter {
+                        Some(setter) => Some(self.typed_member_function(setter)?),
+                        None => None,
+                    },
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
         let member_functions = member_functions
             .into_iter()
             .map(|m| self.typed_member_function(m))
             .collect::<Result<Vec<_>>>()?;
+        let type_constraints = match type_constraints {
+            None => None,
+            Some(tc) => Some(self.typed_type_constraints(tc)?),
+        };
         self.context.pop_name_space();
         Ok(TypedStruct {
             name,
             type_params,
+            type_constraints,
             stored_properties,
             computed_properties,
+            initializers,
+            subscripts,
             member_functions,
         })
     }
 
     fn typed_stored_property(&mut self, s: TypedStoredProperty) -> Result<TypedStoredProperty> {
-        let TypedStoredProperty { name, type_ } = s;
+        let TypedStoredProperty {
+            is_mut,
+            name,
+            type_,
+        } = s;
         Ok(TypedStoredProperty {
+            is_mut,
             name,
-            type_: self.context.full_type_name(&type_)?,
+            type_: self.typed_type_annotation(&type_)?,
         })
     }
 
+    /// `self` is a mutable local variable of the struct in the body of `init`.
+    fn typed_initializer(&mut self, i: TypedInitializer) -> Result<TypedInitializer> {
+        self.context.push_local_stack();
+        let arg_defs = i
+            .arg_defs
+            .into_iter()
+            .map(|a| {
+                let a = self.typed_arg_def(a)?;
+                self.context
+                    .register_to_env(a.name.clone(), (DeclarationId::DUMMY, a.type_.clone()));
+                Ok(a)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let self_type = self.context.full_type_name(&TypedType::Self_)?;
+        self.context
+            .register_to_env("self".to_string(), (DeclarationId::DUMMY, self_type));
+        let result = self
+            .typed_fun_body(i.body)
+            .map(|body| TypedInitializer { arg_defs, body });
+        self.context.pop_local_stack();
+        result
+    }
+
     fn typed_member_function(&mut self, mf: TypedFun) -> Result<TypedFun> {
         self.context.push_local_stack();
         let arg_defs = mf
@@ -470,12 +997,29 @@ impl<'s> TypeResolver<'s> {
             )
             .unwrap();
         self.context.set_current_namespace_id_force(type_id);
+        let protocol = match &e.protocol {
+            Some(p) => Some(self.context.full_type_name(p)?),
+            None => None,
+        };
+        if let Some(protocol) = &protocol {
+            self.check_conformance(&this_type, protocol)?;
+        }
         let result = Ok(TypedExtension {
             name: this_type,
-            protocol: match &e.protocol {
-                Some(p) => Some(self.context.full_type_name(p)?),
-                None => None,
-            },
+            protocol,
+            associated_types: e
+                .associated_types
+                .into_iter()
+                .map(|a| {
+                    Ok(TypedAssociatedType {
+                        name: a.name,
+                        type_: match a.type_ {
+                            Some(t) => Some(self.context.full_type_name(&t)?),
+                            None => None,
+                        },
+                    })
+                })
+                .collect::<Result<_>>()?,
             computed_properties: e.computed_properties.into_iter().map(|i| i).collect(),
             member_functions: e
                 .member_functions
@@ -487,11 +1031,107 @@ impl<'s> TypeResolver<'s> {
         result
     }
 
+    /// Check that every stored property of the type extended by `#[derive(..)]`
+    /// conforms to the derived protocol.
+    fn check_derivable(&self, e: &TypedExtension) -> Result<()> {
+        let this_type = self.context.full_type_name(&e.name)?;
+        let protocol = match &e.protocol {
+            Some(p) => self.context.full_type_name(p)?,
+            None => return Ok(()),
+        };
+        let derive = Derive::from_name(&protocol.name());
+        let ne = self.context.get_current_name_environment();
+        let rs = ne
+            .get_type_by_typed_type(this_type.clone())
+            .ok_or_else(|| {
+                ResolverError::from(format!("Cannot resolve type `{}`", this_type.to_string()))
+            })?;
+        for name in rs.stored_property_order.iter().flatten() {
+            let type_ = &rs.stored_properties[name];
+            if derive.is_some_and(|d| d.is_trivial(type_)) {
+                continue;
+            }
+            let conformed = ne
+                .get_type_by_typed_type(type_.clone())
+                .is_some_and(|rs| rs.conformed_protocols.contains(&protocol.to_string()));
+            if !conformed {
+                return Err(ResolverError::from(format!(
+                    "Cannot derive `{}` for `{}`, because field `{}` of type `{}` does not conform to `{}`.",
+                    protocol.name(),
+                    this_type.to_string(),
+                    name,
+                    type_.to_string(),
+                    protocol.to_string()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that `this_type` binds all associated types and implements all member functions
+    /// that `protocol` requires.
+    fn check_conformance(&self, this_type: &TypedType, protocol: &TypedType) -> Result<()> {
+        let ne = self.context.get_current_name_environment();
+        let prs = ne.get_type_by_typed_type(protocol.clone()).ok_or_else(|| {
+            ResolverError::from(format!(
+                "Cannot resolve protocol `{}`",
+                protocol.to_string()
+            ))
+        })?;
+        let rs = ne
+            .get_type_by_typed_type(this_type.clone())
+            .ok_or_else(|| {
+                ResolverError::from(format!("Cannot resolve type `{}`", this_type.to_string()))
+            })?;
+        let mut associated_types = vec![];
+        for (name, placeholder) in prs.associated_types.iter() {
+            let bound = rs.associated_types.get(name).ok_or_else(|| {
+                ResolverError::from(format!(
+                    "`{}` does not bind associated type `{}` required by `{}`.",
+                    this_type.to_string(),
+                    name,
+                    protocol.to_string()
+                ))
+            })?;
+            associated_types.push((placeholder, bound));
+        }
+        for (name, required) in prs.member_functions.iter() {
+            let required = associated_types
+                .iter()
+                .fold(required.replace(protocol, this_type), |t, (from, to)| {
+                    t.replace(from, to)
+                });
+            match rs.member_functions.get(name) {
+                None => {
+                    return Err(ResolverError::from(format!(
+                        "`{}` does not implement `{}` required by `{}`.",
+                        this_type.to_string(),
+                        name,
+                        protocol.to_string()
+                    )))
+                }
+                Some(actual) if *actual != required => {
+                    return Err(ResolverError::from(format!(
+                        "`{}` of `{}` has type `{}`, but `{}` requires `{}`.",
+                        name,
+                        this_type.to_string(),
+                        actual.to_string(),
+                        protocol.to_string(),
+                        required.to_string()
+                    )))
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
     fn typed_protocol(&mut self, p: TypedProtocol) -> Result<TypedProtocol> {
         self.context.push_name_space(&p.name);
         let result = TypedProtocol {
             name: p.name,
             type_params: p.type_params, // TODO type params
+            associated_types: p.associated_types,
             member_functions: p
                 .member_functions
                 .into_iter()
@@ -567,7 +1207,7 @@ impl<'s> TypeResolver<'s> {
             TypedExprKind::Dict => TypedExpr::new(TypedExprKind::Dict, None),
             TypedExprKind::StringBuilder => TypedExpr::new(TypedExprKind::StringBuilder, None),
             TypedExprKind::Call(c) => {
-                let (kind, ty) = self.typed_call(c)?;
+                let (kind, ty) = self.typed_call(c, type_annotation)?;
                 TypedExpr::new(TypedExprKind::Call(kind), ty)
             }
             TypedExprKind::If(i) => {
@@ -613,6 +1253,7 @@ impl<'s> TypeResolver<'s> {
             match &mut item.kind {
                 DeclarationItemKind::Namespace => {}
                 DeclarationItemKind::Type(t) => {}
+                DeclarationItemKind::TypeAlias(_) => {}
                 DeclarationItemKind::Variable(t) => {}
                 DeclarationItemKind::Function(rf) => {
                     if rf.is_generic() {
@@ -663,9 +1304,16 @@ impl<'s> TypeResolver<'s> {
                     Some(TypedType::double())
                 }
             }
-            TypedLiteralKind::String { .. } => Some(TypedType::string_ref()),
+            TypedLiteralKind::String { .. } => match type_annotation {
+                Some(t) if t == TypedType::slice(TypedType::uint8()) => Some(t),
+                _ => Some(TypedType::string_ref()),
+            },
             TypedLiteralKind::Boolean { .. } => Some(TypedType::bool()),
             TypedLiteralKind::NullLiteral => type_annotation,
+            TypedLiteralKind::SourceLocation(TypedSourceLocationKind::File, _) => {
+                Some(TypedType::string_ref())
+            }
+            TypedLiteralKind::SourceLocation(_, _) => Some(TypedType::uint32()),
         };
         Ok((l, ty))
     }
@@ -731,6 +1379,7 @@ impl<'s> TypeResolver<'s> {
             TypedPostfixUnaryOp {
                 operator: u.operator,
                 target,
+                location: u.location,
             },
             ty,
         ))
@@ -801,6 +1450,7 @@ impl<'s> TypeResolver<'s> {
                 left: Box::new(left),
                 operator: b.operator,
                 right: Box::new(right),
+                location: b.location,
             },
             Some(type_),
         ))
@@ -830,6 +1480,31 @@ impl<'s> TypeResolver<'s> {
     ) -> Result<(TypedSubscript, Option<TypedType>)> {
         let target = self.expr(*s.target, None)?;
         let target_type = target.ty.clone().unwrap();
+        if let [index] = s.indexes.as_slice() {
+            let index = self.expr(index.clone(), None)?;
+            if index.ty.as_ref().is_some_and(TypedType::is_range) {
+                let element_type = match &target_type {
+                    TypedType::Value(TypedValueType::Array(e, _))
+                    | TypedType::Value(TypedValueType::Slice(e)) => Some(*e.clone()),
+                    t if t.is_string() || t.is_string_ref() => Some(TypedType::uint8()),
+                    _ => None,
+                };
+                return match element_type {
+                    Some(e) => Ok((
+                        TypedSubscript {
+                            target: Box::new(target),
+                            indexes: vec![index],
+                            location: s.location,
+                        },
+                        Some(TypedType::slice(e)),
+                    )),
+                    None => Err(ResolverError::from(format!(
+                        "`{}` can not be sliced with a range",
+                        target_type.to_string()
+                    ))),
+                };
+            }
+        }
         if let TypedType::Value(v) = target_type {
             match v {
                 TypedValueType::Value(v) => {
@@ -842,6 +1517,7 @@ impl<'s> TypeResolver<'s> {
                                     .into_iter()
                                     .map(|i| self.expr(i, None))
                                     .collect::<Result<_>>()?,
+                                location: s.location.clone(),
                             },
                             Some(TypedType::uint8()),
                         ));
@@ -856,6 +1532,21 @@ impl<'s> TypeResolver<'s> {
                                 .into_iter()
                                 .map(|i| self.expr(i, None))
                                 .collect::<Result<_>>()?,
+                            location: s.location.clone(),
+                        },
+                        Some(*et),
+                    ))
+                }
+                TypedValueType::Slice(et) => {
+                    return Ok((
+                        TypedSubscript {
+                            target: Box::new(target),
+                            indexes: s
+                                .indexes
+                                .into_iter()
+                                .map(|i| self.expr(i, None))
+                                .collect::<Result<_>>()?,
+                            location: s.location.clone(),
                         },
                         Some(*et),
                     ))
@@ -872,6 +1563,7 @@ impl<'s> TypeResolver<'s> {
                                 .into_iter()
                                 .map(|i| self.expr(i, None))
                                 .collect::<Result<_>>()?,
+                            location: s.location.clone(),
                         },
                         Some(*p),
                     ))
@@ -886,13 +1578,60 @@ impl<'s> TypeResolver<'s> {
                                     .into_iter()
                                     .map(|i| self.expr(i, None))
                                     .collect::<Result<_>>()?,
+                                location: s.location.clone(),
                             },
                             Some(TypedType::uint8()),
                         ));
                     }
                 }
+                TypedValueType::Function(_) => {}
             }
         }
+        if let Some((name, subscripts)) = self.struct_subscripts(target.ty.as_ref().unwrap()) {
+            let candidates = subscripts
+                .iter()
+                .filter(|c| c.index_types().count() == s.indexes.len())
+                .collect::<Vec<_>>();
+            let indexes = match candidates.as_slice() {
+                [c] => s
+                    .indexes
+                    .into_iter()
+                    .zip(c.index_types())
+                    .map(|(i, t)| self.expr(i, Some(t.clone())))
+                    .collect::<Result<Vec<_>>>()?,
+                _ => s
+                    .indexes
+                    .into_iter()
+                    .map(|i| self.expr(i, None))
+                    .collect::<Result<Vec<_>>>()?,
+            };
+            let index_types = indexes
+                .iter()
+                .map(|i| i.ty.clone().unwrap())
+                .collect::<Vec<_>>();
+            let subscript = subscripts
+                .iter()
+                .find(|c| c.index_types().eq(index_types.iter()))
+                .ok_or_else(|| {
+                    ResolverError::from(format!(
+                        "`{}` has no subscript taking `({})`",
+                        name,
+                        index_types
+                            .iter()
+                            .map(|t| t.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                })?;
+            return Ok((
+                TypedSubscript {
+                    target: Box::new(target),
+                    indexes,
+                    location: s.location,
+                },
+                Some(subscript.element_type().clone()),
+            ));
+        }
         Ok((
             TypedSubscript {
                 target: Box::new(target),
@@ -901,11 +1640,32 @@ impl<'s> TypeResolver<'s> {
                     .into_iter()
                     .map(|i| self.expr(i, None))
                     .collect::<Result<_>>()?,
+                location: s.location.clone(),
             },
             ty,
         ))
     }
 
+    /// Name and subscripts of the struct `target_type` refers to, if it declares any.
+    fn struct_subscripts(
+        &mut self,
+        target_type: &TypedType,
+    ) -> Option<(String, Vec<ArenaSubscript>)> {
+        let mut target_type = target_type;
+        while let TypedType::Value(TypedValueType::Reference(t)) = target_type {
+            target_type = t;
+        }
+        match target_type {
+            TypedType::Value(TypedValueType::Value(v)) => self
+                .context
+                .arena_mut()
+                .get_type(&v.package.clone().into_resolved().names, &v.name)
+                .filter(|rs| !rs.subscripts.is_empty())
+                .map(|rs| (v.to_string(), rs.subscripts.clone())),
+            _ => None,
+        }
+    }
+
     pub fn typed_array(&mut self, a: TypedArray) -> Result<(TypedArray, Option<TypedType>)> {
         let elements = a
             .elements
@@ -928,38 +1688,108 @@ impl<'s> TypeResolver<'s> {
         })
     }
 
-    pub fn typed_call(&mut self, c: TypedCall) -> Result<(TypedCall, Option<TypedType>)> {
+    pub fn typed_call(
+        &mut self,
+        c: TypedCall,
+        type_annotation: Option<TypedType>,
+    ) -> Result<(TypedCall, Option<TypedType>)> {
         let (target, args) = match self.expr((*c.target).clone(), None) {
             Ok(TypedExpr {
                 kind: TypedExprKind::Name(n),
                 ty,
             }) => {
+                let type_params = self.generic_type_parameters(&n);
+                let arg_defaults = match &ty {
+                    Some(ty) => {
+                        self.arg_defaults(&n.package.clone().into_resolved().names, &n.name, ty)
+                    }
+                    None => vec![],
+                };
                 let target = TypedExpr::new(TypedExprKind::Name(n), ty);
-                if let Some(TypedType::Function(f)) = target.ty.clone() {
-                    if c.args.len() != f.arguments.len() {
+                if let Some(
+                    TypedType::Function(f) | TypedType::Value(TypedValueType::Function(f)),
+                ) = target.ty.clone()
+                {
+                    let args =
+                        Self::fill_default_args(&f.arguments, &arg_defaults, c.args, &c.location);
+                    if args.len() != f.arguments.len() {
                         Err(ResolverError::from(format!(
                             "{:?} required {} arguments, but {} were given.",
                             target,
                             f.arguments.len(),
-                            c.args.len()
+                            args.len()
                         )))
+                    } else if let Some(type_params) = type_params {
+                        self.typed_generic_call(target, *f, type_params, args, type_annotation)
                     } else {
                         Ok((
                             target,
-                            c.args
-                                .into_iter()
+                            args.into_iter()
                                 .zip(f.arguments)
                                 .map(|(c, annotation)| self.typed_call_arg(c, Some(annotation.typ)))
                                 .collect::<Result<Vec<_>>>()?,
                         ))
                     }
+                } else if matches!(&target.ty, Some(TypedType::Type(t)) if Self::is_atomic(t)) {
+                    self.typed_atomic_init(target, c.args, type_annotation)
                 } else if let Some(TypedType::Type(t)) = &target.ty {
                     let rs = self
                         .context
                         .arena_mut()
                         .get_type(&t.package().into_resolved().names, &t.name())
                         .unwrap();
-                    if rs.stored_properties.len() != c.args.len() {
+                    let labels = c
+                        .args
+                        .iter()
+                        .map(|a| a.label.as_deref().unwrap_or("_"))
+                        .collect::<Vec<_>>();
+                    let initializer = rs
+                        .initializers
+                        .iter()
+                        .find(|i| Self::argument_labels(i) == labels)
+                        .cloned();
+                    if let Some(TypedType::Function(f)) = initializer {
+                        let mut package = t.package().into_resolved();
+                        package.names.push(t.name());
+                        let target = TypedExpr::new(
+                            TypedExprKind::Name(TypedName {
+                                package: TypedPackage::Resolved(package),
+                                name: TypedInitializer::NAME.to_string(),
+                                type_arguments: None,
+                            }),
+                            Some(TypedType::Function(f.clone())),
+                        );
+                        Ok((
+                            target,
+                            c.args
+                                .into_iter()
+                                .zip(f.arguments)
+                                .map(|(c, annotation)| self.typed_call_arg(c, Some(annotation.typ)))
+                                .collect::<Result<Vec<_>>>()?,
+                        ))
+                    } else if rs.kind.is_union() {
+                        let field_type = match c.args.as_slice() {
+                            [arg] => arg
+                                .label
+                                .as_ref()
+                                .and_then(|label| rs.stored_properties.get(label))
+                                .cloned(),
+                            _ => None,
+                        };
+                        match field_type {
+                            Some(field_type) => Ok((
+                                target,
+                                c.args
+                                    .into_iter()
+                                    .map(|c| self.typed_call_arg(c, Some(field_type.clone())))
+                                    .collect::<Result<Vec<_>>>()?,
+                            )),
+                            None => Err(ResolverError::from(format!(
+                                "union `{}` must be initialized with exactly one labeled field.",
+                                t.name()
+                            ))),
+                        }
+                    } else if rs.stored_properties.len() != c.args.len() {
                         Err(ResolverError::from(format!(
                             "`{}` required {} arguments, but {} were given.",
                             t.name(),
@@ -984,10 +1814,42 @@ impl<'s> TypeResolver<'s> {
                 }
             }
             Ok(target) => {
-                let args = c
-                    .args
+                let args = match (&target.kind, &target.ty) {
+                    (TypedExprKind::Member(m), Some(ty @ TypedType::Function(f))) => {
+                        let arg_defaults = match &m.target.ty {
+                            Some(
+                                t @ TypedType::Value(
+                                    TypedValueType::Value(_)
+                                    | TypedValueType::Reference(_)
+                                    | TypedValueType::Pointer(_),
+                                ),
+                            ) => {
+                                let mut namespace = t.package().into_resolved().names;
+                                namespace.push(t.name());
+                                self.arg_defaults(&namespace, &m.name, ty)
+                            }
+                            _ => vec![],
+                        };
+                        // NOTE: skip `self`
+                        let args = match (f.arguments.split_first(), arg_defaults.split_first()) {
+                            (Some((_, params)), Some((_, arg_defaults))) => {
+                                Self::fill_default_args(params, arg_defaults, c.args, &c.location)
+                            }
+                            _ => c.args,
+                        };
+                        let params = f.arguments.iter().skip(1).map(|a| Some(a.typ.clone()));
+                        let annotations = if args.len() + 1 == f.arguments.len() {
+                            params.collect()
+                        } else {
+                            vec![None; args.len()]
+                        };
+                        args.into_iter().zip(annotations).collect()
+                    }
+                    _ => c.args.into_iter().map(|a| (a, None)).collect::<Vec<_>>(),
+                };
+                let args = args
                     .into_iter()
-                    .map(|c| self.typed_call_arg(c, None))
+                    .map(|(c, annotation)| self.typed_call_arg(c, annotation))
                     .collect::<Result<Vec<_>>>()?;
                 Ok((target, args))
             }
@@ -1011,7 +1873,12 @@ impl<'s> TypeResolver<'s> {
                 Ok((target, args))
             }
         }?;
+        let args = match self.atomic_intrinsic(&target) {
+            Some((intrinsic, receivers)) => self.fold_orderings(intrinsic, receivers, args)?,
+            None => args,
+        };
         let c_type = match target.ty.clone().unwrap() {
+            TypedType::Value(TypedValueType::Function(f)) => Ok(f.return_type),
             TypedType::Value(v) => Err(ResolverError::from(format!("{:?} is not callable.", v))),
             TypedType::Type(t) => Ok(*t),
             TypedType::Self_ => Err(ResolverError::from("Self is not callable.")),
@@ -1021,11 +1888,393 @@ impl<'s> TypeResolver<'s> {
             TypedCall {
                 target: Box::new(target),
                 args,
+                location: c.location,
             },
             Some(c_type),
         ))
     }
 
+    fn is_atomic(t: &TypedType) -> bool {
+        matches!(t, TypedType::Value(TypedValueType::Value(v)) if v.is_atomic())
+    }
+
+    /// Resolve `Atomic<T>(value: v)`, which is represented as `v` itself.
+    /// `T` is inferred from the expected type unless it is given explicitly.
+    fn typed_atomic_init(
+        &mut self,
+        target: TypedExpr,
+        args: Vec<TypedCallArg>,
+        type_annotation: Option<TypedType>,
+    ) -> Result<(TypedExpr, Vec<TypedCallArg>)> {
+        let type_arguments = match &target.kind {
+            TypedExprKind::Name(n) => n.type_arguments.clone(),
+            _ => None,
+        };
+        let value_type = match (type_arguments.as_deref(), &type_annotation) {
+            (Some([t]), _) => t.clone(),
+            (None, Some(annotation)) if annotation.atomic_value_type().is_some() => {
+                annotation.atomic_value_type().unwrap().clone()
+            }
+            _ => {
+                return Err(ResolverError::from(
+                    "type argument of `Atomic` can not be inferred",
+                ))
+            }
+        };
+        verify_atomic_value_type(&value_type)?;
+        let arg = match <[TypedCallArg; 1]>::try_from(args) {
+            Ok([arg]) if arg.label.as_deref() == Some("value") => arg,
+            _ => {
+                return Err(ResolverError::from(
+                    "`Atomic` must be initialized with exactly one argument labeled `value`",
+                ))
+            }
+        };
+        let arg = self.typed_call_arg(arg, Some(value_type.clone()))?;
+        let target = TypedExpr::new(
+            target.kind,
+            Some(TypedType::Type(Box::new(TypedType::atomic(value_type)))),
+        );
+        Ok((target, vec![arg]))
+    }
+
+    /// Atomic intrinsic called by `target` and the number of its parameters
+    /// which are passed as receivers instead of arguments.
+    fn atomic_intrinsic(&self, target: &TypedExpr) -> Option<(Intrinsic, usize)> {
+        match &target.kind {
+            TypedExprKind::Name(n) => {
+                let item = self
+                    .context
+                    .arena()
+                    .get(&n.package.clone().into_resolved().names, &n.name)?;
+                if item.has_annotation(BUILTIN) {
+                    Intrinsic::from_name(&n.name).map(|i| (i, 0))
+                } else {
+                    None
+                }
+            }
+            TypedExprKind::Member(m) => {
+                let mut receiver = m.target.ty.as_ref()?;
+                while let TypedType::Value(TypedValueType::Reference(t)) = receiver {
+                    receiver = t;
+                }
+                if receiver.atomic_value_type().is_some() {
+                    Intrinsic::from_atomic_method(&m.name).map(|i| (i, 1))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Fold memory orderings passed to an atomic intrinsic into literals,
+    /// since they select the instruction to emit.
+    fn fold_orderings(
+        &self,
+        intrinsic: Intrinsic,
+        receivers: usize,
+        mut args: Vec<TypedCallArg>,
+    ) -> Result<Vec<TypedCallArg>> {
+        let mut orderings = vec![];
+        for param in intrinsic.ordering_params() {
+            let arg = match args.get_mut(param - receivers) {
+                Some(arg) => arg,
+                None => continue,
+            };
+            let folded = self.fold_constant(&arg.arg).map_err(|e| {
+                ResolverError::from(format!(
+                    "Memory ordering of `{}` is not a constant expression: {}",
+                    intrinsic.name(),
+                    e
+                ))
+            })?;
+            let ordering = match &folded.kind {
+                TypedExprKind::Literal(TypedLiteralKind::Integer(value)) => {
+                    value.parse().ok().and_then(MemoryOrdering::from_value)
+                }
+                _ => None,
+            }
+            .ok_or_else(|| {
+                ResolverError::from(format!(
+                    "Memory ordering of `{}` must be one of `RELAXED`, `ACQUIRE`, `RELEASE`, `ACQ_REL` and `SEQ_CST`",
+                    intrinsic.name()
+                ))
+            })?;
+            orderings.push(ordering);
+            *arg.arg = folded;
+        }
+        intrinsic
+            .verify_orderings(&orderings)
+            .map_err(ResolverError::from)?;
+        Ok(args)
+    }
+
+    fn arg_defaults(
+        &self,
+        namespace: &[String],
+        name: &str,
+        ty: &TypedType,
+    ) -> Vec<Option<TypedExpr>> {
+        self.context
+            .arena()
+            .get_function(namespace, name, ty)
+            .map(|rf| rf.arg_defaults.clone())
+            .unwrap_or_default()
+    }
+
+    /// Complement omitted arguments with default values of parameters.
+    /// A labeled argument skips parameters that have default values until the same labeled one.
+    /// Source location literals used as default values are replaced with the caller's location.
+    fn fill_default_args(
+        params: &[TypedArgType],
+        arg_defaults: &[Option<TypedExpr>],
+        args: Vec<TypedCallArg>,
+        location: &Option<TypedLocation>,
+    ) -> Vec<TypedCallArg> {
+        if arg_defaults.iter().all(Option::is_none) {
+            return args;
+        }
+        let mut args = args.into_iter().peekable();
+        let mut filled = vec![];
+        for (param, default) in params.iter().zip(arg_defaults) {
+            let skipped = match (args.peek(), default) {
+                (Some(a), Some(_)) => a.label.is_some() && a.label.as_ref() != Some(&param.label),
+                (Some(_), None) => false,
+                (None, default) => default.is_some(),
+            };
+            if skipped {
+                filled.push(TypedCallArg {
+                    label: if param.label == "_" {
+                        None
+                    } else {
+                        Some(param.label.clone())
+                    },
+                    arg: Box::new(match (default.clone().unwrap(), location) {
+                        (
+                            TypedExpr {
+                                kind:
+                                    TypedExprKind::Literal(TypedLiteralKind::SourceLocation(kind, _)),
+                                ty,
+                            },
+                            Some(location),
+                        ) => TypedExpr::new(
+                            TypedExprKind::Literal(TypedLiteralKind::SourceLocation(
+                                kind,
+                                location.clone(),
+                            )),
+                            ty,
+                        ),
+                        (default, _) => default,
+                    }),
+                    is_vararg: false,
+                });
+            } else if let Some(a) = args.next() {
+                filled.push(a);
+            }
+        }
+        filled.extend(args);
+        filled
+    }
+
+    fn generic_type_parameters(&mut self, n: &TypedName) -> Option<Vec<TypedTypeParam>> {
+        let item = self
+            .context
+            .arena_mut()
+            .get(&n.package.clone().into_resolved().names, &n.name)?;
+        match &item.kind {
+            DeclarationItemKind::Function(rf) => rf.type_parameters.clone(),
+            _ => None,
+        }
+    }
+
+    /// Resolve call of generic function.
+    /// Type arguments are inferred from the expected return type and argument types
+    /// unless they are given explicitly.
+    fn typed_generic_call(
+        &mut self,
+        target: TypedExpr,
+        f: TypedFunctionType,
+        type_params: Vec<TypedTypeParam>,
+        args: Vec<TypedCallArg>,
+        type_annotation: Option<TypedType>,
+    ) -> Result<(TypedExpr, Vec<TypedCallArg>)> {
+        let n = match target.kind {
+            TypedExprKind::Name(n) => n,
+            _ => unreachable!(),
+        };
+        let mut fqn = n.package.clone().into_resolved().names;
+        fqn.push(n.name.clone());
+        let mut substitution =
+            TypeParameterSubstitution::new(&n.name, Package::from(&fqn), type_params.clone());
+        if let Some(type_arguments) = &n.type_arguments {
+            substitution = substitution.with_type_arguments(type_arguments)?;
+        }
+        if let Some(type_annotation) = &type_annotation {
+            substitution.unify(&f.return_type, type_annotation)?;
+        }
+        let args = args
+            .into_iter()
+            .zip(f.arguments.iter())
+            .map(|(a, param)| {
+                let annotation = if substitution.is_determined(&param.typ) {
+                    Some(substitution.substitute(&param.typ))
+                } else {
+                    None
+                };
+                let a = self.typed_call_arg(a, annotation)?;
+                if let Some(arg_type) = &a.arg.ty {
+                    substitution.unify(&param.typ, arg_type)?;
+                }
+                Ok(a)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let type_arguments = substitution.type_arguments()?;
+        for (type_param, type_argument) in type_params.iter().zip(type_arguments.iter()) {
+            self.check_type_constraint(&n.name, &fqn, type_param, type_argument)?;
+        }
+        let ty = substitution.substitute(&TypedType::Function(Box::new(f)));
+        let ty = self
+            .associated_type_bindings(&fqn, &type_params, &type_arguments)
+            .iter()
+            .fold(ty, |t, (from, to)| t.replace(from, to));
+        if let Some(item) = self
+            .context
+            .arena_mut()
+            .get_mut(&n.package.clone().into_resolved().names, &n.name)
+        {
+            if let DeclarationItemKind::Function(rf) = &mut item.kind {
+                let used = substitution.into_map();
+                if !rf.used.contains(&used) {
+                    rf.used.push(used);
+                }
+            }
+        }
+        Ok((
+            TypedExpr::new(
+                TypedExprKind::Name(TypedName {
+                    package: n.package,
+                    name: n.name,
+                    type_arguments: Some(type_arguments),
+                }),
+                Some(ty),
+            ),
+            args,
+        ))
+    }
+
+    /// Pairs of associated type of type parameter and one bound by its type argument.
+    fn associated_type_bindings(
+        &self,
+        namespace: &[String],
+        type_params: &[TypedTypeParam],
+        type_arguments: &[TypedType],
+    ) -> Vec<(TypedType, TypedType)> {
+        let arena = self.context.arena();
+        let mut bindings = vec![];
+        for (type_param, type_argument) in type_params.iter().zip(type_arguments) {
+            let param_associated_types = match arena.get_type(namespace, &type_param.name) {
+                Some(rs) => &rs.associated_types,
+                None => continue,
+            };
+            let argument_associated_types = match type_argument {
+                TypedType::Value(TypedValueType::Value(v)) => {
+                    match arena.get_type(&v.package.clone().into_resolved().names, &v.name) {
+                        Some(rs) => &rs.associated_types,
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+            for (name, placeholder) in param_associated_types {
+                if let Some(bound) = argument_associated_types.get(name) {
+                    bindings.push((placeholder.clone(), bound.clone()));
+                }
+            }
+        }
+        bindings
+    }
+
+    /// Check that type arguments of generic types in `t` conform to their type parameters.
+    fn check_type_arguments(&mut self, t: &TypedType) -> Result<()> {
+        match t {
+            TypedType::Self_ => Ok(()),
+            TypedType::Type(t)
+            | TypedType::Value(TypedValueType::Array(t, _))
+            | TypedType::Value(TypedValueType::Slice(t))
+            | TypedType::Value(TypedValueType::Pointer(t))
+            | TypedType::Value(TypedValueType::Reference(t)) => self.check_type_arguments(t),
+            TypedType::Value(TypedValueType::Tuple(ts)) => {
+                ts.iter().try_for_each(|t| self.check_type_arguments(t))
+            }
+            TypedType::Function(f) | TypedType::Value(TypedValueType::Function(f)) => {
+                for a in f.arguments.iter() {
+                    self.check_type_arguments(&a.typ)?;
+                }
+                self.check_type_arguments(&f.return_type)
+            }
+            TypedType::Value(TypedValueType::Value(v)) => {
+                let type_arguments = match &v.type_args {
+                    Some(type_arguments) => type_arguments,
+                    None => return Ok(()),
+                };
+                let mut fqn = v.package.clone().into_resolved().names;
+                let type_params = self
+                    .context
+                    .arena()
+                    .get_type(&fqn, &v.name)
+                    .and_then(|rs| rs.type_parameters.clone())
+                    .unwrap_or_default();
+                fqn.push(v.name.clone());
+                for type_argument in type_arguments {
+                    self.check_type_arguments(type_argument)?;
+                }
+                for (type_param, type_argument) in type_params.iter().zip(type_arguments) {
+                    self.check_type_constraint(&v.name, &fqn, type_param, type_argument)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Check that `type_argument` conforms to all protocols that `type_param` is bounded.
+    fn check_type_constraint(
+        &mut self,
+        name: &str,
+        namespace: &[String],
+        type_param: &TypedTypeParam,
+        type_argument: &TypedType,
+    ) -> Result<()> {
+        let arena = self.context.arena_mut();
+        let constraints = match arena.get_type(namespace, &type_param.name) {
+            Some(rs) => rs.conformed_protocols.clone(),
+            None => return Ok(()),
+        };
+        let conformed_protocols = match type_argument {
+            TypedType::Value(TypedValueType::Value(v)) => arena
+                .get_type(&v.package.clone().into_resolved().names, &v.name)
+                .map(|rs| rs.conformed_protocols.clone())
+                .unwrap_or_default(),
+            _ => Default::default(),
+        };
+        let mut not_conformed = constraints
+            .difference(&conformed_protocols)
+            .map(|p| format!("`{}`", p))
+            .collect::<Vec<_>>();
+        if not_conformed.is_empty() {
+            Ok(())
+        } else {
+            not_conformed.sort();
+            Err(ResolverError::from(format!(
+                "`{}` does not conform to {} required by type parameter `{}` of `{}`.",
+                type_argument.to_string(),
+                not_conformed.join(", "),
+                type_param.name,
+                name
+            )))
+        }
+    }
+
     pub fn typed_call_arg(
         &mut self,
         a: TypedCallArg,
@@ -1076,6 +2325,7 @@ impl<'s> TypeResolver<'s> {
             target: Box::new(self.expr(*t.target, None)?),
             is_safe: t.is_safe,
             type_: self.context.full_type_name(&t.type_)?,
+            location: t.location,
         };
         let ty = kind.type_.clone();
         Ok((kind, Some(ty)))
@@ -1085,8 +2335,14 @@ impl<'s> TypeResolver<'s> {
         Ok(match s {
             TypedStmt::Expr(e) => TypedStmt::Expr(self.expr(e, None)?),
             TypedStmt::Decl(d) => TypedStmt::Decl({
-                let mut d = self.decl(d)?;
-                if let TypedDeclKind::Var(v) = &d.kind {
+                let mut d = match d.kind {
+                    TypedDeclKind::Var(v) => TypedTopLevelDecl {
+                        kind: TypedDeclKind::Var(self.typed_var(v)?),
+                        ..d
+                    },
+                    kind => self.decl(TypedTopLevelDecl { kind, ..d })?,
+                };
+                if let TypedDeclKind::Var(v) | TypedDeclKind::Const(v) = &d.kind {
                     d.package = Package::new();
                     self.context.register_to_env(
                         v.name.clone(),
@@ -1102,6 +2358,7 @@ impl<'s> TypeResolver<'s> {
             }),
             TypedStmt::Assignment(a) => TypedStmt::Assignment(self.assignment_stmt(a)?),
             TypedStmt::Loop(l) => TypedStmt::Loop(self.typed_loop_stmt(l)?),
+            TypedStmt::Defer(b) => TypedStmt::Defer(self.typed_block(b)?),
         })
     }
 
@@ -1117,9 +2374,13 @@ impl<'s> TypeResolver<'s> {
     }
 
     pub fn typed_assignment(&mut self, a: TypedAssignment) -> Result<TypedAssignment> {
+        let target = self.expr(a.target, None)?;
+        let value_type = self
+            .subscript_setter_value_type(&target)?
+            .or_else(|| target.ty.clone());
         Ok(TypedAssignment {
-            target: self.expr(a.target, None)?,
-            value: self.expr(a.value, None)?,
+            target,
+            value: self.expr(a.value, value_type)?,
         })
     }
 
@@ -1127,13 +2388,47 @@ impl<'s> TypeResolver<'s> {
         &mut self,
         a: TypedAssignmentAndOperation,
     ) -> Result<TypedAssignmentAndOperation> {
+        let target = self.expr(a.target, None)?;
+        let value_type = self
+            .subscript_setter_value_type(&target)?
+            .or_else(|| target.ty.clone());
         Ok(TypedAssignmentAndOperation {
-            target: self.expr(a.target, None)?,
+            target,
             operator: a.operator, // TODO
-            value: self.expr(a.value, None)?,
+            value: self.expr(a.value, value_type)?,
         })
     }
 
+    /// When `target` is a subscript declared by a struct, the assignment calls its setter,
+    /// which takes the element type.
+    fn subscript_setter_value_type(&mut self, target: &TypedExpr) -> Result<Option<TypedType>> {
+        let s = match &target.kind {
+            TypedExprKind::Subscript(s) => s,
+            _ => return Ok(None),
+        };
+        let (name, subscripts) = match self.struct_subscripts(s.target.ty.as_ref().unwrap()) {
+            Some(subscripts) => subscripts,
+            None => return Ok(None),
+        };
+        let index_types = s
+            .indexes
+            .iter()
+            .map(|i| i.ty.clone().unwrap())
+            .collect::<Vec<_>>();
+        match subscripts
+            .iter()
+            .find(|c| c.index_types().eq(index_types.iter()))
+        {
+            Some(subscript) if subscript.setter.is_some() => {
+                Ok(Some(subscript.element_type().clone()))
+            }
+            _ => Err(ResolverError::from(format!(
+                "Cannot assign through subscript of `{}`, because it has no setter",
+                name
+            ))),
+        }
+    }
+
     pub fn typed_loop_stmt(&mut self, l: TypedLoopStmt) -> Result<TypedLoopStmt> {
         Ok(match l {
             TypedLoopStmt::While(w) => TypedLoopStmt::While(self.typed_while_loop_stmt(w)?),
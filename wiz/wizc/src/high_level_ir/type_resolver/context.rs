@@ -4,20 +4,25 @@ pub(crate) use crate::high_level_ir::type_resolver::context::env_value::EnvValue
 use crate::high_level_ir::type_resolver::error::ResolverError;
 use crate::high_level_ir::type_resolver::name_environment::NameEnvironment;
 use crate::high_level_ir::type_resolver::result::Result;
+use crate::high_level_ir::type_resolver::type_parameter::TypeParameterSubstitution;
 use std::collections::HashMap;
 use wiz_arena::{Arena, ArenaStruct, DeclarationId, DeclarationItemKind};
 use wiz_data_structure::annotation::Annotations;
 use wiz_hir::typed_decl::TypedFunBody;
-use wiz_hir::typed_expr::TypedBinaryOperator;
+use wiz_hir::typed_expr::{TypedBinaryOperator, TypedExpr};
 use wiz_hir::typed_type::{
     Package, TypedArgType, TypedFunctionType, TypedNamedValueType, TypedPackage, TypedType,
     TypedTypeParam, TypedValueType,
 };
+use wiz_hir::typed_use::TypedUse;
 use wiz_utils::StackedHashMap;
 
+/// Namespaces whose names are usable in every file without `use`.
+const PRELUDE: [[&str; 2]; 2] = [["core", "builtin"], ["std", "builtin"]];
+
 #[derive(Debug)]
 pub struct ResolverContext<'a> {
-    used_name_space: Vec<Vec<String>>,
+    used_name_space: Vec<TypedUse>,
     arena: &'a mut Arena,
     current_namespace_id: DeclarationId,
     local_stack: StackedHashMap<String, EnvValue>,
@@ -77,7 +82,8 @@ impl<'a> ResolverContext<'a> {
             DeclarationItemKind::Type(_) => Some(id),
             DeclarationItemKind::Namespace
             | DeclarationItemKind::Variable(_)
-            | DeclarationItemKind::Function(..) => self._current_type_id(item.parent()?),
+            | DeclarationItemKind::Function(..)
+            | DeclarationItemKind::TypeAlias(_) => self._current_type_id(item.parent()?),
         }
     }
 
@@ -87,7 +93,8 @@ impl<'a> ResolverContext<'a> {
             DeclarationItemKind::Type(rs) => Some(rs),
             DeclarationItemKind::Namespace
             | DeclarationItemKind::Variable(_)
-            | DeclarationItemKind::Function(..) => None,
+            | DeclarationItemKind::Function(..)
+            | DeclarationItemKind::TypeAlias(_) => None,
         }
     }
 
@@ -99,9 +106,9 @@ impl<'a> ResolverContext<'a> {
         let item = self.arena().get_by_id(&id)?;
         match &item.kind {
             DeclarationItemKind::Namespace => Some(id),
-            DeclarationItemKind::Type(_) | DeclarationItemKind::Function(..) => {
-                self._current_module_id(item.parent().unwrap())
-            }
+            DeclarationItemKind::Type(_)
+            | DeclarationItemKind::Function(..)
+            | DeclarationItemKind::TypeAlias(_) => self._current_module_id(item.parent().unwrap()),
             DeclarationItemKind::Variable(_) => None,
         }
     }
@@ -146,18 +153,26 @@ impl<'a> ResolverContext<'a> {
             .resolve_fully_qualified_name(&self.current_namespace_id);
         env.use_asterisk(&namespace_name);
 
-        for u in self.used_name_space.iter() {
+        for prelude in PRELUDE {
+            env.use_asterisk(&prelude.map(String::from));
+        }
+
+        // NOTE: names used explicitly take precedence over ones used by `use a::*`
+        for u in self.used_name_space.iter().filter(|u| u.is_glob()) {
+            env.use_(u);
+        }
+        for u in self.used_name_space.iter().filter(|u| !u.is_glob()) {
             env.use_(u);
         }
         env
     }
 
-    pub(crate) fn use_name_space(&mut self, n: Vec<String>) {
-        self.used_name_space.push(n);
+    pub(crate) fn use_name_space(&mut self, u: TypedUse) {
+        self.used_name_space.push(u);
     }
 
-    pub(crate) fn unuse_name_space(&mut self, n: &Vec<String>) {
-        let i = self.used_name_space.iter().rposition(|i| i.eq(n));
+    pub(crate) fn unuse_name_space(&mut self, u: &TypedUse) {
+        let i = self.used_name_space.iter().rposition(|i| i.eq(u));
         if let Some(i) = i {
             self.used_name_space.remove(i);
         };
@@ -213,6 +228,7 @@ impl<'a> ResolverContext<'a> {
             TypedValueType::Array(a, n) => {
                 TypedValueType::Array(Box::new(self.full_type_name(a)?), *n)
             }
+            TypedValueType::Slice(t) => TypedValueType::Slice(Box::new(self.full_type_name(t)?)),
             TypedValueType::Tuple(_) => {
                 todo!()
             }
@@ -222,6 +238,9 @@ impl<'a> ResolverContext<'a> {
             TypedValueType::Reference(t) => {
                 TypedValueType::Reference(Box::new(self.full_type_name(t)?))
             }
+            TypedValueType::Function(f) => {
+                TypedValueType::Function(Box::new(self.full_function_type_name(f)?))
+            }
         })
     }
 
@@ -233,10 +252,12 @@ impl<'a> ResolverContext<'a> {
         Ok(match type_.package {
             TypedPackage::Raw(ref p) => {
                 let env_value = env.get_env_item(&p.names, &type_.name).ok_or_else(|| {
-                    ResolverError::from(format!(
-                        "Cannot resolve name => {:?}{}",
-                        &p.names, &type_.name
-                    ))
+                    env.ambiguity(&p.names, &type_.name).unwrap_or_else(|| {
+                        ResolverError::from(format!(
+                            "Cannot resolve name => {:?}{}",
+                            &p.names, &type_.name
+                        ))
+                    })
                 })?;
                 match env_value {
                     EnvValue::Type(id) => {
@@ -264,22 +285,70 @@ impl<'a> ResolverContext<'a> {
     pub fn full_type_name(&self, typ: &TypedType) -> Result<TypedType> {
         let env = self.get_current_name_environment();
         Ok(match typ {
-            TypedType::Value(v) => TypedType::Value(self.full_value_type_name(v)?),
+            TypedType::Value(v) => match self.expand_type_alias(v)? {
+                Some(t) => t,
+                None => TypedType::Value(self.full_value_type_name(v)?),
+            },
             TypedType::Type(v) => TypedType::Type(Box::new(self.full_type_name(v)?)),
             TypedType::Self_ => env.resolve_current_type()?,
-            TypedType::Function(f) => TypedType::Function(Box::new(TypedFunctionType {
-                arguments: f
-                    .arguments
-                    .iter()
-                    .map(|a| {
-                        Ok(TypedArgType {
-                            label: a.label.clone(),
-                            typ: self.full_type_name(&a.typ)?,
-                        })
+            TypedType::Function(f) => {
+                TypedType::Function(Box::new(self.full_function_type_name(f)?))
+            }
+        })
+    }
+
+    /// Expand `type_` if it names a type alias, with its type parameters substituted.
+    fn expand_type_alias(&self, type_: &TypedValueType) -> Result<Option<TypedType>> {
+        let (package, type_) = match type_ {
+            TypedValueType::Value(t) => match &t.package {
+                TypedPackage::Raw(p) => (p, t),
+                TypedPackage::Resolved(_) => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        let env = self.get_current_name_environment();
+        let id = match env.get_env_item(&package.names, &type_.name) {
+            Some(EnvValue::Type(id)) => id,
+            _ => return Ok(None),
+        };
+        let type_alias = match self.arena().get_type_alias_by_id(&id) {
+            Some(type_alias) => type_alias,
+            None => return Ok(None),
+        };
+        if !type_alias.is_resolved {
+            return Err(ResolverError::from(format!(
+                "Cannot resolve type alias `{}`",
+                type_.name
+            )));
+        }
+        let type_arguments = type_
+            .type_args
+            .iter()
+            .flatten()
+            .map(|t| self.full_type_name(t))
+            .collect::<Result<Vec<_>>>()?;
+        let substitution = TypeParameterSubstitution::new(
+            &type_.name,
+            Package::from(&self.arena().resolve_fully_qualified_name(&id)),
+            type_alias.type_parameters.clone().unwrap_or_default(),
+        )
+        .with_type_arguments(&type_arguments)?;
+        Ok(Some(substitution.substitute(&type_alias.type_)))
+    }
+
+    fn full_function_type_name(&self, f: &TypedFunctionType) -> Result<TypedFunctionType> {
+        Ok(TypedFunctionType {
+            arguments: f
+                .arguments
+                .iter()
+                .map(|a| {
+                    Ok(TypedArgType {
+                        label: a.label.clone(),
+                        typ: self.full_type_name(&a.typ)?,
                     })
-                    .collect::<Result<Vec<_>>>()?,
-                return_type: self.full_type_name(&f.return_type)?,
-            })),
+                })
+                .collect::<Result<Vec<_>>>()?,
+            return_type: self.full_type_name(&f.return_type)?,
         })
     }
 
@@ -315,6 +384,90 @@ impl<'a> ResolverContext<'a> {
         self.arena_mut().register_value(&id, name, ty, annotation)
     }
 
+    /// Register type alias `name`, or find it if it is already registered by ast lowering.
+    pub(crate) fn register_type_alias(
+        &mut self,
+        name: &str,
+        type_parameters: Option<Vec<TypedTypeParam>>,
+        ty: TypedType,
+        annotation: Annotations,
+    ) -> Option<DeclarationId> {
+        let namespace_id = self.current_namespace_id;
+        let arena = self.arena_mut();
+        let registered = arena
+            .get_by_id(&namespace_id)?
+            .get_child(name)
+            .and_then(|ids| {
+                ids.iter()
+                    .find(|id| arena.get_type_alias_by_id(id).is_some())
+            })
+            .copied();
+        match registered {
+            Some(id) => Some(id),
+            None => arena.register_type_alias(&namespace_id, name, type_parameters, ty, annotation),
+        }
+    }
+
+    /// Register constant `name`, or update it if it is already registered by ast lowering.
+    pub(crate) fn register_constant(
+        &mut self,
+        name: &str,
+        ty: TypedType,
+        value: TypedExpr,
+        annotation: Annotations,
+    ) -> Option<DeclarationId> {
+        let namespace_id = self.current_namespace_id;
+        let arena = self.arena_mut();
+        let registered = arena
+            .get_by_id(&namespace_id)?
+            .get_child(name)
+            .and_then(|ids| ids.iter().find(|id| arena.get_constant(id).is_some()))
+            .copied();
+        match registered {
+            Some(id) => {
+                arena.update_constant(&id, ty, value)?;
+                Some(id)
+            }
+            None => arena.register_constant(&namespace_id, name, ty, value, annotation),
+        }
+    }
+
+    /// Find function `name` registered by ast lowering, whose type is not resolved yet.
+    pub(crate) fn lowered_function(
+        &self,
+        name: &str,
+        arg_names: &[String],
+        body: &Option<TypedFunBody>,
+    ) -> Option<DeclarationId> {
+        let arena = self.arena();
+        arena
+            .get_by_id(&self.current_namespace_id)?
+            .get_child(name)?
+            .iter()
+            .find(|id| {
+                matches!(
+                    arena.get_by_id(id).map(|item| &item.kind),
+                    Some(DeclarationItemKind::Function(rf))
+                        if rf.ty == TypedType::noting() && rf.arg_names == arg_names && rf.body == *body
+                )
+            })
+            .copied()
+    }
+
+    pub(crate) fn update_arg_names(
+        &mut self,
+        id: &DeclarationId,
+        arg_names: Vec<String>,
+    ) -> Option<()> {
+        match &mut self.arena_mut().get_mut_by_id(id)?.kind {
+            DeclarationItemKind::Function(rf) => {
+                rf.arg_names = arg_names;
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
     pub(crate) fn update_function(&mut self, id: &DeclarationId, ty: TypedType) -> Option<()> {
         let item = self.arena_mut().get_mut_by_id(id)?;
         if let DeclarationItemKind::Function(rf) = &item.kind {
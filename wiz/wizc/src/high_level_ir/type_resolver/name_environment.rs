@@ -1,15 +1,21 @@
 use crate::high_level_ir::type_resolver::context::EnvValue;
 use crate::high_level_ir::type_resolver::error::ResolverError;
 use crate::high_level_ir::type_resolver::result::Result;
+use crate::intrinsic::{verify_atomic_value_type, Intrinsic};
 use std::collections::{HashMap, HashSet};
 use wiz_arena::{Arena, ArenaStruct, DeclarationId, DeclarationItemKind};
 use wiz_hir::typed_type::{Package, TypedPackage, TypedType, TypedValueType};
+use wiz_hir::typed_use::TypedUse;
 use wiz_utils::StackedHashMap;
 
 #[derive(Debug, Clone)]
 pub(crate) struct NameEnvironment<'a> {
     local_stack: StackedHashMap<String, EnvValue>,
     values: HashMap<String, HashSet<DeclarationId>>,
+    /// Names used by `use [namespace]::*`, with the namespace.
+    glob_uses: HashMap<String, Vec<String>>,
+    /// Names used by `use [namespace]::*` from several namespaces declaring them differently.
+    ambiguous_names: HashMap<String, (Vec<String>, Vec<String>)>,
     arena: &'a Arena,
 }
 
@@ -35,7 +41,7 @@ impl<'a> NameEnvironment<'a> {
                 let ids = ids.iter().collect::<Vec<_>>();
                 let items = arena.get_by_ids(&ids).unwrap();
                 let env_item = if let Some(i) = items.first() {
-                    if let DeclarationItemKind::Type(_) = i.kind {
+                    if i.is_type() {
                         EnvValue::from(**ids.first().unwrap())
                     } else if let DeclarationItemKind::Namespace = i.kind {
                         EnvValue::Namespace(**ids.first().unwrap())
@@ -64,6 +70,8 @@ impl<'a> NameEnvironment<'a> {
         Self {
             local_stack: init_local_stack(arena, self_id, local_stack),
             values: arena.get_root().children().clone(),
+            glob_uses: Default::default(),
+            ambiguous_names: Default::default(),
             arena,
         }
     }
@@ -74,26 +82,69 @@ impl<'a> NameEnvironment<'a> {
     pub(crate) fn use_asterisk(&mut self, namespace: &[String]) -> Option<()> {
         let ns_id = self.arena.resolve_declaration_id_from_root(namespace)?;
         let ns = self.arena.get_by_id(&ns_id).unwrap();
-        self.values.extend(ns.children().clone());
+        self.values.extend(ns.visible_children());
         Some(())
     }
 
-    /// use [namespace]::[name];
-    pub(crate) fn use_(&mut self, fqn: &[String]) -> Option<()> {
-        let last = fqn.last()?;
-        if last == "*" {
-            self.use_asterisk(&fqn[..fqn.len() - 1])?;
+    /// use [namespace]::[name] as [alias];
+    pub(crate) fn use_(&mut self, u: &TypedUse) -> Option<()> {
+        let fqn = &u.package.names;
+        if u.is_glob() {
+            self.use_glob(&fqn[..fqn.len() - 1])?;
         } else {
-            let item = self
+            let items = self
                 .arena
-                .resolve_declaration_id_from_root(fqn)
-                .expect(&format!("Can not use {:?}", fqn));
-            let entry = self.values.entry(last.to_string()).or_default();
-            entry.insert(item);
+                .resolve_declaration_ids_from_root(fqn)
+                .unwrap_or_else(|| panic!("Can not use {:?}", fqn));
+            let name = u.name()?;
+            self.ambiguous_names.remove(name);
+            if self.glob_uses.remove(name).is_some() {
+                self.values.insert(name.to_string(), items.clone());
+            } else {
+                let entry = self.values.entry(name.to_string()).or_default();
+                entry.extend(items);
+            }
         };
         Some(())
     }
 
+    /// Names used by `use a::*` from several namespaces are ambiguous, unless all of
+    /// them refer to the same declarations. Names declared or used explicitly are
+    /// never replaced.
+    fn use_glob(&mut self, namespace: &[String]) -> Option<()> {
+        let ns_id = self.arena.resolve_declaration_id_from_root(namespace)?;
+        let ns = self.arena.get_by_id(&ns_id).unwrap();
+        for (name, ids) in ns.visible_children() {
+            match self.glob_uses.get(&name) {
+                None if self.values.contains_key(&name) => {}
+                Some(other) if self.values.get(&name) != Some(&ids) => {
+                    self.ambiguous_names
+                        .insert(name, (other.clone(), namespace.to_vec()));
+                }
+                _ => {
+                    self.glob_uses.insert(name.clone(), namespace.to_vec());
+                    self.values.insert(name, ids);
+                }
+            }
+        }
+        Some(())
+    }
+
+    /// Error for `namespace::name` which is not resolved because of ambiguous `use a::*`.
+    pub(crate) fn ambiguity(&self, namespace: &[String], name: &str) -> Option<ResolverError> {
+        let name = namespace.first().map(String::as_str).unwrap_or(name);
+        if self.local_stack.get(name).is_some() {
+            return None;
+        }
+        let (a, b) = self.ambiguous_names.get(name)?;
+        Some(ResolverError::from(format!(
+            "`{}` is ambiguous, because it is used by both `{}::*` and `{}::*`",
+            name,
+            a.join("::"),
+            b.join("::")
+        )))
+    }
+
     pub(crate) fn get_type(&self, name_space: &[String], type_name: &str) -> Option<&ArenaStruct> {
         self.arena
             .get_type_by_id(&self.get_type_id(name_space, type_name)?)
@@ -119,11 +170,14 @@ impl<'a> NameEnvironment<'a> {
             let maybe_local_value = self.local_stack.get(name).cloned();
             match maybe_local_value {
                 None => {
+                    if self.ambiguous_names.contains_key(name) {
+                        return None;
+                    }
                     let ids = self.values.get(name)?;
                     let ids = ids.iter().collect::<Vec<_>>();
                     let items = self.arena.get_by_ids(&ids)?;
                     if !items.is_empty() {
-                        return if let DeclarationItemKind::Type(_) = &items.first().unwrap().kind {
+                        return if items.first().unwrap().is_type() {
                             Some(EnvValue::from(**ids.first().unwrap()))
                         } else {
                             let mut values = HashSet::new();
@@ -144,6 +198,11 @@ impl<'a> NameEnvironment<'a> {
                 Some(t) => Some(t),
             }
         } else {
+            if self.local_stack.get(&namespace[0]).is_none()
+                && self.ambiguous_names.contains_key(&namespace[0])
+            {
+                return None;
+            }
             let ids = self.values.get(&namespace[0])?;
             let ids = ids.iter().copied().collect::<Vec<_>>();
             let parent_id = ids.first()?;
@@ -155,7 +214,7 @@ impl<'a> NameEnvironment<'a> {
             let children = children.iter().collect::<Vec<_>>();
             let items = self.arena.get_by_ids(&children)?;
             if !items.is_empty() {
-                return if let DeclarationItemKind::Type(_) = &items.first().unwrap().kind {
+                return if items.first().unwrap().is_type() {
                     Some(EnvValue::from(**children.first().unwrap()))
                 } else {
                     let mut values = HashSet::new();
@@ -180,6 +239,21 @@ impl<'a> NameEnvironment<'a> {
     pub fn resolve_member_type(&self, t: TypedType, name: &str) -> Result<TypedType> {
         match t {
             TypedType::Value(v) => match v {
+                TypedValueType::Value(v) if v.is_atomic() => {
+                    let value_type = v
+                        .atomic_value_type()
+                        .ok_or_else(|| ResolverError::from("`Atomic` requires a type argument"))?;
+                    verify_atomic_value_type(value_type)?;
+                    Intrinsic::from_atomic_method(name)
+                        .map(|i| TypedType::Function(Box::new(i.atomic_method_type(value_type))))
+                        .ok_or_else(|| {
+                            ResolverError::from(format!(
+                                "`{}` not has member named `{}`",
+                                v.to_string(),
+                                name
+                            ))
+                        })
+                }
                 TypedValueType::Value(v) => {
                     let rs = self
                         .get_type(&v.package.clone().into_resolved().names, &v.name)
@@ -209,9 +283,21 @@ impl<'a> NameEnvironment<'a> {
                         })
                 }
                 TypedValueType::Array(_, _) => todo!(),
+                TypedValueType::Slice(e) => match name {
+                    "pointer" => Ok(TypedType::unsafe_pointer(*e)),
+                    "length" => Ok(TypedType::usize()),
+                    _ => Err(ResolverError::from(format!(
+                        "slice has no member named `{}`",
+                        name
+                    ))),
+                },
                 TypedValueType::Tuple(_) => todo!(),
                 TypedValueType::Pointer(_) => todo!(),
                 TypedValueType::Reference(rt) => self.resolve_member_type(*rt, name),
+                TypedValueType::Function(_) => Err(ResolverError::from(format!(
+                    "function pointer has no member {}",
+                    name
+                ))),
             },
             TypedType::Type(v) => Err(ResolverError::from(format!(
                 "{:?} has no member {}",
@@ -234,7 +320,9 @@ impl<'a> NameEnvironment<'a> {
         type_annotation: Option<TypedType>,
     ) -> Result<(TypedType, TypedPackage)> {
         let env_value = self.get_env_item(&name_space, name).ok_or_else(|| {
-            ResolverError::from(format!("Cannot resolve name =>{:?} {:?}", name_space, name))
+            self.ambiguity(&name_space, name).unwrap_or_else(|| {
+                ResolverError::from(format!("Cannot resolve name =>{:?} {:?}", name_space, name))
+            })
         })?;
         match env_value {
             EnvValue::Value(t_set) => self
@@ -260,6 +348,23 @@ impl<'a> NameEnvironment<'a> {
                         name
                     ))
                 }),
+            EnvValue::Type(id) if self.arena.get_type_alias_by_id(&id).is_some() => {
+                let ta = self.arena.get_type_alias_by_id(&id).unwrap();
+                match &ta.type_ {
+                    TypedType::Value(TypedValueType::Value(t))
+                        if ta.is_resolved && !ta.is_generic() =>
+                    {
+                        Ok((
+                            TypedType::Type(Box::new(ta.type_.clone())),
+                            t.package.clone(),
+                        ))
+                    }
+                    _ => Err(ResolverError::from(format!(
+                        "type alias `{}` can not be used as a value",
+                        name
+                    ))),
+                }
+            }
             EnvValue::Type(id) => {
                 let rs = self.arena.get_type_by_id(&id).unwrap();
                 let self_type = rs.self_type();
@@ -286,6 +391,12 @@ impl<'a> NameEnvironment<'a> {
                         return Some((id, ty));
                     }
                 }
+            } else if let Some(annotation @ TypedType::Value(TypedValueType::Function(_))) =
+                &type_annotation
+            {
+                if ty.is_function_type() && *annotation == ty.clone().decay() {
+                    return Some((id, ty));
+                }
             }
         }
         None
@@ -1,23 +1,33 @@
+use crate::high_level_ir::wlib::WLib;
 use crate::high_level_ir::AstLowering;
 use crate::ModuleId;
+use std::fs::{create_dir_all, remove_dir_all, write};
+use std::path::Path;
 use wiz_arena::Arena;
+use wiz_constants::annotation::DERIVE;
 use wiz_hir::typed_decl::{
     TypedArgDef, TypedDeclKind, TypedFun, TypedFunBody, TypedStoredProperty, TypedStruct,
     TypedTopLevelDecl, TypedVar,
 };
 use wiz_hir::typed_expr::{
     TypedBinOp, TypedBinaryOperator, TypedCall, TypedCallArg, TypedExpr, TypedExprKind, TypedIf,
-    TypedInstanceMember, TypedLiteralKind, TypedName, TypedPrefixUnaryOp, TypedPrefixUnaryOperator,
-    TypedReturn, TypedSubscript, TypedTypeCast, TypedUnaryOp,
+    TypedInstanceMember, TypedLiteralKind, TypedLocation, TypedName, TypedPrefixUnaryOp,
+    TypedPrefixUnaryOperator, TypedReturn, TypedSourceLocationKind, TypedSubscript, TypedTypeCast,
+    TypedUnaryOp,
 };
 use wiz_hir::typed_file::TypedSpellBook;
-use wiz_hir::typed_stmt::{TypedBlock, TypedStmt};
+use wiz_hir::typed_stmt::{TypedAssignmentStmt, TypedBlock, TypedStmt};
 use wiz_hir::typed_type::{
     Package, TypedArgType, TypedFunctionType, TypedNamedValueType, TypedPackage, TypedType,
-    TypedValueType,
+    TypedTypeParam, TypedValueType,
 };
-use wiz_session::{ParseSession, Session};
-use wiz_syntax_parser::parser::wiz::parse_from_string;
+use wiz_hir::typed_type_constraint::TypedTypeConstraint;
+use wiz_session::{DataLayout, ParseSession, Session};
+use wiz_syntax::syntax::declaration::{DeclKind, DeclarationSyntax};
+use wiz_syntax::syntax::{FileSyntax, WizFile};
+use wiz_syntax_parser::parser::wiz::{parse_from_file_path, parse_from_string};
+use wiz_utils::temp::temp_dir;
+use wizc_cli::{Config, ConfigBuilder};
 
 fn check(source: &str, typed_file: TypedSpellBook) {
     let session = ParseSession::default();
@@ -34,6 +44,14 @@ fn check(source: &str, typed_file: TypedSpellBook) {
     assert_eq!(f, typed_file);
 }
 
+fn location(line: u32, column: u32) -> Option<TypedLocation> {
+    Some(TypedLocation {
+        file: "<unknown source>".to_string(),
+        line,
+        column,
+    })
+}
+
 #[test]
 fn test_empty() {
     let source = "";
@@ -71,11 +89,15 @@ fn test_unsafe_pointer() {
                     kind: TypedDeclKind::Struct(TypedStruct {
                         name: "A".to_string(),
                         type_params: None,
+                        type_constraints: None,
                         stored_properties: vec![TypedStoredProperty {
+                            is_mut: false,
                             name: "a".to_string(),
                             type_: TypedType::unsafe_pointer(TypedType::uint8()),
                         }],
                         computed_properties: vec![],
+                        initializers: vec![],
+                        subscripts: vec![],
                         member_functions: vec![TypedFun::size(TypedType::Value(
                             TypedValueType::Value(TypedNamedValueType {
                                 package: TypedPackage::Resolved(Package::from(&["test"])),
@@ -101,6 +123,7 @@ fn test_unsafe_pointer() {
                                 name: "A".to_string(),
                                 type_args: None,
                             })),
+                            default: None,
                         }],
                         body: Some(TypedFunBody::Block(TypedBlock {
                             body: vec![TypedStmt::Decl(TypedTopLevelDecl {
@@ -169,11 +192,15 @@ fn test_struct_stored_property() {
                     kind: TypedDeclKind::Struct(TypedStruct {
                         name: "A".to_string(),
                         type_params: None,
+                        type_constraints: None,
                         stored_properties: vec![TypedStoredProperty {
+                            is_mut: false,
                             name: "a".to_string(),
                             type_: TypedType::int64(),
                         }],
                         computed_properties: vec![],
+                        initializers: vec![],
+                        subscripts: vec![],
                         member_functions: vec![TypedFun::size(TypedType::Value(
                             TypedValueType::Value(TypedNamedValueType {
                                 package: TypedPackage::Resolved(Package::from(&["test"])),
@@ -199,6 +226,7 @@ fn test_struct_stored_property() {
                                 name: "A".to_string(),
                                 type_args: None,
                             })),
+                            default: None,
                         }],
                         body: Some(TypedFunBody::Block(TypedBlock {
                             body: vec![TypedStmt::Decl(TypedTopLevelDecl {
@@ -267,11 +295,15 @@ fn test_struct_init() {
                     kind: TypedDeclKind::Struct(TypedStruct {
                         name: "A".to_string(),
                         type_params: None,
+                        type_constraints: None,
                         stored_properties: vec![TypedStoredProperty {
+                            is_mut: false,
                             name: "a".to_string(),
                             type_: TypedType::int64(),
                         }],
                         computed_properties: vec![],
+                        initializers: vec![],
+                        subscripts: vec![],
                         member_functions: vec![TypedFun::size(TypedType::Value(
                             TypedValueType::Value(TypedNamedValueType {
                                 package: TypedPackage::Resolved(Package::from(&["test"])),
@@ -297,6 +329,7 @@ fn test_struct_init() {
                                 name: "A".to_string(),
                                 type_args: None,
                             })),
+                            default: None,
                         }],
                         body: Some(TypedFunBody::Block(TypedBlock {
                             body: vec![TypedStmt::Decl(TypedTopLevelDecl {
@@ -345,6 +378,7 @@ fn test_struct_init() {
                                                 )),
                                                 is_vararg: false,
                                             }],
+                                            location: location(6, 21),
                                         }),
                                         Some(TypedType::Value(TypedValueType::Value(
                                             TypedNamedValueType {
@@ -391,25 +425,29 @@ fn test_struct_member_function() {
                 kind: TypedDeclKind::Struct(TypedStruct {
                     name: "A".to_string(),
                     type_params: None,
+                    type_constraints: None,
                     stored_properties: vec![TypedStoredProperty {
+                        is_mut: false,
                         name: "a".to_string(),
                         type_: TypedType::int64(),
                     }],
                     computed_properties: vec![],
+                    initializers: vec![],
+                    subscripts: vec![],
                     member_functions: vec![
                         TypedFun {
                             name: "getA".to_string(),
                             arg_defs: vec![TypedArgDef {
                                 label: "_".to_string(),
                                 name: "self".to_string(),
-                                type_: TypedType::Value(
-                                    // TODO: Reference
+                                type_: TypedType::reference(TypedType::Value(
                                     TypedValueType::Value(TypedNamedValueType {
                                         package: TypedPackage::Resolved(Package::from(&["test"])),
                                         name: "A".to_string(),
                                         type_args: None,
                                     }),
-                                ),
+                                )),
+                                default: None,
                             }],
                             type_params: None,
                             body: Some(TypedFunBody::Block(TypedBlock {
@@ -425,14 +463,16 @@ fn test_struct_member_function() {
                                                         name: "self".to_string(),
                                                         type_arguments: None,
                                                     }),
-                                                    Some(TypedType::Value(TypedValueType::Value(
-                                                        TypedNamedValueType {
-                                                            package: TypedPackage::Resolved(
-                                                                Package::from(&["test"]),
-                                                            ),
-                                                            name: "A".to_string(),
-                                                            type_args: None,
-                                                        },
+                                                    Some(TypedType::reference(TypedType::Value(
+                                                        TypedValueType::Value(
+                                                            TypedNamedValueType {
+                                                                package: TypedPackage::Resolved(
+                                                                    Package::from(&["test"]),
+                                                                ),
+                                                                name: "A".to_string(),
+                                                                type_args: None,
+                                                            },
+                                                        ),
                                                     ))),
                                                 )),
                                                 name: "a".to_string(),
@@ -490,19 +530,22 @@ fn test_struct_member_function_call() {
                     kind: TypedDeclKind::Struct(TypedStruct {
                         name: "A".to_string(),
                         type_params: None,
+                        type_constraints: None,
                         stored_properties: vec![TypedStoredProperty {
+                            is_mut: false,
                             name: "a".to_string(),
                             type_: TypedType::int64(),
                         }],
                         computed_properties: vec![],
+                        initializers: vec![],
+                        subscripts: vec![],
                         member_functions: vec![
                             TypedFun {
                                 name: "getA".to_string(),
                                 arg_defs: vec![TypedArgDef {
                                     label: "_".to_string(),
                                     name: "self".to_string(),
-                                    type_: TypedType::Value(
-                                        // TODO: Reference
+                                    type_: TypedType::reference(TypedType::Value(
                                         TypedValueType::Value(TypedNamedValueType {
                                             package: TypedPackage::Resolved(Package::from(&[
                                                 "test",
@@ -510,7 +553,8 @@ fn test_struct_member_function_call() {
                                             name: "A".to_string(),
                                             type_args: None,
                                         }),
-                                    ),
+                                    )),
+                                    default: None,
                                 }],
                                 type_params: None,
                                 body: Some(TypedFunBody::Block(TypedBlock {
@@ -526,15 +570,20 @@ fn test_struct_member_function_call() {
                                                             name: "self".to_string(),
                                                             type_arguments: None,
                                                         }),
-                                                        Some(TypedType::Value(
-                                                            TypedValueType::Value(
-                                                                TypedNamedValueType {
-                                                                    package: TypedPackage::Resolved(
-                                                                        Package::from(&["test"]),
-                                                                    ),
-                                                                    name: "A".to_string(),
-                                                                    type_args: None,
-                                                                },
+                                                        Some(TypedType::reference(
+                                                            TypedType::Value(
+                                                                TypedValueType::Value(
+                                                                    TypedNamedValueType {
+                                                                        package:
+                                                                            TypedPackage::Resolved(
+                                                                                Package::from(&[
+                                                                                    "test",
+                                                                                ]),
+                                                                            ),
+                                                                        name: "A".to_string(),
+                                                                        type_args: None,
+                                                                    },
+                                                                ),
                                                             ),
                                                         )),
                                                     )),
@@ -576,6 +625,7 @@ fn test_struct_member_function_call() {
                                 name: "A".to_string(),
                                 type_args: None,
                             })),
+                            default: None,
                         }],
                         body: Some(TypedFunBody::Block(TypedBlock {
                             body: vec![TypedStmt::Expr(TypedExpr::new(
@@ -604,20 +654,21 @@ fn test_struct_member_function_call() {
                                         Some(TypedType::Function(Box::new(TypedFunctionType {
                                             arguments: vec![TypedArgType {
                                                 label: "_".to_string(),
-                                                typ: TypedType::Value(TypedValueType::Value(
-                                                    TypedNamedValueType {
+                                                typ: TypedType::reference(TypedType::Value(
+                                                    TypedValueType::Value(TypedNamedValueType {
                                                         package: TypedPackage::Resolved(
                                                             Package::from(&["test"]),
                                                         ),
                                                         name: "A".to_string(),
                                                         type_args: None,
-                                                    },
+                                                    }),
                                                 )),
                                             }],
                                             return_type: TypedType::int64(),
                                         }))),
                                     )),
                                     args: vec![],
+                                    location: location(11, 13),
                                 }),
                                 Some(TypedType::int64()),
                             ))],
@@ -684,6 +735,7 @@ fn test_expr_function_with_arg() {
                         label: "_".to_string(),
                         name: "i".to_string(),
                         type_: TypedType::int32(),
+                        default: None,
                     }],
                     body: Some(TypedFunBody::Expr(TypedExpr::new(
                         TypedExprKind::Name(TypedName {
@@ -757,6 +809,7 @@ fn test_function_call() {
                                         }))),
                                     )),
                                     args: vec![],
+                                    location: location(4, 13),
                                 }),
                                 Some(TypedType::int64()),
                             ))],
@@ -891,6 +944,7 @@ fn test_binop() {
                                     )),
                                     Some(TypedType::int64()),
                                 )),
+                                location: location(3, 15),
                             }),
                             Some(TypedType::int64()),
                         ))],
@@ -925,6 +979,7 @@ fn test_subscript() {
                         label: "_".to_string(),
                         name: "p".to_string(),
                         type_: TypedType::unsafe_pointer(TypedType::uint8()),
+                        default: None,
                     }],
                     body: Option::from(TypedFunBody::Expr(TypedExpr::new(
                         TypedExprKind::Subscript(TypedSubscript {
@@ -940,6 +995,7 @@ fn test_subscript() {
                                 TypedExprKind::Literal(TypedLiteralKind::Integer("0".to_string())),
                                 Some(TypedType::int64()),
                             )],
+                            location: location(2, 45),
                         }),
                         Some(TypedType::uint8()),
                     ))),
@@ -975,6 +1031,7 @@ fn test_if_else() {
                         label: "i".to_string(),
                         name: "i".to_string(),
                         type_: TypedType::int64(),
+                        default: None,
                     }],
                     body: Some(TypedFunBody::Block(TypedBlock {
                         body: vec![TypedStmt::Expr(TypedExpr::new(
@@ -1000,6 +1057,7 @@ fn test_if_else() {
                                                     ),
                                                     Some(TypedType::int64()),
                                                 )),
+                                                location: location(3, 25),
                                             }),
                                             Some(TypedType::bool()),
                                         )),
@@ -1062,6 +1120,7 @@ fn test_if() {
                         label: "i".to_string(),
                         name: "i".to_string(),
                         type_: TypedType::int64(),
+                        default: None,
                     }],
                     body: Some(TypedFunBody::Block(TypedBlock {
                         body: vec![TypedStmt::Expr(TypedExpr::new(
@@ -1083,6 +1142,7 @@ fn test_if() {
                                             )),
                                             Some(TypedType::int64()),
                                         )),
+                                        location: location(3, 18),
                                     }),
                                     Some(TypedType::bool()),
                                 )),
@@ -1293,6 +1353,7 @@ fn test_type_cast() {
                                         }),
                                         is_safe: false,
                                         type_: TypedType::unsafe_pointer(TypedType::uint8()),
+                                        location: location(3, 18),
                                     }),
                                     ty: Some(TypedType::unsafe_pointer(TypedType::uint8())),
                                 })),
@@ -1336,6 +1397,7 @@ fn test_function_overload_by_arguments() {
                             label: "_".to_string(),
                             name: "d".to_string(),
                             type_: TypedType::double(),
+                            default: None,
                         }],
                         body: Option::from(TypedFunBody::Block(TypedBlock { body: vec![] })),
                         return_type: TypedType::unit(),
@@ -1353,6 +1415,7 @@ fn test_function_overload_by_arguments() {
                             label: "_".to_string(),
                             name: "i".to_string(),
                             type_: TypedType::int64(),
+                            default: None,
                         }],
                         body: Option::from(TypedFunBody::Block(TypedBlock { body: vec![] })),
                         return_type: TypedType::unit(),
@@ -1401,6 +1464,7 @@ fn test_function_overload_by_arguments() {
                                             )),
                                             is_vararg: false,
                                         }],
+                                        location: location(5, 13),
                                     }),
                                     Some(TypedType::unit()),
                                 )),
@@ -1434,6 +1498,7 @@ fn test_function_overload_by_arguments() {
                                             )),
                                             is_vararg: false,
                                         }],
+                                        location: location(6, 13),
                                     }),
                                     Some(TypedType::unit()),
                                 )),
@@ -1446,3 +1511,2268 @@ fn test_function_overload_by_arguments() {
         },
     );
 }
+
+#[test]
+fn test_generic_function_call_type_inference() {
+    let source = r"
+        fun identity<T>(_ t: T): T {
+            return t
+        }
+        fun call(): Int64 {
+            return identity(42)
+        }
+        ";
+
+    let type_param = TypedType::Value(TypedValueType::Value(TypedNamedValueType {
+        package: TypedPackage::Resolved(Package::from(&["test", "identity"])),
+        name: "T".to_string(),
+        type_args: None,
+    }));
+
+    check(
+        source,
+        TypedSpellBook {
+            name: "test".to_string(),
+            uses: vec![],
+            body: vec![
+                TypedTopLevelDecl {
+                    annotations: Default::default(),
+                    package: Package::from(&["test"]),
+                    modifiers: vec![],
+                    kind: TypedDeclKind::Fun(TypedFun {
+                        name: "identity".to_string(),
+                        type_params: Some(vec![TypedTypeParam {
+                            name: "T".to_string(),
+                        }]),
+                        type_constraints: Some(vec![TypedTypeConstraint {
+                            type_: TypedType::Type(Box::new(type_param.clone())),
+                            constraints: vec![],
+                        }]),
+                        arg_defs: vec![TypedArgDef {
+                            label: "_".to_string(),
+                            name: "t".to_string(),
+                            type_: type_param.clone(),
+                            default: None,
+                        }],
+                        body: Some(TypedFunBody::Block(TypedBlock {
+                            body: vec![TypedStmt::Expr(TypedExpr::new(
+                                TypedExprKind::Return(TypedReturn {
+                                    value: Some(Box::new(TypedExpr::new(
+                                        TypedExprKind::Name(TypedName {
+                                            package: TypedPackage::Resolved(Package::new()),
+                                            name: "t".to_string(),
+                                            type_arguments: None,
+                                        }),
+                                        Some(type_param.clone()),
+                                    ))),
+                                }),
+                                Some(TypedType::noting()),
+                            ))],
+                        })),
+                        return_type: type_param,
+                    }),
+                },
+                TypedTopLevelDecl {
+                    annotations: Default::default(),
+                    package: Package::from(&["test"]),
+                    modifiers: vec![],
+                    kind: TypedDeclKind::Fun(TypedFun {
+                        name: "call".to_string(),
+                        type_params: None,
+                        type_constraints: None,
+                        arg_defs: vec![],
+                        body: Some(TypedFunBody::Block(TypedBlock {
+                            body: vec![TypedStmt::Expr(TypedExpr::new(
+                                TypedExprKind::Return(TypedReturn {
+                                    value: Some(Box::new(TypedExpr::new(
+                                        TypedExprKind::Call(TypedCall {
+                                            target: Box::new(TypedExpr::new(
+                                                TypedExprKind::Name(TypedName {
+                                                    package: TypedPackage::Resolved(Package::from(
+                                                        &["test"],
+                                                    )),
+                                                    name: "identity".to_string(),
+                                                    type_arguments: Some(vec![TypedType::int64()]),
+                                                }),
+                                                Some(TypedType::Function(Box::new(
+                                                    TypedFunctionType {
+                                                        arguments: vec![TypedArgType {
+                                                            label: "_".to_string(),
+                                                            typ: TypedType::int64(),
+                                                        }],
+                                                        return_type: TypedType::int64(),
+                                                    },
+                                                ))),
+                                            )),
+                                            args: vec![TypedCallArg {
+                                                label: None,
+                                                arg: Box::new(TypedExpr::new(
+                                                    TypedExprKind::Literal(
+                                                        TypedLiteralKind::Integer("42".to_string()),
+                                                    ),
+                                                    Some(TypedType::int64()),
+                                                )),
+                                                is_vararg: false,
+                                            }],
+                                            location: location(6, 20),
+                                        }),
+                                        Some(TypedType::int64()),
+                                    ))),
+                                }),
+                                Some(TypedType::noting()),
+                            ))],
+                        })),
+                        return_type: TypedType::int64(),
+                    }),
+                },
+            ],
+        },
+    );
+}
+
+#[test]
+fn test_generic_function_call_cannot_infer() {
+    let source = r"
+        fun none<T>(): Int64 {
+            return 0
+        }
+        fun call(): Int64 {
+            return none()
+        }
+        ";
+
+    let session = ParseSession::default();
+    let ast = parse_from_string::<&str>(&session, None, source, Some("test")).unwrap();
+
+    let mut session = Session::default();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    let error = ast2hlir.lowing(ast, ModuleId::DUMMY).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("Cannot infer type parameter `T` of `none`."));
+}
+
+#[test]
+fn test_generic_function_type_constraint() {
+    let source = r"
+        protocol Add {
+            fun add(self, _ rhs: Self): Self
+        }
+        struct A {
+            val a: Int64
+        }
+        extension A: Add {
+            fun add(self, _ rhs: A): A {
+                return A(a: self.a + rhs.a)
+            }
+        }
+        fun sum<T: Add>(_ a: T, _ b: T): T {
+            return a.add(b)
+        }
+        fun call(): A {
+            return sum(A(a: 1), A(a: 2))
+        }
+        ";
+
+    let session = ParseSession::default();
+    let ast = parse_from_string::<&str>(&session, None, source, Some("test")).unwrap();
+
+    let mut session = Session::default();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    let f = ast2hlir.lowing(ast, ModuleId::DUMMY).unwrap();
+
+    let sum = f
+        .body
+        .iter()
+        .find_map(|d| match &d.kind {
+            TypedDeclKind::Fun(f) if f.name == "sum" => Some(f),
+            _ => None,
+        })
+        .unwrap();
+    let type_param = TypedType::Value(TypedValueType::Value(TypedNamedValueType {
+        package: TypedPackage::Resolved(Package::from(&["test", "sum"])),
+        name: "T".to_string(),
+        type_args: None,
+    }));
+    match sum.body.as_ref().unwrap() {
+        TypedFunBody::Block(b) => match &b.body[0] {
+            TypedStmt::Expr(TypedExpr {
+                kind: TypedExprKind::Return(r),
+                ..
+            }) => assert_eq!(r.value.as_ref().unwrap().ty, Some(type_param)),
+            s => panic!("{:?}", s),
+        },
+        b => panic!("{:?}", b),
+    }
+}
+
+#[test]
+fn test_generic_function_type_constraint_not_satisfied() {
+    let source = r"
+        protocol Add {
+            fun add(self, _ rhs: Self): Self
+        }
+        fun sum<T: Add>(_ a: T, _ b: T): T {
+            return a.add(b)
+        }
+        fun call(): Int64 {
+            return sum(1, 2)
+        }
+        ";
+
+    let session = ParseSession::default();
+    let ast = parse_from_string::<&str>(&session, None, source, Some("test")).unwrap();
+
+    let mut session = Session::default();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    let error = ast2hlir.lowing(ast, ModuleId::DUMMY).unwrap_err();
+
+    assert!(error.to_string().contains(
+        "`Int64` does not conform to `test::Add` required by type parameter `T` of `sum`."
+    ));
+}
+
+#[test]
+fn test_struct_type_constraint_not_satisfied() {
+    let source = r"
+        protocol Equals {
+            fun equals(self, _ other: Self): Bool
+        }
+        struct Box<T: Equals> {
+        }
+        struct Foo {
+        }
+        fun f(_ b: Box<Foo>): Unit {
+        }
+        ";
+
+    let session = ParseSession::default();
+    let ast = parse_from_string::<&str>(&session, None, source, Some("test")).unwrap();
+
+    let mut session = Session::default();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    let error = ast2hlir.lowing(ast, ModuleId::DUMMY).unwrap_err();
+
+    assert!(error.to_string().contains(
+        "`test::Foo` does not conform to `test::Equals` required by type parameter `T` of `Box`."
+    ));
+}
+
+#[test]
+fn test_struct_type_constraint_satisfied_by_later_extension() {
+    let source = r"
+        protocol Equals {
+            fun equals(self, _ other: Self): Bool
+        }
+        struct Box<T: Equals> {
+        }
+        struct Foo {
+        }
+        fun f(_ b: Box<Foo>): Unit {
+        }
+        extension Foo: Equals {
+            fun equals(self, _ other: Self): Bool {
+                return true
+            }
+        }
+        ";
+
+    let session = ParseSession::default();
+    let ast = parse_from_string::<&str>(&session, None, source, Some("test")).unwrap();
+
+    let mut session = Session::default();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    ast2hlir.lowing(ast, ModuleId::DUMMY).unwrap();
+}
+
+#[test]
+fn test_protocol_associated_type() {
+    let source = r"
+        protocol Iterator {
+            type Element
+            fun next(self): Element
+        }
+        struct Counter {
+            val count: Int64
+        }
+        extension Counter: Iterator {
+            type Element = Int64
+            fun next(self): Int64 {
+                return self.count + 1
+            }
+        }
+        fun first<I: Iterator>(_ i: I): I::Element {
+            return i.next()
+        }
+        fun call(): Int64 {
+            return first(Counter(count: 1))
+        }
+        ";
+
+    let session = ParseSession::default();
+    let ast = parse_from_string::<&str>(&session, None, source, Some("test")).unwrap();
+
+    let mut session = Session::default();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    let f = ast2hlir.lowing(ast, ModuleId::DUMMY).unwrap();
+
+    let return_value_type = |name: &str| {
+        let fun = f
+            .body
+            .iter()
+            .find_map(|d| match &d.kind {
+                TypedDeclKind::Fun(f) if f.name == name => Some(f),
+                _ => None,
+            })
+            .unwrap();
+        match fun.body.as_ref().unwrap() {
+            TypedFunBody::Block(b) => match &b.body[0] {
+                TypedStmt::Expr(TypedExpr {
+                    kind: TypedExprKind::Return(r),
+                    ..
+                }) => r.value.as_ref().unwrap().ty.clone(),
+                s => panic!("{:?}", s),
+            },
+            b => panic!("{:?}", b),
+        }
+    };
+    assert_eq!(
+        return_value_type("first"),
+        Some(TypedType::Value(TypedValueType::Value(
+            TypedNamedValueType {
+                package: TypedPackage::Resolved(Package::from(&["test", "first", "I"])),
+                name: "Element".to_string(),
+                type_args: None,
+            }
+        )))
+    );
+    assert_eq!(return_value_type("call"), Some(TypedType::int64()));
+    assert_eq!(
+        arena
+            .get_type(&["test"], "Counter")
+            .unwrap()
+            .associated_types
+            .get("Element"),
+        Some(&TypedType::int64())
+    );
+}
+
+#[test]
+fn test_protocol_associated_type_not_bound() {
+    let source = r"
+        protocol Iterator {
+            type Element
+            fun next(self): Element
+        }
+        struct Counter {
+            val count: Int64
+        }
+        extension Counter: Iterator {
+            fun next(self): Int64 {
+                return self.count + 1
+            }
+        }
+        ";
+
+    let session = ParseSession::default();
+    let ast = parse_from_string::<&str>(&session, None, source, Some("test")).unwrap();
+
+    let mut session = Session::default();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    let error = ast2hlir.lowing(ast, ModuleId::DUMMY).unwrap_err();
+
+    assert!(error.to_string().contains(
+        "`test::Counter` does not bind associated type `Element` required by `test::Iterator`."
+    ));
+}
+
+#[test]
+fn test_protocol_conformance_mismatch() {
+    let source = r"
+        protocol Iterator {
+            type Element
+            fun next(self): Element
+        }
+        struct Counter {
+            val count: Int64
+        }
+        extension Counter: Iterator {
+            type Element = Int32
+            fun next(self): Int64 {
+                return self.count + 1
+            }
+        }
+        ";
+
+    let session = ParseSession::default();
+    let ast = parse_from_string::<&str>(&session, None, source, Some("test")).unwrap();
+
+    let mut session = Session::default();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    let error = ast2hlir.lowing(ast, ModuleId::DUMMY).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("`next` of `test::Counter` has type"));
+}
+
+fn return_call_args(f: &TypedSpellBook, name: &str) -> Vec<TypedCallArg> {
+    let fun = f
+        .body
+        .iter()
+        .find_map(|d| match &d.kind {
+            TypedDeclKind::Fun(f) if f.name == name => Some(f),
+            _ => None,
+        })
+        .unwrap();
+    match fun.body.as_ref().unwrap() {
+        TypedFunBody::Block(b) => match b.body.last().unwrap() {
+            TypedStmt::Expr(TypedExpr {
+                kind: TypedExprKind::Return(r),
+                ..
+            }) => match &r.value.as_ref().unwrap().kind {
+                TypedExprKind::Call(c) => c.args.clone(),
+                e => panic!("{:?}", e),
+            },
+            s => panic!("{:?}", s),
+        },
+        b => panic!("{:?}", b),
+    }
+}
+
+fn int64_arg(label: Option<&str>, value: &str) -> TypedCallArg {
+    TypedCallArg {
+        label: label.map(ToString::to_string),
+        arg: Box::new(TypedExpr::new(
+            TypedExprKind::Literal(TypedLiteralKind::Integer(value.to_string())),
+            Some(TypedType::int64()),
+        )),
+        is_vararg: false,
+    }
+}
+
+#[test]
+fn test_default_argument() {
+    let source = r"
+        fun f(_ a: Int64, b: Int64 = 2, c: Int64 = 3): Int64 {
+            return a + b + c
+        }
+        fun trailing(): Int64 {
+            return f(1)
+        }
+        fun labeled(): Int64 {
+            return f(1, c: 4)
+        }
+        ";
+
+    let session = ParseSession::default();
+    let ast = parse_from_string::<&str>(&session, None, source, Some("test")).unwrap();
+
+    let mut session = Session::default();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    let f = ast2hlir.lowing(ast, ModuleId::DUMMY).unwrap();
+
+    assert_eq!(
+        return_call_args(&f, "trailing"),
+        vec![
+            int64_arg(None, "1"),
+            int64_arg(Some("b"), "2"),
+            int64_arg(Some("c"), "3"),
+        ]
+    );
+    assert_eq!(
+        return_call_args(&f, "labeled"),
+        vec![
+            int64_arg(None, "1"),
+            int64_arg(Some("b"), "2"),
+            int64_arg(Some("c"), "4"),
+        ]
+    );
+}
+
+#[test]
+fn test_default_argument_member_function() {
+    let source = r"
+        struct A {
+            val a: Int64
+            fun get(&self, offset: Int64 = 1): Int64 {
+                return self.a + offset
+            }
+        }
+        fun call(): Int64 {
+            val a = A(a: 1)
+            return a.get()
+        }
+        ";
+
+    let session = ParseSession::default();
+    let ast = parse_from_string::<&str>(&session, None, source, Some("test")).unwrap();
+
+    let mut session = Session::default();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    let f = ast2hlir.lowing(ast, ModuleId::DUMMY).unwrap();
+
+    assert_eq!(
+        return_call_args(&f, "call"),
+        vec![int64_arg(Some("offset"), "1")]
+    );
+}
+
+#[test]
+fn test_default_argument_source_location() {
+    let source = r"
+        fun here(line: UInt32 = #line, column: UInt32 = #column, file: &str = #file): UInt32 {
+            return line
+        }
+        fun call(): UInt32 {
+            return here()
+        }
+        ";
+
+    let session = ParseSession::default();
+    let ast = parse_from_string(&session, Some("test.wiz"), source, Some("test")).unwrap();
+
+    let mut session = Session::default();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    let f = ast2hlir.lowing(ast, ModuleId::DUMMY).unwrap();
+
+    let caller = TypedLocation {
+        file: "test.wiz".to_string(),
+        line: 6,
+        column: 20,
+    };
+    let arg = |label: &str, kind, ty| TypedCallArg {
+        label: Some(label.to_string()),
+        arg: Box::new(TypedExpr::new(
+            TypedExprKind::Literal(TypedLiteralKind::SourceLocation(kind, caller.clone())),
+            Some(ty),
+        )),
+        is_vararg: false,
+    };
+    assert_eq!(
+        return_call_args(&f, "call"),
+        vec![
+            arg("line", TypedSourceLocationKind::Line, TypedType::uint32()),
+            arg(
+                "column",
+                TypedSourceLocationKind::Column,
+                TypedType::uint32()
+            ),
+            arg(
+                "file",
+                TypedSourceLocationKind::File,
+                TypedType::string_ref()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_default_argument_declared_in_later_namespace() {
+    let source = r"
+        namespace a {
+            use test::b::*
+            fun f(x: Int64 = N): Int64 = x
+        }
+        namespace b {
+            val N: Int64 = 2
+        }
+        fun call(): Int64 = a::f()
+        ";
+
+    let f = lower(source).unwrap();
+
+    assert_eq!(
+        function_type(module(&f, "a"), "f"),
+        TypedType::Function(Box::new(TypedFunctionType {
+            arguments: vec![TypedArgType {
+                label: "x".to_string(),
+                typ: TypedType::int64(),
+            }],
+            return_type: TypedType::int64(),
+        }))
+    );
+}
+
+#[test]
+fn test_default_argument_type_mismatch() {
+    let source = r#"
+        fun f(_ a: Int64, b: Bool = "true"): Int64 {
+            return a
+        }
+        "#;
+
+    let session = ParseSession::default();
+    let ast = parse_from_string::<&str>(&session, None, source, Some("test")).unwrap();
+
+    let mut session = Session::default();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    let error = ast2hlir.lowing(ast, ModuleId::DUMMY).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("Default value of `b` has type `&str`, but `Bool` is expected."));
+}
+
+fn lower(source: &str) -> wiz_result::Result<TypedSpellBook> {
+    lower_with_session(Session::default(), source)
+}
+
+fn lower_with_session(mut session: Session, source: &str) -> wiz_result::Result<TypedSpellBook> {
+    let parse_session = ParseSession::default();
+    let ast = parse_from_string::<&str>(&parse_session, None, source, Some("test")).unwrap();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    ast2hlir.lowing(ast, ModuleId::DUMMY)
+}
+
+fn global_value(f: &TypedSpellBook, name: &str) -> TypedExpr {
+    f.body
+        .iter()
+        .find_map(|d| match &d.kind {
+            TypedDeclKind::Var(v) | TypedDeclKind::Const(v) if v.name == name => {
+                Some(v.value.clone())
+            }
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_const_arithmetic() {
+    let source = r"
+        const A: Int64 = 2 * 3
+        const B: Int64 = A - 10
+        val c = B as UInt8
+        ";
+
+    let f = lower(source).unwrap();
+
+    assert_eq!(
+        global_value(&f, "A"),
+        TypedExpr::new(
+            TypedExprKind::Literal(TypedLiteralKind::Integer("6".to_string())),
+            Some(TypedType::int64()),
+        )
+    );
+    assert_eq!(
+        global_value(&f, "B"),
+        TypedExpr::new(
+            TypedExprKind::Literal(TypedLiteralKind::Integer("-4".to_string())),
+            Some(TypedType::int64()),
+        )
+    );
+    assert_eq!(
+        global_value(&f, "c"),
+        TypedExpr::new(
+            TypedExprKind::Literal(TypedLiteralKind::Integer("252".to_string())),
+            Some(TypedType::uint8()),
+        )
+    );
+}
+
+#[test]
+fn test_const_array_length() {
+    let source = r"
+        const N: Int64 = 4
+        fun double(_ n: Int64): Int64 {
+            return n * 2
+        }
+        struct A {
+            val a: [Int8; N]
+            val b: [Int8; double(N) + 1]
+        }
+        val size = A::size()
+        ";
+
+    let f = lower(source).unwrap();
+
+    let a = f
+        .body
+        .iter()
+        .find_map(|d| match &d.kind {
+            TypedDeclKind::Struct(s) if s.name == "A" => Some(s),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(
+        a.stored_properties
+            .iter()
+            .map(|p| p.type_.clone())
+            .collect::<Vec<_>>(),
+        vec![
+            TypedType::Value(TypedValueType::Array(Box::new(TypedType::int8()), 4)),
+            TypedType::Value(TypedValueType::Array(Box::new(TypedType::int8()), 9)),
+        ]
+    );
+    assert_eq!(
+        global_value(&f, "size"),
+        TypedExpr::new(
+            TypedExprKind::Literal(TypedLiteralKind::Integer("13".to_string())),
+            Some(TypedType::usize()),
+        )
+    );
+}
+
+#[test]
+fn test_size_with_data_layout() {
+    let source = r"
+        class A {
+            val a: Int8
+        }
+        struct B {
+            val a: Int8
+            val b: Int64
+            val c: Double
+        }
+        struct C {
+            val a: Int8
+            val b: A
+        }
+        val a = A::size()
+        val b = B::size()
+        val c = C::size()
+        ";
+
+    let mut session = Session::default();
+    session.set_data_layout(DataLayout::parse(
+        "e-m:e-p:32:32-p270:32:32-p271:32:32-p272:64:64-f64:32:64-f80:32-n8:16:32-S128",
+    ));
+    let f = lower_with_session(session, source).unwrap();
+
+    let size = |size: &str| {
+        TypedExpr::new(
+            TypedExprKind::Literal(TypedLiteralKind::Integer(size.to_string())),
+            Some(TypedType::usize()),
+        )
+    };
+    assert_eq!(global_value(&f, "a"), size("4"));
+    assert_eq!(global_value(&f, "b"), size("20"));
+    assert_eq!(global_value(&f, "c"), size("8"));
+}
+
+#[test]
+fn test_global_not_constant() {
+    let source = r"
+        fun f(): Int64
+        val a = f()
+        ";
+
+    let error = lower(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("Initializer of global `a` is not a constant expression: `f` has no body"));
+}
+
+#[test]
+fn test_array_length_not_constant() {
+    let source = r"
+        val n: Int64 = 1
+        struct A {
+            val a: [Int8; n]
+        }
+        ";
+
+    let error = lower(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("Array length is not a constant expression: `n` is not a constant"));
+}
+
+/// Lower `source` after `core::builtin`, which declares `Clone`, `Equals`, `Range`, `Atomic`
+/// and memory orderings.
+fn lower_with_core(source: &str) -> wiz_result::Result<TypedSpellBook> {
+    lower_with_libraries(&[], source)
+}
+
+/// Lower `source` after `core` and the `(name, source)` pairs in `libraries`.
+fn lower_with_libraries(
+    libraries: &[(&str, &str)],
+    source: &str,
+) -> wiz_result::Result<TypedSpellBook> {
+    let session = ParseSession::default();
+    let builtin = parse_from_string::<&str>(
+        &session,
+        None,
+        r"
+        protocol Clone {
+            fun clone(&self): Self
+        }
+        protocol Equals {
+            fun equals(&self, _ other: &Self): Bool
+        }
+        struct Range {
+            val start: USize
+            val end: USize
+        }
+        #[builtin]
+        struct Atomic<T> {
+        }
+        const RELAXED: UInt8 = 0
+        const ACQUIRE: UInt8 = 1
+        const RELEASE: UInt8 = 2
+        const ACQ_REL: UInt8 = 3
+        const SEQ_CST: UInt8 = 4
+        ",
+        Some("builtin"),
+    )
+    .unwrap();
+    let core = WizFile {
+        name: "core".to_string(),
+        path: None,
+        syntax: FileSyntax {
+            leading_trivia: Default::default(),
+            body: vec![DeclarationSyntax {
+                annotations: None,
+                kind: DeclKind::Module((builtin.name, Some(builtin.syntax))),
+            }],
+            trailing_trivia: Default::default(),
+        },
+    };
+    let libraries = libraries
+        .iter()
+        .map(|(name, source)| {
+            parse_from_string::<&str>(&session, None, source, Some(name)).unwrap()
+        })
+        .collect::<Vec<_>>();
+    let ast = parse_from_string::<&str>(&session, None, source, Some("test")).unwrap();
+
+    let mut session = Session::default();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    ast2hlir.lowing(core, ModuleId::DUMMY)?;
+    for library in libraries {
+        ast2hlir.lowing(library, ModuleId::DUMMY)?;
+    }
+    ast2hlir.lowing(ast, ModuleId::DUMMY)
+}
+
+fn local_values(f: &TypedSpellBook, name: &str) -> Vec<(String, TypedExpr)> {
+    let fun = f
+        .body
+        .iter()
+        .find_map(|d| match &d.kind {
+            TypedDeclKind::Fun(f) if f.name == name => Some(f),
+            _ => None,
+        })
+        .unwrap();
+    match fun.body.as_ref().unwrap() {
+        TypedFunBody::Block(b) => b
+            .body
+            .iter()
+            .filter_map(|s| match s {
+                TypedStmt::Decl(TypedTopLevelDecl {
+                    kind: TypedDeclKind::Var(v),
+                    ..
+                }) => Some((v.name.clone(), v.value.clone())),
+                _ => None,
+            })
+            .collect(),
+        b => panic!("{:?}", b),
+    }
+}
+
+#[test]
+fn test_range() {
+    let source = r"
+        fun f(_ n: USize): Unit {
+            val a = 1..<3
+            val b = 0..2
+            val c = 0..n
+        }
+        ";
+
+    let f = lower_with_core(source).unwrap();
+
+    let usize_literal = |value: &str| {
+        TypedExpr::new(
+            TypedExprKind::Literal(TypedLiteralKind::Integer(value.to_string())),
+            Some(TypedType::usize()),
+        )
+    };
+    let bounds = local_values(&f, "f")
+        .into_iter()
+        .map(|(name, value)| {
+            assert_eq!(value.ty, Some(TypedType::range()));
+            match value.kind {
+                TypedExprKind::Call(c) => (
+                    name,
+                    c.args
+                        .into_iter()
+                        .map(|a| (a.label.unwrap(), *a.arg))
+                        .collect::<Vec<_>>(),
+                ),
+                e => panic!("{:?}", e),
+            }
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        bounds[0],
+        (
+            "a".to_string(),
+            vec![
+                ("start".to_string(), usize_literal("1")),
+                ("end".to_string(), usize_literal("3")),
+            ]
+        )
+    );
+    assert_eq!(
+        bounds[1],
+        (
+            "b".to_string(),
+            vec![
+                ("start".to_string(), usize_literal("0")),
+                ("end".to_string(), usize_literal("3")),
+            ]
+        )
+    );
+    assert_eq!(
+        bounds[2].1[1].1.kind,
+        TypedExprKind::BinOp(TypedBinOp {
+            left: Box::new(TypedExpr::new(
+                TypedExprKind::Name(TypedName {
+                    package: TypedPackage::Resolved(Package::new()),
+                    name: "n".to_string(),
+                    type_arguments: None,
+                }),
+                Some(TypedType::usize()),
+            )),
+            operator: TypedBinaryOperator::Add,
+            right: Box::new(usize_literal("1")),
+            location: location(5, 22),
+        })
+    );
+}
+
+#[test]
+fn test_closed_range_ending_at_max_literal() {
+    let source = r"
+        fun f(): Unit {
+            val a = 0..340282366920938463463374607431768211455
+        }
+        ";
+
+    let f = lower_with_core(source).unwrap();
+
+    let (_, value) = local_values(&f, "f").remove(0);
+    match value.kind {
+        TypedExprKind::Call(c) => match &c.args[1].arg.kind {
+            TypedExprKind::BinOp(TypedBinOp {
+                left,
+                operator: TypedBinaryOperator::Add,
+                ..
+            }) => assert_eq!(
+                left.kind,
+                TypedExprKind::Literal(TypedLiteralKind::Integer(
+                    "340282366920938463463374607431768211455".to_string()
+                ))
+            ),
+            e => panic!("{:?}", e),
+        },
+        e => panic!("{:?}", e),
+    }
+}
+
+#[test]
+fn test_slice() {
+    let source = r#"
+        fun f(_ a: [UInt8; 4], _ s: [UInt8]): Unit {
+            val x = a[1..<3]
+            val y = s[0..1]
+            val z = s[0]
+            val n = s.length
+            val b: [UInt8] = "abc"
+            val c = "abc"[0..<2]
+        }
+        "#;
+
+    let f = lower_with_core(source).unwrap();
+
+    let types = local_values(&f, "f")
+        .into_iter()
+        .map(|(name, value)| (name, value.ty.unwrap()))
+        .collect::<Vec<_>>();
+    let bytes = TypedType::slice(TypedType::uint8());
+    assert_eq!(
+        types,
+        vec![
+            ("x".to_string(), bytes.clone()),
+            ("y".to_string(), bytes.clone()),
+            ("z".to_string(), TypedType::uint8()),
+            ("n".to_string(), TypedType::usize()),
+            ("b".to_string(), bytes.clone()),
+            ("c".to_string(), bytes),
+        ]
+    );
+}
+
+#[test]
+fn test_slice_with_range_of_pointer() {
+    let source = r"
+        fun f(_ p: *UInt8): Unit {
+            val x = p[0..<2]
+        }
+        ";
+
+    let error = lower_with_core(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("`*UInt8` can not be sliced with a range"));
+}
+
+#[test]
+fn test_intrinsic_declaration() {
+    let source = r"
+        #[builtin]
+        fun trap(): Noting
+        #[builtin]
+        fun addWithOverflow<T>(_ lhs: T, _ rhs: T, _ result: *T): Bool
+        #[builtin]
+        fun bitcast<T, U>(_ value: T): U
+        ";
+
+    assert!(lower(source).is_ok());
+}
+
+#[test]
+fn test_intrinsic_signature_mismatch() {
+    let source = r"
+        #[builtin]
+        fun addWithOverflow<T>(_ lhs: T, _ rhs: T): Bool
+        ";
+
+    let error = lower(source).unwrap_err();
+
+    assert!(error.to_string().contains(
+        "intrinsic `addWithOverflow` must be declared as `fun addWithOverflow<T>(T, T, *T): Bool`"
+    ));
+}
+
+#[test]
+fn test_unknown_intrinsic() {
+    let source = r"
+        #[builtin]
+        fun fork(): Size
+        ";
+
+    let error = lower(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("`fork` is not a compiler intrinsic"));
+}
+
+#[test]
+fn test_atomic() {
+    let source = r"
+        fun f(): Int32 {
+            val a: Atomic<Int32> = Atomic(value: 1)
+            val b = Atomic<Int32>(value: 2)
+            val c = a.fetchAdd(1, ACQ_REL)
+            val d = b.compareExchange(2, 3, SEQ_CST, RELAXED)
+            return a.load(ACQUIRE)
+        }
+        ";
+
+    let f = lower_with_core(source).unwrap();
+
+    let values = local_values(&f, "f");
+    assert_eq!(values[0].1.ty, Some(TypedType::atomic(TypedType::int32())));
+    assert_eq!(values[1].1.ty, Some(TypedType::atomic(TypedType::int32())));
+    assert_eq!(values[2].1.ty, Some(TypedType::int32()));
+    let args = match &values[2].1.kind {
+        TypedExprKind::Call(c) => c.args.iter().map(|a| *a.arg.clone()).collect::<Vec<_>>(),
+        e => panic!("{:?}", e),
+    };
+    assert_eq!(
+        args,
+        vec![
+            TypedExpr::new(
+                TypedExprKind::Literal(TypedLiteralKind::Integer("1".to_string())),
+                Some(TypedType::int32()),
+            ),
+            TypedExpr::new(
+                TypedExprKind::Literal(TypedLiteralKind::Integer("3".to_string())),
+                Some(TypedType::uint8()),
+            ),
+        ]
+    );
+    assert_eq!(values[3].1.ty, Some(TypedType::bool()));
+}
+
+#[test]
+fn test_atomic_invalid_ordering() {
+    let source = r"
+        fun f(_ a: &Atomic<Int64>): Int64 {
+            return a.load(RELEASE)
+        }
+        ";
+
+    let error = lower_with_core(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("`RELEASE` is not a valid memory ordering for atomic load"));
+}
+
+#[test]
+fn test_atomic_ordering_not_constant() {
+    let source = r"
+        fun f(_ a: &Atomic<Int64>, _ ordering: UInt8): Int64 {
+            return a.load(ordering)
+        }
+        ";
+
+    let error = lower_with_core(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("Memory ordering of `atomicLoad` is not a constant expression"));
+}
+
+#[test]
+fn test_atomic_of_floating_point() {
+    let source = r"
+        fun f(): Unit {
+            val a = Atomic<Double>(value: 1.0)
+        }
+        ";
+
+    let error = lower_with_core(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("`Atomic` requires an integer type argument, but `Double` was given"));
+}
+
+#[test]
+fn test_derive_clone_and_equals() {
+    let source = r"
+        #[derive(Clone, Equals)]
+        struct A {
+            val a: Int64
+            val p: *UInt8
+        }
+        #[derive(Clone, Equals)]
+        struct B {
+            val a: A
+            val b: Bool
+        }
+        fun f(_ b: &B): Bool {
+            val c = b.clone()
+            return c.equals(b)
+        }
+        ";
+
+    let f = lower_with_core(source).unwrap();
+
+    let derived = f
+        .body
+        .iter()
+        .filter_map(|d| match &d.kind {
+            TypedDeclKind::Extension(e) if d.annotations.has_annotate(DERIVE) => Some((
+                e.name.name(),
+                e.protocol.as_ref().unwrap().to_string(),
+                e.member_functions[0].name.clone(),
+            )),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        derived,
+        vec![
+            (
+                "A".to_string(),
+                "core::builtin::Clone".to_string(),
+                "clone".to_string()
+            ),
+            (
+                "A".to_string(),
+                "core::builtin::Equals".to_string(),
+                "equals".to_string()
+            ),
+            (
+                "B".to_string(),
+                "core::builtin::Clone".to_string(),
+                "clone".to_string()
+            ),
+            (
+                "B".to_string(),
+                "core::builtin::Equals".to_string(),
+                "equals".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_derive_printable() {
+    let std = r#"
+        namespace string {
+            struct String {
+                val length: USize
+                fun new(_ s: &str): Self {
+                    return Self(length: 0)
+                }
+                fun push(&self, _ s: &str): Unit {
+                }
+                fun join(&self, _ s: String): Unit {
+                }
+            }
+        }
+        namespace format {
+            use std::string::String
+            use std::format::Printable
+            protocol Printable {
+                fun description(&self): String
+            }
+            extension Int64: Printable {
+                fun description(&self): String {
+                    return String::new("")
+                }
+            }
+        }
+        "#;
+    let source = r"
+        #[derive(Printable)]
+        struct A {
+            val a: Int64
+        }
+        #[derive(Printable)]
+        struct B {
+            val a: A
+            val b: Int64
+        }
+        ";
+
+    let f = lower_with_libraries(&[("std", std)], source).unwrap();
+
+    let derived = f
+        .body
+        .iter()
+        .filter_map(|d| match &d.kind {
+            TypedDeclKind::Extension(e) if d.annotations.has_annotate(DERIVE) => Some((
+                e.name.name(),
+                e.protocol.as_ref().unwrap().to_string(),
+                e.member_functions[0].return_type.to_string(),
+            )),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        derived,
+        vec![
+            (
+                "A".to_string(),
+                "std::format::Printable".to_string(),
+                "std::string::String".to_string()
+            ),
+            (
+                "B".to_string(),
+                "std::format::Printable".to_string(),
+                "std::string::String".to_string()
+            ),
+        ]
+    );
+
+    let error = lower_with_libraries(
+        &[("std", std)],
+        r"
+        #[derive(Printable)]
+        struct C {
+            val c: Bool
+        }
+        ",
+    )
+    .unwrap_err();
+
+    assert!(error.to_string().contains(
+        "Cannot derive `Printable` for `test::C`, because field `c` of type `Bool` does not conform to `std::format::Printable`."
+    ));
+}
+
+#[test]
+fn test_derive_field_not_conform() {
+    let source = r"
+        struct A {
+            val a: Int64
+        }
+        #[derive(Equals)]
+        struct B {
+            val a: A
+        }
+        ";
+
+    let error = lower_with_core(source).unwrap_err();
+
+    assert!(error.to_string().contains(
+        "Cannot derive `Equals` for `test::B`, because field `a` of type `test::A` does not conform to `core::builtin::Equals`."
+    ));
+}
+
+#[test]
+fn test_derive_unknown_protocol() {
+    let source = r"
+        #[derive(Hash)]
+        struct A {
+            val a: Int64
+        }
+        ";
+
+    let error = lower_with_core(source).unwrap_err();
+
+    assert!(error.to_string().contains(
+        "Cannot derive `Hash`, derivable protocols are `Clone`, `Equals` and `Printable`"
+    ));
+}
+
+#[test]
+fn test_annotation_arguments() {
+    let session = ParseSession::default();
+    let ast = parse_from_string::<&str>(
+        &session,
+        None,
+        r#"
+        #[deprecated("use g"), link(name: "m", static: true), cfg(level: 2)]
+        fun f(): Int32 = 1
+        "#,
+        Some("test"),
+    )
+    .unwrap();
+
+    let mut session = Session::default();
+    let mut arena = Arena::default();
+    let f = AstLowering::new(&mut session, &mut arena)
+        .lowing(ast, ModuleId::DUMMY)
+        .unwrap();
+    let library: WLib =
+        serde_json::from_str(&serde_json::to_string(&WLib::new(f)).unwrap()).unwrap();
+
+    let annotations = library
+        .typed_ir
+        .body
+        .iter()
+        .find(|d| matches!(&d.kind, TypedDeclKind::Fun(f) if f.name == "f"))
+        .map(|d| &d.annotations)
+        .unwrap();
+    let deprecated = annotations.get("deprecated").unwrap();
+    let link = annotations.get("link").unwrap();
+    let cfg = annotations.get("cfg").unwrap();
+    assert_eq!(
+        deprecated.positional_argument::<String>(0),
+        Some("use g".to_string())
+    );
+    assert_eq!(link.argument::<String>("name"), Some("m".to_string()));
+    assert_eq!(link.argument::<bool>("static"), Some(true));
+    assert_eq!(cfg.argument::<i64>("level"), Some(2));
+}
+
+#[test]
+fn test_annotation_argument_not_literal() {
+    let source = r"
+        #[deprecated(a::b)]
+        fun f(): Int32 = 1
+        ";
+
+    let error = lower_with_core(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("Annotation argument must be a literal or a name"));
+}
+
+#[test]
+fn test_initializer_call() {
+    let source = r"
+        struct A {
+            val a: Int64
+            init(value: Int64) {
+                self.a = value
+            }
+        }
+        fun f(): A {
+            return A(value: 1)
+        }
+        fun g(): A {
+            return A(a: 1)
+        }
+        ";
+
+    let f = lower(source).unwrap();
+
+    let targets = f
+        .body
+        .iter()
+        .filter_map(|d| match &d.kind {
+            TypedDeclKind::Fun(f) => match &f.body {
+                Some(TypedFunBody::Block(b)) => match &b.body[0] {
+                    TypedStmt::Expr(TypedExpr {
+                        kind: TypedExprKind::Return(r),
+                        ..
+                    }) => match &r.value.as_ref().unwrap().kind {
+                        TypedExprKind::Call(c) => {
+                            Some((c.target.kind.clone(), c.target.ty.clone()))
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let a = TypedType::Value(TypedValueType::Value(TypedNamedValueType {
+        package: TypedPackage::Resolved(Package::from(&["test"])),
+        name: "A".to_string(),
+        type_args: None,
+    }));
+    assert_eq!(
+        targets,
+        vec![
+            (
+                TypedExprKind::Name(TypedName {
+                    package: TypedPackage::Resolved(Package::from(&["test", "A"])),
+                    name: "init".to_string(),
+                    type_arguments: None,
+                }),
+                Some(TypedType::Function(Box::new(TypedFunctionType {
+                    arguments: vec![TypedArgType {
+                        label: "value".to_string(),
+                        typ: TypedType::int64(),
+                    }],
+                    return_type: a.clone(),
+                }))),
+            ),
+            (
+                TypedExprKind::Name(TypedName {
+                    package: TypedPackage::Resolved(Package::from(&["test"])),
+                    name: "A".to_string(),
+                    type_arguments: None,
+                }),
+                Some(TypedType::Type(Box::new(a))),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_initializers_with_same_labels() {
+    let source = r"
+        struct A {
+            val a: Int64
+            init(value: Int64) {
+                self.a = value
+            }
+            init(value: Int32) {
+                self.a = value as Int64
+            }
+        }
+        ";
+
+    let error = lower(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("`A` has multiple initializers with the same argument labels"));
+}
+
+#[test]
+fn test_init_in_extension() {
+    let source = r"
+        struct A {
+            val a: Int64
+        }
+        extension A {
+            init(value: Int64) {
+                self.a = value
+            }
+        }
+        ";
+
+    let error = lower(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("`init` is not allowed in extension of `A`"));
+}
+
+#[test]
+fn test_init_in_protocol() {
+    let source = r"
+        protocol P {
+            init(value: Int64) {
+            }
+        }
+        ";
+
+    let error = lower(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("`init` is not allowed in protocol `P`"));
+}
+
+#[test]
+fn test_subscript_get_and_set() {
+    let source = r"
+        struct Grid {
+            var cell: Int64
+            subscript(row: Int64, column: Int64): Int64 {
+                get {
+                    return self.cell
+                }
+                set {
+                    self.cell = newValue
+                }
+            }
+        }
+        fun f(_ g: Grid) {
+            var g = g
+            val c = g[0, 1]
+            g[0, 1] = c
+            g[0, 1] += 1
+        }
+        ";
+
+    let f = lower(source).unwrap();
+
+    let body = f
+        .body
+        .iter()
+        .find_map(|d| match &d.kind {
+            TypedDeclKind::Fun(f) if f.name == "f" => match &f.body {
+                Some(TypedFunBody::Block(b)) => Some(b.body.clone()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .unwrap();
+    let subscript_type = |e: &TypedExpr| match &e.kind {
+        TypedExprKind::Subscript(s) => Some((
+            s.indexes.iter().map(|i| i.ty.clone()).collect::<Vec<_>>(),
+            e.ty.clone(),
+        )),
+        _ => None,
+    };
+    let expected = Some((
+        vec![Some(TypedType::int64()), Some(TypedType::int64())],
+        Some(TypedType::int64()),
+    ));
+    match &body[1] {
+        TypedStmt::Decl(TypedTopLevelDecl {
+            kind: TypedDeclKind::Var(v),
+            ..
+        }) => assert_eq!(subscript_type(&v.value), expected),
+        s => panic!("{:?}", s),
+    }
+    match &body[2] {
+        TypedStmt::Assignment(TypedAssignmentStmt::Assignment(a)) => {
+            assert_eq!(subscript_type(&a.target), expected);
+            assert_eq!(a.value.ty, Some(TypedType::int64()));
+        }
+        s => panic!("{:?}", s),
+    }
+    match &body[3] {
+        TypedStmt::Assignment(TypedAssignmentStmt::AssignmentAndOperation(a)) => {
+            assert_eq!(subscript_type(&a.target), expected);
+            assert_eq!(a.value.ty, Some(TypedType::int64()));
+        }
+        s => panic!("{:?}", s),
+    }
+}
+
+#[test]
+fn test_subscript_without_setter() {
+    let source = r"
+        struct A {
+            val a: Int64
+            subscript(index: Int64): Int64 {
+                get {
+                    return self.a
+                }
+            }
+        }
+        fun f(_ a: A) {
+            var a = a
+            a[0] = 1
+        }
+        ";
+
+    let error = lower(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("Cannot assign through subscript of `test::A`, because it has no setter"));
+}
+
+#[test]
+fn test_subscript_in_extension() {
+    let source = r"
+        struct A {
+            val a: Int64
+        }
+        extension A {
+            subscript(index: Int64): Int64 {
+                get {
+                    return self.a
+                }
+            }
+        }
+        ";
+
+    let error = lower(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("`subscript` is not allowed in extension of `A`"));
+}
+
+#[test]
+fn test_subscript_in_protocol() {
+    let source = r"
+        protocol P {
+            subscript(index: Int64): Int64 {
+                get {
+                    return index
+                }
+            }
+        }
+        ";
+
+    let error = lower(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("`subscript` is not allowed in protocol `P`"));
+}
+
+#[test]
+fn test_deinit_in_union() {
+    let source = r"
+        union A {
+            val a: Int64
+            deinit {
+            }
+        }
+        ";
+
+    let error = lower(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("`deinit` is only allowed in struct and class, but `A` is a union"));
+}
+
+#[test]
+fn test_associated_type_in_struct() {
+    let source = r"
+        struct A {
+            type Item = Int64
+        }
+        ";
+
+    let error = lower(source).unwrap_err();
+
+    assert!(error.to_string().contains(
+        "associated type `Item` is only allowed in protocol and extension, but `A` is a struct"
+    ));
+}
+
+fn function_type(f: &TypedSpellBook, name: &str) -> TypedType {
+    f.body
+        .iter()
+        .find_map(|d| match &d.kind {
+            TypedDeclKind::Fun(f) if f.name == name => Some(f.type_()),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_type_alias() {
+    let source = r"
+        typealias Fd = Int32
+        typealias Ptr<T> = *T
+        typealias Bytes = Ptr<UInt8>
+        typealias Later = Fd2
+        typealias Fd2 = Fd
+        struct File {
+            val fd: Later
+        }
+        fun read(_ fd: Fd, _ buffer: Bytes, _ p: Ptr<Ptr<Int64>>): Fd2 {
+            return fd
+        }
+        ";
+
+    let f = lower(source).unwrap();
+
+    assert_eq!(
+        function_type(&f, "read"),
+        TypedType::Function(Box::new(TypedFunctionType {
+            arguments: vec![
+                TypedArgType {
+                    label: "_".to_string(),
+                    typ: TypedType::int32(),
+                },
+                TypedArgType {
+                    label: "_".to_string(),
+                    typ: TypedType::unsafe_pointer(TypedType::uint8()),
+                },
+                TypedArgType {
+                    label: "_".to_string(),
+                    typ: TypedType::unsafe_pointer(TypedType::unsafe_pointer(TypedType::int64())),
+                },
+            ],
+            return_type: TypedType::int32(),
+        }))
+    );
+    let fd = f
+        .body
+        .iter()
+        .find_map(|d| match &d.kind {
+            TypedDeclKind::Struct(s) => Some(s.stored_properties[0].type_.clone()),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(fd, TypedType::int32());
+    let ptr = f
+        .body
+        .iter()
+        .find_map(|d| match &d.kind {
+            TypedDeclKind::TypeAlias(t) if t.name == "Ptr" => Some(t.type_.clone()),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(
+        ptr,
+        TypedType::unsafe_pointer(TypedType::Value(TypedValueType::Value(
+            TypedNamedValueType {
+                package: TypedPackage::Resolved(Package::from(&["test", "Ptr"])),
+                name: "T".to_string(),
+                type_args: None,
+            }
+        )))
+    );
+}
+
+#[test]
+fn test_type_alias_from_library() {
+    let session = ParseSession::default();
+    let library = parse_from_string::<&str>(
+        &session,
+        None,
+        r"
+        typealias FileDescriptor = Int32
+        typealias Ptr<T> = *T
+        ",
+        Some("lib"),
+    )
+    .unwrap();
+    let ast = parse_from_string::<&str>(
+        &session,
+        None,
+        r"
+        use lib::Ptr
+        fun f(_ fd: lib::FileDescriptor): Ptr<UInt8>
+        ",
+        Some("test"),
+    )
+    .unwrap();
+
+    let mut session = Session::default();
+    let mut arena = Arena::default();
+    let library = AstLowering::new(&mut session, &mut arena)
+        .lowing(library, ModuleId::DUMMY)
+        .unwrap();
+
+    let mut arena = Arena::default();
+    WLib::new(library).apply_to(&mut arena).unwrap();
+    let f = AstLowering::new(&mut session, &mut arena)
+        .lowing(ast, ModuleId::DUMMY)
+        .unwrap();
+
+    assert_eq!(
+        function_type(&f, "f"),
+        TypedType::Function(Box::new(TypedFunctionType {
+            arguments: vec![TypedArgType {
+                label: "_".to_string(),
+                typ: TypedType::int32(),
+            }],
+            return_type: TypedType::unsafe_pointer(TypedType::uint8()),
+        }))
+    );
+}
+
+#[test]
+fn test_type_alias_type_arguments_mismatch() {
+    let source = r"
+        typealias Ptr<T> = *T
+        fun f(_ p: Ptr<Int8, Int8>): Unit {
+        }
+        ";
+
+    let error = lower(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("`Ptr` required 1 type arguments, but 2 were given."));
+}
+
+#[test]
+fn test_recursive_type_alias() {
+    let source = r"
+        typealias A = *B
+        typealias B = *A
+        ";
+
+    let error = lower(source).unwrap_err();
+
+    assert!(error.to_string().contains("Cannot resolve type alias `B`"));
+}
+
+fn module<'a>(f: &'a TypedSpellBook, name: &str) -> &'a TypedSpellBook {
+    f.body
+        .iter()
+        .find_map(|d| match &d.kind {
+            TypedDeclKind::Module(m) if m.name == name => Some(m),
+            _ => None,
+        })
+        .unwrap()
+}
+
+fn lower_files(files: &[(&str, &str)]) -> wiz_result::Result<TypedSpellBook> {
+    let root = temp_dir();
+    for (path, source) in files {
+        let path = root.join(path);
+        create_dir_all(path.parent().unwrap()).unwrap();
+        write(path, source).unwrap();
+    }
+    let mut session = Session::new(Config::default().input(root.join("lib.wiz")));
+    let ast =
+        parse_from_file_path(&session.parse_session, root.join("lib.wiz"), Some("test")).unwrap();
+
+    let mut arena = Arena::default();
+
+    let mut ast2hlir = AstLowering::new(&mut session, &mut arena);
+
+    let result = ast2hlir.lowing(ast, ModuleId::DUMMY);
+    remove_dir_all(root).unwrap();
+    result
+}
+
+#[test]
+fn test_inline_namespace() {
+    let source = r"
+        namespace foo {
+            namespace bar {
+                fun f(): Int32 = 1
+            }
+        }
+        fun g(): Int32 = foo::bar::f()
+        ";
+
+    let f = lower(source).unwrap();
+
+    assert_eq!(
+        function_type(module(module(&f, "foo"), "bar"), "f"),
+        TypedType::Function(Box::new(TypedFunctionType {
+            arguments: vec![],
+            return_type: TypedType::int32(),
+        }))
+    );
+}
+
+#[test]
+fn test_namespace_files() {
+    let f = lower_files(&[
+        (
+            "lib.wiz",
+            r"
+            namespace net
+            namespace foo {
+                namespace bar
+            }
+            ",
+        ),
+        ("net.wiz", "namespace address"),
+        ("net/address.wiz", "namespace ip"),
+        ("net/address/ip.wiz", "fun f(): Int32 = 1"),
+        (
+            "foo/bar.wiz",
+            "fun g(): Int32 = test::net::address::ip::f()",
+        ),
+    ])
+    .unwrap();
+
+    let ip = module(module(module(&f, "net"), "address"), "ip");
+    assert_eq!(
+        function_type(ip, "f"),
+        TypedType::Function(Box::new(TypedFunctionType {
+            arguments: vec![],
+            return_type: TypedType::int32(),
+        }))
+    );
+    let bar = module(module(&f, "foo"), "bar");
+    assert_eq!(
+        function_type(bar, "g"),
+        TypedType::Function(Box::new(TypedFunctionType {
+            arguments: vec![],
+            return_type: TypedType::int32(),
+        }))
+    );
+}
+
+#[test]
+fn test_namespace_file_not_found() {
+    let error = lower_files(&[
+        ("lib.wiz", "namespace net"),
+        ("net.wiz", "namespace address"),
+    ])
+    .unwrap_err();
+
+    let message = error.to_string();
+    assert!(message.contains("namespace `test::net::address` is declared in"));
+    assert!(message.contains(&format!(
+        "{}` is not found",
+        Path::new("net").join("address.wiz").display()
+    )));
+}
+
+#[test]
+fn test_use_group() {
+    let source = r"
+        namespace a {
+            struct A {
+                val x: Int32
+            }
+            fun f(): Int32 = 1
+            fun g(): Int32 = 2
+        }
+        use test::a::{A, f, g as h}
+        fun x(_ a: A): Int32 = f() + h()
+        ";
+
+    let f = lower(source).unwrap();
+
+    assert_eq!(
+        function_type(&f, "x"),
+        TypedType::Function(Box::new(TypedFunctionType {
+            arguments: vec![TypedArgType {
+                label: "_".to_string(),
+                typ: TypedType::Value(TypedValueType::Value(TypedNamedValueType {
+                    package: TypedPackage::Resolved(Package::from(&["test", "a"])),
+                    name: "A".to_string(),
+                    type_args: None,
+                })),
+            }],
+            return_type: TypedType::int32(),
+        }))
+    );
+}
+
+#[test]
+fn test_re_export() {
+    let source = r"
+        namespace a {
+            fun f(): Int32 = 1
+        }
+        namespace prelude {
+            public use test::a::f
+        }
+        fun g(): Int32 = prelude::f()
+        ";
+
+    let f = lower(source).unwrap();
+
+    assert_eq!(
+        function_type(&f, "g"),
+        TypedType::Function(Box::new(TypedFunctionType {
+            arguments: vec![],
+            return_type: TypedType::int32(),
+        }))
+    );
+}
+
+#[test]
+fn test_re_export_from_library() {
+    let session = ParseSession::default();
+    let library = parse_from_string::<&str>(
+        &session,
+        None,
+        r"
+        namespace string {
+            struct String {
+                val length: Int32
+            }
+            fun len(): Int32 = 1
+        }
+        namespace prelude {
+            public use lib::string::{String, len as length}
+        }
+        ",
+        Some("lib"),
+    )
+    .unwrap();
+    let ast = parse_from_string::<&str>(
+        &session,
+        None,
+        r"
+        use lib::prelude::*
+        fun f(_ s: String): Int32 = length()
+        ",
+        Some("test"),
+    )
+    .unwrap();
+
+    let mut session = Session::default();
+    let mut arena = Arena::default();
+    let library = AstLowering::new(&mut session, &mut arena)
+        .lowing(library, ModuleId::DUMMY)
+        .unwrap();
+    let library: WLib =
+        serde_json::from_str(&serde_json::to_string(&WLib::new(library)).unwrap()).unwrap();
+
+    let mut arena = Arena::default();
+    library.apply_to(&mut arena).unwrap();
+    let f = AstLowering::new(&mut session, &mut arena)
+        .lowing(ast, ModuleId::DUMMY)
+        .unwrap();
+
+    assert_eq!(
+        function_type(&f, "f"),
+        TypedType::Function(Box::new(TypedFunctionType {
+            arguments: vec![TypedArgType {
+                label: "_".to_string(),
+                typ: TypedType::Value(TypedValueType::Value(TypedNamedValueType {
+                    package: TypedPackage::Resolved(Package::from(&["lib", "string"])),
+                    name: "String".to_string(),
+                    type_args: None,
+                })),
+            }],
+            return_type: TypedType::int32(),
+        }))
+    );
+}
+
+#[test]
+fn test_ambiguous_glob_use() {
+    let source = r"
+        namespace a {
+            fun f(): Int32 = 1
+        }
+        namespace b {
+            fun f(): Int32 = 2
+        }
+        use test::a::*
+        use test::b::*
+        fun g(): Int32 = f()
+        ";
+
+    let error = lower(source).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("`f` is ambiguous, because it is used by both `test::a::*` and `test::b::*`"));
+}
+
+#[test]
+fn test_ambiguous_glob_use_of_libraries() {
+    let libc = r"
+        namespace stdlib {
+            fun abort(): Noting
+        }
+        ";
+    let std = r"
+        namespace process {
+            fun abort(_ message: &str): Noting
+        }
+        ";
+    let source = r"
+        use libc::stdlib::*
+        use std::process::*
+        fun f(): Noting = abort()
+        ";
+
+    let error = lower_with_libraries(&[("libc", libc), ("std", std)], source).unwrap_err();
+
+    assert!(error.to_string().contains(
+        "`abort` is ambiguous, because it is used by both `libc::stdlib::*` and `std::process::*`"
+    ));
+}
+
+#[test]
+fn test_glob_use_shadowed_by_explicit_use() {
+    let source = r"
+        namespace a {
+            fun f(): Int32 = 1
+        }
+        namespace b {
+            fun f(): Int32 = 2
+        }
+        use test::a::*
+        use test::b::*
+        use test::b::f
+        fun g(): Int32 = f()
+        ";
+
+    let f = lower(source).unwrap();
+
+    let body = f
+        .body
+        .iter()
+        .find_map(|d| match &d.kind {
+            TypedDeclKind::Fun(f) if f.name == "g" => f.body.clone(),
+            _ => None,
+        })
+        .unwrap();
+    match body {
+        TypedFunBody::Expr(TypedExpr {
+            kind: TypedExprKind::Call(TypedCall { target, .. }),
+            ..
+        }) => assert_eq!(
+            target.kind,
+            TypedExprKind::Name(TypedName {
+                package: TypedPackage::Resolved(Package::from(&["test", "b"])),
+                name: "f".to_string(),
+                type_arguments: None,
+            })
+        ),
+        body => panic!("{:?}", body),
+    }
+}
+
+#[test]
+fn test_explicit_use_not_shadowed_by_later_glob_use() {
+    let source = r"
+        namespace a {
+            fun f(): Int32 = 1
+        }
+        namespace b {
+            fun f(): Int32 = 2
+        }
+        use test::a::f
+        use test::b::*
+        fun g(): Int32 = f()
+        ";
+
+    let f = lower(source).unwrap();
+
+    let body = f
+        .body
+        .iter()
+        .find_map(|d| match &d.kind {
+            TypedDeclKind::Fun(f) if f.name == "g" => f.body.clone(),
+            _ => None,
+        })
+        .unwrap();
+    match body {
+        TypedFunBody::Expr(TypedExpr {
+            kind: TypedExprKind::Call(TypedCall { target, .. }),
+            ..
+        }) => assert_eq!(
+            target.kind,
+            TypedExprKind::Name(TypedName {
+                package: TypedPackage::Resolved(Package::from(&["test", "a"])),
+                name: "f".to_string(),
+                type_arguments: None,
+            })
+        ),
+        body => panic!("{:?}", body),
+    }
+}
+
+#[test]
+fn test_declaration_not_shadowed_by_glob_use() {
+    let source = r"
+        namespace b {
+            fun f(): Int32 = 2
+        }
+        use test::b::*
+        fun f(): Int32 = 1
+        fun g(): Int32 = f()
+        ";
+
+    let f = lower(source).unwrap();
+
+    let body = f
+        .body
+        .iter()
+        .find_map(|d| match &d.kind {
+            TypedDeclKind::Fun(f) if f.name == "g" => f.body.clone(),
+            _ => None,
+        })
+        .unwrap();
+    match body {
+        TypedFunBody::Expr(TypedExpr {
+            kind: TypedExprKind::Call(TypedCall { target, .. }),
+            ..
+        }) => assert_eq!(
+            target.kind,
+            TypedExprKind::Name(TypedName {
+                package: TypedPackage::Resolved(Package::from(&["test"])),
+                name: "f".to_string(),
+                type_arguments: None,
+            })
+        ),
+        body => panic!("{:?}", body),
+    }
+}
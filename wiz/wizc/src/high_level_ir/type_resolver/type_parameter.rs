@@ -0,0 +1,225 @@
+use crate::high_level_ir::type_resolver::error::ResolverError;
+use crate::high_level_ir::type_resolver::result::Result;
+use std::collections::HashMap;
+use wiz_hir::typed_type::{
+    Package, TypedArgType, TypedFunctionType, TypedNamedValueType, TypedPackage, TypedType,
+    TypedTypeParam, TypedValueType,
+};
+
+/// Type arguments of generic declaration, determined by unification.
+#[derive(Debug, Clone)]
+pub(crate) struct TypeParameterSubstitution {
+    name: String,
+    package: Package,
+    parameters: Vec<TypedTypeParam>,
+    arguments: HashMap<TypedTypeParam, TypedType>,
+}
+
+impl TypeParameterSubstitution {
+    /// `package` is the namespace that type parameters are declared in.
+    pub fn new(name: &str, package: Package, parameters: Vec<TypedTypeParam>) -> Self {
+        Self {
+            name: name.to_string(),
+            package,
+            parameters,
+            arguments: HashMap::new(),
+        }
+    }
+
+    pub fn with_type_arguments(mut self, type_arguments: &[TypedType]) -> Result<Self> {
+        if self.parameters.len() != type_arguments.len() {
+            return Err(ResolverError::from(format!(
+                "`{}` required {} type arguments, but {} were given.",
+                self.name,
+                self.parameters.len(),
+                type_arguments.len()
+            )));
+        }
+        for (p, a) in self.parameters.iter().zip(type_arguments) {
+            self.arguments.insert(p.clone(), a.clone());
+        }
+        Ok(self)
+    }
+
+    fn type_parameter(&self, t: &TypedNamedValueType) -> Option<TypedTypeParam> {
+        let is_parameter = match &t.package {
+            TypedPackage::Resolved(p) => *p == self.package,
+            TypedPackage::Raw(_) => false,
+        } && t.type_args.is_none();
+        let param = TypedTypeParam {
+            name: t.name.clone(),
+        };
+        if is_parameter && self.parameters.contains(&param) {
+            Some(param)
+        } else {
+            None
+        }
+    }
+
+    /// Bind type parameters that appear in `param` by matching against `arg`.
+    pub fn unify(&mut self, param: &TypedType, arg: &TypedType) -> Result<()> {
+        match (param, arg) {
+            (TypedType::Value(TypedValueType::Value(p)), _) if self.type_parameter(p).is_some() => {
+                let p = self.type_parameter(p).unwrap();
+                match self.arguments.get(&p) {
+                    Some(bound) if bound != arg => Err(ResolverError::from(format!(
+                        "type parameter `{}` of `{}` is inferred as both `{}` and `{}`.",
+                        p.name,
+                        self.name,
+                        bound.to_string(),
+                        arg.to_string()
+                    ))),
+                    Some(_) => Ok(()),
+                    None => {
+                        self.arguments.insert(p, arg.clone());
+                        Ok(())
+                    }
+                }
+            }
+            (
+                TypedType::Value(TypedValueType::Value(p)),
+                TypedType::Value(TypedValueType::Value(a)),
+            ) => {
+                if let (Some(p), Some(a)) = (&p.type_args, &a.type_args) {
+                    for (p, a) in p.iter().zip(a) {
+                        self.unify(p, a)?;
+                    }
+                }
+                Ok(())
+            }
+            (
+                TypedType::Value(TypedValueType::Pointer(p)),
+                TypedType::Value(TypedValueType::Pointer(a)),
+            )
+            | (
+                TypedType::Value(TypedValueType::Reference(p)),
+                TypedType::Value(TypedValueType::Reference(a)),
+            )
+            | (
+                TypedType::Value(TypedValueType::Array(p, _)),
+                TypedType::Value(TypedValueType::Array(a, _)),
+            )
+            | (
+                TypedType::Value(TypedValueType::Slice(p)),
+                TypedType::Value(TypedValueType::Slice(a)),
+            ) => self.unify(p, a),
+            (
+                TypedType::Value(TypedValueType::Function(p)),
+                TypedType::Value(TypedValueType::Function(a)),
+            )
+            | (TypedType::Function(p), TypedType::Function(a)) => {
+                for (p, a) in p.arguments.iter().zip(a.arguments.iter()) {
+                    self.unify(&p.typ, &a.typ)?;
+                }
+                self.unify(&p.return_type, &a.return_type)
+            }
+            (TypedType::Value(TypedValueType::Function(_)), TypedType::Function(_)) => {
+                self.unify(param, &arg.clone().decay())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns `true` if `t` contains no undetermined type parameters.
+    pub fn is_determined(&self, t: &TypedType) -> bool {
+        match t {
+            TypedType::Self_ => true,
+            TypedType::Value(v) => match v {
+                TypedValueType::Value(n) => match self.type_parameter(n) {
+                    Some(p) => self.arguments.contains_key(&p),
+                    None => n.type_args.iter().flatten().all(|t| self.is_determined(t)),
+                },
+                TypedValueType::Array(t, _)
+                | TypedValueType::Slice(t)
+                | TypedValueType::Pointer(t)
+                | TypedValueType::Reference(t) => self.is_determined(t),
+                TypedValueType::Tuple(t) => t.iter().all(|t| self.is_determined(t)),
+                TypedValueType::Function(f) => self.is_determined_function(f),
+            },
+            TypedType::Function(f) => self.is_determined_function(f),
+            TypedType::Type(t) => self.is_determined(t),
+        }
+    }
+
+    fn is_determined_function(&self, f: &TypedFunctionType) -> bool {
+        f.arguments.iter().all(|a| self.is_determined(&a.typ)) && self.is_determined(&f.return_type)
+    }
+
+    /// Replace determined type parameters in `t` with its type arguments.
+    pub fn substitute(&self, t: &TypedType) -> TypedType {
+        match t {
+            TypedType::Self_ => TypedType::Self_,
+            TypedType::Value(v) => match v {
+                TypedValueType::Value(n) => {
+                    match self.type_parameter(n).and_then(|p| self.arguments.get(&p)) {
+                        Some(a) => a.clone(),
+                        None => TypedType::Value(TypedValueType::Value(TypedNamedValueType {
+                            package: n.package.clone(),
+                            name: n.name.clone(),
+                            type_args: n
+                                .type_args
+                                .as_ref()
+                                .map(|a| a.iter().map(|t| self.substitute(t)).collect()),
+                        })),
+                    }
+                }
+                TypedValueType::Array(t, len) => {
+                    TypedType::Value(TypedValueType::Array(Box::new(self.substitute(t)), *len))
+                }
+                TypedValueType::Slice(t) => TypedType::slice(self.substitute(t)),
+                TypedValueType::Tuple(t) => TypedType::Value(TypedValueType::Tuple(
+                    t.iter().map(|t| self.substitute(t)).collect(),
+                )),
+                TypedValueType::Pointer(t) => TypedType::unsafe_pointer(self.substitute(t)),
+                TypedValueType::Reference(t) => TypedType::reference(self.substitute(t)),
+                TypedValueType::Function(f) => {
+                    TypedType::function_pointer(self.substitute_function(f))
+                }
+            },
+            TypedType::Function(f) => TypedType::Function(Box::new(self.substitute_function(f))),
+            TypedType::Type(t) => TypedType::Type(Box::new(self.substitute(t))),
+        }
+    }
+
+    fn substitute_function(&self, f: &TypedFunctionType) -> TypedFunctionType {
+        TypedFunctionType {
+            arguments: f
+                .arguments
+                .iter()
+                .map(|a| TypedArgType {
+                    label: a.label.clone(),
+                    typ: self.substitute(&a.typ),
+                })
+                .collect(),
+            return_type: self.substitute(&f.return_type),
+        }
+    }
+
+    /// Type arguments in declaration order.
+    /// Returns error that lists parameters which could not be determined.
+    pub fn type_arguments(&self) -> Result<Vec<TypedType>> {
+        let undetermined: Vec<_> = self
+            .parameters
+            .iter()
+            .filter(|p| !self.arguments.contains_key(p))
+            .map(|p| format!("`{}`", p.name))
+            .collect();
+        if undetermined.is_empty() {
+            Ok(self
+                .parameters
+                .iter()
+                .map(|p| self.arguments[p].clone())
+                .collect())
+        } else {
+            Err(ResolverError::from(format!(
+                "Cannot infer type parameter {} of `{}`.",
+                undetermined.join(", "),
+                self.name
+            )))
+        }
+    }
+
+    pub fn into_map(self) -> HashMap<TypedTypeParam, TypedType> {
+        self.arguments
+    }
+}
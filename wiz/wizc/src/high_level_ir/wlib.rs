@@ -1,9 +1,13 @@
+use crate::high_level_ir::re_export::ReExports;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use std::path::Path;
-use wiz_arena::{Arena, DeclarationId, DeclarationItemKind};
-use wiz_hir::typed_decl::TypedDeclKind;
+use wiz_arena::{Arena, ArenaSubscript, DeclarationId, DeclarationItemKind};
+use wiz_data_structure::annotation::Annotations;
+use wiz_hir::typed_decl::{TypedDeclKind, TypedFun, TypedInitializer};
 use wiz_hir::typed_file::TypedSpellBook;
+use wiz_hir::typed_type::{TypedType, TypedTypeParam};
+use wiz_hir::typed_type_constraint::TypedTypeConstraint;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WLib {
@@ -28,7 +32,10 @@ impl WLib {
 
     pub fn apply_to(&self, arena: &mut Arena) -> Result<(), String> {
         let namespace_id = DeclarationId::ROOT;
-        self._apply_to(&namespace_id, &self.typed_ir, arena)
+        self._apply_to(&namespace_id, &self.typed_ir, arena)?;
+        ReExports::new(&self.typed_ir)
+            .apply_to(arena)
+            .map_err(|e| e.to_string())
     }
 
     fn _apply_to(
@@ -50,46 +57,123 @@ impl WLib {
                         decl.annotations.clone(),
                     );
                 }
-                TypedDeclKind::Fun(f) => {
-                    arena.register_function(
+                TypedDeclKind::ExternVar(v) => {
+                    arena.register_value(&id, &v.name, v.type_.clone(), decl.annotations.clone());
+                }
+                TypedDeclKind::Const(c) => {
+                    arena.register_constant(
                         &id,
-                        &f.name,
-                        f.type_(),
-                        f.type_params.clone(),
-                        f.body.clone(),
+                        &c.name,
+                        c.type_.clone().unwrap(),
+                        c.value.clone(),
                         decl.annotations.clone(),
                     );
                 }
-                TypedDeclKind::Struct(s) => {
-                    let id = arena
-                        .register_struct(&id, &s.name, decl.annotations.clone())
-                        .unwrap();
+                TypedDeclKind::Fun(f) => {
+                    let fun_id =
+                        Self::register_function(arena, &id, f, decl.annotations.clone()).unwrap();
+                    Self::register_type_parameters(
+                        arena,
+                        &fun_id,
+                        &f.type_params,
+                        &f.type_constraints,
+                    );
+                }
+                TypedDeclKind::Struct(s) | TypedDeclKind::Union(s) | TypedDeclKind::Class(s) => {
+                    let id = match &decl.kind {
+                        TypedDeclKind::Union(_) => {
+                            arena.register_union(&id, &s.name, decl.annotations.clone())
+                        }
+                        TypedDeclKind::Class(_) => {
+                            arena.register_class(&id, &s.name, decl.annotations.clone())
+                        }
+                        _ => arena.register_struct(&id, &s.name, decl.annotations.clone()),
+                    }
+                    .unwrap();
+                    Self::register_type_parameters(arena, &id, &s.type_params, &s.type_constraints);
+                    let mut initializers = vec![];
                     let item = arena.get_mut_by_id(&id).unwrap();
                     if let DeclarationItemKind::Type(rs) = &mut item.kind {
+                        rs.type_parameters = s.type_params.clone();
+                        let self_type = rs.self_type();
+                        initializers = s
+                            .initializers
+                            .iter()
+                            .map(|i| (i, i.type_().replace(&TypedType::Self_, &self_type)))
+                            .collect();
+                        rs.initializers
+                            .extend(initializers.iter().map(|(_, t)| t.clone()));
+                        rs.subscripts
+                            .extend(s.subscripts.iter().map(|s| ArenaSubscript {
+                                getter: s.getter.function_type(),
+                                setter: s.setter.as_ref().map(TypedFun::function_type),
+                            }));
                         rs.stored_properties.extend(
                             s.stored_properties
                                 .iter()
                                 .cloned()
                                 .map(|t| (t.name, t.type_)),
-                        )
+                        );
+                        rs.stored_property_order =
+                            Some(s.stored_properties.iter().map(|t| t.name.clone()).collect());
+                        rs.mutable_properties.extend(
+                            s.stored_properties
+                                .iter()
+                                .filter(|t| t.is_mut)
+                                .map(|t| t.name.clone()),
+                        );
                     }
-                    for member_function in s.member_functions.iter() {
-                        arena.register_function(
+                    for (initializer, type_) in initializers {
+                        if let Some(id) = arena.register_function(
                             &id,
-                            &member_function.name,
-                            member_function.type_(),
-                            member_function.type_params.clone(),
-                            member_function.body.clone(),
+                            TypedInitializer::NAME,
+                            type_,
+                            None,
+                            Some(initializer.body.clone()),
                             Default::default(),
-                        );
+                        ) {
+                            if let Some(DeclarationItemKind::Function(rf)) =
+                                arena.get_mut_by_id(&id).map(|i| &mut i.kind)
+                            {
+                                rf.arg_names = initializer.arg_names();
+                            }
+                        }
+                    }
+                    for member_function in s.member_functions.iter() {
+                        Self::register_function(arena, &id, member_function, Default::default());
                     }
                 }
                 TypedDeclKind::Module(m) => {
                     self._apply_to(&id, m, arena)?;
                 }
+                TypedDeclKind::TypeAlias(t) => {
+                    let id = arena
+                        .register_type_alias(
+                            &id,
+                            &t.name,
+                            t.type_params.clone(),
+                            t.type_.clone(),
+                            decl.annotations.clone(),
+                        )
+                        .unwrap();
+                    arena.resolve_type_alias(&id, t.type_.clone());
+                }
                 TypedDeclKind::Enum => {}
                 TypedDeclKind::Protocol(p) => {
-                    arena.register_struct(&id, &p.name, decl.annotations.clone());
+                    let id = arena
+                        .register_protocol(&id, &p.name, decl.annotations.clone())
+                        .unwrap();
+                    for associated_type in p.associated_types.iter() {
+                        arena.register_associated_type(&id, &associated_type.name);
+                    }
+                    let item = arena.get_mut_by_id(&id).unwrap();
+                    if let DeclarationItemKind::Type(rs) = &mut item.kind {
+                        rs.member_functions.extend(
+                            p.member_functions
+                                .iter()
+                                .map(|m| (m.name.clone(), m.type_())),
+                        )
+                    }
                 }
                 TypedDeclKind::Extension(e) => {
                     let mut fqn = Vec::new();
@@ -98,19 +182,78 @@ impl WLib {
                     }
                     fqn.push(e.name.name());
                     let id = arena.resolve_declaration_id_from_root(&fqn).unwrap();
+                    if let Some(protocol) = &e.protocol {
+                        let item = arena.get_mut_by_id(&id).unwrap();
+                        if let DeclarationItemKind::Type(rs) = &mut item.kind {
+                            rs.conformed_protocols.insert(protocol.to_string());
+                            rs.associated_types.extend(
+                                e.associated_types
+                                    .iter()
+                                    .filter_map(|a| Some((a.name.clone(), a.type_.clone()?))),
+                            );
+                        }
+                    }
                     for member_function in e.member_functions.iter() {
-                        arena.register_function(
-                            &id,
-                            &member_function.name,
-                            member_function.type_(),
-                            member_function.type_params.clone(),
-                            member_function.body.clone(),
-                            Default::default(),
-                        );
+                        Self::register_function(arena, &id, member_function, Default::default());
                     }
                 }
             };
         }
         Ok(())
     }
+
+    fn register_function(
+        arena: &mut Arena,
+        namespace: &DeclarationId,
+        f: &TypedFun,
+        annotation: Annotations,
+    ) -> Option<DeclarationId> {
+        let id = arena.register_function(
+            namespace,
+            &f.name,
+            f.type_(),
+            f.type_params.clone(),
+            f.body.clone(),
+            annotation,
+        )?;
+        if let DeclarationItemKind::Function(rf) = &mut arena.get_mut_by_id(&id)?.kind {
+            rf.arg_names = f.arg_names();
+            rf.arg_defaults = f.arg_defs.iter().map(|a| a.default.clone()).collect();
+        }
+        Some(id)
+    }
+
+    /// Register type parameters of `owner` with protocols they are constrained to.
+    fn register_type_parameters(
+        arena: &mut Arena,
+        owner: &DeclarationId,
+        type_params: &Option<Vec<TypedTypeParam>>,
+        type_constraints: &Option<Vec<TypedTypeConstraint>>,
+    ) {
+        for type_param in type_params.iter().flatten() {
+            let type_param_id = arena
+                .register_type_parameter(owner, &type_param.name, Default::default())
+                .unwrap();
+            let item = arena.get_mut_by_id(&type_param_id).unwrap();
+            if let DeclarationItemKind::Type(rs) = &mut item.kind {
+                for tc in type_constraints.iter().flatten() {
+                    if tc.type_.name() == type_param.name {
+                        rs.conformed_protocols
+                            .extend(tc.constraints.iter().map(|c| c.to_string()));
+                    }
+                }
+            }
+            let associated_types = type_constraints
+                .iter()
+                .flatten()
+                .filter(|tc| tc.type_.name() == type_param.name)
+                .flat_map(|tc| tc.constraints.iter())
+                .filter_map(|c| arena.get_type(&c.package().into_resolved().names, &c.name()))
+                .flat_map(|prs| prs.associated_types.keys().cloned())
+                .collect::<Vec<_>>();
+            for associated_type in associated_types {
+                arena.register_associated_type(&type_param_id, &associated_type);
+            }
+        }
+    }
 }
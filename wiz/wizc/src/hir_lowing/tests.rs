@@ -2,20 +2,23 @@ use crate::high_level_ir::node_id::ModuleId;
 use crate::high_level_ir::AstLowering;
 use wiz_arena::Arena;
 use wiz_mir::expr::{
-    MLCall, MLCallArg, MLExpr, MLLiteral, MLLiteralKind, MLName, MLTypeCast, MLUnaryOp,
+    MLBinOp, MLBinOpKind, MLBlock, MLCall, MLCallArg, MLExpr, MLIf, MLIntrinsic, MLLiteral,
+    MLLiteralKind, MLLocation, MLMember, MLName, MLSlice, MLSubscript, MLTypeCast, MLUnaryOp,
     MLUnaryOpKind,
 };
-use wiz_mir::ml_decl::{MLArgDef, MLDecl, MLField, MLFun, MLFunBody, MLStruct, MLVar};
+use wiz_mir::ml_decl::{
+    MLArgDef, MLDecl, MLField, MLFun, MLFunBody, MLGlobalVar, MLStruct, MLUnion, MLVar,
+};
 use wiz_mir::ml_file::MLFile;
 use wiz_mir::ml_type::{MLFunctionType, MLPrimitiveType, MLType, MLValueType};
-use wiz_mir::statement::{MLReturn, MLStmt};
+use wiz_mir::statement::{MLAssignmentStmt, MLReturn, MLStmt};
 use wiz_session::{ParseSession, Session};
 use wiz_syntax_parser::parser::wiz::parse_from_string;
 use wizc_hir_lowing::hlir2mlir;
 
-fn check(source: &str, except: MLFile) {
+fn lower(source: &str, name: &str) -> MLFile {
     let session = ParseSession::default();
-    let ast = parse_from_string::<&str>(&session, None, source, Some(&except.name)).unwrap();
+    let ast = parse_from_string::<&str>(&session, None, source, Some(name)).unwrap();
 
     let mut session = Session::default();
 
@@ -25,11 +28,38 @@ fn check(source: &str, except: MLFile) {
 
     let hl_ss = ast2hlir.lowing(ast, ModuleId::DUMMY).unwrap();
 
-    let f = hlir2mlir(hl_ss, &[], &mut arena, &session, false).unwrap();
+    hlir2mlir(hl_ss, &[], &mut arena, &session, false).unwrap()
+}
+
+fn check(source: &str, except: MLFile) {
+    let f = lower(source, &except.name);
 
     assert_eq!(f, except);
 }
 
+/// Body of the function `name` defined in `f`.
+fn function_body(f: &MLFile, name: &str) -> Vec<MLStmt> {
+    f.body
+        .iter()
+        .find_map(|d| match d {
+            MLDecl::Fun(MLFun {
+                name: n,
+                body: Some(body),
+                ..
+            }) if n == name => Some(body.body.clone()),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("function {} is not defined", name))
+}
+
+fn location(line: u32, column: u32) -> Option<MLLocation> {
+    Some(MLLocation {
+        file: "<unknown source>".to_string(),
+        line,
+        column,
+    })
+}
+
 #[test]
 fn test_empty() {
     let source = "";
@@ -188,19 +218,23 @@ fn test_method_call() {
                     }],
                 }),
                 MLDecl::Fun(MLFun {
-                    name: "test::A::b##_#test::A".to_string(),
+                    name: "test::A::b##_#&test::A".to_string(),
                     arg_defs: vec![MLArgDef {
                         name: "self".to_string(),
-                        type_: MLValueType::Struct("test::A".to_string()),
+                        type_: MLValueType::Reference(Box::new(MLType::Value(
+                            MLValueType::Struct("test::A".to_string()),
+                        ))),
                     }],
                     return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
                     body: None,
                 }),
                 MLDecl::Fun(MLFun {
-                    name: "test::A::c##_#test::A".to_string(),
+                    name: "test::A::c##_#&test::A".to_string(),
                     arg_defs: vec![MLArgDef {
                         name: "self".to_string(),
-                        type_: MLValueType::Struct("test::A".to_string()),
+                        type_: MLValueType::Reference(Box::new(MLType::Value(
+                            MLValueType::Struct("test::A".to_string()),
+                        ))),
                     }],
                     return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
                     body: None,
@@ -218,10 +252,12 @@ fn test_method_call() {
                     body: None,
                 }),
                 MLDecl::Fun(MLFun {
-                    name: "test::A::b##_#test::A".to_string(),
+                    name: "test::A::b##_#&test::A".to_string(),
                     arg_defs: vec![MLArgDef {
                         name: "self".to_string(),
-                        type_: MLValueType::Struct("test::A".to_string()),
+                        type_: MLValueType::Reference(Box::new(MLType::Value(
+                            MLValueType::Struct("test::A".to_string()),
+                        ))),
                     }],
                     return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
                     body: Some(MLFunBody {
@@ -234,28 +270,36 @@ fn test_method_call() {
                     }),
                 }),
                 MLDecl::Fun(MLFun {
-                    name: "test::A::c##_#test::A".to_string(),
+                    name: "test::A::c##_#&test::A".to_string(),
                     arg_defs: vec![MLArgDef {
                         name: "self".to_string(),
-                        type_: MLValueType::Struct("test::A".to_string()),
+                        type_: MLValueType::Reference(Box::new(MLType::Value(
+                            MLValueType::Struct("test::A".to_string()),
+                        ))),
                     }],
                     return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
                     body: Some(MLFunBody {
                         body: vec![MLStmt::Expr(MLExpr::Return(MLReturn {
                             value: Some(Box::new(MLExpr::Call(MLCall {
-                                target: MLName {
-                                    name: "test::A::b##_#test::A".to_string(),
+                                target: Box::new(MLExpr::Name(MLName {
+                                    name: "test::A::b##_#&test::A".to_string(),
                                     type_: MLType::Function(MLFunctionType {
-                                        arguments: vec![MLValueType::Struct("test::A".to_string())],
+                                        arguments: vec![MLValueType::Reference(Box::new(
+                                            MLType::Value(MLValueType::Struct(
+                                                "test::A".to_string(),
+                                            )),
+                                        ))],
                                         return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
                                     }),
-                                },
+                                })),
                                 args: vec![MLCallArg {
                                     arg: MLExpr::Name(MLName {
                                         name: "self".to_string(),
-                                        type_: MLType::Value(MLValueType::Struct(
-                                            "test::A".to_string(),
-                                        )),
+                                        type_: MLType::Value(MLValueType::Reference(Box::new(
+                                            MLType::Value(MLValueType::Struct(
+                                                "test::A".to_string(),
+                                            )),
+                                        ))),
                                     }),
                                 }],
                                 type_: MLValueType::Primitive(MLPrimitiveType::Int64),
@@ -297,13 +341,17 @@ fn test_method_call() {
                                 }),
                             }),
                             MLStmt::Expr(MLExpr::Call(MLCall {
-                                target: MLName {
-                                    name: "test::A::c##_#test::A".to_string(),
+                                target: Box::new(MLExpr::Name(MLName {
+                                    name: "test::A::c##_#&test::A".to_string(),
                                     type_: MLType::Function(MLFunctionType {
-                                        arguments: vec![MLValueType::Struct("test::A".to_string())],
+                                        arguments: vec![MLValueType::Reference(Box::new(
+                                            MLType::Value(MLValueType::Struct(
+                                                "test::A".to_string(),
+                                            )),
+                                        ))],
                                         return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
                                     }),
-                                },
+                                })),
                                 args: vec![MLCallArg {
                                     arg: MLExpr::Name(MLName {
                                         name: "p".to_string(),
@@ -436,7 +484,7 @@ fn test_reference_dereference() {
                                 }),
                             }),
                             MLStmt::Expr(MLExpr::Call(MLCall {
-                                target: MLName {
+                                target: Box::new(MLExpr::Name(MLName {
                                     name: "test::reference_dereference##_#&Int64".to_string(),
                                     type_: MLType::Function(MLFunctionType {
                                         arguments: vec![MLValueType::Reference(Box::new(
@@ -446,7 +494,7 @@ fn test_reference_dereference() {
                                         ))],
                                         return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
                                     }),
-                                },
+                                })),
                                 args: vec![MLCallArg {
                                     arg: MLExpr::PrimitiveUnaryOp(MLUnaryOp {
                                         target: Box::new(MLExpr::Name(MLName {
@@ -507,6 +555,7 @@ fn test_type_cask() {
                                 type_: MLValueType::Pointer(Box::new(MLType::Value(
                                     MLValueType::Primitive(MLPrimitiveType::UInt8),
                                 ))),
+                                location: location(3, 18),
                             }))),
                         }))],
                     }),
@@ -515,3 +564,1376 @@ fn test_type_cask() {
         },
     )
 }
+
+#[test]
+fn test_union() {
+    let source = r"
+    union U {
+        var i: Int64
+        var f: Double
+    }
+    fun asInt(u: U): Int64 {
+        return u.i
+    }
+    ";
+
+    check(
+        source,
+        MLFile {
+            name: "test".to_string(),
+            body: vec![
+                MLDecl::Union(MLUnion {
+                    name: "test::U".to_string(),
+                    fields: vec![
+                        MLField {
+                            name: "i".to_string(),
+                            type_: MLValueType::Primitive(MLPrimitiveType::Int64),
+                        },
+                        MLField {
+                            name: "f".to_string(),
+                            type_: MLValueType::Primitive(MLPrimitiveType::Double),
+                        },
+                    ],
+                }),
+                MLDecl::Fun(MLFun {
+                    name: "test::U::size".to_string(),
+                    arg_defs: vec![],
+                    return_type: MLValueType::Primitive(MLPrimitiveType::USize),
+                    body: None,
+                }),
+                MLDecl::Fun(MLFun {
+                    name: "test::asInt##u#test::U".to_string(),
+                    arg_defs: vec![MLArgDef {
+                        name: "u".to_string(),
+                        type_: MLValueType::Struct("test::U".to_string()),
+                    }],
+                    return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
+                    body: None,
+                }),
+                MLDecl::Fun(MLFun {
+                    name: "test::U::size".to_string(),
+                    arg_defs: vec![],
+                    return_type: MLValueType::Primitive(MLPrimitiveType::USize),
+                    body: Some(MLFunBody {
+                        body: vec![MLStmt::Expr(MLExpr::Return(MLReturn {
+                            value: Some(Box::from(MLExpr::SizeOf(MLType::Value(
+                                MLValueType::Struct("test::U".to_string()),
+                            )))),
+                        }))],
+                    }),
+                }),
+                MLDecl::Fun(MLFun {
+                    name: "test::asInt##u#test::U".to_string(),
+                    arg_defs: vec![MLArgDef {
+                        name: "u".to_string(),
+                        type_: MLValueType::Struct("test::U".to_string()),
+                    }],
+                    return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
+                    body: Some(MLFunBody {
+                        body: vec![MLStmt::Expr(MLExpr::Return(MLReturn {
+                            value: Some(Box::new(MLExpr::Member(MLMember {
+                                target: Box::new(MLExpr::Name(MLName {
+                                    name: "u".to_string(),
+                                    type_: MLType::Value(MLValueType::Struct(
+                                        "test::U".to_string(),
+                                    )),
+                                })),
+                                name: "i".to_string(),
+                                type_: MLType::Value(MLValueType::Primitive(
+                                    MLPrimitiveType::Int64,
+                                )),
+                            }))),
+                        }))],
+                    }),
+                }),
+            ],
+        },
+    );
+}
+
+#[test]
+fn test_function_pointer() {
+    let source = r"
+    fun apply(f: (Int64) -> Int64, x: Int64): Int64 {
+        return f(x)
+    }
+    fun inc(x: Int64): Int64 {
+        return x
+    }
+    fun run(): Int64 {
+        return apply(inc, 1)
+    }
+    ";
+
+    let fun_type = MLFunctionType {
+        arguments: vec![MLValueType::Primitive(MLPrimitiveType::Int64)],
+        return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
+    };
+    let fun_pointer_type = MLValueType::Pointer(Box::new(MLType::Function(fun_type.clone())));
+
+    check(
+        source,
+        MLFile {
+            name: "test".to_string(),
+            body: vec![
+                MLDecl::Fun(MLFun {
+                    name: "test::apply##f#(Int64) -> Int64##x#Int64".to_string(),
+                    arg_defs: vec![
+                        MLArgDef {
+                            name: "f".to_string(),
+                            type_: fun_pointer_type.clone(),
+                        },
+                        MLArgDef {
+                            name: "x".to_string(),
+                            type_: MLValueType::Primitive(MLPrimitiveType::Int64),
+                        },
+                    ],
+                    return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
+                    body: None,
+                }),
+                MLDecl::Fun(MLFun {
+                    name: "test::inc##x#Int64".to_string(),
+                    arg_defs: vec![MLArgDef {
+                        name: "x".to_string(),
+                        type_: MLValueType::Primitive(MLPrimitiveType::Int64),
+                    }],
+                    return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
+                    body: None,
+                }),
+                MLDecl::Fun(MLFun {
+                    name: "test::run".to_string(),
+                    arg_defs: vec![],
+                    return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
+                    body: None,
+                }),
+                MLDecl::Fun(MLFun {
+                    name: "test::apply##f#(Int64) -> Int64##x#Int64".to_string(),
+                    arg_defs: vec![
+                        MLArgDef {
+                            name: "f".to_string(),
+                            type_: fun_pointer_type.clone(),
+                        },
+                        MLArgDef {
+                            name: "x".to_string(),
+                            type_: MLValueType::Primitive(MLPrimitiveType::Int64),
+                        },
+                    ],
+                    return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
+                    body: Some(MLFunBody {
+                        body: vec![MLStmt::Expr(MLExpr::Return(MLReturn {
+                            value: Some(Box::new(MLExpr::Call(MLCall {
+                                target: Box::new(MLExpr::Name(MLName {
+                                    name: "f".to_string(),
+                                    type_: MLType::Value(fun_pointer_type.clone()),
+                                })),
+                                args: vec![MLCallArg {
+                                    arg: MLExpr::Name(MLName {
+                                        name: "x".to_string(),
+                                        type_: MLType::Value(MLValueType::Primitive(
+                                            MLPrimitiveType::Int64,
+                                        )),
+                                    }),
+                                }],
+                                type_: MLValueType::Primitive(MLPrimitiveType::Int64),
+                            }))),
+                        }))],
+                    }),
+                }),
+                MLDecl::Fun(MLFun {
+                    name: "test::inc##x#Int64".to_string(),
+                    arg_defs: vec![MLArgDef {
+                        name: "x".to_string(),
+                        type_: MLValueType::Primitive(MLPrimitiveType::Int64),
+                    }],
+                    return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
+                    body: Some(MLFunBody {
+                        body: vec![MLStmt::Expr(MLExpr::Return(MLReturn {
+                            value: Some(Box::new(MLExpr::Name(MLName {
+                                name: "x".to_string(),
+                                type_: MLType::Value(MLValueType::Primitive(
+                                    MLPrimitiveType::Int64,
+                                )),
+                            }))),
+                        }))],
+                    }),
+                }),
+                MLDecl::Fun(MLFun {
+                    name: "test::run".to_string(),
+                    arg_defs: vec![],
+                    return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
+                    body: Some(MLFunBody {
+                        body: vec![MLStmt::Expr(MLExpr::Return(MLReturn {
+                            value: Some(Box::new(MLExpr::Call(MLCall {
+                                target: Box::new(MLExpr::Name(MLName {
+                                    name: "test::apply##f#(Int64) -> Int64##x#Int64".to_string(),
+                                    type_: MLType::Function(MLFunctionType {
+                                        arguments: vec![
+                                            fun_pointer_type.clone(),
+                                            MLValueType::Primitive(MLPrimitiveType::Int64),
+                                        ],
+                                        return_type: MLValueType::Primitive(MLPrimitiveType::Int64),
+                                    }),
+                                })),
+                                args: vec![
+                                    MLCallArg {
+                                        arg: MLExpr::Name(MLName {
+                                            name: "test::inc##x#Int64".to_string(),
+                                            type_: MLType::Value(fun_pointer_type),
+                                        }),
+                                    },
+                                    MLCallArg {
+                                        arg: MLExpr::Literal(MLLiteral {
+                                            kind: MLLiteralKind::Integer("1".to_string()),
+                                            type_: MLValueType::Primitive(MLPrimitiveType::Int64),
+                                        }),
+                                    },
+                                ],
+                                type_: MLValueType::Primitive(MLPrimitiveType::Int64),
+                            }))),
+                        }))],
+                    }),
+                }),
+            ],
+        },
+    );
+}
+
+#[test]
+fn test_byte_slice() {
+    let source = r#"
+    fun bytes(): Unit {
+        val s: [UInt8] = "abc"
+        val c = s[1]
+    }
+    "#;
+
+    let usize_literal = |value: &str| {
+        Box::new(MLExpr::Literal(MLLiteral {
+            kind: MLLiteralKind::Integer(value.to_string()),
+            type_: MLValueType::Primitive(MLPrimitiveType::USize),
+        }))
+    };
+    let bytes = MLValueType::Slice(Box::new(MLValueType::Primitive(MLPrimitiveType::UInt8)));
+
+    check(
+        source,
+        MLFile {
+            name: "test".to_string(),
+            body: vec![
+                MLDecl::Fun(MLFun {
+                    name: "test::bytes".to_string(),
+                    arg_defs: vec![],
+                    return_type: MLValueType::Primitive(MLPrimitiveType::Unit),
+                    body: None,
+                }),
+                MLDecl::Fun(MLFun {
+                    name: "test::bytes".to_string(),
+                    arg_defs: vec![],
+                    return_type: MLValueType::Primitive(MLPrimitiveType::Unit),
+                    body: Some(MLFunBody {
+                        body: vec![
+                            MLStmt::Var(MLVar {
+                                is_mute: false,
+                                name: "s".to_string(),
+                                type_: MLType::Value(bytes.clone()),
+                                value: MLExpr::Slice(MLSlice {
+                                    target: Box::new(MLExpr::Literal(MLLiteral {
+                                        kind: MLLiteralKind::String("abc".to_string()),
+                                        type_: MLValueType::Reference(Box::new(MLType::Value(
+                                            MLValueType::Primitive(MLPrimitiveType::String),
+                                        ))),
+                                    })),
+                                    start: usize_literal("0"),
+                                    end: usize_literal("3"),
+                                    type_: bytes.clone(),
+                                    location: None,
+                                }),
+                            }),
+                            MLStmt::Var(MLVar {
+                                is_mute: false,
+                                name: "c".to_string(),
+                                type_: MLType::Value(MLValueType::Primitive(
+                                    MLPrimitiveType::UInt8,
+                                )),
+                                value: MLExpr::PrimitiveSubscript(MLSubscript {
+                                    target: Box::new(MLExpr::Name(MLName {
+                                        name: "s".to_string(),
+                                        type_: MLType::Value(bytes),
+                                    })),
+                                    index: Box::new(MLExpr::Literal(MLLiteral {
+                                        kind: MLLiteralKind::Integer("1".to_string()),
+                                        type_: MLValueType::Primitive(MLPrimitiveType::Int64),
+                                    })),
+                                    type_: MLValueType::Primitive(MLPrimitiveType::UInt8),
+                                    location: location(4, 17),
+                                }),
+                            }),
+                        ],
+                    }),
+                }),
+            ],
+        },
+    );
+}
+
+#[test]
+fn test_intrinsic_call() {
+    let source = r"
+    #[builtin]
+    fun likely(_ condition: Bool): Bool
+
+    fun hot(_ c: Bool): Bool {
+        return likely(c)
+    }
+    ";
+
+    let bool_type = MLValueType::Primitive(MLPrimitiveType::Bool);
+    check(
+        source,
+        MLFile {
+            name: "test".to_string(),
+            body: vec![
+                MLDecl::Fun(MLFun {
+                    name: "test::hot##_#Bool".to_string(),
+                    arg_defs: vec![MLArgDef {
+                        name: "c".to_string(),
+                        type_: bool_type.clone(),
+                    }],
+                    return_type: bool_type.clone(),
+                    body: None,
+                }),
+                MLDecl::Fun(MLFun {
+                    name: "test::hot##_#Bool".to_string(),
+                    arg_defs: vec![MLArgDef {
+                        name: "c".to_string(),
+                        type_: bool_type.clone(),
+                    }],
+                    return_type: bool_type.clone(),
+                    body: Some(MLFunBody {
+                        body: vec![MLStmt::Expr(MLExpr::Return(MLReturn {
+                            value: Some(Box::new(MLExpr::Intrinsic(MLIntrinsic {
+                                name: "likely".to_string(),
+                                type_args: vec![],
+                                args: vec![MLCallArg {
+                                    arg: MLExpr::Name(MLName {
+                                        name: "c".to_string(),
+                                        type_: MLType::Value(bool_type.clone()),
+                                    }),
+                                }],
+                                type_: bool_type,
+                            }))),
+                        }))],
+                    }),
+                }),
+            ],
+        },
+    );
+}
+
+#[test]
+fn test_thread_local_and_extern_variables() {
+    let source = r"
+    extern {
+        var errno: Int32
+    }
+
+    #[thread_local]
+    var counter: Int32 = 0
+
+    fun errnoAddress(): *Int32 {
+        return &errno
+    }
+    ";
+
+    check(
+        source,
+        MLFile {
+            name: "test".to_string(),
+            body: vec![
+                MLDecl::Var(MLGlobalVar {
+                    is_mute: true,
+                    is_thread_local: true,
+                    name: "test::counter".to_string(),
+                    type_: MLType::Value(MLValueType::Primitive(MLPrimitiveType::Int32)),
+                    value: Some(MLExpr::Literal(MLLiteral {
+                        kind: MLLiteralKind::Integer("0".to_string()),
+                        type_: MLValueType::Primitive(MLPrimitiveType::Int32),
+                    })),
+                }),
+                MLDecl::Var(MLGlobalVar {
+                    is_mute: true,
+                    is_thread_local: false,
+                    name: "errno".to_string(),
+                    type_: MLType::Value(MLValueType::Primitive(MLPrimitiveType::Int32)),
+                    value: None,
+                }),
+                MLDecl::Fun(MLFun {
+                    name: "test::errnoAddress".to_string(),
+                    arg_defs: vec![],
+                    return_type: MLValueType::Pointer(Box::new(MLType::Value(
+                        MLValueType::Primitive(MLPrimitiveType::Int32),
+                    ))),
+                    body: None,
+                }),
+                MLDecl::Fun(MLFun {
+                    name: "test::errnoAddress".to_string(),
+                    arg_defs: vec![],
+                    return_type: MLValueType::Pointer(Box::new(MLType::Value(
+                        MLValueType::Primitive(MLPrimitiveType::Int32),
+                    ))),
+                    body: Some(MLFunBody {
+                        body: vec![MLStmt::Expr(MLExpr::Return(MLReturn {
+                            value: Some(Box::new(MLExpr::PrimitiveUnaryOp(MLUnaryOp {
+                                target: Box::new(MLExpr::Name(MLName {
+                                    name: "errno".to_string(),
+                                    type_: MLType::Value(MLValueType::Primitive(
+                                        MLPrimitiveType::Int32,
+                                    )),
+                                })),
+                                kind: MLUnaryOpKind::Ref,
+                                type_: MLValueType::Reference(Box::new(MLType::Value(
+                                    MLValueType::Primitive(MLPrimitiveType::Int32),
+                                ))),
+                            }))),
+                        }))],
+                    }),
+                }),
+            ],
+        },
+    );
+}
+
+#[test]
+fn test_initializer() {
+    let source = r"
+    struct A {
+        val a: Int64
+        init(value: Int64) {
+            self.a = value
+        }
+    }
+    fun make(): A {
+        return A(value: 1)
+    }
+    ";
+
+    let int64 = MLValueType::Primitive(MLPrimitiveType::Int64);
+    let a = MLValueType::Struct("test::A".to_string());
+    let init = MLFun {
+        name: "test::A::init##value#Int64".to_string(),
+        arg_defs: vec![MLArgDef {
+            name: "value".to_string(),
+            type_: int64.clone(),
+        }],
+        return_type: a.clone(),
+        body: None,
+    };
+    let size = MLFun {
+        name: "test::A::size".to_string(),
+        arg_defs: vec![],
+        return_type: MLValueType::Primitive(MLPrimitiveType::USize),
+        body: None,
+    };
+    let make = MLFun {
+        name: "test::make".to_string(),
+        arg_defs: vec![],
+        return_type: a.clone(),
+        body: None,
+    };
+    let self_ = MLExpr::Name(MLName {
+        name: "self".to_string(),
+        type_: MLType::Value(a.clone()),
+    });
+    check(
+        source,
+        MLFile {
+            name: "test".to_string(),
+            body: vec![
+                MLDecl::Struct(MLStruct {
+                    name: "test::A".to_string(),
+                    fields: vec![MLField {
+                        name: "a".to_string(),
+                        type_: int64.clone(),
+                    }],
+                }),
+                MLDecl::Fun(init.clone()),
+                MLDecl::Fun(size.clone()),
+                MLDecl::Fun(make.clone()),
+                MLDecl::Fun(MLFun {
+                    body: Some(MLFunBody {
+                        body: vec![
+                            MLStmt::Var(MLVar {
+                                is_mute: true,
+                                name: "self".to_string(),
+                                type_: MLType::Value(a.clone()),
+                                value: MLExpr::Literal(MLLiteral {
+                                    kind: MLLiteralKind::Struct(vec![]),
+                                    type_: a.clone(),
+                                }),
+                            }),
+                            MLStmt::Assignment(MLAssignmentStmt {
+                                target: MLExpr::Member(MLMember {
+                                    target: Box::new(self_.clone()),
+                                    name: "a".to_string(),
+                                    type_: MLType::Value(int64.clone()),
+                                }),
+                                value: MLExpr::Name(MLName {
+                                    name: "value".to_string(),
+                                    type_: MLType::Value(int64.clone()),
+                                }),
+                            }),
+                            MLStmt::Expr(MLExpr::Return(MLReturn::new(Some(self_)))),
+                        ],
+                    }),
+                    ..init
+                }),
+                MLDecl::Fun(MLFun {
+                    body: Some(MLFunBody {
+                        body: vec![MLStmt::Expr(MLExpr::Return(MLReturn::new(Some(
+                            MLExpr::SizeOf(MLType::Value(a.clone())),
+                        ))))],
+                    }),
+                    ..size
+                }),
+                MLDecl::Fun(MLFun {
+                    body: Some(MLFunBody {
+                        body: vec![MLStmt::Expr(MLExpr::Return(MLReturn::new(Some(
+                            MLExpr::Call(MLCall {
+                                target: Box::new(MLExpr::Name(MLName {
+                                    name: "test::A::init##value#Int64".to_string(),
+                                    type_: MLType::Function(MLFunctionType {
+                                        arguments: vec![int64.clone()],
+                                        return_type: a.clone(),
+                                    }),
+                                })),
+                                args: vec![MLCallArg {
+                                    arg: MLExpr::Literal(MLLiteral {
+                                        kind: MLLiteralKind::Integer("1".to_string()),
+                                        type_: int64,
+                                    }),
+                                }],
+                                type_: a,
+                            }),
+                        ))))],
+                    }),
+                    ..make
+                }),
+            ],
+        },
+    );
+}
+
+#[test]
+fn test_subscript() {
+    let source = r"
+    struct A {
+        var a: Int64
+        subscript(index: Int64): Int64 {
+            get {
+                return self.a
+            }
+            set {
+                self.a = newValue
+            }
+        }
+    }
+    fun f(_ a: &A): Int64 {
+        a[0] = 1
+        return a[0]
+    }
+    ";
+
+    let int64 = MLValueType::Primitive(MLPrimitiveType::Int64);
+    let unit = MLValueType::Primitive(MLPrimitiveType::Unit);
+    let a_ref = MLValueType::Reference(Box::new(MLType::Value(MLValueType::Struct(
+        "test::A".to_string(),
+    ))));
+    let arg = |name: &str, type_: &MLValueType| MLArgDef {
+        name: name.to_string(),
+        type_: type_.clone(),
+    };
+    let name = |name: &str, type_: MLType| {
+        MLExpr::Name(MLName {
+            name: name.to_string(),
+            type_,
+        })
+    };
+    let integer = |value: &str| {
+        MLExpr::Literal(MLLiteral {
+            kind: MLLiteralKind::Integer(value.to_string()),
+            type_: int64.clone(),
+        })
+    };
+    let self_a = MLExpr::Member(MLMember {
+        target: Box::new(name("self", MLType::Value(a_ref.clone()))),
+        name: "a".to_string(),
+        type_: MLType::Value(int64.clone()),
+    });
+    let getter = MLFun {
+        name: "test::A::subscript#get##_#&test::A##index#Int64".to_string(),
+        arg_defs: vec![arg("self", &a_ref), arg("index", &int64)],
+        return_type: int64.clone(),
+        body: None,
+    };
+    let setter = MLFun {
+        name: "test::A::subscript#set##_#&test::A##index#Int64##_#Int64".to_string(),
+        arg_defs: vec![
+            arg("self", &a_ref),
+            arg("index", &int64),
+            arg("newValue", &int64),
+        ],
+        return_type: unit.clone(),
+        body: None,
+    };
+    let size = MLFun {
+        name: "test::A::size".to_string(),
+        arg_defs: vec![],
+        return_type: MLValueType::Primitive(MLPrimitiveType::USize),
+        body: None,
+    };
+    let f = MLFun {
+        name: "test::f##_#&test::A".to_string(),
+        arg_defs: vec![arg("a", &a_ref)],
+        return_type: int64.clone(),
+        body: None,
+    };
+    let call = |fun: &MLFun, args: Vec<MLExpr>| {
+        MLExpr::Call(MLCall {
+            target: Box::new(name(
+                &fun.name,
+                MLType::Function(MLFunctionType {
+                    arguments: fun.arg_defs.iter().map(|a| a.type_.clone()).collect(),
+                    return_type: fun.return_type.clone(),
+                }),
+            )),
+            args: args.into_iter().map(|arg| MLCallArg { arg }).collect(),
+            type_: fun.return_type.clone(),
+        })
+    };
+    let a = name("a", MLType::Value(a_ref.clone()));
+    check(
+        source,
+        MLFile {
+            name: "test".to_string(),
+            body: vec![
+                MLDecl::Struct(MLStruct {
+                    name: "test::A".to_string(),
+                    fields: vec![MLField {
+                        name: "a".to_string(),
+                        type_: int64.clone(),
+                    }],
+                }),
+                MLDecl::Fun(getter.clone()),
+                MLDecl::Fun(setter.clone()),
+                MLDecl::Fun(size.clone()),
+                MLDecl::Fun(f.clone()),
+                MLDecl::Fun(MLFun {
+                    body: Some(MLFunBody {
+                        body: vec![MLStmt::Expr(MLExpr::Return(MLReturn::new(Some(
+                            self_a.clone(),
+                        ))))],
+                    }),
+                    ..getter.clone()
+                }),
+                MLDecl::Fun(MLFun {
+                    body: Some(MLFunBody {
+                        body: vec![MLStmt::Assignment(MLAssignmentStmt {
+                            target: self_a,
+                            value: name("newValue", MLType::Value(int64.clone())),
+                        })],
+                    }),
+                    ..setter.clone()
+                }),
+                MLDecl::Fun(MLFun {
+                    body: Some(MLFunBody {
+                        body: vec![MLStmt::Expr(MLExpr::Return(MLReturn::new(Some(
+                            MLExpr::SizeOf(MLType::Value(MLValueType::Struct(
+                                "test::A".to_string(),
+                            ))),
+                        ))))],
+                    }),
+                    ..size
+                }),
+                MLDecl::Fun(MLFun {
+                    body: Some(MLFunBody {
+                        body: vec![
+                            MLStmt::Expr(call(
+                                &setter,
+                                vec![a.clone(), integer("0"), integer("1")],
+                            )),
+                            MLStmt::Expr(MLExpr::Return(MLReturn::new(Some(call(
+                                &getter,
+                                vec![a, integer("0")],
+                            ))))),
+                        ],
+                    }),
+                    ..f
+                }),
+            ],
+        },
+    );
+}
+
+#[test]
+fn test_defer() {
+    let source = r"
+    fun g(_ a: Int64) {
+    }
+    fun f(): Int64 {
+        defer {
+            g(1)
+        }
+        defer {
+            g(2)
+        }
+        return 3
+    }
+    fun h() {
+        defer {
+            g(1)
+        }
+        g(2)
+    }
+    ";
+
+    let int64 = MLValueType::Primitive(MLPrimitiveType::Int64);
+    let unit = MLValueType::Primitive(MLPrimitiveType::Unit);
+    let integer = |value: &str| {
+        MLExpr::Literal(MLLiteral {
+            kind: MLLiteralKind::Integer(value.to_string()),
+            type_: int64.clone(),
+        })
+    };
+    let call_g = |value: &str| {
+        MLStmt::Expr(MLExpr::Call(MLCall {
+            target: Box::new(MLExpr::Name(MLName {
+                name: "test::g##_#Int64".to_string(),
+                type_: MLType::Function(MLFunctionType {
+                    arguments: vec![int64.clone()],
+                    return_type: unit.clone(),
+                }),
+            })),
+            args: vec![MLCallArg {
+                arg: integer(value),
+            }],
+            type_: unit.clone(),
+        }))
+    };
+    let defer = |value: &str| {
+        MLStmt::Expr(MLExpr::Block(MLBlock {
+            body: vec![call_g(value)],
+        }))
+    };
+    let fun = |name: &str,
+               arg_defs: Vec<MLArgDef>,
+               return_type: &MLValueType,
+               body: Option<Vec<MLStmt>>| {
+        MLDecl::Fun(MLFun {
+            name: name.to_string(),
+            arg_defs,
+            return_type: return_type.clone(),
+            body: body.map(|body| MLFunBody { body }),
+        })
+    };
+    let g_args = || {
+        vec![MLArgDef {
+            name: "a".to_string(),
+            type_: int64.clone(),
+        }]
+    };
+    let return_value = MLExpr::Name(MLName {
+        name: "#return".to_string(),
+        type_: MLType::Value(int64.clone()),
+    });
+    check(
+        source,
+        MLFile {
+            name: "test".to_string(),
+            body: vec![
+                fun("test::g##_#Int64", g_args(), &unit, None),
+                fun("test::f", vec![], &int64, None),
+                fun("test::h", vec![], &unit, None),
+                fun("test::g##_#Int64", g_args(), &unit, Some(vec![])),
+                fun(
+                    "test::f",
+                    vec![],
+                    &int64,
+                    Some(vec![MLStmt::Expr(MLExpr::Block(MLBlock {
+                        body: vec![
+                            MLStmt::Var(MLVar {
+                                is_mute: false,
+                                name: "#return".to_string(),
+                                type_: MLType::Value(int64.clone()),
+                                value: integer("3"),
+                            }),
+                            defer("2"),
+                            defer("1"),
+                            MLStmt::Expr(MLExpr::Return(MLReturn::new(Some(return_value)))),
+                        ],
+                    }))]),
+                ),
+                fun(
+                    "test::h",
+                    vec![],
+                    &unit,
+                    Some(vec![call_g("2"), defer("1")]),
+                ),
+            ],
+        },
+    );
+}
+
+#[test]
+fn test_defer_after_if_without_else() {
+    let source = r"
+    fun g(_ a: Int64) {
+    }
+    fun f(_ c: Bool) {
+        defer {
+            g(1)
+        }
+        if c {
+            g(2)
+        }
+    }
+    ";
+
+    let body = function_body(&lower(source, "test"), "test::f##_#Bool");
+
+    assert!(matches!(
+        body.as_slice(),
+        [MLStmt::Expr(MLExpr::If(_)), MLStmt::Expr(MLExpr::Block(_))]
+    ));
+}
+
+#[test]
+fn test_class() {
+    let source = r"
+    class A {
+        var b: Int64
+    }
+    class B {
+        var a: A
+        deinit {
+            val c = 1
+        }
+    }
+    ";
+
+    let f = lower(source, "test");
+
+    let u64 = MLValueType::Primitive(MLPrimitiveType::UInt64);
+    let a = MLValueType::Reference(Box::new(MLType::Value(MLValueType::Struct(
+        "test::A".to_string(),
+    ))));
+    let b = MLValueType::Reference(Box::new(MLType::Value(MLValueType::Struct(
+        "test::B".to_string(),
+    ))));
+    let structs: Vec<_> = f
+        .body
+        .iter()
+        .filter_map(|d| match d {
+            MLDecl::Struct(s) => Some(s.clone()),
+            _ => None,
+        })
+        .collect();
+    let field = |name: &str, type_: &MLValueType| MLField {
+        name: name.to_string(),
+        type_: type_.clone(),
+    };
+    assert_eq!(
+        structs,
+        vec![
+            MLStruct {
+                name: "test::A".to_string(),
+                fields: vec![
+                    field("#refCount", &u64),
+                    field("b", &MLValueType::Primitive(MLPrimitiveType::Int64)),
+                ],
+            },
+            MLStruct {
+                name: "test::B".to_string(),
+                fields: vec![field("#refCount", &u64), field("a", &a)],
+            },
+        ]
+    );
+
+    let name = |name: &str, type_: &MLValueType| {
+        MLExpr::Name(MLName {
+            name: name.to_string(),
+            type_: MLType::Value(type_.clone()),
+        })
+    };
+    let count = |value: &str| {
+        MLExpr::Literal(MLLiteral {
+            kind: MLLiteralKind::Integer(value.to_string()),
+            type_: u64.clone(),
+        })
+    };
+    let ref_count = MLExpr::Member(MLMember {
+        target: Box::new(name("self", &b)),
+        name: "#refCount".to_string(),
+        type_: MLType::Value(u64.clone()),
+    });
+    let unit = MLValueType::Primitive(MLPrimitiveType::Unit);
+    let bool = MLValueType::Primitive(MLPrimitiveType::Bool);
+    let call = |fun: &str, arg: MLExpr| {
+        MLStmt::Expr(MLExpr::Call(MLCall {
+            target: Box::new(MLExpr::Name(MLName {
+                name: fun.to_string(),
+                type_: MLType::Function(MLFunctionType {
+                    arguments: vec![arg.type_().into_value_type()],
+                    return_type: unit.clone(),
+                }),
+            })),
+            args: vec![MLCallArg { arg }],
+            type_: unit.clone(),
+        }))
+    };
+    let u8_pointer = MLValueType::Pointer(Box::new(MLType::Value(MLValueType::Primitive(
+        MLPrimitiveType::UInt8,
+    ))));
+    assert_eq!(
+        function_body(&f, "test::B::#release"),
+        vec![MLStmt::Expr(MLExpr::If(MLIf {
+            condition: Box::new(MLExpr::PrimitiveBinOp(MLBinOp {
+                left: Box::new(MLExpr::PrimitiveTypeCast(MLTypeCast {
+                    target: Box::new(name("self", &b)),
+                    type_: MLValueType::Primitive(MLPrimitiveType::USize),
+                    location: None,
+                })),
+                kind: MLBinOpKind::NotEqual,
+                right: Box::new(MLExpr::Literal(MLLiteral {
+                    kind: MLLiteralKind::Integer("0".to_string()),
+                    type_: MLValueType::Primitive(MLPrimitiveType::USize),
+                })),
+                type_: bool.clone(),
+                location: None,
+            })),
+            body: MLBlock {
+                body: vec![
+                    MLStmt::Assignment(MLAssignmentStmt {
+                        target: ref_count.clone(),
+                        value: MLExpr::PrimitiveBinOp(MLBinOp {
+                            left: Box::new(ref_count.clone()),
+                            kind: MLBinOpKind::Minus,
+                            right: Box::new(count("1")),
+                            type_: u64.clone(),
+                            location: None,
+                        }),
+                    }),
+                    MLStmt::Expr(MLExpr::If(MLIf {
+                        condition: Box::new(MLExpr::PrimitiveBinOp(MLBinOp {
+                            left: Box::new(ref_count),
+                            kind: MLBinOpKind::Equal,
+                            right: Box::new(count("0")),
+                            type_: bool,
+                            location: None,
+                        })),
+                        body: MLBlock {
+                            body: vec![
+                                call("test::B::#deinit##_#&test::B", name("self", &b)),
+                                call(
+                                    "test::A::#release",
+                                    MLExpr::Member(MLMember {
+                                        target: Box::new(name("self", &b)),
+                                        name: "a".to_string(),
+                                        type_: MLType::Value(a),
+                                    }),
+                                ),
+                                call(
+                                    "std::memory::allocator::deallocateObject##_#*UInt8",
+                                    MLExpr::PrimitiveTypeCast(MLTypeCast {
+                                        target: Box::new(name("self", &b)),
+                                        type_: u8_pointer,
+                                        location: None,
+                                    }),
+                                ),
+                            ],
+                        },
+                        else_body: None,
+                        type_: unit.clone(),
+                    })),
+                ],
+            },
+            else_body: None,
+            type_: unit,
+        }))]
+    );
+}
+
+#[test]
+fn test_class_reference_counting() {
+    let source = r"
+    class A {
+        var b: Int64
+    }
+    fun make(): A {
+        return A(b: 1)
+    }
+    fun take(_ a: A) {
+    }
+    fun f(_ x: A) {
+        val a = x
+        val b = make()
+        take(make())
+    }
+    ";
+
+    let f = lower(source, "test");
+
+    let a = MLValueType::Reference(Box::new(MLType::Value(MLValueType::Struct(
+        "test::A".to_string(),
+    ))));
+    let unit = MLValueType::Primitive(MLPrimitiveType::Unit);
+    let name = |name: &str| {
+        MLExpr::Name(MLName {
+            name: name.to_string(),
+            type_: MLType::Value(a.clone()),
+        })
+    };
+    let call = |fun: &str, args: Vec<MLExpr>, type_: &MLValueType| {
+        MLExpr::Call(MLCall {
+            target: Box::new(MLExpr::Name(MLName {
+                name: fun.to_string(),
+                type_: MLType::Function(MLFunctionType {
+                    arguments: args.iter().map(|a| a.type_().into_value_type()).collect(),
+                    return_type: type_.clone(),
+                }),
+            })),
+            args: args.into_iter().map(|arg| MLCallArg { arg }).collect(),
+            type_: type_.clone(),
+        })
+    };
+    let val = |n: &str, value: MLExpr| {
+        MLStmt::Var(MLVar {
+            is_mute: false,
+            name: n.to_string(),
+            type_: MLType::Value(a.clone()),
+            value,
+        })
+    };
+    let release = |n: &str| MLStmt::Expr(call("test::A::#release", vec![name(n)], &unit));
+    assert_eq!(
+        function_body(&f, "test::make"),
+        vec![MLStmt::Expr(MLExpr::Return(MLReturn::new(Some(call(
+            "test::A::#new",
+            vec![MLExpr::Literal(MLLiteral {
+                kind: MLLiteralKind::Struct(vec![
+                    (
+                        "#refCount".to_string(),
+                        MLExpr::Literal(MLLiteral {
+                            kind: MLLiteralKind::Integer("0".to_string()),
+                            type_: MLValueType::Primitive(MLPrimitiveType::UInt64),
+                        })
+                    ),
+                    (
+                        "b".to_string(),
+                        MLExpr::Literal(MLLiteral {
+                            kind: MLLiteralKind::Integer("1".to_string()),
+                            type_: MLValueType::Primitive(MLPrimitiveType::Int64),
+                        })
+                    ),
+                ]),
+                type_: MLValueType::Struct("test::A".to_string()),
+            })],
+            &a,
+        )))))]
+    );
+    assert_eq!(
+        function_body(&f, "test::f##_#test::A"),
+        vec![
+            val(
+                "a",
+                MLExpr::Block(MLBlock {
+                    body: vec![
+                        MLStmt::Expr(call("test::A::#retain", vec![name("x")], &unit)),
+                        MLStmt::Expr(name("x")),
+                    ],
+                })
+            ),
+            val("b", call("test::make", vec![], &a)),
+            MLStmt::Expr(MLExpr::Block(MLBlock {
+                body: vec![
+                    val("#arg0", call("test::make", vec![], &a)),
+                    MLStmt::Expr(call("test::take##_#test::A", vec![name("#arg0")], &unit)),
+                    release("#arg0"),
+                ],
+            })),
+            release("b"),
+            release("a"),
+        ]
+    );
+}
+
+#[test]
+fn test_nested_call_temporaries() {
+    let source = r"
+    class A {
+        var b: Int64
+    }
+    fun make(): A {
+        return A(b: 1)
+    }
+    fun wrap(_ a: A): A {
+        return a
+    }
+    fun pair(_ a: A, _ b: A) {
+    }
+    fun f() {
+        pair(wrap(make()), wrap(make()))
+    }
+    ";
+
+    let f = lower(source, "test");
+
+    let a = MLValueType::Reference(Box::new(MLType::Value(MLValueType::Struct(
+        "test::A".to_string(),
+    ))));
+    let unit = MLValueType::Primitive(MLPrimitiveType::Unit);
+    let name = |name: &str| {
+        MLExpr::Name(MLName {
+            name: name.to_string(),
+            type_: MLType::Value(a.clone()),
+        })
+    };
+    let call = |fun: &str, args: Vec<MLExpr>, type_: &MLValueType| {
+        MLExpr::Call(MLCall {
+            target: Box::new(MLExpr::Name(MLName {
+                name: fun.to_string(),
+                type_: MLType::Function(MLFunctionType {
+                    arguments: args.iter().map(|a| a.type_().into_value_type()).collect(),
+                    return_type: type_.clone(),
+                }),
+            })),
+            args: args.into_iter().map(|arg| MLCallArg { arg }).collect(),
+            type_: type_.clone(),
+        })
+    };
+    let val = |n: &str, value: MLExpr| {
+        MLStmt::Var(MLVar {
+            is_mute: false,
+            name: n.to_string(),
+            type_: MLType::Value(a.clone()),
+            value,
+        })
+    };
+    let release = |n: &str| MLStmt::Expr(call("test::A::#release", vec![name(n)], &unit));
+    // every reference made by `make` is released once, after the call it is passed to
+    let wrap = |arg: &str, result: &str| {
+        MLExpr::Block(MLBlock {
+            body: vec![
+                val(arg, call("test::make", vec![], &a)),
+                val(result, call("test::wrap##_#test::A", vec![name(arg)], &a)),
+                release(arg),
+                MLStmt::Expr(name(result)),
+            ],
+        })
+    };
+    assert_eq!(
+        function_body(&f, "test::f"),
+        vec![MLStmt::Expr(MLExpr::Block(MLBlock {
+            body: vec![
+                val("#arg2", wrap("#arg0", "#result1")),
+                val("#arg5", wrap("#arg3", "#result4")),
+                MLStmt::Expr(call(
+                    "test::pair##_#test::A##_#test::A",
+                    vec![name("#arg2"), name("#arg5")],
+                    &unit,
+                )),
+                release("#arg2"),
+                release("#arg5"),
+            ],
+        }))]
+    );
+}
+
+#[test]
+fn test_deinit_live_owner() {
+    let source = r"
+    struct A {
+        val a: Int64
+        deinit {
+        }
+    }
+    fun take(_ a: A) {
+    }
+    fun f(_ c: Bool) {
+        val a = A(a: 1)
+        if c {
+            take(a)
+        }
+    }
+    ";
+
+    let f = lower(source, "test");
+
+    let a = MLType::Value(MLValueType::Struct("test::A".to_string()));
+    let bool = MLType::Value(MLValueType::Primitive(MLPrimitiveType::Bool));
+    let unit = MLValueType::Primitive(MLPrimitiveType::Unit);
+    let name = |name: &str, type_: &MLType| {
+        MLExpr::Name(MLName {
+            name: name.to_string(),
+            type_: type_.clone(),
+        })
+    };
+    let literal = |value: &str| {
+        MLExpr::Literal(MLLiteral {
+            kind: MLLiteralKind::Boolean(value.to_string()),
+            type_: MLValueType::Primitive(MLPrimitiveType::Bool),
+        })
+    };
+    let live = |n: &str| {
+        MLStmt::Var(MLVar {
+            is_mute: true,
+            name: format!("#live#{}", n),
+            type_: bool.clone(),
+            value: literal("true"),
+        })
+    };
+    let call = |fun: &str, arg: MLExpr| {
+        MLExpr::Call(MLCall {
+            target: Box::new(MLExpr::Name(MLName {
+                name: fun.to_string(),
+                type_: MLType::Function(MLFunctionType {
+                    arguments: vec![arg.type_().into_value_type()],
+                    return_type: unit.clone(),
+                }),
+            })),
+            args: vec![MLCallArg { arg }],
+            type_: unit.clone(),
+        })
+    };
+    let deinit_if_live = |n: &str| {
+        MLStmt::Expr(MLExpr::If(MLIf {
+            condition: Box::new(name(&format!("#live#{}", n), &bool)),
+            body: MLBlock {
+                body: vec![MLStmt::Expr(call(
+                    "test::A::#deinit##_#&test::A",
+                    MLExpr::PrimitiveUnaryOp(MLUnaryOp {
+                        target: Box::new(name(n, &a)),
+                        kind: MLUnaryOpKind::Ref,
+                        type_: MLValueType::Reference(Box::new(a.clone())),
+                    }),
+                ))],
+            },
+            else_body: None,
+            type_: unit.clone(),
+        }))
+    };
+    assert_eq!(
+        function_body(&f, "test::take##_#test::A"),
+        vec![live("a"), deinit_if_live("a")]
+    );
+    assert_eq!(
+        function_body(&f, "test::f##_#Bool"),
+        vec![
+            MLStmt::Var(MLVar {
+                is_mute: false,
+                name: "a".to_string(),
+                type_: a.clone(),
+                value: MLExpr::Literal(MLLiteral {
+                    kind: MLLiteralKind::Struct(vec![(
+                        "a".to_string(),
+                        MLExpr::Literal(MLLiteral {
+                            kind: MLLiteralKind::Integer("1".to_string()),
+                            type_: MLValueType::Primitive(MLPrimitiveType::Int64),
+                        })
+                    )]),
+                    type_: MLValueType::Struct("test::A".to_string()),
+                }),
+            }),
+            live("a"),
+            MLStmt::Expr(MLExpr::If(MLIf {
+                condition: Box::new(name("c", &bool)),
+                body: MLBlock {
+                    body: vec![MLStmt::Expr(call(
+                        "test::take##_#test::A",
+                        MLExpr::Block(MLBlock {
+                            body: vec![
+                                MLStmt::Assignment(MLAssignmentStmt {
+                                    target: name("#live#a", &bool),
+                                    value: literal("false"),
+                                }),
+                                MLStmt::Expr(name("a", &a)),
+                            ],
+                        }),
+                    ))],
+                },
+                else_body: None,
+                type_: MLValueType::Primitive(MLPrimitiveType::Noting),
+            })),
+            deinit_if_live("a"),
+        ]
+    );
+}
+
+#[test]
+fn test_slice_by_range_evaluated_once() {
+    let source = r"
+    namespace builtin {
+        struct Range {
+            val start: USize
+            val end: USize
+        }
+    }
+    fun range(): builtin::Range {
+        return builtin::Range(start: 1, end: 2)
+    }
+    fun bytes(_ s: [UInt8]): [UInt8] {
+        return s[range()]
+    }
+    ";
+
+    let f = lower(source, "core");
+
+    let range_struct = MLValueType::Struct("core::builtin::Range".to_string());
+    let range_type = MLType::Value(range_struct.clone());
+    let range = MLName {
+        name: "#range".to_string(),
+        type_: range_type.clone(),
+    };
+    let bound = |name: &str| {
+        Box::new(MLExpr::Member(MLMember {
+            target: Box::new(MLExpr::Name(range.clone())),
+            name: name.to_string(),
+            type_: MLType::Value(MLValueType::Primitive(MLPrimitiveType::USize)),
+        }))
+    };
+    let bytes = MLValueType::Slice(Box::new(MLValueType::Primitive(MLPrimitiveType::UInt8)));
+    assert_eq!(
+        function_body(&f, "core::bytes##_#[UInt8]"),
+        vec![MLStmt::Expr(MLExpr::Return(MLReturn::new(Some(
+            MLExpr::Block(MLBlock {
+                body: vec![
+                    MLStmt::Var(MLVar {
+                        is_mute: false,
+                        name: range.name.clone(),
+                        type_: range_type,
+                        value: MLExpr::Call(MLCall {
+                            target: Box::new(MLExpr::Name(MLName {
+                                name: "core::range".to_string(),
+                                type_: MLType::Function(MLFunctionType {
+                                    arguments: vec![],
+                                    return_type: range_struct.clone(),
+                                }),
+                            })),
+                            args: vec![],
+                            type_: range_struct,
+                        }),
+                    }),
+                    MLStmt::Expr(MLExpr::Slice(MLSlice {
+                        target: Box::new(MLExpr::Name(MLName {
+                            name: "s".to_string(),
+                            type_: MLType::Value(bytes.clone()),
+                        })),
+                        start: bound("start"),
+                        end: bound("end"),
+                        type_: bytes,
+                        location: location(12, 16),
+                    })),
+                ],
+            })
+        ))))]
+    );
+}
@@ -0,0 +1,398 @@
+use std::fmt;
+use wiz_hir::typed_decl::TypedFun;
+use wiz_hir::typed_type::{TypedArgType, TypedFunctionType, TypedType, TypedValueType};
+
+/// Functions declared with `#[builtin]`, which the code generator expands at each call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Intrinsic {
+    Panic,
+    Trap,
+    Unreachable,
+    Likely,
+    Unlikely,
+    Memcpy,
+    Memset,
+    AddWithOverflow,
+    SubWithOverflow,
+    MulWithOverflow,
+    Bitcast,
+    AlignOf,
+    AtomicLoad,
+    AtomicStore,
+    AtomicExchange,
+    AtomicAdd,
+    AtomicSub,
+    AtomicCompareExchange,
+}
+
+/// Types in intrinsic signatures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ty {
+    Noting,
+    Unit,
+    Bool,
+    UInt8,
+    UInt32,
+    USize,
+    Str,
+    /// `*UInt8`
+    BytePointer,
+    /// `UInt8` which must be a constant [MemoryOrdering]
+    Ordering,
+    /// `n`-th type parameter
+    Param(usize),
+    /// Pointer to `n`-th type parameter
+    ParamPointer(usize),
+}
+
+const TYPE_PARAMS: [&str; 2] = ["T", "U"];
+
+/// Memory orderings of atomic operations.
+/// Values are ones of the constants declared in `core::builtin`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MemoryOrdering {
+    Relaxed,
+    Acquire,
+    Release,
+    AcqRel,
+    SeqCst,
+}
+
+impl MemoryOrdering {
+    pub(crate) fn from_value(value: i128) -> Option<Self> {
+        Some(match value {
+            0 => Self::Relaxed,
+            1 => Self::Acquire,
+            2 => Self::Release,
+            3 => Self::AcqRel,
+            4 => Self::SeqCst,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for MemoryOrdering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Relaxed => "RELAXED",
+            Self::Acquire => "ACQUIRE",
+            Self::Release => "RELEASE",
+            Self::AcqRel => "ACQ_REL",
+            Self::SeqCst => "SEQ_CST",
+        })
+    }
+}
+
+impl Intrinsic {
+    const ALL: [Self; 18] = [
+        Self::Panic,
+        Self::Trap,
+        Self::Unreachable,
+        Self::Likely,
+        Self::Unlikely,
+        Self::Memcpy,
+        Self::Memset,
+        Self::AddWithOverflow,
+        Self::SubWithOverflow,
+        Self::MulWithOverflow,
+        Self::Bitcast,
+        Self::AlignOf,
+        Self::AtomicLoad,
+        Self::AtomicStore,
+        Self::AtomicExchange,
+        Self::AtomicAdd,
+        Self::AtomicSub,
+        Self::AtomicCompareExchange,
+    ];
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|i| i.name() == name)
+    }
+
+    /// Intrinsic named `name` in MLIR, where member functions of `Atomic<T>` are `Atomic::name`.
+    pub(crate) fn from_ml_name(name: &str) -> Option<Self> {
+        match name.strip_prefix("Atomic::") {
+            Some(method) => Self::from_atomic_method(method),
+            None => Self::from_name(name),
+        }
+    }
+
+    /// Intrinsic which implements the member function `name` of `Atomic<T>`,
+    /// whose receiver is passed as the first argument.
+    pub(crate) fn from_atomic_method(name: &str) -> Option<Self> {
+        Some(match name {
+            "load" => Self::AtomicLoad,
+            "store" => Self::AtomicStore,
+            "swap" => Self::AtomicExchange,
+            "compareExchange" => Self::AtomicCompareExchange,
+            "fetchAdd" => Self::AtomicAdd,
+            "fetchSub" => Self::AtomicSub,
+            _ => return None,
+        })
+    }
+
+    /// Type of the member function of `Atomic<T>` implemented by this intrinsic.
+    pub(crate) fn atomic_method_type(self, value_type: &TypedType) -> TypedFunctionType {
+        let (_, params, return_type) = self.signature();
+        let type_ = |t: &Ty| match t {
+            Ty::Param(_) => value_type.clone(),
+            Ty::Ordering => TypedType::uint8(),
+            Ty::Unit => TypedType::unit(),
+            Ty::Bool => TypedType::bool(),
+            t => unreachable!("{} in signature of atomic intrinsic", t),
+        };
+        let receiver = TypedArgType {
+            label: "_".to_string(),
+            typ: TypedType::reference(TypedType::atomic(value_type.clone())),
+        };
+        TypedFunctionType {
+            arguments: std::iter::once(receiver)
+                .chain(params[1..].iter().map(|p| TypedArgType {
+                    label: "_".to_string(),
+                    typ: type_(p),
+                }))
+                .collect(),
+            return_type: type_(&return_type),
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Panic => "panic",
+            Self::Trap => "trap",
+            Self::Unreachable => "unreachable",
+            Self::Likely => "likely",
+            Self::Unlikely => "unlikely",
+            Self::Memcpy => "memcpy",
+            Self::Memset => "memset",
+            Self::AddWithOverflow => "addWithOverflow",
+            Self::SubWithOverflow => "subWithOverflow",
+            Self::MulWithOverflow => "mulWithOverflow",
+            Self::Bitcast => "bitcast",
+            Self::AlignOf => "alignOf",
+            Self::AtomicLoad => "atomicLoad",
+            Self::AtomicStore => "atomicStore",
+            Self::AtomicExchange => "atomicExchange",
+            Self::AtomicAdd => "atomicAdd",
+            Self::AtomicSub => "atomicSub",
+            Self::AtomicCompareExchange => "atomicCompareExchange",
+        }
+    }
+
+    /// Whether the call never returns.
+    pub(crate) fn is_diverging(self) -> bool {
+        self.signature().2 == Ty::Noting
+    }
+
+    /// Indices of parameters which are memory orderings.
+    pub(crate) fn ordering_params(self) -> Vec<usize> {
+        let (_, params, _) = self.signature();
+        (0..params.len())
+            .filter(|i| params[*i] == Ty::Ordering)
+            .collect()
+    }
+
+    /// Check that the instruction can be emitted with `orderings`.
+    pub(crate) fn verify_orderings(self, orderings: &[MemoryOrdering]) -> Result<(), String> {
+        let invalid = match (self, orderings) {
+            (Self::AtomicLoad, [o @ (MemoryOrdering::Release | MemoryOrdering::AcqRel)]) => {
+                Some(("load", o))
+            }
+            (Self::AtomicStore, [o @ (MemoryOrdering::Acquire | MemoryOrdering::AcqRel)]) => {
+                Some(("store", o))
+            }
+            (
+                Self::AtomicCompareExchange,
+                [_, o @ (MemoryOrdering::Release | MemoryOrdering::AcqRel)],
+            ) => Some(("failed compare exchange", o)),
+            _ => None,
+        };
+        match invalid {
+            Some((operation, ordering)) => Err(format!(
+                "`{}` is not a valid memory ordering for atomic {}",
+                ordering, operation
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Number of type parameters, parameter types and return type.
+    fn signature(self) -> (usize, &'static [Ty], Ty) {
+        match self {
+            Self::Panic => (0, &[Ty::Str, Ty::Str, Ty::UInt32, Ty::UInt32], Ty::Noting),
+            Self::Trap | Self::Unreachable => (0, &[], Ty::Noting),
+            Self::Likely | Self::Unlikely => (0, &[Ty::Bool], Ty::Bool),
+            Self::Memcpy => (0, &[Ty::BytePointer, Ty::BytePointer, Ty::USize], Ty::Unit),
+            Self::Memset => (0, &[Ty::BytePointer, Ty::UInt8, Ty::USize], Ty::Unit),
+            Self::AddWithOverflow | Self::SubWithOverflow | Self::MulWithOverflow => (
+                1,
+                &[Ty::Param(0), Ty::Param(0), Ty::ParamPointer(0)],
+                Ty::Bool,
+            ),
+            Self::Bitcast => (2, &[Ty::Param(0)], Ty::Param(1)),
+            Self::AlignOf => (1, &[], Ty::USize),
+            Self::AtomicLoad => (1, &[Ty::ParamPointer(0), Ty::Ordering], Ty::Param(0)),
+            Self::AtomicStore => (
+                1,
+                &[Ty::ParamPointer(0), Ty::Param(0), Ty::Ordering],
+                Ty::Unit,
+            ),
+            Self::AtomicExchange | Self::AtomicAdd | Self::AtomicSub => (
+                1,
+                &[Ty::ParamPointer(0), Ty::Param(0), Ty::Ordering],
+                Ty::Param(0),
+            ),
+            Self::AtomicCompareExchange => (
+                1,
+                &[
+                    Ty::ParamPointer(0),
+                    Ty::Param(0),
+                    Ty::Param(0),
+                    Ty::Ordering,
+                    Ty::Ordering,
+                ],
+                Ty::Bool,
+            ),
+        }
+    }
+
+    /// Check that `f` is declared with the signature of the intrinsic.
+    pub(crate) fn verify(self, f: &TypedFun) -> Result<(), String> {
+        let (type_param_count, params, return_type) = self.signature();
+        let type_params = f
+            .type_params
+            .iter()
+            .flatten()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>();
+        let matches = type_params.len() == type_param_count
+            && f.arg_defs.len() == params.len()
+            && f.arg_defs
+                .iter()
+                .zip(params)
+                .all(|(a, p)| p.matches(&a.type_, &type_params))
+            && return_type.matches(&f.return_type, &type_params);
+        if matches {
+            Ok(())
+        } else {
+            Err(format!(
+                "intrinsic `{}` must be declared as `{}`",
+                self.name(),
+                self
+            ))
+        }
+    }
+}
+
+impl fmt::Display for Intrinsic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (type_param_count, params, return_type) = self.signature();
+        write!(f, "fun {}", self.name())?;
+        if type_param_count > 0 {
+            write!(f, "<{}>", TYPE_PARAMS[..type_param_count].join(", "))?;
+        }
+        let params = params.iter().map(Ty::to_string).collect::<Vec<_>>();
+        write!(f, "({}): {}", params.join(", "), return_type)
+    }
+}
+
+impl Ty {
+    fn matches(self, t: &TypedType, type_params: &[&str]) -> bool {
+        match self {
+            Ty::Noting => *t == TypedType::noting(),
+            Ty::Unit => *t == TypedType::unit(),
+            Ty::Bool => *t == TypedType::bool(),
+            Ty::UInt8 | Ty::Ordering => *t == TypedType::uint8(),
+            Ty::UInt32 => *t == TypedType::uint32(),
+            Ty::USize => *t == TypedType::usize(),
+            Ty::Str => *t == TypedType::string_ref(),
+            Ty::BytePointer => {
+                *t == TypedType::Value(TypedValueType::Pointer(Box::new(TypedType::uint8())))
+            }
+            Ty::Param(n) => Self::is_type_param(t, type_params[n]),
+            Ty::ParamPointer(n) => match t {
+                TypedType::Value(TypedValueType::Pointer(p)) => {
+                    Self::is_type_param(p, type_params[n])
+                }
+                _ => false,
+            },
+        }
+    }
+
+    fn is_type_param(t: &TypedType, name: &str) -> bool {
+        matches!(t, TypedType::Value(TypedValueType::Value(v)) if v.name == name && v.type_args.is_none())
+    }
+}
+
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ty::Noting => f.write_str("Noting"),
+            Ty::Unit => f.write_str("Unit"),
+            Ty::Bool => f.write_str("Bool"),
+            Ty::UInt8 | Ty::Ordering => f.write_str("UInt8"),
+            Ty::UInt32 => f.write_str("UInt32"),
+            Ty::USize => f.write_str("USize"),
+            Ty::Str => f.write_str("&str"),
+            Ty::BytePointer => f.write_str("*UInt8"),
+            Ty::Param(n) => f.write_str(TYPE_PARAMS[*n]),
+            Ty::ParamPointer(n) => write!(f, "*{}", TYPE_PARAMS[*n]),
+        }
+    }
+}
+
+/// Check that `Atomic<T>` can be lowered to atomic instructions.
+pub(crate) fn verify_atomic_value_type(t: &TypedType) -> Result<(), String> {
+    if t.is_integer() {
+        Ok(())
+    } else {
+        Err(format!(
+            "`Atomic` requires an integer type argument, but `{}` was given",
+            t.to_string()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Intrinsic, MemoryOrdering};
+
+    #[test]
+    fn test_name() {
+        for intrinsic in Intrinsic::ALL {
+            assert_eq!(Intrinsic::from_name(intrinsic.name()), Some(intrinsic));
+        }
+        assert_eq!(Intrinsic::from_name("fork"), None);
+        assert_eq!(
+            Intrinsic::from_ml_name("Atomic::fetchAdd"),
+            Some(Intrinsic::AtomicAdd)
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Intrinsic::AddWithOverflow.to_string(),
+            "fun addWithOverflow<T>(T, T, *T): Bool"
+        );
+        assert_eq!(Intrinsic::Bitcast.to_string(), "fun bitcast<T, U>(T): U");
+        assert_eq!(
+            Intrinsic::AtomicStore.to_string(),
+            "fun atomicStore<T>(*T, T, UInt8): Unit"
+        );
+    }
+
+    #[test]
+    fn test_verify_orderings() {
+        assert!(Intrinsic::AtomicLoad
+            .verify_orderings(&[MemoryOrdering::Acquire])
+            .is_ok());
+        assert_eq!(
+            Intrinsic::AtomicLoad.verify_orderings(&[MemoryOrdering::Release]),
+            Err("`RELEASE` is not a valid memory ordering for atomic load".to_string())
+        );
+        assert!(Intrinsic::AtomicCompareExchange
+            .verify_orderings(&[MemoryOrdering::AcqRel, MemoryOrdering::Acquire])
+            .is_ok());
+    }
+}
@@ -1,30 +1,41 @@
 use either::Either;
+use inkwell::attributes::{Attribute, AttributeLoc};
 use inkwell::builder::Builder;
 use inkwell::context::Context;
 use inkwell::execution_engine::ExecutionEngine;
 use inkwell::module::Module;
 use inkwell::support::LLVMString;
 use inkwell::targets::{CodeModel, FileType, RelocMode, Target, TargetMachine, TargetTriple};
-use inkwell::types::{AnyType, AnyTypeEnum, BasicMetadataTypeEnum, BasicType, BasicTypeEnum};
+use inkwell::types::{
+    AnyType, AnyTypeEnum, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType, IntType,
+};
 use inkwell::values::{
-    AnyValue, AnyValueEnum, BasicMetadataValueEnum, BasicValueEnum, FunctionValue,
+    AnyValue, AnyValueEnum, BasicMetadataValueEnum, BasicValue, BasicValueEnum, CallableValue,
+    FunctionValue, InstructionValue, IntValue, PointerValue,
+};
+use inkwell::{
+    AddressSpace, AtomicOrdering, AtomicRMWBinOp, FloatPredicate, IntPredicate, OptimizationLevel,
 };
-use inkwell::{AddressSpace, FloatPredicate, IntPredicate, OptimizationLevel};
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::path::Path;
 use wiz_mir::expr::{
-    MLArray, MLBinOp, MLBinOpKind, MLBlock, MLCall, MLExpr, MLIf, MLLiteral, MLLiteralKind,
-    MLMember, MLName, MLSubscript, MLTypeCast, MLUnaryOp, MLUnaryOpKind,
+    MLArray, MLBinOp, MLBinOpKind, MLBlock, MLCall, MLCallArg, MLExpr, MLIf, MLIntrinsic,
+    MLLiteral, MLLiteralKind, MLLocation, MLMember, MLName, MLSlice, MLSubscript, MLTypeCast,
+    MLUnaryOp, MLUnaryOpKind, MLUnwrap,
 };
-use wiz_mir::ml_decl::{MLDecl, MLFun, MLStruct, MLVar};
+use wiz_mir::ml_decl::{MLDecl, MLFun, MLGlobalVar, MLStruct, MLUnion, MLVar};
 use wiz_mir::ml_file::MLFile;
-use wiz_mir::ml_type::{MLPrimitiveType, MLType, MLValueType};
+use wiz_mir::ml_type::{MLFunctionType, MLPrimitiveType, MLType, MLValueType};
 use wiz_mir::statement::{MLAssignmentStmt, MLLoopStmt, MLReturn, MLStmt};
+use wiz_session::DataLayout;
 use wiz_utils::StackedHashMap;
 
+use crate::intrinsic::{Intrinsic, MemoryOrdering};
+
 pub(crate) struct MLContext<'ctx> {
     pub(crate) struct_environment: StackedHashMap<String, MLStruct>,
+    pub(crate) union_environment: StackedHashMap<String, MLUnion>,
     pub(crate) local_environments: StackedHashMap<String, AnyValueEnum<'ctx>>,
     pub(crate) current_function: Option<FunctionValue<'ctx>>,
 }
@@ -33,6 +44,7 @@ impl<'ctx> MLContext<'ctx> {
     pub(crate) fn new() -> Self {
         Self {
             struct_environment: StackedHashMap::from(HashMap::new()),
+            union_environment: StackedHashMap::from(HashMap::new()),
             local_environments: StackedHashMap::from(HashMap::new()),
             current_function: None,
         }
@@ -42,11 +54,13 @@ impl<'ctx> MLContext<'ctx> {
 impl<'ctx> MLContext<'ctx> {
     pub fn push_environment(&mut self) {
         self.struct_environment.push(HashMap::new());
+        self.union_environment.push(HashMap::new());
         self.local_environments.push(HashMap::new());
     }
 
     pub fn pop_environment(&mut self) {
         self.struct_environment.pop();
+        self.union_environment.pop();
         self.local_environments.pop();
     }
 
@@ -57,6 +71,14 @@ impl<'ctx> MLContext<'ctx> {
     pub fn get_struct(&self, name: &str) -> Option<&MLStruct> {
         self.struct_environment.get(name)
     }
+
+    pub fn put_union(&mut self, u: MLUnion) {
+        self.union_environment.insert(u.name.clone(), u);
+    }
+
+    pub fn get_union(&self, name: &str) -> Option<&MLUnion> {
+        self.union_environment.get(name)
+    }
 }
 
 pub struct CodeGen<'ctx> {
@@ -66,6 +88,8 @@ pub struct CodeGen<'ctx> {
     pub(crate) execution_engine: ExecutionEngine<'ctx>,
     pub(crate) target: Target,
     pub(crate) ml_context: MLContext<'ctx>,
+    /// Emit bounds and overflow checks that panic at runtime.
+    pub(crate) runtime_checks: bool,
 }
 
 impl<'ctx> CodeGen<'ctx> {
@@ -89,9 +113,21 @@ impl<'ctx> CodeGen<'ctx> {
             execution_engine,
             target: Target::from_triple(&target_triple).unwrap(),
             ml_context: MLContext::new(),
+            runtime_checks: false,
         }
     }
 
+    pub(crate) fn with_runtime_checks(mut self, runtime_checks: bool) -> Self {
+        self.runtime_checks = runtime_checks;
+        self
+    }
+
+    /// Data layout of the target, which types are laid out by.
+    pub(crate) fn data_layout(&self) -> DataLayout {
+        let data_layout = self.execution_engine.get_target_data().get_data_layout();
+        DataLayout::parse(&data_layout.as_str().to_string_lossy())
+    }
+
     fn get_from_environment(&self, name: &str) -> Option<AnyValueEnum<'ctx>> {
         match self.ml_context.local_environments.get(name) {
             Some(v) => Some(*v),
@@ -119,6 +155,38 @@ impl<'ctx> CodeGen<'ctx> {
         }
     }
 
+    fn get_union_by_ml_type(&self, m: &MLValueType) -> Option<&MLUnion> {
+        match m {
+            MLValueType::Struct(type_name) => self.ml_context.get_union(type_name),
+            MLValueType::Reference(ref_mltype) => {
+                self.get_union_by_ml_type(&ref_mltype.clone().into_value_type())
+            }
+            _ => None,
+        }
+    }
+
+    fn get_union_field_type_by_name(&self, m: &MLValueType, n: &str) -> Option<MLValueType> {
+        self.get_union_by_ml_type(m)?
+            .fields
+            .iter()
+            .find(|f| f.name == n)
+            .map(|f| f.type_.clone())
+    }
+
+    /// Reinterpret a pointer to union as a pointer to one of its members.
+    fn union_field_pointer(
+        &self,
+        target: PointerValue<'ctx>,
+        field_type: MLValueType,
+    ) -> PointerValue<'ctx> {
+        let field_type = BasicTypeEnum::try_from(self.ml_type_to_type(field_type)).unwrap();
+        self.builder.build_pointer_cast(
+            target,
+            field_type.ptr_type(AddressSpace::Generic),
+            "union_field",
+        )
+    }
+
     fn get_struct_field_index_by_name(&self, m: &MLValueType, n: &str) -> Option<u32> {
         match self.get_struct_by_ml_type(m) {
             None => {
@@ -151,6 +219,9 @@ impl<'ctx> CodeGen<'ctx> {
             MLExpr::PrimitiveTypeCast(t) => self.type_cast(t),
             MLExpr::Block(b) => self.block(b),
             MLExpr::SizeOf(t) => self.size_of(t),
+            MLExpr::Slice(s) => self.slice(s),
+            MLExpr::Unwrap(u) => self.unwrap(u),
+            MLExpr::Intrinsic(i) => self.intrinsic(i),
         }
     }
 
@@ -162,7 +233,8 @@ impl<'ctx> CodeGen<'ctx> {
     pub fn literal(&mut self, l: MLLiteral) -> AnyValueEnum<'ctx> {
         match l.kind {
             MLLiteralKind::Integer(value) => {
-                let i: u64 = value.parse().unwrap();
+                // NOTE: folded constants may be negative, which are emitted in two's complement
+                let i = value.parse::<i128>().unwrap() as u64;
                 let int_type = match l.type_ {
                     MLValueType::Primitive(name) => match name {
                         MLPrimitiveType::Int8 | MLPrimitiveType::UInt8 => self.context.i8_type(),
@@ -216,13 +288,32 @@ impl<'ctx> CodeGen<'ctx> {
                 let ptr_ty = ty.ptr_type(AddressSpace::Generic);
                 ptr_ty.const_null().as_any_value_enum()
             }
+            MLLiteralKind::Struct(fields) if self.get_union_by_ml_type(&l.type_).is_some() => {
+                let union_type = self.ml_type_to_type(l.type_.clone()).into_struct_type();
+                let u = self.builder.build_alloca(union_type, "u_tmp");
+                for (name, expr) in fields.into_iter() {
+                    let field_type = self
+                        .get_union_field_type_by_name(&l.type_, &name)
+                        .unwrap_or_else(|| panic!("union {:?} has no field {}", l.type_, name));
+                    let f_ptr = self.union_field_pointer(u, field_type);
+                    let expr_type = expr.type_().into_value_type();
+                    let expr = self.expr(expr);
+                    let expr = self.load_if_pointer_value(expr, &expr_type);
+                    let expr = BasicValueEnum::try_from(expr).unwrap();
+                    self.builder.build_store(f_ptr, expr);
+                }
+                u.as_any_value_enum()
+            }
             MLLiteralKind::Struct(fields) => {
                 let struct_type = self.module.get_struct_type(match &l.type_ {
                     MLValueType::Struct(name) => name,
                     p => panic!("Invalid Struct Literal {:?}", p),
                 });
                 let struct_type = struct_type.unwrap();
-                if fields.iter().all(|(_, y)| y.is_primitive_literal()) {
+                if fields.is_empty() {
+                    // `self` of initializers starts from a zero value
+                    struct_type.const_zero().as_any_value_enum()
+                } else if fields.iter().all(|(_, y)| y.is_primitive_literal()) {
                     let f = fields
                         .into_iter()
                         .map(|(_, e)| BasicValueEnum::try_from(self.expr(e)).unwrap())
@@ -250,29 +341,22 @@ impl<'ctx> CodeGen<'ctx> {
     }
 
     pub fn call(&mut self, c: MLCall) -> AnyValueEnum<'ctx> {
-        let target = self.name_expr(c.target);
-        let args = c.args.into_iter().map(|arg| {
-            if let MLValueType::Primitive(name) = arg.arg.type_().into_value_type() {
-                if name != MLPrimitiveType::String {
-                    let t = MLValueType::Primitive(name);
-                    let e = self.expr(arg.arg);
-                    self.load_if_pointer_value(e, &t)
-                } else {
-                    self.expr(arg.arg)
-                }
-            } else if let MLValueType::Pointer(p) = arg.arg.type_().into_value_type() {
-                let t = MLValueType::Pointer(p);
-                let e = self.expr(arg.arg);
-                self.load_if_pointer_value(e, &t)
-            } else {
-                self.expr(arg.arg)
-            }
-        });
+        let target_type = c.target.type_();
+        let target = self.expr(*c.target);
+        let args = c.args.into_iter().map(|arg| self.call_arg(arg));
         let args: Vec<BasicMetadataValueEnum> = args
             .filter_map(|arg| BasicValueEnum::try_from(arg).ok())
             .map(|i| i.into())
             .collect();
-        let function = target.into_function_value();
+        let function = match target {
+            AnyValueEnum::FunctionValue(function) => CallableValue::from(function),
+            target => {
+                // indirect call via function pointer
+                let target = self.load_if_pointer_value(target, &target_type.into_value_type());
+                CallableValue::try_from(target.into_pointer_value())
+                    .unwrap_or_else(|_| panic!("{:?} is not callable", target))
+            }
+        };
         let bv = self
             .builder
             .build_call(function, &args, "f_call")
@@ -283,6 +367,24 @@ impl<'ctx> CodeGen<'ctx> {
         }
     }
 
+    fn call_arg(&mut self, arg: MLCallArg) -> AnyValueEnum<'ctx> {
+        if let MLValueType::Primitive(name) = arg.arg.type_().into_value_type() {
+            if name != MLPrimitiveType::String {
+                let t = MLValueType::Primitive(name);
+                let e = self.expr(arg.arg);
+                self.load_if_pointer_value(e, &t)
+            } else {
+                self.expr(arg.arg)
+            }
+        } else if let MLValueType::Pointer(p) = arg.arg.type_().into_value_type() {
+            let t = MLValueType::Pointer(p);
+            let e = self.expr(arg.arg);
+            self.load_if_pointer_value(e, &t)
+        } else {
+            self.expr(arg.arg)
+        }
+    }
+
     pub fn binop(&mut self, b: MLBinOp) -> AnyValueEnum<'ctx> {
         let l_type = b.left.type_().into_value_type();
         let r_type = b.right.type_().into_value_type();
@@ -292,72 +394,31 @@ impl<'ctx> CodeGen<'ctx> {
         let rit = self.load_if_pointer_value(rit, &r_type);
 
         match (lft, rit) {
-            (AnyValueEnum::IntValue(left), AnyValueEnum::IntValue(right)) => match b.kind {
-                MLBinOpKind::Plus => {
-                    let v = self.builder.build_int_add(left, right, "sum");
-                    v.as_any_value_enum()
-                }
-                MLBinOpKind::Minus => {
-                    let v = self.builder.build_int_sub(left, right, "sub");
-                    v.as_any_value_enum()
-                }
-                MLBinOpKind::Mul => {
-                    let v = self.builder.build_int_mul(left, right, "mul");
-                    v.as_any_value_enum()
-                }
-                MLBinOpKind::Div => {
-                    let v = self.builder.build_int_signed_div(left, right, "sdiv");
-                    v.as_any_value_enum()
-                }
-                MLBinOpKind::Mod => {
-                    let v = self.builder.build_int_signed_rem(left, right, "srem");
-                    v.as_any_value_enum()
-                }
-                MLBinOpKind::Equal => {
-                    let v = self
-                        .builder
-                        .build_int_compare(IntPredicate::EQ, left, right, "eq");
-                    v.as_any_value_enum()
-                }
-                MLBinOpKind::GrateThanEqual => {
-                    let v = self
-                        .builder
-                        .build_int_compare(IntPredicate::SGE, left, right, "gte");
-                    v.as_any_value_enum()
-                }
-                MLBinOpKind::GrateThan => {
-                    let v = self
-                        .builder
-                        .build_int_compare(IntPredicate::SGT, left, right, "gt");
-                    v.as_any_value_enum()
-                }
-                MLBinOpKind::LessThanEqual => {
-                    let v = self
-                        .builder
-                        .build_int_compare(IntPredicate::SLE, left, right, "lte");
-                    v.as_any_value_enum()
-                }
-                MLBinOpKind::LessThan => {
-                    let v = self
-                        .builder
-                        .build_int_compare(IntPredicate::SLT, left, right, "lt");
-                    v.as_any_value_enum()
-                }
-                MLBinOpKind::NotEqual => {
-                    let v = self
-                        .builder
-                        .build_int_compare(IntPredicate::NE, left, right, "neq");
-                    v.as_any_value_enum()
-                }
-                MLBinOpKind::And => {
-                    let v = self.builder.build_and(left, right, "and");
-                    v.as_any_value_enum()
-                }
-                MLBinOpKind::Or => {
-                    let v = self.builder.build_or(left, right, "or");
-                    v.as_any_value_enum()
+            (AnyValueEnum::IntValue(left), AnyValueEnum::IntValue(right))
+                if self.runtime_checks && l_type.is_integer() =>
+            {
+                let is_signed = l_type.is_signed_integer();
+                let location = b.location.as_ref();
+                match b.kind {
+                    MLBinOpKind::Plus => self
+                        .build_checked_int_op("add", "add", is_signed, left, right, location)
+                        .as_any_value_enum(),
+                    MLBinOpKind::Minus => self
+                        .build_checked_int_op("sub", "subtract", is_signed, left, right, location)
+                        .as_any_value_enum(),
+                    MLBinOpKind::Mul => self
+                        .build_checked_int_op("mul", "multiply", is_signed, left, right, location)
+                        .as_any_value_enum(),
+                    kind @ (MLBinOpKind::Div | MLBinOpKind::Mod) => {
+                        self.build_division_check(is_signed, left, right, location);
+                        self.int_binop(kind, left, right)
+                    }
+                    kind => self.int_binop(kind, left, right),
                 }
-            },
+            }
+            (AnyValueEnum::IntValue(left), AnyValueEnum::IntValue(right)) => {
+                self.int_binop(b.kind, left, right)
+            }
             (AnyValueEnum::FloatValue(left), AnyValueEnum::FloatValue(right)) => match b.kind {
                 MLBinOpKind::Plus => {
                     let v = self.builder.build_float_add(left, right, "sum");
@@ -500,6 +561,574 @@ impl<'ctx> CodeGen<'ctx> {
         }
     }
 
+    /// Integer binary operation without runtime checks.
+    fn int_binop(
+        &self,
+        kind: MLBinOpKind,
+        left: IntValue<'ctx>,
+        right: IntValue<'ctx>,
+    ) -> AnyValueEnum<'ctx> {
+        match kind {
+            MLBinOpKind::Plus => {
+                let v = self.builder.build_int_add(left, right, "sum");
+                v.as_any_value_enum()
+            }
+            MLBinOpKind::Minus => {
+                let v = self.builder.build_int_sub(left, right, "sub");
+                v.as_any_value_enum()
+            }
+            MLBinOpKind::Mul => {
+                let v = self.builder.build_int_mul(left, right, "mul");
+                v.as_any_value_enum()
+            }
+            MLBinOpKind::Div => {
+                let v = self.builder.build_int_signed_div(left, right, "sdiv");
+                v.as_any_value_enum()
+            }
+            MLBinOpKind::Mod => {
+                let v = self.builder.build_int_signed_rem(left, right, "srem");
+                v.as_any_value_enum()
+            }
+            MLBinOpKind::Equal => {
+                let v = self
+                    .builder
+                    .build_int_compare(IntPredicate::EQ, left, right, "eq");
+                v.as_any_value_enum()
+            }
+            MLBinOpKind::GrateThanEqual => {
+                let v = self
+                    .builder
+                    .build_int_compare(IntPredicate::SGE, left, right, "gte");
+                v.as_any_value_enum()
+            }
+            MLBinOpKind::GrateThan => {
+                let v = self
+                    .builder
+                    .build_int_compare(IntPredicate::SGT, left, right, "gt");
+                v.as_any_value_enum()
+            }
+            MLBinOpKind::LessThanEqual => {
+                let v = self
+                    .builder
+                    .build_int_compare(IntPredicate::SLE, left, right, "lte");
+                v.as_any_value_enum()
+            }
+            MLBinOpKind::LessThan => {
+                let v = self
+                    .builder
+                    .build_int_compare(IntPredicate::SLT, left, right, "lt");
+                v.as_any_value_enum()
+            }
+            MLBinOpKind::NotEqual => {
+                let v = self
+                    .builder
+                    .build_int_compare(IntPredicate::NE, left, right, "neq");
+                v.as_any_value_enum()
+            }
+            MLBinOpKind::And => {
+                let v = self.builder.build_and(left, right, "and");
+                v.as_any_value_enum()
+            }
+            MLBinOpKind::Or => {
+                let v = self.builder.build_or(left, right, "or");
+                v.as_any_value_enum()
+            }
+        }
+    }
+
+    /// `left op right` through `llvm.[su]{op}.with.overflow`, panicking on overflow.
+    fn build_checked_int_op(
+        &mut self,
+        op: &str,
+        verb: &str,
+        is_signed: bool,
+        left: IntValue<'ctx>,
+        right: IntValue<'ctx>,
+        location: Option<&MLLocation>,
+    ) -> IntValue<'ctx> {
+        let (value, overflow) = self.build_with_overflow(op, is_signed, left, right);
+        self.build_runtime_check(
+            overflow,
+            &format!("attempt to {} with overflow", verb),
+            &[],
+            location,
+        );
+        value
+    }
+
+    /// `left op right` and whether it overflowed, through `llvm.[su]{op}.with.overflow`.
+    fn build_with_overflow(
+        &mut self,
+        op: &str,
+        is_signed: bool,
+        left: IntValue<'ctx>,
+        right: IntValue<'ctx>,
+    ) -> (IntValue<'ctx>, IntValue<'ctx>) {
+        let int_type = left.get_type();
+        let result_type = self
+            .context
+            .struct_type(&[int_type.into(), self.context.bool_type().into()], false);
+        let intrinsic = self.external_function(
+            &format!(
+                "llvm.{}{}.with.overflow.i{}",
+                if is_signed { "s" } else { "u" },
+                op,
+                int_type.get_bit_width()
+            ),
+            result_type.fn_type(&[int_type.into(), int_type.into()], false),
+        );
+        let result = self
+            .builder
+            .build_call(intrinsic, &[left.into(), right.into()], op)
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_struct_value();
+        let value = self
+            .builder
+            .build_extract_value(result, 0, op)
+            .unwrap()
+            .into_int_value();
+        let overflow = self
+            .builder
+            .build_extract_value(result, 1, "overflow")
+            .unwrap()
+            .into_int_value();
+        (value, overflow)
+    }
+
+    /// Panic on division by zero and on `MIN / -1` for signed integers.
+    fn build_division_check(
+        &mut self,
+        is_signed: bool,
+        left: IntValue<'ctx>,
+        right: IntValue<'ctx>,
+        location: Option<&MLLocation>,
+    ) {
+        let int_type = left.get_type();
+        let is_zero = self.builder.build_int_compare(
+            IntPredicate::EQ,
+            right,
+            int_type.const_zero(),
+            "div_zero",
+        );
+        self.build_runtime_check(is_zero, "attempt to divide by zero", &[], location);
+        if is_signed {
+            let min = int_type
+                .const_int(1, false)
+                .const_shl(int_type.const_int(u64::from(int_type.get_bit_width() - 1), false));
+            let is_min = self
+                .builder
+                .build_int_compare(IntPredicate::EQ, left, min, "div_min");
+            let is_minus_one = self.builder.build_int_compare(
+                IntPredicate::EQ,
+                right,
+                int_type.const_all_ones(),
+                "div_minus_one",
+            );
+            let overflow = self.builder.build_and(is_min, is_minus_one, "div_overflow");
+            self.build_runtime_check(overflow, "attempt to divide with overflow", &[], location);
+        }
+    }
+
+    /// Panic unless `index < length`.
+    fn build_bounds_check(
+        &mut self,
+        index: IntValue<'ctx>,
+        is_signed: bool,
+        length: IntValue<'ctx>,
+        location: Option<&MLLocation>,
+    ) {
+        let i64_type = self.context.i64_type();
+        let index = self.build_int_resize(index, i64_type, is_signed);
+        let length = self.build_int_resize(length, i64_type, false);
+        // a negative index wraps to a large unsigned value, so one compare covers both ends
+        let out_of_bounds =
+            self.builder
+                .build_int_compare(IntPredicate::UGE, index, length, "out_of_bounds");
+        self.build_runtime_check(
+            out_of_bounds,
+            if is_signed {
+                "index out of bounds: the len is %llu but the index is %lld"
+            } else {
+                "index out of bounds: the len is %llu but the index is %llu"
+            },
+            &[length.into(), index.into()],
+            location,
+        );
+    }
+
+    /// Panic unless `start <= end` and, if the length is known, `end <= length`.
+    fn build_range_check(
+        &mut self,
+        start: IntValue<'ctx>,
+        end: IntValue<'ctx>,
+        length: Option<IntValue<'ctx>>,
+        location: Option<&MLLocation>,
+    ) {
+        let i64_type = self.context.i64_type();
+        let start = self.build_int_resize(start, i64_type, false);
+        let end = self.build_int_resize(end, i64_type, false);
+        let reversed =
+            self.builder
+                .build_int_compare(IntPredicate::UGT, start, end, "slice_reversed");
+        self.build_runtime_check(
+            reversed,
+            "slice index starts at %llu but ends at %llu",
+            &[start.into(), end.into()],
+            location,
+        );
+        if let Some(length) = length {
+            let length = self.build_int_resize(length, i64_type, false);
+            let out_of_bounds = self.builder.build_int_compare(
+                IntPredicate::UGT,
+                end,
+                length,
+                "slice_out_of_bounds",
+            );
+            self.build_runtime_check(
+                out_of_bounds,
+                "range end index %llu out of range for slice of length %llu",
+                &[end.into(), length.into()],
+                location,
+            );
+        }
+    }
+
+    /// Panic unless `value` survives the round trip through the type of `converted`.
+    fn build_cast_check(
+        &mut self,
+        value: IntValue<'ctx>,
+        from_signed: bool,
+        converted: IntValue<'ctx>,
+        to_signed: bool,
+        type_name: &str,
+        location: Option<&MLLocation>,
+    ) {
+        let back = self.build_int_resize(converted, value.get_type(), to_signed);
+        let mut failed =
+            self.builder
+                .build_int_compare(IntPredicate::NE, back, value, "cast_lossy");
+        if from_signed != to_signed {
+            // the value must not be negative on the signed side
+            let signed = if from_signed { value } else { converted };
+            let negative = self.builder.build_int_compare(
+                IntPredicate::SLT,
+                signed,
+                signed.get_type().const_zero(),
+                "cast_negative",
+            );
+            failed = self.builder.build_or(failed, negative, "cast_failed");
+        }
+        self.build_runtime_check(
+            failed,
+            &format!("attempt to cast to `{}` with overflow", type_name),
+            &[],
+            location,
+        );
+    }
+
+    /// Truncate or extend `value` to `int_type`.
+    fn build_int_resize(
+        &self,
+        value: IntValue<'ctx>,
+        int_type: IntType<'ctx>,
+        is_signed: bool,
+    ) -> IntValue<'ctx> {
+        let from = value.get_type().get_bit_width();
+        let to = int_type.get_bit_width();
+        if from > to {
+            self.builder.build_int_truncate(value, int_type, "trunc")
+        } else if from < to && is_signed {
+            self.builder.build_int_s_extend(value, int_type, "sext")
+        } else if from < to {
+            self.builder.build_int_z_extend(value, int_type, "zext")
+        } else {
+            value
+        }
+    }
+
+    /// Branch to a panic block when `failed` holds and continue in a new block.
+    fn build_runtime_check(
+        &mut self,
+        failed: IntValue<'ctx>,
+        message: &str,
+        args: &[BasicMetadataValueEnum<'ctx>],
+        location: Option<&MLLocation>,
+    ) {
+        let function = self.ml_context.current_function.unwrap();
+        let panic_block = self.context.append_basic_block(function, "panic");
+        let ok_block = self.context.append_basic_block(function, "ok");
+        self.builder
+            .build_conditional_branch(failed, panic_block, ok_block);
+        self.builder.position_at_end(panic_block);
+        self.build_panic(message, args, location);
+        self.builder.position_at_end(ok_block);
+    }
+
+    /// Print `message` with the source location, or the enclosing function and module
+    /// when it is unknown, to stderr and abort.
+    ///
+    /// `message` is a `printf` format filled with `args`.
+    fn build_panic(
+        &mut self,
+        message: &str,
+        args: &[BasicMetadataValueEnum<'ctx>],
+        location: Option<&MLLocation>,
+    ) {
+        let function = self.ml_context.current_function.unwrap();
+        let position = match location {
+            Some(l) => format!("{}:{}:{}", l.file.replace('%', "%%"), l.line, l.column),
+            None => format!(
+                "`{}` in {}",
+                function.get_name().to_string_lossy(),
+                self.module.get_name().to_string_lossy()
+            ),
+        };
+        self.build_print_and_abort(&format!("panic at {}: {}\n", position, message), args);
+    }
+
+    /// Expand a call of an intrinsic in place.
+    pub fn intrinsic(&mut self, i: MLIntrinsic) -> AnyValueEnum<'ctx> {
+        let intrinsic = Intrinsic::from_ml_name(&i.name)
+            .unwrap_or_else(|| panic!("unknown intrinsic `{}`", i.name));
+        let arg_types = i
+            .args
+            .iter()
+            .map(|a| a.arg.type_().into_value_type())
+            .collect::<Vec<_>>();
+        let args = i
+            .args
+            .into_iter()
+            .map(|a| {
+                let t = a.arg.type_().into_value_type();
+                let e = self.expr(a.arg);
+                self.load_if_pointer_value(e, &t)
+            })
+            .collect::<Vec<_>>();
+        let result = match intrinsic {
+            Intrinsic::Panic => {
+                let args = args
+                    .into_iter()
+                    .map(|a| BasicValueEnum::try_from(a).unwrap().into())
+                    .collect::<Vec<BasicMetadataValueEnum>>();
+                self.build_print_and_abort(
+                    "panic at %s:%u:%u: %s\n",
+                    &[args[1], args[2], args[3], args[0]],
+                )
+                .as_any_value_enum()
+            }
+            Intrinsic::Trap => {
+                let trap = self
+                    .external_function("llvm.trap", self.context.void_type().fn_type(&[], false));
+                self.builder.build_call(trap, &[], "");
+                self.builder.build_unreachable().as_any_value_enum()
+            }
+            Intrinsic::Unreachable => self.builder.build_unreachable().as_any_value_enum(),
+            Intrinsic::Likely | Intrinsic::Unlikely => {
+                let bool_type = self.context.bool_type();
+                let expect = self.external_function(
+                    "llvm.expect.i1",
+                    bool_type.fn_type(&[bool_type.into(), bool_type.into()], false),
+                );
+                let expected =
+                    bool_type.const_int(u64::from(intrinsic == Intrinsic::Likely), false);
+                self.builder
+                    .build_call(
+                        expect,
+                        &[args[0].into_int_value().into(), expected.into()],
+                        "expect",
+                    )
+                    .try_as_basic_value()
+                    .left()
+                    .unwrap()
+                    .as_any_value_enum()
+            }
+            Intrinsic::Memcpy => self
+                .builder
+                .build_memcpy(
+                    args[0].into_pointer_value(),
+                    1,
+                    args[1].into_pointer_value(),
+                    1,
+                    args[2].into_int_value(),
+                )
+                .unwrap()
+                .as_any_value_enum(),
+            Intrinsic::Memset => self
+                .builder
+                .build_memset(
+                    args[0].into_pointer_value(),
+                    1,
+                    args[1].into_int_value(),
+                    args[2].into_int_value(),
+                )
+                .unwrap()
+                .as_any_value_enum(),
+            Intrinsic::AddWithOverflow
+            | Intrinsic::SubWithOverflow
+            | Intrinsic::MulWithOverflow => {
+                let op = match intrinsic {
+                    Intrinsic::AddWithOverflow => "add",
+                    Intrinsic::SubWithOverflow => "sub",
+                    _ => "mul",
+                };
+                let (value, overflow) = self.build_with_overflow(
+                    op,
+                    arg_types[0].is_signed_integer(),
+                    args[0].into_int_value(),
+                    args[1].into_int_value(),
+                );
+                self.builder
+                    .build_store(args[2].into_pointer_value(), value);
+                overflow.as_any_value_enum()
+            }
+            Intrinsic::Bitcast => {
+                let to = BasicTypeEnum::try_from(self.ml_type_to_type(i.type_)).unwrap();
+                match (BasicValueEnum::try_from(args[0]).unwrap(), to) {
+                    (BasicValueEnum::IntValue(v), BasicTypeEnum::PointerType(t)) => self
+                        .builder
+                        .build_int_to_ptr(v, t, "bitcast")
+                        .as_any_value_enum(),
+                    (BasicValueEnum::PointerValue(v), BasicTypeEnum::IntType(t)) => self
+                        .builder
+                        .build_ptr_to_int(v, t, "bitcast")
+                        .as_any_value_enum(),
+                    (v, t) => self
+                        .builder
+                        .build_bitcast(v, t, "bitcast")
+                        .as_any_value_enum(),
+                }
+            }
+            Intrinsic::AlignOf => {
+                let t = self.ml_type_to_type(i.type_args[0].clone());
+                let target_data = self.execution_engine.get_target_data();
+                let align = target_data.get_abi_alignment(&t);
+                self.context
+                    .ptr_sized_int_type(target_data, None)
+                    .const_int(u64::from(align), false)
+                    .as_any_value_enum()
+            }
+            Intrinsic::AtomicLoad => {
+                let ptr = args[0].into_pointer_value();
+                let value = self.builder.build_load(ptr, "atomic_load");
+                let ordering = Self::atomic_ordering(args[1]);
+                self.set_atomic(value.as_instruction_value().unwrap(), ptr, ordering);
+                value.as_any_value_enum()
+            }
+            Intrinsic::AtomicStore => {
+                let ptr = args[0].into_pointer_value();
+                let store = self
+                    .builder
+                    .build_store(ptr, BasicValueEnum::try_from(args[1]).unwrap());
+                self.set_atomic(store, ptr, Self::atomic_ordering(args[2]));
+                store.as_any_value_enum()
+            }
+            Intrinsic::AtomicExchange | Intrinsic::AtomicAdd | Intrinsic::AtomicSub => {
+                let op = match intrinsic {
+                    Intrinsic::AtomicExchange => AtomicRMWBinOp::Xchg,
+                    Intrinsic::AtomicAdd => AtomicRMWBinOp::Add,
+                    _ => AtomicRMWBinOp::Sub,
+                };
+                self.builder
+                    .build_atomicrmw(
+                        op,
+                        args[0].into_pointer_value(),
+                        args[1].into_int_value(),
+                        Self::atomic_ordering(args[2]),
+                    )
+                    .unwrap()
+                    .as_any_value_enum()
+            }
+            Intrinsic::AtomicCompareExchange => {
+                let result = self
+                    .builder
+                    .build_cmpxchg(
+                        args[0].into_pointer_value(),
+                        BasicValueEnum::try_from(args[1]).unwrap(),
+                        BasicValueEnum::try_from(args[2]).unwrap(),
+                        Self::atomic_ordering(args[3]),
+                        Self::atomic_ordering(args[4]),
+                    )
+                    .unwrap();
+                self.builder
+                    .build_extract_value(result, 1, "exchanged")
+                    .unwrap()
+                    .as_any_value_enum()
+            }
+        };
+        if intrinsic.is_diverging() {
+            // code following the call is dead, but is emitted into a block of its own
+            let function = self.ml_context.current_function.unwrap();
+            let block = self.context.append_basic_block(function, "diverged");
+            self.builder.position_at_end(block);
+        }
+        result
+    }
+
+    /// Memory ordering passed as a constant, which is folded by the resolver.
+    fn atomic_ordering(ordering: AnyValueEnum<'ctx>) -> AtomicOrdering {
+        let value = ordering
+            .into_int_value()
+            .get_zero_extended_constant()
+            .expect("memory ordering must be a constant");
+        match MemoryOrdering::from_value(i128::from(value)) {
+            Some(MemoryOrdering::Relaxed) => AtomicOrdering::Monotonic,
+            Some(MemoryOrdering::Acquire) => AtomicOrdering::Acquire,
+            Some(MemoryOrdering::Release) => AtomicOrdering::Release,
+            Some(MemoryOrdering::AcqRel) => AtomicOrdering::AcquireRelease,
+            Some(MemoryOrdering::SeqCst) => AtomicOrdering::SequentiallyConsistent,
+            None => panic!("invalid memory ordering {}", value),
+        }
+    }
+
+    /// Make a load or store atomic.
+    fn set_atomic(
+        &self,
+        instruction: InstructionValue<'ctx>,
+        ptr: PointerValue<'ctx>,
+        ordering: AtomicOrdering,
+    ) {
+        let align = self
+            .execution_engine
+            .get_target_data()
+            .get_abi_alignment(&ptr.get_type().get_element_type());
+        instruction.set_alignment(align).unwrap();
+        instruction.set_atomic_ordering(ordering).unwrap();
+    }
+
+    /// Print `format` filled with `args` to stderr and abort.
+    fn build_print_and_abort(
+        &mut self,
+        format: &str,
+        args: &[BasicMetadataValueEnum<'ctx>],
+    ) -> InstructionValue<'ctx> {
+        let i32_type = self.context.i32_type();
+        let i8_ptr_type = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let dprintf = self.external_function(
+            "dprintf",
+            i32_type.fn_type(&[i32_type.into(), i8_ptr_type.into()], true),
+        );
+        let abort = self.external_function("abort", self.context.void_type().fn_type(&[], false));
+        let format = self
+            .builder
+            .build_global_string_ptr(format, "panic_message");
+        let mut dprintf_args: Vec<BasicMetadataValueEnum> = vec![
+            i32_type.const_int(2, false).into(),
+            format.as_pointer_value().into(),
+        ];
+        dprintf_args.extend_from_slice(args);
+        self.builder.build_call(dprintf, &dprintf_args, "");
+        self.builder.build_call(abort, &[], "");
+        self.builder.build_unreachable()
+    }
+
+    fn external_function(&self, name: &str, fn_type: FunctionType<'ctx>) -> FunctionValue<'ctx> {
+        self.module
+            .get_function(name)
+            .unwrap_or_else(|| self.module.add_function(name, fn_type, None))
+    }
+
     pub fn unary_op(&mut self, u: MLUnaryOp) -> AnyValueEnum<'ctx> {
         let target = self.expr(*u.target);
         match target {
@@ -572,6 +1201,34 @@ impl<'ctx> CodeGen<'ctx> {
         let target = self.load_if_pointer_value(target, &t_type);
         let index = self.expr(*s.index);
         let index = self.load_if_pointer_value(index, &i_type);
+        if self.runtime_checks {
+            let length = match target {
+                AnyValueEnum::ArrayValue(a) => Some(a.get_type().len()),
+                AnyValueEnum::PointerValue(p) => match p.get_type().get_element_type() {
+                    AnyTypeEnum::ArrayType(a) => Some(a.len()),
+                    _ => None,
+                },
+                _ => None,
+            }
+            .map(|len| self.context.i64_type().const_int(u64::from(len), false));
+            let length = match target {
+                AnyValueEnum::StructValue(slice) => Some(
+                    self.builder
+                        .build_extract_value(slice, 1, "slice_length")
+                        .unwrap()
+                        .into_int_value(),
+                ),
+                _ => length,
+            };
+            if let Some(length) = length {
+                self.build_bounds_check(
+                    index.into_int_value(),
+                    i_type.is_signed_integer(),
+                    length,
+                    s.location.as_ref(),
+                );
+            }
+        }
         match target {
             AnyValueEnum::ArrayValue(a) => unsafe {
                 let a_type = a
@@ -605,17 +1262,148 @@ impl<'ctx> CodeGen<'ctx> {
                     .build_in_bounds_gep(p, &[index.into_int_value()], "idx");
                 i.as_any_value_enum()
             },
-            // AnyValueEnum::StructValue(_) => {}
+            AnyValueEnum::StructValue(slice) => unsafe {
+                let p = self
+                    .builder
+                    .build_extract_value(slice, 0, "slice_ptr")
+                    .unwrap()
+                    .into_pointer_value();
+                let i = self
+                    .builder
+                    .build_in_bounds_gep(p, &[index.into_int_value()], "idx");
+                i.as_any_value_enum()
+            },
             // AnyValueEnum::VectorValue(_) => {}
             // AnyValueEnum::InstructionValue(_) => {}
             t => panic!("unsupported subscript {:?}", t),
         }
     }
 
-    pub fn member(&mut self, m: MLMember) -> AnyValueEnum<'ctx> {
-        let field_index = self
-            .get_struct_field_index_by_name(&m.target.type_().into_value_type(), &m.name)
+    /// `target!!`, panics when `target` is null.
+    pub fn unwrap(&mut self, u: MLUnwrap) -> AnyValueEnum<'ctx> {
+        let t_type = u.target.type_().into_value_type();
+        let target = self.expr(*u.target);
+        let target = self.load_if_pointer_value(target, &t_type);
+        let is_null = self
+            .builder
+            .build_is_null(target.into_pointer_value(), "is_null");
+        self.build_runtime_check(is_null, "unwrap of null pointer", &[], u.location.as_ref());
+        target
+    }
+
+    /// Build `{ *T, usize }` pointing at `start..<end` of the target.
+    pub fn slice(&mut self, s: MLSlice) -> AnyValueEnum<'ctx> {
+        let t_type = s.target.type_().into_value_type();
+        let start_type = s.start.type_().into_value_type();
+        let end_type = s.end.type_().into_value_type();
+        let target = self.expr(*s.target);
+        let start = self.expr(*s.start);
+        let start = self
+            .load_if_pointer_value(start, &start_type)
+            .into_int_value();
+        let end = self.expr(*s.end);
+        let end = self.load_if_pointer_value(end, &end_type).into_int_value();
+        if self.runtime_checks {
+            let length = match &t_type {
+                MLValueType::Slice(_) => Some(
+                    self.builder
+                        .build_extract_value(
+                            self.load_if_pointer_value(target, &t_type)
+                                .into_struct_value(),
+                            1,
+                            "slice_length",
+                        )
+                        .unwrap()
+                        .into_int_value(),
+                ),
+                MLValueType::Array(_, len) => {
+                    Some(self.context.i64_type().const_int(*len as u64, false))
+                }
+                _ => None,
+            };
+            self.build_range_check(start, end, length, s.location.as_ref());
+        }
+        let base = match &t_type {
+            MLValueType::Slice(_) => self
+                .builder
+                .build_extract_value(
+                    self.load_if_pointer_value(target, &t_type)
+                        .into_struct_value(),
+                    0,
+                    "slice_ptr",
+                )
+                .unwrap()
+                .into_pointer_value(),
+            MLValueType::Array(_, _) => {
+                let p = match target {
+                    AnyValueEnum::PointerValue(p) => p,
+                    AnyValueEnum::ArrayValue(a) => {
+                        let p = self.builder.build_alloca(a.get_type(), "array_tmp");
+                        self.builder.build_store(p, a);
+                        p
+                    }
+                    t => panic!("unsupported slice target {:?}", t),
+                };
+                let e_type = p
+                    .get_type()
+                    .get_element_type()
+                    .into_array_type()
+                    .get_element_type()
+                    .ptr_type(AddressSpace::Generic);
+                self.builder
+                    .build_bitcast(p, e_type, "aptr")
+                    .into_pointer_value()
+            }
+            _ => self
+                .load_if_pointer_value(target, &t_type)
+                .into_pointer_value(),
+        };
+        let ptr = unsafe {
+            self.builder
+                .build_in_bounds_gep(base, &[start], "slice_start")
+        };
+        let length = self.builder.build_int_sub(end, start, "slice_length");
+        let slice_type = self.ml_type_to_type(s.type_).into_struct_type();
+        let slice = self.builder.build_alloca(slice_type, "slice_tmp");
+        let ptr_field = self
+            .builder
+            .build_struct_gep(slice, 0, "slice_ptr")
+            .unwrap();
+        self.builder.build_store(ptr_field, ptr);
+        let length_field = self
+            .builder
+            .build_struct_gep(slice, 1, "slice_length")
             .unwrap();
+        self.builder.build_store(length_field, length);
+        slice.as_any_value_enum()
+    }
+
+    pub fn member(&mut self, m: MLMember) -> AnyValueEnum<'ctx> {
+        let target_type = m.target.type_().into_value_type();
+        if let Some(field_type) = self.get_union_field_type_by_name(&target_type, &m.name) {
+            let target = match self.expr(*m.target) {
+                AnyValueEnum::PointerValue(p) => p,
+                AnyValueEnum::StructValue(u) => {
+                    let p = self.builder.build_alloca(u.get_type(), "union_tmp");
+                    self.builder.build_store(p, u);
+                    p
+                }
+                t => panic!("unsupported union member target {:?}", t),
+            };
+            return self
+                .union_field_pointer(target, field_type)
+                .as_any_value_enum();
+        }
+        let field_index = match &target_type {
+            MLValueType::Slice(_) => match m.name.as_str() {
+                "pointer" => 0,
+                "length" => 1,
+                n => panic!("slice has no member {}", n),
+            },
+            _ => self
+                .get_struct_field_index_by_name(&target_type, &m.name)
+                .unwrap(),
+        };
         let target = match self.expr(*m.target) {
             AnyValueEnum::PointerValue(p) => p,
             AnyValueEnum::StructValue(_) => {
@@ -704,9 +1492,12 @@ impl<'ctx> CodeGen<'ctx> {
                     .build_conditional_branch(cond.into_int_value(), if_block, else_block);
                 self.builder.position_at_end(if_block);
                 let stmt_last_expr = self.block(body);
+                // the body may have split into further blocks
+                let if_end_block = self.builder.get_insert_block().unwrap();
                 self.builder.build_unconditional_branch(after_if_block);
                 self.builder.position_at_end(else_block);
                 let else_stmt_last_expr = self.block(else_body);
+                let else_end_block = self.builder.get_insert_block().unwrap();
                 self.builder.build_unconditional_branch(after_if_block);
                 self.builder.position_at_end(after_if_block);
                 match (
@@ -715,7 +1506,7 @@ impl<'ctx> CodeGen<'ctx> {
                 ) {
                     (Ok(if_), Ok(else_)) => {
                         let if_value = self.builder.build_phi(i64_type, "if_value");
-                        if_value.add_incoming(&[(&if_, if_block), (&else_, else_block)]);
+                        if_value.add_incoming(&[(&if_, if_end_block), (&else_, else_end_block)]);
                         if_value.as_any_value_enum()
                     }
                     _ => i64_type.const_int(0, false).as_any_value_enum(),
@@ -755,7 +1546,7 @@ impl<'ctx> CodeGen<'ctx> {
         let target = self.load_if_pointer_value(target, &target_type);
         match target {
             // AnyValueEnum::ArrayValue(_) => {}
-            AnyValueEnum::IntValue(i) => match self.ml_type_to_type(t.type_) {
+            AnyValueEnum::IntValue(i) => match self.ml_type_to_type(t.type_.clone()) {
                 AnyTypeEnum::ArrayType(_) => {
                     todo!()
                 }
@@ -770,6 +1561,22 @@ impl<'ctx> CodeGen<'ctx> {
                 AnyTypeEnum::FunctionType(_) => {
                     todo!()
                 }
+                AnyTypeEnum::IntType(ty)
+                    if self.runtime_checks && target_type.is_integer() && t.type_.is_integer() =>
+                {
+                    let from_signed = target_type.is_signed_integer();
+                    let to_signed = t.type_.is_signed_integer();
+                    let converted = self.build_int_resize(i, ty, from_signed);
+                    self.build_cast_check(
+                        i,
+                        from_signed,
+                        converted,
+                        to_signed,
+                        &t.type_.name(),
+                        t.location.as_ref(),
+                    );
+                    converted.as_any_value_enum()
+                }
                 AnyTypeEnum::IntType(ty) => {
                     let t = self.builder.build_int_cast(i, ty, "int_cast");
                     t.as_any_value_enum()
@@ -895,7 +1702,9 @@ impl<'ctx> CodeGen<'ctx> {
         v: AnyValueEnum<'ctx>,
         typ: &MLValueType,
     ) -> AnyValueEnum<'ctx> {
-        if Self::need_load(v.get_type(), typ) {
+        if let AnyValueEnum::FunctionValue(f) = v {
+            f.as_global_value().as_pointer_value().as_any_value_enum()
+        } else if Self::need_load(v.get_type(), typ) {
             let p = v.into_pointer_value();
             self.builder.build_load(p, "v").as_any_value_enum()
         } else {
@@ -915,11 +1724,10 @@ impl<'ctx> CodeGen<'ctx> {
                 MLValueType::Struct(_) => true,
                 MLValueType::Pointer(r) | MLValueType::Reference(r) => match &**r {
                     MLType::Value(r) => Self::need_load(p.get_element_type(), r),
-                    MLType::Function(_) => {
-                        todo!()
-                    }
+                    MLType::Function(_) => p.get_element_type().is_pointer_type(),
                 },
                 MLValueType::Array(_, _) => false,
+                MLValueType::Slice(_) => true,
             },
             _ => false,
         }
@@ -930,12 +1738,14 @@ impl<'ctx> CodeGen<'ctx> {
             MLDecl::Var(v) => self.global_var(v),
             MLDecl::Fun(f) => self.fun(f),
             MLDecl::Struct(s) => self.struct_(s),
+            MLDecl::Union(u) => self.union_(u),
         }
     }
 
-    fn global_var(&mut self, v: MLVar) -> AnyValueEnum<'ctx> {
-        let MLVar {
+    fn global_var(&mut self, v: MLGlobalVar) -> AnyValueEnum<'ctx> {
+        let MLGlobalVar {
             is_mute,
+            is_thread_local,
             name,
             type_,
             value,
@@ -943,14 +1753,22 @@ impl<'ctx> CodeGen<'ctx> {
         let ty = self.ml_type_to_type(type_.into_value_type());
         let ty = BasicTypeEnum::try_from(ty).unwrap();
         let v = self.module.add_global(ty, None, &*name);
-        let value = self.expr(value);
-        let value = BasicValueEnum::try_from(value).unwrap();
-        v.set_initializer(&value);
-        if is_mute {
-            v.set_constant(false)
-        } else {
-            v.set_constant(true)
-        };
+        // NOTE: a global without initializer is an external declaration
+        if let Some(value) = value {
+            let value = match self.expr(value) {
+                AnyValueEnum::FunctionValue(f) => {
+                    f.as_global_value().as_pointer_value().as_basic_value_enum()
+                }
+                value => BasicValueEnum::try_from(value).unwrap(),
+            };
+            v.set_initializer(&value);
+            if is_mute {
+                v.set_constant(false)
+            } else {
+                v.set_constant(true)
+            };
+        }
+        v.set_thread_local(is_thread_local);
         self.set_to_environment(name, v.as_any_value_enum());
         v.as_any_value_enum()
     }
@@ -1007,6 +1825,7 @@ impl<'ctx> CodeGen<'ctx> {
             return_type,
             body,
         } = f;
+        let is_diverging = return_type == MLValueType::Primitive(MLPrimitiveType::Noting);
         let return_type = self.ml_type_to_type(return_type);
         let args: Vec<BasicMetadataTypeEnum> = arg_defs
             .iter()
@@ -1058,6 +1877,14 @@ impl<'ctx> CodeGen<'ctx> {
             }
             if is_void_type {
                 self.builder.build_return(None);
+            } else if self
+                .builder
+                .get_insert_block()
+                .and_then(|b| b.get_terminator())
+                .is_none()
+            {
+                // every path returns or diverges, so the fallthrough is dead
+                self.builder.build_unreachable();
             };
             self.ml_context.pop_environment();
             AnyValueEnum::from(function)
@@ -1081,6 +1908,13 @@ impl<'ctx> CodeGen<'ctx> {
             self.ml_context.current_function = Some(f);
             AnyValueEnum::from(f)
         };
+        if is_diverging {
+            let no_return = Attribute::get_named_enum_kind_id("noreturn");
+            result.into_function_value().add_attribute(
+                AttributeLoc::Function,
+                self.context.create_enum_attribute(no_return, 0),
+            );
+        }
         result
     }
 
@@ -1099,6 +1933,45 @@ impl<'ctx> CodeGen<'ctx> {
         struct_.const_zero().as_any_value_enum()
     }
 
+    /// Lay out a union as its most aligned member followed by padding up to the largest member.
+    pub fn union_(&mut self, u: MLUnion) -> AnyValueEnum<'ctx> {
+        self.ml_context.put_union(u.clone());
+        let union_ = self.module.get_struct_type(&*u.name).unwrap();
+        let field_types: Vec<_> = u
+            .fields
+            .into_iter()
+            .map(|f| BasicTypeEnum::try_from(self.ml_type_to_type(f.type_)).unwrap())
+            .collect();
+        let target_data = self.execution_engine.get_target_data();
+        let size = field_types
+            .iter()
+            .map(|t| target_data.get_abi_size(t))
+            .max()
+            .unwrap_or_default();
+        let body = match field_types
+            .iter()
+            .max_by_key(|t| target_data.get_abi_alignment(*t))
+        {
+            None => vec![],
+            Some(aligned) => {
+                let padding = size - target_data.get_abi_size(aligned);
+                if padding > 0 {
+                    vec![
+                        *aligned,
+                        self.context
+                            .i8_type()
+                            .array_type(padding as u32)
+                            .as_basic_type_enum(),
+                    ]
+                } else {
+                    vec![*aligned]
+                }
+            }
+        };
+        union_.set_body(&body, false);
+        union_.const_zero().as_any_value_enum()
+    }
+
     pub fn stmt(&mut self, s: MLStmt) -> AnyValueEnum<'ctx> {
         match s {
             MLStmt::Expr(expr) => self.expr(expr),
@@ -1173,8 +2046,14 @@ impl<'ctx> CodeGen<'ctx> {
     pub fn file(&mut self, f: MLFile) {
         // detect type
         for d in f.body.iter() {
-            if let MLDecl::Struct(s) = d {
-                self.context.opaque_struct_type(&*s.name);
+            match d {
+                MLDecl::Struct(s) => {
+                    self.context.opaque_struct_type(&*s.name);
+                }
+                MLDecl::Union(u) => {
+                    self.context.opaque_struct_type(&*u.name);
+                }
+                MLDecl::Var(_) | MLDecl::Fun(_) => {}
             }
         }
         for d in f.body {
@@ -1247,10 +2126,26 @@ impl<'ctx> CodeGen<'ctx> {
                     .unwrap()
                     .ptr_type(AddressSpace::Generic)
                     .as_any_type_enum(),
-                MLType::Function(_) => {
-                    todo!()
-                }
+                MLType::Function(f) => self
+                    .ml_function_type_to_type(f)
+                    .ptr_type(AddressSpace::Generic)
+                    .as_any_type_enum(),
             },
+            MLValueType::Slice(e) => {
+                let e = BasicTypeEnum::try_from(self.ml_type_to_type(*e)).unwrap();
+                let usize_type = self
+                    .context
+                    .ptr_sized_int_type(self.execution_engine.get_target_data(), None);
+                self.context
+                    .struct_type(
+                        &[
+                            e.ptr_type(AddressSpace::Generic).as_basic_type_enum(),
+                            usize_type.as_basic_type_enum(),
+                        ],
+                        false,
+                    )
+                    .as_any_type_enum()
+            }
             MLValueType::Array(a, size) => {
                 let size = size as u32;
                 match self.ml_type_to_type(*a) {
@@ -1271,4 +2166,32 @@ impl<'ctx> CodeGen<'ctx> {
             }
         }
     }
+
+    fn ml_function_type_to_type(&self, f: MLFunctionType) -> FunctionType<'ctx> {
+        let args: Vec<BasicMetadataTypeEnum> = f
+            .arguments
+            .into_iter()
+            .map(|a| {
+                let a = self.ml_type_to_type(a);
+                if a.is_struct_type() {
+                    let a = a.into_struct_type().ptr_type(AddressSpace::Generic);
+                    a.as_any_type_enum()
+                } else {
+                    a
+                }
+            })
+            .map(|a| BasicTypeEnum::try_from(a).unwrap())
+            .map(|i| i.into())
+            .collect();
+        match self.ml_type_to_type(f.return_type) {
+            AnyTypeEnum::FloatType(float_type) => float_type.fn_type(&args, false),
+            AnyTypeEnum::IntType(int_type) => int_type.fn_type(&args, false),
+            AnyTypeEnum::PointerType(ptr_type) => ptr_type.fn_type(&args, false),
+            AnyTypeEnum::StructType(struct_type) => struct_type.fn_type(&args, false),
+            AnyTypeEnum::VoidType(void_type) => void_type.fn_type(&args, false),
+            a => {
+                panic!("Return Type Error. {:?}", a);
+            }
+        }
+    }
 }
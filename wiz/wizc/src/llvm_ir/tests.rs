@@ -1,11 +1,13 @@
 use crate::llvm_ir::codegen::CodeGen;
 use inkwell::context::Context;
 use inkwell::execution_engine::JitFunction;
-use wiz_mir::expr::{MLExpr, MLLiteral, MLLiteralKind, MLName};
-use wiz_mir::ml_decl::{MLDecl, MLFun, MLFunBody, MLVar};
+use wiz_mir::expr::{
+    MLBinOp, MLBinOpKind, MLCall, MLExpr, MLLiteral, MLLiteralKind, MLMember, MLName,
+};
+use wiz_mir::ml_decl::{MLDecl, MLField, MLFun, MLFunBody, MLGlobalVar, MLUnion, MLVar};
 use wiz_mir::ml_file::MLFile;
-use wiz_mir::ml_type::{MLPrimitiveType, MLType, MLValueType};
-use wiz_mir::statement::{MLReturn, MLStmt};
+use wiz_mir::ml_type::{MLFunctionType, MLPrimitiveType, MLType, MLValueType};
+use wiz_mir::statement::{MLAssignmentStmt, MLReturn, MLStmt};
 
 #[test]
 fn test_return_integer() {
@@ -175,14 +177,15 @@ fn test_return_global_constant() {
     let mlfile = MLFile {
         name: "name".to_string(),
         body: vec![
-            MLDecl::Var(MLVar {
+            MLDecl::Var(MLGlobalVar {
                 is_mute: false,
+                is_thread_local: false,
                 name: "i".to_string(),
                 type_: MLType::Value(MLValueType::Primitive(MLPrimitiveType::UInt8)),
-                value: MLExpr::Literal(MLLiteral {
+                value: Some(MLExpr::Literal(MLLiteral {
                     kind: MLLiteralKind::Integer("5".to_string()),
                     type_: MLValueType::Primitive(MLPrimitiveType::UInt8),
-                }),
+                })),
             }),
             MLDecl::Fun(MLFun {
                 name: "test".to_string(),
@@ -214,3 +217,190 @@ fn test_return_global_constant() {
 
     assert_eq!(result, 5);
 }
+
+#[test]
+fn test_assign_global_variable() {
+    type MainFunc = unsafe extern "C" fn() -> u8;
+    let uint8 = MLType::Value(MLValueType::Primitive(MLPrimitiveType::UInt8));
+    let literal = |value: &str| {
+        MLExpr::Literal(MLLiteral {
+            kind: MLLiteralKind::Integer(value.to_string()),
+            type_: MLValueType::Primitive(MLPrimitiveType::UInt8),
+        })
+    };
+    let name = || {
+        MLExpr::Name(MLName {
+            name: "i".to_string(),
+            type_: uint8.clone(),
+        })
+    };
+    let mlfile = MLFile {
+        name: "name".to_string(),
+        body: vec![
+            MLDecl::Var(MLGlobalVar {
+                is_mute: true,
+                is_thread_local: false,
+                name: "i".to_string(),
+                type_: uint8.clone(),
+                value: Some(literal("5")),
+            }),
+            MLDecl::Fun(MLFun {
+                name: "test".to_string(),
+                arg_defs: vec![],
+                return_type: MLValueType::Primitive(MLPrimitiveType::UInt8),
+                body: Some(MLFunBody {
+                    body: vec![
+                        MLStmt::Assignment(MLAssignmentStmt {
+                            target: name(),
+                            value: literal("7"),
+                        }),
+                        MLStmt::Expr(MLExpr::Return(MLReturn {
+                            value: Some(Box::new(name())),
+                        })),
+                    ],
+                }),
+            }),
+        ],
+    };
+    let module_name = &mlfile.name;
+    let context = Context::create();
+    let mut codegen = CodeGen::new(&context, module_name, None);
+
+    codegen.file(mlfile.clone());
+
+    let fun_name = "test";
+
+    let result = unsafe {
+        let main: JitFunction<MainFunc> = codegen.execution_engine.get_function(fun_name).unwrap();
+        main.call()
+    };
+
+    assert_eq!(result, 7);
+}
+
+#[test]
+fn test_checked_add() {
+    type MainFunc = unsafe extern "C" fn() -> u8;
+    let uint8 = MLValueType::Primitive(MLPrimitiveType::UInt8);
+    let literal = |value: &str| {
+        Box::new(MLExpr::Literal(MLLiteral {
+            kind: MLLiteralKind::Integer(value.to_string()),
+            type_: MLValueType::Primitive(MLPrimitiveType::UInt8),
+        }))
+    };
+    let mlfile = MLFile {
+        name: "name".to_string(),
+        body: vec![MLDecl::Fun(MLFun {
+            name: "test".to_string(),
+            arg_defs: vec![],
+            return_type: uint8.clone(),
+            body: Some(MLFunBody {
+                body: vec![MLStmt::Expr(MLExpr::Return(MLReturn {
+                    value: Some(Box::new(MLExpr::PrimitiveBinOp(MLBinOp {
+                        left: literal("2"),
+                        kind: MLBinOpKind::Plus,
+                        right: literal("3"),
+                        type_: uint8,
+                        location: None,
+                    }))),
+                }))],
+            }),
+        })],
+    };
+    let module_name = &mlfile.name;
+    let context = Context::create();
+    let mut codegen = CodeGen::new(&context, module_name, None).with_runtime_checks(true);
+
+    codegen.file(mlfile.clone());
+
+    let fun_name = "test";
+
+    let result = unsafe {
+        let main: JitFunction<MainFunc> = codegen.execution_engine.get_function(fun_name).unwrap();
+        main.call()
+    };
+
+    assert_eq!(result, 5);
+}
+
+#[test]
+fn test_union_member_of_value() {
+    type MainFunc = unsafe extern "C" fn() -> u32;
+    let uint32 = MLValueType::Primitive(MLPrimitiveType::UInt32);
+    let union_type = MLValueType::Struct("U".to_string());
+    let mlfile = MLFile {
+        name: "name".to_string(),
+        body: vec![
+            MLDecl::Union(MLUnion {
+                name: "U".to_string(),
+                fields: vec![
+                    MLField {
+                        name: "a".to_string(),
+                        type_: uint32.clone(),
+                    },
+                    MLField {
+                        name: "b".to_string(),
+                        type_: MLValueType::Primitive(MLPrimitiveType::UInt8),
+                    },
+                ],
+            }),
+            MLDecl::Fun(MLFun {
+                name: "make".to_string(),
+                arg_defs: vec![],
+                return_type: union_type.clone(),
+                body: Some(MLFunBody {
+                    body: vec![MLStmt::Expr(MLExpr::Return(MLReturn {
+                        value: Some(Box::new(MLExpr::Literal(MLLiteral {
+                            kind: MLLiteralKind::Struct(vec![(
+                                "a".to_string(),
+                                MLExpr::Literal(MLLiteral {
+                                    kind: MLLiteralKind::Integer("7".to_string()),
+                                    type_: uint32.clone(),
+                                }),
+                            )]),
+                            type_: union_type.clone(),
+                        }))),
+                    }))],
+                }),
+            }),
+            MLDecl::Fun(MLFun {
+                name: "test".to_string(),
+                arg_defs: vec![],
+                return_type: uint32.clone(),
+                body: Some(MLFunBody {
+                    body: vec![MLStmt::Expr(MLExpr::Return(MLReturn {
+                        value: Some(Box::new(MLExpr::Member(MLMember {
+                            target: Box::new(MLExpr::Call(MLCall {
+                                target: Box::new(MLExpr::Name(MLName {
+                                    name: "make".to_string(),
+                                    type_: MLType::Function(MLFunctionType {
+                                        arguments: vec![],
+                                        return_type: union_type.clone(),
+                                    }),
+                                })),
+                                args: vec![],
+                                type_: union_type,
+                            })),
+                            name: "a".to_string(),
+                            type_: MLType::Value(uint32),
+                        }))),
+                    }))],
+                }),
+            }),
+        ],
+    };
+    let module_name = &mlfile.name;
+    let context = Context::create();
+    let mut codegen = CodeGen::new(&context, module_name, None);
+
+    codegen.file(mlfile.clone());
+
+    let fun_name = "test";
+
+    let result = unsafe {
+        let main: JitFunction<MainFunc> = codegen.execution_engine.get_function(fun_name).unwrap();
+        main.call()
+    };
+
+    assert_eq!(result, 7);
+}
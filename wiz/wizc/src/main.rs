@@ -22,6 +22,7 @@ use wizc_message::{Message, MessageFormatter};
 mod high_level_ir;
 #[cfg(test)]
 mod hir_lowing;
+mod intrinsic;
 mod llvm_ir;
 
 fn get_builtin_find_path() -> PathBuf {
@@ -63,6 +64,15 @@ fn run_compiler_internal(session: &mut Session, no_std: bool) -> Result<()> {
 
     let mlir_out_dir = out_dir.join("mlir");
 
+    let context = Context::create();
+    let data_layout = CodeGen::new(
+        &context,
+        "data_layout",
+        session.config.target_triple().as_deref(),
+    )
+    .data_layout();
+    session.set_data_layout(data_layout);
+
     let input_source = session.timer::<Result<_>, _>("parse files", |session| {
         read_book_from_path(
             &session.parse_session,
@@ -135,6 +145,32 @@ fn run_compiler_internal(session: &mut Session, no_std: bool) -> Result<()> {
         let mut type_checker = TypeChecker::new(session, &arena);
         type_checker.verify(&hlfiles);
     });
+    let warnings = session
+        .warnings()
+        .iter()
+        .map(|w| Message::warn(&w.to_string()))
+        .collect::<Vec<_>>();
+    for w in warnings {
+        writeln!(session.out_stream, "{}", message_formatter.format(w))?;
+    }
+    if session.has_error() {
+        let errors = session
+            .errors()
+            .iter()
+            .map(|e| Message::error(&e.to_string()))
+            .collect::<Vec<_>>();
+        for e in errors.iter() {
+            writeln!(
+                session.out_stream,
+                "{}",
+                message_formatter.format(e.clone())
+            )?;
+        }
+        return Err(Box::new(Error::new(format!(
+            "could not compile due to {} previous error(s)",
+            errors.len()
+        ))));
+    }
     if let BuildType::Library = session.config.type_() {
         let wlib = WLib::new(hlfiles);
         let wlib_path = {
@@ -175,12 +211,12 @@ fn run_compiler_internal(session: &mut Session, no_std: bool) -> Result<()> {
 
     writeln!(session.out_stream, "==== codegen ====")?;
     let module_name = &mlfile.name;
-    let context = Context::create();
     let mut codegen = CodeGen::new(
         &context,
         module_name,
         session.config.target_triple().as_deref(),
-    );
+    )
+    .with_runtime_checks(session.config.profile().runtime_checks());
 
     for m in std_mlir.into_iter() {
         codegen.file(m);
@@ -258,9 +294,11 @@ mod lib {
 #[cfg(test)]
 mod tests {
     use super::run_compiler;
+    use std::fs::read_to_string;
     use std::path::{Path, PathBuf};
+    use std::process::Command;
     use wiz_session::Session;
-    use wizc_cli::{Config, ConfigBuilder, Emit};
+    use wizc_cli::{Config, ConfigBuilder, Emit, Profile};
 
     struct TestContext {
         manifest_dir: PathBuf,
@@ -347,4 +385,57 @@ mod tests {
 
         assert!(context.out_dir().join("helloworld.o").exists())
     }
+
+    #[test]
+    fn compile_use_after_move() {
+        let context = TestContext::new().extra_out("use_after_move");
+        let target_file_path = context.test_resource_dir().join("use_after_move.wiz");
+
+        let config = Config::default()
+            .input(target_file_path)
+            .path(context.lib_path())
+            .out_dir(context.out_dir())
+            .emit(Emit::LlvmIr);
+        let mut session = Session::new(config);
+        assert!(run_compiler(&mut session).is_err());
+
+        assert!(session
+            .errors()
+            .iter()
+            .any(|e| e.to_string().contains("`a` of type")
+                && e.to_string().contains("is used after being moved")));
+        assert!(!context.out_dir().join("use_after_move.ll").exists())
+    }
+
+    #[test]
+    fn compile_overflow_check() {
+        let context = TestContext::new().extra_out("overflow");
+        let target_file_path = context.test_resource_dir().join("overflow.wiz");
+
+        let config = Config::default()
+            .input(&target_file_path)
+            .path(context.lib_path())
+            .out_dir(context.out_dir())
+            .profile(Profile::Debug)
+            .emit(Emit::LlvmIr);
+        let mut session = Session::new(config);
+        run_compiler(&mut session).unwrap();
+
+        let ir = read_to_string(context.out_dir().join("overflow.ll")).unwrap();
+        assert!(ir.contains("llvm.sadd.with.overflow.i64"));
+
+        let config = Config::default()
+            .input(&target_file_path)
+            .path(context.lib_path())
+            .out_dir(context.out_dir())
+            .profile(Profile::Debug);
+        let mut session = Session::new(config);
+        run_compiler(&mut session).unwrap();
+
+        let output = Command::new(context.out_dir().join("overflow"))
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("attempt to add with overflow"));
+    }
 }
@@ -1,11 +1,13 @@
 mod build_type;
 mod emit;
 mod message_format;
+mod profile;
 
 pub use build_type::BuildType;
 use clap::ArgMatches;
 pub use emit::Emit;
 pub use message_format::MessageFormat;
+pub use profile::Profile;
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 
@@ -21,6 +23,7 @@ pub struct Config {
     libraries: Vec<PathBuf>,
     emit: Option<Emit>,
     message_format: Option<MessageFormat>,
+    profile: Option<Profile>,
     quiet: bool,
 }
 
@@ -34,6 +37,7 @@ pub trait ConfigExt {
     fn libraries(&self) -> Vec<PathBuf>;
     fn emit(&self) -> Emit;
     fn message_format(&self) -> MessageFormat;
+    fn profile(&self) -> Profile;
     fn quiet(&self) -> bool;
 }
 
@@ -74,6 +78,10 @@ impl ConfigExt for Config {
         self.message_format.unwrap_or_default()
     }
 
+    fn profile(&self) -> Profile {
+        self.profile.unwrap_or_default()
+    }
+
     fn quiet(&self) -> bool {
         self.quiet
     }
@@ -91,6 +99,7 @@ pub trait ConfigBuilder {
     fn libraries<P: AsRef<Path>>(self, libraries: &[P]) -> Self;
     fn emit(self, emit: Emit) -> Self;
     fn message_format(self, message_format: MessageFormat) -> Self;
+    fn profile(self, profile: Profile) -> Self;
     fn quiet(self, quiet: bool) -> Self;
     fn as_args(&self) -> Vec<&OsStr>;
 }
@@ -155,6 +164,11 @@ impl ConfigBuilder for Config {
         self
     }
 
+    fn profile(mut self, profile: Profile) -> Self {
+        self.profile.replace(profile);
+        self
+    }
+
     fn quiet(mut self, quiet: bool) -> Self {
         self.quiet = quiet;
         self
@@ -183,6 +197,9 @@ impl ConfigBuilder for Config {
                 message_format.as_str().as_ref(),
             ]);
         };
+        if let Some(profile) = self.profile {
+            args.extend::<&[&OsStr]>(&["--profile".as_ref(), profile.as_str().as_ref()]);
+        }
         if self.quiet {
             args.push("-q".as_ref());
         }
@@ -220,6 +237,9 @@ impl<'ctx> From<&'ctx ArgMatches> for Config {
             message_format: matches
                 .get_one::<String>("message-format")
                 .map(|s| MessageFormat::from(s.as_str())),
+            profile: matches
+                .get_one::<String>("profile")
+                .map(|s| Profile::from(s.as_str())),
             quiet: matches.get_flag("quite"),
         }
     }
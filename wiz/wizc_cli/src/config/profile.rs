@@ -0,0 +1,36 @@
+/// Code generation profile.
+///
+/// `Debug` emits runtime bounds and overflow checks, `Release` does not.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Debug,
+    #[default]
+    Release,
+}
+
+impl Profile {
+    pub fn all_str() -> &'static [&'static str] {
+        &["debug", "release"]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Profile::Debug => "debug",
+            Profile::Release => "release",
+        }
+    }
+
+    pub fn runtime_checks(&self) -> bool {
+        matches!(self, Profile::Debug)
+    }
+}
+
+impl From<&str> for Profile {
+    fn from(s: &str) -> Self {
+        match s {
+            "debug" => Profile::Debug,
+            "release" => Profile::Release,
+            _ => panic!("Unknown profile: {}", s),
+        }
+    }
+}
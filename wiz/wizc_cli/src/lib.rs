@@ -2,7 +2,7 @@ mod config;
 
 use clap::builder::PossibleValuesParser;
 use clap::{Arg, ArgAction, Command};
-pub use config::{BuildType, Config, ConfigBuilder, ConfigExt, Emit, MessageFormat};
+pub use config::{BuildType, Config, ConfigBuilder, ConfigExt, Emit, MessageFormat, Profile};
 
 pub fn app(name: &'static str) -> Command {
     Command::new(name)
@@ -23,6 +23,12 @@ pub fn app(name: &'static str) -> Command {
                 .num_args(1)
                 .value_parser(PossibleValuesParser::new(Emit::all_str())),
         )
+        .arg(
+            long("profile")
+                .num_args(1)
+                .value_parser(PossibleValuesParser::new(Profile::all_str()))
+                .help("`debug` emits runtime bounds and overflow checks"),
+        )
         .arg(long("message-format").num_args(1).value_parser(["json"]))
         .arg(
             long("quite")
@@ -68,4 +74,19 @@ mod tests {
             vec![PathBuf::from("./std"), PathBuf::from("./libc")]
         )
     }
+
+    #[test]
+    fn test_parse_profile() {
+        let app = super::app("test");
+        let matches = app.get_matches_from(["test", "main.wiz", "--profile", "debug"]);
+        let config = super::Config::from(&matches);
+
+        assert_eq!(config.profile(), super::Profile::Debug);
+
+        let app = super::app("test");
+        let matches = app.get_matches_from(["test", "main.wiz"]);
+        let config = super::Config::from(&matches);
+
+        assert_eq!(config.profile(), super::Profile::Release);
+    }
 }
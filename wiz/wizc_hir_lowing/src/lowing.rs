@@ -1,15 +1,21 @@
+mod ownership;
+mod reference_counting;
+
+use self::ownership::Owner;
+use self::reference_counting::Cleanup;
 use std::collections::HashMap;
-use wiz_arena::{Arena, DeclarationItem, DeclarationItemKind};
-use wiz_constants::annotation::{BUILTIN, ENTRY, NO_MANGLE, TEST};
+use wiz_arena::{Arena, ArenaSubscript, DeclarationItem, DeclarationItemKind};
+use wiz_constants::annotation::{BUILTIN, ENTRY, NO_MANGLE, TEST, THREAD_LOCAL};
 use wiz_data_structure::annotation::Annotations;
 use wiz_hir::typed_decl::{
-    TypedArgDef, TypedDeclKind, TypedExtension, TypedFun, TypedFunBody, TypedProtocol, TypedStruct,
-    TypedTopLevelDecl, TypedVar,
+    TypedArgDef, TypedDeclKind, TypedExtension, TypedExternVar, TypedFun, TypedFunBody,
+    TypedInitializer, TypedProtocol, TypedStruct, TypedSubscriptDecl, TypedTopLevelDecl, TypedVar,
 };
 use wiz_hir::typed_expr::{
     TypedArray, TypedBinOp, TypedBinaryOperator, TypedCall, TypedCallArg, TypedExpr, TypedExprKind,
-    TypedIf, TypedInstanceMember, TypedLiteralKind, TypedName, TypedPrefixUnaryOperator,
-    TypedReturn, TypedSubscript, TypedTypeCast, TypedUnaryOp,
+    TypedIf, TypedInstanceMember, TypedLiteralKind, TypedLocation, TypedName,
+    TypedPostfixUnaryOperator, TypedPrefixUnaryOperator, TypedReturn, TypedSourceLocationKind,
+    TypedSubscript, TypedTypeCast, TypedUnaryOp,
 };
 use wiz_hir::typed_file::TypedSpellBook;
 use wiz_hir::typed_stmt::{
@@ -20,10 +26,13 @@ use wiz_hir::typed_type::{
 };
 use wiz_mir::builder::{FunBuilder, MLIRModule};
 use wiz_mir::expr::{
-    MLArray, MLBinOp, MLBinOpKind, MLBlock, MLCall, MLCallArg, MLExpr, MLIf, MLLiteral,
-    MLLiteralKind, MLMember, MLName, MLSubscript, MLTypeCast, MLUnaryOp, MLUnaryOpKind,
+    MLArray, MLBinOp, MLBinOpKind, MLBlock, MLCall, MLCallArg, MLExpr, MLIf, MLIntrinsic,
+    MLLiteral, MLLiteralKind, MLLocation, MLMember, MLName, MLSlice, MLSubscript, MLTypeCast,
+    MLUnaryOp, MLUnaryOpKind, MLUnwrap,
+};
+use wiz_mir::ml_decl::{
+    MLArgDef, MLDecl, MLField, MLFun, MLFunBody, MLGlobalVar, MLStruct, MLUnion, MLVar,
 };
-use wiz_mir::ml_decl::{MLArgDef, MLDecl, MLField, MLFun, MLFunBody, MLStruct, MLVar};
 use wiz_mir::ml_file::MLFile;
 use wiz_mir::ml_type::{MLFunctionType, MLPrimitiveType, MLType, MLValueType};
 use wiz_mir::statement::{MLAssignmentStmt, MLLoopStmt, MLReturn, MLStmt};
@@ -49,6 +58,10 @@ struct HLIR2MLIR<'a> {
     arena: &'a Arena,
     module: MLIRModule,
     tests: Vec<MLFun>,
+    /// Work to be done when leaving each of the enclosing blocks.
+    cleanups: Vec<Vec<Cleanup>>,
+    /// Number of temporaries declared in the function being lowered, see [Self::temporary].
+    temporaries: usize,
 }
 
 impl<'a> HLIR2MLIR<'a> {
@@ -58,6 +71,8 @@ impl<'a> HLIR2MLIR<'a> {
             arena,
             module: Default::default(),
             tests: Default::default(),
+            cleanups: Default::default(),
+            temporaries: Default::default(),
         }
     }
 
@@ -79,10 +94,11 @@ impl<'a> HLIR2MLIR<'a> {
             MLDecl::Var(v) => self.load_dependencies_var(v),
             MLDecl::Fun(f) => self.load_dependencies_function(f),
             MLDecl::Struct(s) => self.load_dependencies_struct(s),
+            MLDecl::Union(u) => self.load_dependencies_union(u),
         }
     }
 
-    fn load_dependencies_var(&mut self, v: &MLVar) -> Result<()> {
+    fn load_dependencies_var(&mut self, v: &MLGlobalVar) -> Result<()> {
         self.module.add_global_var(v.clone());
         Ok(())
     }
@@ -92,6 +108,11 @@ impl<'a> HLIR2MLIR<'a> {
         Ok(())
     }
 
+    fn load_dependencies_union(&mut self, u: &MLUnion) -> Result<()> {
+        self.module.add_union(u.clone());
+        Ok(())
+    }
+
     fn load_dependencies_function(&mut self, f: &MLFun) -> Result<()> {
         if f.body.is_none() {
             self.module._add_function(FunBuilder::from(f.clone()));
@@ -126,6 +147,12 @@ impl<'a> HLIR2MLIR<'a> {
 
     fn value_type(&self, t: TypedValueType) -> MLValueType {
         match t {
+            // instances of classes are references already
+            TypedValueType::Reference(t) if self.is_class(&t) => self.type_(*t).into_value_type(),
+            TypedValueType::Value(t) if t.atomic_value_type().is_some() => {
+                let value_type = t.atomic_value_type().unwrap().clone();
+                self.type_(value_type).into_value_type()
+            }
             TypedValueType::Value(t) => {
                 let mut pkg = t.package.clone().into_resolved().names;
                 if pkg.is_empty() {
@@ -137,18 +164,33 @@ impl<'a> HLIR2MLIR<'a> {
                         }
                     }
                 } else {
+                    let is_class = self
+                        .arena
+                        .get_type(&pkg, &t.name)
+                        .is_some_and(|rs| rs.kind.is_class());
                     pkg.push(t.name);
-                    MLValueType::Struct(pkg.join("::"))
+                    let struct_ = MLValueType::Struct(pkg.join("::"));
+                    if is_class {
+                        MLValueType::Reference(Box::new(MLType::Value(struct_)))
+                    } else {
+                        struct_
+                    }
                 }
             }
             TypedValueType::Array(t, len) => {
                 MLValueType::Array(Box::new(self.type_(*t).into_value_type()), len)
             }
+            TypedValueType::Slice(t) => {
+                MLValueType::Slice(Box::new(self.type_(*t).into_value_type()))
+            }
             TypedValueType::Tuple(_) => {
                 todo!()
             }
             TypedValueType::Pointer(t) => MLValueType::Pointer(Box::new(self.type_(*t))),
             TypedValueType::Reference(t) => MLValueType::Reference(Box::new(self.type_(*t))),
+            TypedValueType::Function(f) => {
+                MLValueType::Pointer(Box::new(MLType::Function(self.function_type(*f))))
+            }
         }
     }
 
@@ -159,12 +201,12 @@ impl<'a> HLIR2MLIR<'a> {
                 .into_iter()
                 .map(|a| match self.type_(a.typ) {
                     MLType::Value(v) => v,
-                    MLType::Function(f) => todo!("{:?}", f),
+                    f => MLValueType::Pointer(Box::new(f)),
                 })
                 .collect(),
             return_type: match self.type_(t.return_type) {
                 MLType::Value(v) => v,
-                MLType::Function(f) => todo!("{:?}", f),
+                f => MLValueType::Pointer(Box::new(f)),
             },
         }
     }
@@ -175,31 +217,62 @@ impl<'a> HLIR2MLIR<'a> {
 
     fn stmt(&mut self, s: TypedStmt) -> Vec<MLStmt> {
         match s {
+            TypedStmt::Expr(e) if e.ty.as_ref().is_some_and(|t| self.is_class(t)) => {
+                vec![MLStmt::Expr(self.discard(e))]
+            }
+            TypedStmt::Expr(e)
+                if matches!(e.kind, TypedExprKind::Call(_))
+                    && e.ty.as_ref().is_some_and(|t| self.has_deinit(t)) =>
+            {
+                let deinit = self.deinit_function(e.ty.as_ref().unwrap());
+                let value = self.expr(e);
+                vec![MLStmt::Expr(self.discard_value(value, deinit))]
+            }
             TypedStmt::Expr(e) => vec![MLStmt::Expr(self.expr(e))],
             TypedStmt::Decl(d) => match d.kind {
-                TypedDeclKind::Var(v) => {
-                    vec![MLStmt::Var(self.var(v))]
+                TypedDeclKind::Var(v) | TypedDeclKind::Const(v) => {
+                    let is_class = v.type_.as_ref().is_some_and(|t| self.is_class(t));
+                    let owner = v.type_.clone().filter(|t| self.has_deinit(t));
+                    let v = self.var(v);
+                    let name = MLName {
+                        name: v.name.clone(),
+                        type_: v.type_.clone(),
+                    };
+                    if is_class {
+                        self.own(name);
+                    } else if let Some(t) = owner {
+                        let owner = self.owner(name, &t);
+                        return vec![MLStmt::Var(v), self.own_value(owner)];
+                    }
+                    vec![MLStmt::Var(v)]
                 }
+                TypedDeclKind::ExternVar(_) => todo!("local extern variable"),
                 TypedDeclKind::Fun(_) => todo!("local function"),
                 TypedDeclKind::Struct(_) => todo!("local struct"),
+                TypedDeclKind::Union(_) => todo!("local union"),
+                TypedDeclKind::Class(_) => todo!("local class"),
                 TypedDeclKind::Module(m) => todo!(),
                 TypedDeclKind::Enum => todo!(),
                 TypedDeclKind::Protocol(_) => todo!("local protocol"),
                 TypedDeclKind::Extension(_) => todo!("local extension"),
+                TypedDeclKind::TypeAlias(_) => vec![],
             },
-            TypedStmt::Assignment(a) => vec![MLStmt::Assignment(self.assignment(a))],
+            TypedStmt::Assignment(a) => vec![self.assignment(a)],
             TypedStmt::Loop(l) => vec![MLStmt::Loop(self.loop_stmt(l))],
+            TypedStmt::Defer(d) => {
+                match self.cleanups.last_mut() {
+                    Some(cleanups) => cleanups.push(Cleanup::Defer(d)),
+                    None => unreachable!("`defer` outside of block"),
+                };
+                vec![]
+            }
         }
     }
 
-    fn assignment(&mut self, a: TypedAssignmentStmt) -> MLAssignmentStmt {
-        match a {
-            TypedAssignmentStmt::Assignment(a) => MLAssignmentStmt {
-                target: self.expr(a.target),
-                value: self.expr(a.value),
-            },
+    fn assignment(&mut self, a: TypedAssignmentStmt) -> MLStmt {
+        let (target, value) = match a {
+            TypedAssignmentStmt::Assignment(a) => (a.target, a.value),
             TypedAssignmentStmt::AssignmentAndOperation(a) => {
-                let target = self.expr(a.target.clone());
                 let value = TypedExpr::new(
                     TypedExprKind::BinOp(TypedBinOp {
                         left: Box::new(a.target.clone()),
@@ -211,15 +284,50 @@ impl<'a> HLIR2MLIR<'a> {
                             TypedAssignmentAndOperator::Mod => TypedBinaryOperator::Mod,
                         },
                         right: Box::new(a.value),
+                        location: None,
                     }),
-                    a.target.ty,
+                    a.target.ty.clone(),
                 );
-                MLAssignmentStmt {
-                    target,
-                    value: self.expr(value),
+                (a.target, value)
+            }
+        };
+        // `a[i] = v` on a struct is a call of its setter, `a.subscript#set(i, v)`
+        if let TypedExprKind::Subscript(s) = target.kind {
+            match self.user_defined_subscript(&s) {
+                Some(subscript) => {
+                    let setter = subscript.setter.expect("subscript without setter");
+                    return MLStmt::Expr(self.accessor_call(
+                        s,
+                        TypedSubscriptDecl::SETTER,
+                        setter,
+                        Some(value),
+                        TypedType::unit(),
+                    ));
+                }
+                None => {
+                    return MLStmt::Assignment(MLAssignmentStmt {
+                        target: self.expr(TypedExpr::new(TypedExprKind::Subscript(s), target.ty)),
+                        value: self.owned_value(value),
+                    })
                 }
             }
         }
+        if target.ty.as_ref().is_some_and(|t| self.is_class(t)) {
+            return MLStmt::Expr(self.replace(target, value));
+        }
+        if let TypedExprKind::Name(n) = &target.kind {
+            if self.is_owner(&n.name) {
+                let value = self.owned_value(value);
+                return match self.expr(target) {
+                    MLExpr::Name(target) => MLStmt::Expr(self.reassign(target, value)),
+                    target => unreachable!("{:?} is not a local binding", target),
+                };
+            }
+        }
+        MLStmt::Assignment(MLAssignmentStmt {
+            target: self.expr(target),
+            value: self.owned_value(value),
+        })
     }
 
     fn loop_stmt(&mut self, l: TypedLoopStmt) -> MLLoopStmt {
@@ -240,10 +348,16 @@ impl<'a> HLIR2MLIR<'a> {
             kind,
         } = d;
         match kind {
-            TypedDeclKind::Var(v) => {
-                let v = self.global_var(v, &package);
+            TypedDeclKind::Var(v) | TypedDeclKind::Const(v) => {
+                let v = self.global_var(v, &annotations, &package);
+                self.module.add_global_var(v);
+            }
+            TypedDeclKind::ExternVar(v) => {
+                let v = self.extern_var(v, &annotations);
                 self.module.add_global_var(v);
             }
+            TypedDeclKind::Fun(_) | TypedDeclKind::Struct(_)
+                if annotations.has_annotate(BUILTIN) => {}
             TypedDeclKind::Fun(f) => {
                 if BuildType::Test == self.session.config.type_() && annotations.has_annotate(TEST)
                 {
@@ -265,6 +379,20 @@ impl<'a> HLIR2MLIR<'a> {
                     self.module._add_function(FunBuilder::from(f));
                 }
             }
+            TypedDeclKind::Union(u) => {
+                let (MLStruct { name, fields }, fns) = self.struct_(u, package);
+                self.module.add_union(MLUnion { name, fields });
+                for f in fns {
+                    self.module._add_function(FunBuilder::from(f));
+                }
+            }
+            TypedDeclKind::Class(c) => {
+                let (st, fns) = self.class(c, package);
+                self.module.add_struct(st);
+                for f in fns {
+                    self.module._add_function(FunBuilder::from(f));
+                }
+            }
             TypedDeclKind::Module(m) => {
                 self.file(m)?;
             }
@@ -281,22 +409,40 @@ impl<'a> HLIR2MLIR<'a> {
                     self.module._add_function(FunBuilder::from(f));
                 }
             }
+            // NOTE: type aliases are expanded by type resolver
+            TypedDeclKind::TypeAlias(_) => {}
         };
         Ok(())
     }
 
-    fn global_var(&mut self, v: TypedVar, package: &Package) -> MLVar {
-        let expr = self.expr(v.value);
-        MLVar {
+    fn global_var(
+        &mut self,
+        v: TypedVar,
+        annotations: &Annotations,
+        package: &Package,
+    ) -> MLGlobalVar {
+        let expr = self.value_expr(v.value);
+        MLGlobalVar {
             is_mute: v.is_mut,
+            is_thread_local: annotations.has_annotate(THREAD_LOCAL),
             name: self.package_name_mangling_(package, &v.name),
             type_: self.type_(v.type_.unwrap()),
-            value: expr,
+            value: Some(expr),
+        }
+    }
+
+    fn extern_var(&mut self, v: TypedExternVar, annotations: &Annotations) -> MLGlobalVar {
+        MLGlobalVar {
+            is_mute: v.is_mut,
+            is_thread_local: annotations.has_annotate(THREAD_LOCAL),
+            name: v.name,
+            type_: self.type_(v.type_),
+            value: None,
         }
     }
 
     fn var(&mut self, v: TypedVar) -> MLVar {
-        let expr = self.expr(v.value);
+        let expr = self.owned_value(v.value);
         MLVar {
             is_mute: v.is_mut,
             name: v.name,
@@ -335,12 +481,13 @@ impl<'a> HLIR2MLIR<'a> {
                 package_mangled_name + "##" + &fun_arg_label_type_mangled_name
             }
         };
+        let owners = self.owned_arguments(&arg_defs);
         let args = arg_defs.into_iter().map(|a| self.arg_def(a)).collect();
         MLFun {
             name: mangled_name,
             arg_defs: args,
             return_type: self.type_(return_type).into_value_type(),
-            body: body.map(|b| self.fun_body(b, type_arguments)),
+            body: body.map(|b| self.fun_body(b, owners, type_arguments)),
         }
     }
 
@@ -348,8 +495,11 @@ impl<'a> HLIR2MLIR<'a> {
         let TypedStruct {
             name,
             type_params,
+            type_constraints: _,
             stored_properties,
             computed_properties,
+            initializers,
+            subscripts,
             member_functions,
         } = s;
         let struct_ = MLStruct {
@@ -363,8 +513,10 @@ impl<'a> HLIR2MLIR<'a> {
                 .collect(),
         };
 
-        let members: Vec<MLFun> = member_functions
+        let members: Vec<MLFun> = subscripts
             .into_iter()
+            .flat_map(|s| std::iter::once(s.getter).chain(s.setter))
+            .chain(member_functions)
             .map(|mf| {
                 let TypedFun {
                     name: fname,
@@ -375,6 +527,7 @@ impl<'a> HLIR2MLIR<'a> {
                     return_type,
                 } = mf;
                 let fun_arg_label_type_mangled_name = self.fun_arg_label_type_name_mangling(&args);
+                let owners = self.owned_arguments(&args);
                 let args = args.into_iter().map(|a| self.arg_def(a)).collect();
                 MLFun {
                     name: self.package_name_mangling_(&package, &name)
@@ -387,17 +540,74 @@ impl<'a> HLIR2MLIR<'a> {
                         },
                     arg_defs: args,
                     return_type: self.type_(return_type).into_value_type(),
-                    body: body.map(|body| self.fun_body(body, None)),
+                    body: body.map(|body| self.fun_body(body, owners, None)),
                 }
             })
             .collect();
+        let is_class = self
+            .arena
+            .get_type(&package.names, &name)
+            .is_some_and(|rs| rs.kind.is_class());
+        let members = initializers
+            .into_iter()
+            .map(|i| self.initializer(i, &struct_.name, is_class))
+            .chain(members)
+            .collect();
         (struct_, members)
     }
 
+    /// `init` is lowered to a function which assigns the fields of zero-initialized `self`
+    /// and returns it. `self` of a class is allocated first.
+    fn initializer(&mut self, i: TypedInitializer, struct_name: &str, is_class: bool) -> MLFun {
+        let TypedInitializer { arg_defs, body } = i;
+        let fun_arg_label_type_mangled_name = self.fun_arg_label_type_name_mangling(&arg_defs);
+        let zero = MLLiteral {
+            kind: MLLiteralKind::Struct(vec![]),
+            type_: MLValueType::Struct(struct_name.to_string()),
+        };
+        let (self_type, value) = if is_class {
+            (Self::class_type(struct_name), Self::new_instance(zero))
+        } else {
+            (zero.type_.clone(), MLExpr::Literal(zero))
+        };
+        let self_name = MLName {
+            name: "self".to_string(),
+            type_: MLType::Value(self_type.clone()),
+        };
+        let owners = self.owned_arguments(&arg_defs);
+        let mut body = self.fun_body(body, owners, None).body;
+        body.insert(
+            0,
+            MLStmt::Var(MLVar {
+                is_mute: true,
+                name: self_name.name.clone(),
+                type_: self_name.type_.clone(),
+                value,
+            }),
+        );
+        body.push(MLStmt::Expr(MLExpr::Return(MLReturn::new(Some(
+            MLExpr::Name(self_name),
+        )))));
+        MLFun {
+            name: struct_name.to_string()
+                + "::"
+                + TypedInitializer::NAME
+                + &if fun_arg_label_type_mangled_name.is_empty() {
+                    String::new()
+                } else {
+                    String::from("##") + &fun_arg_label_type_mangled_name
+                },
+            arg_defs: arg_defs.into_iter().map(|a| self.arg_def(a)).collect(),
+            return_type: self_type,
+            body: Some(MLFunBody { body }),
+        }
+    }
+
     fn extension(&mut self, e: TypedExtension) -> Vec<MLFun> {
         let TypedExtension {
             name,
             protocol,
+            associated_types: _,
             computed_properties,
             member_functions,
         } = e;
@@ -413,6 +623,7 @@ impl<'a> HLIR2MLIR<'a> {
                     return_type,
                 } = mf;
                 let fun_arg_label_type_mangled_name = self.fun_arg_label_type_name_mangling(&args);
+                let owners = self.owned_arguments(&args);
                 let args = args.into_iter().map(|a| self.arg_def(a)).collect();
                 MLFun {
                     name: self.package_name_mangling(&name.package(), &name.name())
@@ -425,7 +636,7 @@ impl<'a> HLIR2MLIR<'a> {
                         },
                     arg_defs: args,
                     return_type: self.type_(return_type).into_value_type(),
-                    body: body.map(|body| self.fun_body(body, None)),
+                    body: body.map(|body| self.fun_body(body, owners, None)),
                 }
             })
             .collect()
@@ -439,9 +650,14 @@ impl<'a> HLIR2MLIR<'a> {
         let TypedExpr { kind, ty } = e;
         match kind {
             TypedExprKind::Name(name) => self.name(name, ty),
+            TypedExprKind::Literal(TypedLiteralKind::String(value))
+                if ty.as_ref().is_some_and(TypedType::is_slice_type) =>
+            {
+                self.byte_slice(value)
+            }
             TypedExprKind::Literal(l) => MLExpr::Literal(self.literal(l, ty)),
             TypedExprKind::BinOp(b) => MLExpr::PrimitiveBinOp(self.binop(b, ty)),
-            TypedExprKind::UnaryOp(u) => MLExpr::PrimitiveUnaryOp(self.unary_op(u, ty)),
+            TypedExprKind::UnaryOp(u) => self.unary_op(u, ty),
             TypedExprKind::Subscript(s) => self.subscript(s, ty),
             TypedExprKind::Member(m) => self.member(m, ty),
             TypedExprKind::Array(a) => MLExpr::Array(self.array(a, ty)),
@@ -452,12 +668,27 @@ impl<'a> HLIR2MLIR<'a> {
             TypedExprKind::If(i) => MLExpr::If(self.if_expr(i, ty)),
             TypedExprKind::When => todo!(),
             TypedExprKind::Lambda(l) => todo!(),
-            TypedExprKind::Return(r) => MLExpr::Return(self.return_expr(r)),
+            TypedExprKind::Return(r) => self.return_expr(r),
             TypedExprKind::TypeCast(t) => MLExpr::PrimitiveTypeCast(self.type_cast(t)),
             TypedExprKind::SizeOf(t) => MLExpr::SizeOf(self.type_(t)),
         }
     }
 
+    /// Lower expression that is used as a value.
+    /// Function designator used as a value is converted to function pointer.
+    fn value_expr(&mut self, e: TypedExpr) -> MLExpr {
+        match self.expr(e) {
+            MLExpr::Name(MLName {
+                name,
+                type_: type_ @ MLType::Function(_),
+            }) => MLExpr::Name(MLName {
+                name,
+                type_: MLType::Value(MLValueType::Pointer(Box::new(type_))),
+            }),
+            e => e,
+        }
+    }
+
     fn name(&self, n: TypedName, ty: Option<TypedType>) -> MLExpr {
         if let TypedType::Type(t) = ty.as_ref().unwrap() {
             let package = t.package().into_resolved();
@@ -522,6 +753,7 @@ impl<'a> HLIR2MLIR<'a> {
                                     label: a.label.to_string(),
                                     name: "".to_string(),
                                     type_: a.typ.clone(),
+                                    default: None,
                                 })
                                 .collect(),
                         )
@@ -557,6 +789,18 @@ impl<'a> HLIR2MLIR<'a> {
                 MLLiteralKind::Null,
                 self.type_(type_.unwrap()).into_value_type(),
             ),
+            TypedLiteralKind::SourceLocation(kind, location) => (
+                match kind {
+                    TypedSourceLocationKind::File => MLLiteralKind::String(location.file),
+                    TypedSourceLocationKind::Line => {
+                        MLLiteralKind::Integer(location.line.to_string())
+                    }
+                    TypedSourceLocationKind::Column => {
+                        MLLiteralKind::Integer(location.column.to_string())
+                    }
+                },
+                self.type_(type_.unwrap()).into_value_type(),
+            ),
         };
         MLLiteral { kind, type_ }
     }
@@ -566,6 +810,7 @@ impl<'a> HLIR2MLIR<'a> {
             left,
             operator: kind,
             right,
+            location,
         } = b;
         MLBinOp {
             left: Box::new(self.expr(*left)),
@@ -589,14 +834,27 @@ impl<'a> HLIR2MLIR<'a> {
             },
             right: Box::new(self.expr(*right)),
             type_: self.type_(ty.unwrap()).into_value_type(),
+            location: Self::location(location),
         }
     }
 
-    fn unary_op(&mut self, u: TypedUnaryOp, ty: Option<TypedType>) -> MLUnaryOp {
+    fn unary_op(&mut self, u: TypedUnaryOp, ty: Option<TypedType>) -> MLExpr {
         match u {
+            // references to instances of classes are the instances themselves
+            TypedUnaryOp::Prefix(p)
+                if matches!(
+                    p.operator,
+                    TypedPrefixUnaryOperator::Reference | TypedPrefixUnaryOperator::Dereference
+                ) && [&p.target.ty, &ty]
+                    .into_iter()
+                    .flatten()
+                    .any(|t| self.is_class(t)) =>
+            {
+                self.expr(*p.target)
+            }
             TypedUnaryOp::Prefix(p) => {
                 let target = self.expr(*p.target);
-                MLUnaryOp {
+                MLExpr::PrimitiveUnaryOp(MLUnaryOp {
                     kind: match p.operator {
                         TypedPrefixUnaryOperator::Positive => MLUnaryOpKind::Positive,
                         TypedPrefixUnaryOperator::Negative => MLUnaryOpKind::Negative,
@@ -606,33 +864,122 @@ impl<'a> HLIR2MLIR<'a> {
                     },
                     type_: self.type_(ty.unwrap()).into_value_type(),
                     target: Box::new(target),
-                }
+                })
             }
-            TypedUnaryOp::Postfix(p) => {
-                todo!()
+            TypedUnaryOp::Postfix(p) => match p.operator {
+                TypedPostfixUnaryOperator::Unwrap => MLExpr::Unwrap(MLUnwrap {
+                    target: Box::new(self.expr(*p.target)),
+                    type_: self.type_(ty.unwrap()).into_value_type(),
+                    location: Self::location(p.location),
+                }),
+            },
+        }
+    }
+
+    fn location(l: Option<TypedLocation>) -> Option<MLLocation> {
+        l.map(|l| MLLocation {
+            file: l.file,
+            line: l.line,
+            column: l.column,
+        })
+    }
+
+    /// String literal used as `[UInt8]`.
+    fn byte_slice(&mut self, value: String) -> MLExpr {
+        let length = value.len();
+        MLExpr::Slice(MLSlice {
+            target: Box::new(MLExpr::Literal(MLLiteral {
+                kind: MLLiteralKind::String(value),
+                type_: self.type_(TypedType::string_ref()).into_value_type(),
+            })),
+            start: Box::new(Self::usize_literal(0)),
+            end: Box::new(Self::usize_literal(length)),
+            type_: MLValueType::Slice(Box::new(MLValueType::Primitive(MLPrimitiveType::UInt8))),
+            location: None,
+        })
+    }
+
+    fn usize_literal(value: usize) -> MLExpr {
+        MLExpr::Literal(MLLiteral {
+            kind: MLLiteralKind::Integer(value.to_string()),
+            type_: MLValueType::Primitive(MLPrimitiveType::USize),
+        })
+    }
+
+    /// `start` and `end` of a `Range`, taken from its initializer when it is created in place.
+    /// Otherwise the range is evaluated once into a temporary declared by the returned statement.
+    fn range_bounds(&mut self, range: TypedExpr) -> (Option<MLStmt>, MLExpr, MLExpr) {
+        match self.expr(range) {
+            MLExpr::Literal(MLLiteral {
+                kind: MLLiteralKind::Struct(fields),
+                ..
+            }) => {
+                let mut fields: HashMap<_, _> = fields.into_iter().collect();
+                (
+                    None,
+                    fields.remove("start").unwrap(),
+                    fields.remove("end").unwrap(),
+                )
+            }
+            range => {
+                let name = MLName {
+                    name: "#range".to_string(),
+                    type_: range.type_(),
+                };
+                let bound = |bound: &str| {
+                    MLExpr::Member(MLMember {
+                        target: Box::new(MLExpr::Name(name.clone())),
+                        name: bound.to_string(),
+                        type_: MLType::Value(MLValueType::Primitive(MLPrimitiveType::USize)),
+                    })
+                };
+                let (start, end) = (bound("start"), bound("end"));
+                let var = MLStmt::Var(MLVar {
+                    is_mute: false,
+                    name: name.name,
+                    type_: name.type_,
+                    value: range,
+                });
+                (Some(var), start, end)
             }
         }
     }
 
     fn subscript(&mut self, s: TypedSubscript, ty: Option<TypedType>) -> MLExpr {
         let t = s.target.ty.clone().unwrap();
+        if s.indexes.len() == 1 && s.indexes[0].ty.as_ref().is_some_and(TypedType::is_range) {
+            let TypedSubscript {
+                target,
+                mut indexes,
+                location,
+            } = s;
+            let (range, start, end) = self.range_bounds(indexes.remove(0));
+            let slice = MLExpr::Slice(MLSlice {
+                target: Box::new(self.expr(*target)),
+                start: Box::new(start),
+                end: Box::new(end),
+                type_: self.type_(ty.unwrap()).into_value_type(),
+                location: Self::location(location),
+            });
+            return match range {
+                Some(range) => MLExpr::Block(MLBlock {
+                    body: vec![range, MLStmt::Expr(slice)],
+                }),
+                None => slice,
+            };
+        }
         if t.is_pointer_type() && s.indexes.len() == 1 {
             match t {
                 TypedType::Value(v) => match v {
-                    TypedValueType::Value(v) => {
-                        if v.type_args.as_ref().unwrap()[0].is_primitive() {
-                            MLExpr::PrimitiveSubscript(MLSubscript {
-                                target: Box::new(self.expr(*s.target)),
-                                index: Box::new(self.expr(s.indexes[0].clone())),
-                                type_: self
-                                    .type_(v.type_args.unwrap()[0].clone())
-                                    .into_value_type(),
-                            })
-                        } else {
-                            self.subscript_for_user_defined(s, ty)
-                        }
-                    }
-                    TypedValueType::Array(_, _) => {
+                    TypedValueType::Value(v) => MLExpr::PrimitiveSubscript(MLSubscript {
+                        target: Box::new(self.expr(*s.target)),
+                        index: Box::new(self.expr(s.indexes[0].clone())),
+                        type_: self
+                            .type_(v.type_args.unwrap()[0].clone())
+                            .into_value_type(),
+                        location: Self::location(s.location),
+                    }),
+                    TypedValueType::Array(_, _) | TypedValueType::Slice(_) => {
                         todo!()
                     }
                     TypedValueType::Tuple(_) => {
@@ -642,10 +989,12 @@ impl<'a> HLIR2MLIR<'a> {
                         target: Box::new(self.expr(*s.target)),
                         index: Box::new(self.expr(s.indexes[0].clone())),
                         type_: self.type_(*p).into_value_type(),
+                        location: Self::location(s.location),
                     }),
                     TypedValueType::Reference(_) => {
                         todo!()
                     }
+                    TypedValueType::Function(_) => unreachable!(),
                 },
                 t => panic!("function pointer detected. {:?}", t),
             }
@@ -654,37 +1003,92 @@ impl<'a> HLIR2MLIR<'a> {
                 target: Box::new(self.expr(*s.target)),
                 index: Box::new(self.expr(s.indexes[0].clone())),
                 type_: MLValueType::Primitive(MLPrimitiveType::UInt8),
+                location: Self::location(s.location),
             })
-        } else if t.is_array_type() {
+        } else if t.is_array_type() || t.is_slice_type() {
             MLExpr::PrimitiveSubscript(MLSubscript {
                 target: Box::new(self.expr(*s.target)),
                 index: Box::new(self.expr(s.indexes[0].clone())),
                 type_: match t {
-                    TypedType::Value(TypedValueType::Array(e, _)) => {
+                    TypedType::Value(TypedValueType::Array(e, _))
+                    | TypedType::Value(TypedValueType::Slice(e)) => {
                         self.type_(*e).into_value_type()
                     }
                     _ => unreachable!(),
                 },
+                location: Self::location(s.location),
             })
         } else {
             self.subscript_for_user_defined(s, ty)
         }
     }
 
+    /// Accessors of the subscript declared by the struct `s.target` is typed with.
+    fn user_defined_subscript(&self, s: &TypedSubscript) -> Option<ArenaSubscript> {
+        match Self::receiver_type(&s.target) {
+            TypedType::Value(TypedValueType::Value(v)) => {
+                let index_types: Vec<_> = s.indexes.iter().map(|i| i.ty.clone().unwrap()).collect();
+                self.arena
+                    .get_type(&v.package.clone().into_resolved().names, &v.name)?
+                    .get_subscript(&index_types)
+                    .cloned()
+            }
+            _ => None,
+        }
+    }
+
+    /// `a[i]` on a struct is a call of its getter, `a.subscript#get(i)`.
     fn subscript_for_user_defined(&mut self, s: TypedSubscript, ty: Option<TypedType>) -> MLExpr {
-        let target = self.expr(*s.target);
-        match target {
-            MLExpr::Name(target) => MLExpr::Call(MLCall {
+        let subscript = self
+            .user_defined_subscript(&s)
+            .unwrap_or_else(|| panic!("{:?} has no subscript", s.target.ty));
+        self.accessor_call(
+            s,
+            TypedSubscriptDecl::GETTER,
+            subscript.getter,
+            None,
+            ty.unwrap(),
+        )
+    }
+
+    fn accessor_call(
+        &mut self,
+        s: TypedSubscript,
+        name: &str,
+        type_: TypedFunctionType,
+        value: Option<TypedExpr>,
+        ty: TypedType,
+    ) -> MLExpr {
+        let TypedSubscript {
+            target,
+            indexes,
+            location,
+        } = s;
+        let args = indexes
+            .into_iter()
+            .chain(value)
+            .map(|i| TypedCallArg {
+                label: None,
+                arg: Box::new(i),
+                is_vararg: false,
+            })
+            .collect();
+        let target = TypedExpr::new(
+            TypedExprKind::Member(TypedInstanceMember {
                 target,
-                args: s
-                    .indexes
-                    .into_iter()
-                    .map(|i| MLCallArg { arg: self.expr(i) })
-                    .collect(),
-                type_: self.type_(ty.unwrap()).into_value_type(),
+                name: name.to_string(),
+                is_safe: false,
             }),
-            a => panic!("{:?}", a),
-        }
+            Some(TypedType::Function(Box::new(type_))),
+        );
+        self.call(
+            TypedCall {
+                target: Box::new(target),
+                args,
+                location,
+            },
+            Some(ty),
+        )
     }
 
     fn member(&mut self, m: TypedInstanceMember, ty: Option<TypedType>) -> MLExpr {
@@ -710,7 +1114,31 @@ impl<'a> HLIR2MLIR<'a> {
     }
 
     fn call(&mut self, c: TypedCall, ty: Option<TypedType>) -> MLExpr {
-        let TypedCall { target, mut args } = c;
+        let TypedCall {
+            target, mut args, ..
+        } = c;
+        if let TypedExprKind::Name(n) = &target.kind {
+            if self.is_builtin(n) {
+                return self.intrinsic(n.clone(), args, ty);
+            }
+        }
+        // `Atomic<T>(value: v)` has the same representation as `v`
+        if let Some(TypedType::Type(t)) = &target.ty {
+            if t.atomic_value_type().is_some() {
+                return self.expr(*args.remove(0).arg);
+            }
+        }
+        let target = match target.kind {
+            TypedExprKind::Member(m)
+                if Self::receiver_type(&m.target).atomic_value_type().is_some() =>
+            {
+                return self.atomic_method(m, args, ty);
+            }
+            kind => Box::new(TypedExpr {
+                kind,
+                ty: target.ty,
+            }),
+        };
         let target = match *target {
             TypedExpr {
                 kind: TypedExprKind::Member(m),
@@ -723,7 +1151,14 @@ impl<'a> HLIR2MLIR<'a> {
                 } = m;
                 match target.ty.clone().unwrap() {
                     TypedType::Self_ => unreachable!(),
-                    TypedType::Value(v) => {
+                    TypedType::Value(mut v) => {
+                        // `&self` receivers are named after the referenced type
+                        while let TypedValueType::Reference(r) = v {
+                            match *r {
+                                TypedType::Value(r) => v = r,
+                                r => panic!("invalid receiver type {:?}", r),
+                            }
+                        }
                         let type_ = ty.unwrap();
                         if let TypedType::Function(fun_type) = &type_ {
                             args.insert(
@@ -734,23 +1169,8 @@ impl<'a> HLIR2MLIR<'a> {
                                     is_vararg: false,
                                 },
                             );
-                            let mut mangled_name = v.to_string() + "::" + &name;
-                            if !fun_type.arguments.is_empty() {
-                                mangled_name += "##";
-                                mangled_name += &self.fun_arg_label_type_name_mangling(
-                                    &fun_type
-                                        .arguments
-                                        .iter()
-                                        .map(|a| TypedArgDef {
-                                            label: a.label.to_string(),
-                                            name: "".to_string(),
-                                            type_: a.typ.clone(),
-                                        })
-                                        .collect(),
-                                )
-                            }
                             MLExpr::Name(MLName {
-                                name: mangled_name,
+                                name: self.member_function_name(&v.to_string(), &name, fun_type),
                                 type_: self.type_(type_),
                             })
                         } else {
@@ -787,7 +1207,7 @@ impl<'a> HLIR2MLIR<'a> {
                                     type_,
                                 })
                             }
-                            TypedValueType::Array(_, _) => {
+                            TypedValueType::Array(_, _) | TypedValueType::Slice(_) => {
                                 todo!()
                             }
                             TypedValueType::Tuple(_) => {
@@ -799,6 +1219,9 @@ impl<'a> HLIR2MLIR<'a> {
                             TypedValueType::Reference(_) => {
                                 todo!()
                             }
+                            TypedValueType::Function(_) => {
+                                todo!()
+                            }
                         },
                         TypedType::Function(_) => {
                             todo!()
@@ -812,28 +1235,130 @@ impl<'a> HLIR2MLIR<'a> {
             t => self.expr(t),
         };
         let target = match target {
-            MLExpr::Name(name) => name,
             MLExpr::Literal(MLLiteral {
                 kind: MLLiteralKind::Struct(mut s),
                 type_,
             }) => {
+                let is_class = ty.as_ref().is_some_and(|t| self.is_class(t));
+                if is_class {
+                    s.push(Self::zero_reference_count());
+                }
                 for arg in args.into_iter() {
-                    let v = self.expr(*arg.arg);
+                    let v = self.owned_value(*arg.arg);
                     s.push((arg.label.unwrap(), v));
                 }
-                return MLExpr::Literal(MLLiteral {
+                let literal = MLLiteral {
                     kind: MLLiteralKind::Struct(s),
                     type_,
-                });
+                };
+                return if is_class {
+                    Self::new_instance(literal)
+                } else {
+                    MLExpr::Literal(literal)
+                };
             }
-            a => panic!("{:?}", a),
+            target => target,
         };
-        MLExpr::Call(MLCall {
-            target,
+        let parameters = match target.type_() {
+            MLType::Function(f) => f.arguments,
+            _ => vec![],
+        };
+        let mut temporaries = vec![];
+        let args = args
+            .into_iter()
+            .enumerate()
+            .map(|(i, a)| {
+                let arg = self.argument(*a.arg, &mut temporaries);
+                // arguments passed by value are moved to the callee
+                let arg = match parameters.get(i) {
+                    Some(MLValueType::Reference(_)) | None => arg,
+                    Some(_) => self.move_out(arg),
+                };
+                MLCallArg { arg }
+            })
+            .collect();
+        let call = MLExpr::Call(MLCall {
+            target: Box::new(target),
+            args,
+            type_: self.type_(ty.unwrap()).into_value_type(),
+        });
+        self.release_temporaries(call, temporaries)
+    }
+
+    fn is_builtin(&self, n: &TypedName) -> bool {
+        let package = n.package.clone().into_resolved();
+        self.arena
+            .get(&package.names, &n.name)
+            .is_some_and(|i| i.has_annotation(BUILTIN))
+    }
+
+    /// Type of `target` whose references are stripped.
+    fn receiver_type(target: &TypedExpr) -> &TypedType {
+        let mut receiver = target.ty.as_ref().unwrap();
+        while let TypedType::Value(TypedValueType::Reference(t)) = receiver {
+            receiver = t;
+        }
+        receiver
+    }
+
+    /// Call of a member function of `Atomic<T>`, which is expanded to an intrinsic named
+    /// `Atomic::name` with a pointer to the receiver as the first argument.
+    fn atomic_method(
+        &mut self,
+        m: TypedInstanceMember,
+        args: Vec<TypedCallArg>,
+        ty: Option<TypedType>,
+    ) -> MLExpr {
+        let value_type = Self::receiver_type(&m.target)
+            .atomic_value_type()
+            .unwrap()
+            .clone();
+        let value_type = self.type_(value_type);
+        let is_reference = matches!(
+            m.target.ty,
+            Some(TypedType::Value(TypedValueType::Reference(_)))
+        );
+        let target = self.expr(*m.target);
+        let receiver = if is_reference {
+            target
+        } else {
+            MLExpr::PrimitiveUnaryOp(MLUnaryOp {
+                target: Box::new(target),
+                kind: MLUnaryOpKind::Ref,
+                type_: MLValueType::Pointer(Box::new(value_type.clone())),
+            })
+        };
+        MLExpr::Intrinsic(MLIntrinsic {
+            name: format!("Atomic::{}", m.name),
+            type_args: vec![value_type.into_value_type()],
+            args: std::iter::once(MLCallArg { arg: receiver })
+                .chain(args.into_iter().map(|a| MLCallArg {
+                    arg: self.value_expr(*a.arg),
+                }))
+                .collect(),
+            type_: self.type_(ty.unwrap()).into_value_type(),
+        })
+    }
+
+    /// Call of a function declared with `#[builtin]`, which has no definition to call.
+    fn intrinsic(
+        &mut self,
+        n: TypedName,
+        args: Vec<TypedCallArg>,
+        ty: Option<TypedType>,
+    ) -> MLExpr {
+        MLExpr::Intrinsic(MLIntrinsic {
+            name: n.name,
+            type_args: n
+                .type_arguments
+                .into_iter()
+                .flatten()
+                .map(|t| self.type_(t).into_value_type())
+                .collect(),
             args: args
                 .into_iter()
                 .map(|a| MLCallArg {
-                    arg: self.expr(*a.arg),
+                    arg: self.value_expr(*a.arg),
                 })
                 .collect(),
             type_: self.type_(ty.unwrap()).into_value_type(),
@@ -849,16 +1374,53 @@ impl<'a> HLIR2MLIR<'a> {
         }
     }
 
-    fn return_expr(&mut self, r: TypedReturn) -> MLReturn {
-        MLReturn {
-            value: r.value.map(|v| Box::new(self.expr(*v))),
+    /// `return` runs every pending cleanup, innermost first, after evaluating its value.
+    fn return_expr(&mut self, r: TypedReturn) -> MLExpr {
+        let value = r.value.map(|v| self.owned_value(*v));
+        if self.cleanups.iter().all(Vec::is_empty) {
+            return MLExpr::Return(MLReturn::new(value));
         }
+        let mut body = vec![];
+        let value = value.map(|value| {
+            let type_ = value.type_();
+            let name = String::from("#return");
+            body.push(MLStmt::Var(MLVar {
+                is_mute: false,
+                name: name.clone(),
+                type_: type_.clone(),
+                value,
+            }));
+            MLExpr::Name(MLName { name, type_ })
+        });
+        let cleanups = self
+            .cleanups
+            .iter()
+            .rev()
+            .flat_map(|cleanups| cleanups.iter().rev())
+            .cloned()
+            .collect::<Vec<_>>();
+        body.extend(self.cleanups(cleanups));
+        body.push(MLStmt::Expr(MLExpr::Return(MLReturn::new(value))));
+        MLExpr::Block(MLBlock { body })
+    }
+
+    fn cleanups(&mut self, cleanups: Vec<Cleanup>) -> Vec<MLStmt> {
+        cleanups
+            .into_iter()
+            .filter_map(|c| match c {
+                Cleanup::Defer(d) if d.body.is_empty() => None,
+                Cleanup::Defer(d) => Some(MLStmt::Expr(MLExpr::Block(self.block(d)))),
+                Cleanup::Release(n) => Some(MLStmt::Expr(Self::release(MLExpr::Name(n)))),
+                Cleanup::Deinit(o) => Some(Self::deinit_if_live(o)),
+            })
+            .collect()
     }
 
     fn type_cast(&mut self, t: TypedTypeCast) -> MLTypeCast {
         MLTypeCast {
             target: Box::new(self.expr(*t.target)),
             type_: self.type_(t.type_).into_value_type(),
+            location: Self::location(t.location),
         }
     }
 
@@ -869,27 +1431,109 @@ impl<'a> HLIR2MLIR<'a> {
         }
     }
 
+    /// `owners` are the arguments the function owns, see [Self::owned_arguments].
     fn fun_body(
         &mut self,
         b: TypedFunBody,
+        owners: Vec<Owner>,
         type_arguments: Option<HashMap<TypedTypeParam, TypedType>>,
     ) -> MLFunBody {
-        match b {
-            TypedFunBody::Expr(e) => MLFunBody {
-                body: vec![MLStmt::Expr(MLExpr::Return(MLReturn::new(Some(
-                    self.expr(e),
-                ))))],
-            },
-            TypedFunBody::Block(b) => MLFunBody {
-                body: self.block(b).body,
-            },
-        }
+        let temporaries = std::mem::take(&mut self.temporaries);
+        let cleanups = std::mem::take(&mut self.cleanups);
+        let body = match b {
+            TypedFunBody::Expr(e) if owners.is_empty() => vec![MLStmt::Expr(MLExpr::Return(
+                MLReturn::new(Some(self.owned_value(e))),
+            ))],
+            // the owned arguments are deinitialized after the value is evaluated
+            TypedFunBody::Expr(e) => {
+                let r = TypedExprKind::Return(TypedReturn {
+                    value: Some(Box::new(e)),
+                });
+                let b = TypedBlock {
+                    body: vec![TypedStmt::Expr(TypedExpr::new(
+                        r,
+                        Some(TypedType::noting()),
+                    ))],
+                };
+                self.scope(b, owners).body
+            }
+            TypedFunBody::Block(b) => self.scope(b, owners).body,
+        };
+        self.cleanups = cleanups;
+        self.temporaries = temporaries;
+        MLFunBody { body }
+    }
+
+    /// Name of a temporary, which is unique in the function being lowered, so that
+    /// temporaries of nested expressions do not shadow each other.
+    fn temporary(&mut self, name: &str, type_: MLType) -> MLName {
+        let name = format!("#{}{}", name, self.temporaries);
+        self.temporaries += 1;
+        MLName { name, type_ }
     }
 
     fn block(&mut self, b: TypedBlock) -> MLBlock {
-        MLBlock {
-            body: b.body.into_iter().flat_map(|s| self.stmt(s)).collect(),
+        self.scope(b, vec![])
+    }
+
+    /// Lowers `b` in which `owners` own their values, running its cleanups in reverse order
+    /// when it is left by falling off its end.
+    fn scope(&mut self, b: TypedBlock, owners: Vec<Owner>) -> MLBlock {
+        self.cleanups.push(vec![]);
+        let mut body: Vec<_> = owners.into_iter().map(|o| self.own_value(o)).collect();
+        body.extend(b.body.into_iter().flat_map(|s| self.stmt(s)));
+        let cleanups = self.cleanups.pop().unwrap_or_default();
+        if cleanups.is_empty() {
+            return MLBlock { body };
         }
+        let value = match body.pop() {
+            // already left by `return`, which ran the cleanups
+            Some(MLStmt::Return(r)) => {
+                body.push(MLStmt::Return(r));
+                return MLBlock { body };
+            }
+            Some(MLStmt::Expr(e)) => match e.type_() {
+                // `if` without `else` is typed as `Noting`, but it may fall through
+                MLType::Value(MLValueType::Primitive(MLPrimitiveType::Noting))
+                    if matches!(
+                        e,
+                        MLExpr::If(MLIf {
+                            else_body: None,
+                            ..
+                        })
+                    ) =>
+                {
+                    body.push(MLStmt::Expr(e));
+                    None
+                }
+                MLType::Value(MLValueType::Primitive(MLPrimitiveType::Noting)) => {
+                    body.push(MLStmt::Expr(e));
+                    return MLBlock { body };
+                }
+                MLType::Value(MLValueType::Primitive(MLPrimitiveType::Unit)) => {
+                    body.push(MLStmt::Expr(e));
+                    None
+                }
+                type_ => {
+                    let name = String::from("#defer");
+                    body.push(MLStmt::Var(MLVar {
+                        is_mute: false,
+                        name: name.clone(),
+                        type_: type_.clone(),
+                        value: Self::move_out_of(&cleanups, e),
+                    }));
+                    Some(MLExpr::Name(MLName { name, type_ }))
+                }
+            },
+            Some(s) => {
+                body.push(s);
+                None
+            }
+            None => None,
+        };
+        body.extend(self.cleanups(cleanups.into_iter().rev().collect()));
+        body.extend(value.map(MLStmt::Expr));
+        MLBlock { body }
     }
 
     fn package_name_mangling_(&self, package: &Package, name: &str) -> String {
@@ -917,6 +1561,32 @@ impl<'a> HLIR2MLIR<'a> {
             .join("##")
     }
 
+    /// Mangled name of the member function `name` of the type named `type_name`.
+    fn member_function_name(
+        &self,
+        type_name: &str,
+        name: &str,
+        fun_type: &TypedFunctionType,
+    ) -> String {
+        let mut mangled_name = type_name.to_string() + "::" + name;
+        if !fun_type.arguments.is_empty() {
+            mangled_name += "##";
+            mangled_name += &self.fun_arg_label_type_name_mangling(
+                &fun_type
+                    .arguments
+                    .iter()
+                    .map(|a| TypedArgDef {
+                        label: a.label.to_string(),
+                        name: "".to_string(),
+                        type_: a.typ.clone(),
+                        default: None,
+                    })
+                    .collect(),
+            )
+        }
+        mangled_name
+    }
+
     fn generate_test_harness(&self) -> MLFun {
         MLFun {
             name: "main".to_string(),
@@ -930,7 +1600,7 @@ impl<'a> HLIR2MLIR<'a> {
                         .flat_map(|n| {
                             [
                                 MLStmt::Expr(MLExpr::Call(MLCall {
-                                    target: MLName {
+                                    target: Box::new(MLExpr::Name(MLName {
                                         name: "puts".to_string(),
                                         type_: MLType::Function(MLFunctionType {
                                             arguments: vec![MLValueType::Pointer(Box::new(
@@ -942,7 +1612,7 @@ impl<'a> HLIR2MLIR<'a> {
                                                 MLPrimitiveType::Size,
                                             ),
                                         }),
-                                    },
+                                    })),
                                     args: vec![MLCallArg {
                                         arg: MLExpr::Literal(MLLiteral {
                                             kind: MLLiteralKind::String(n.name.clone()),
@@ -954,7 +1624,7 @@ impl<'a> HLIR2MLIR<'a> {
                                     type_: MLValueType::Primitive(MLPrimitiveType::Unit),
                                 })),
                                 MLStmt::Expr(MLExpr::Call(MLCall {
-                                    target: MLName {
+                                    target: Box::new(MLExpr::Name(MLName {
                                         name: n.name.clone(),
                                         type_: MLType::Function(MLFunctionType {
                                             arguments: n
@@ -964,7 +1634,7 @@ impl<'a> HLIR2MLIR<'a> {
                                                 .collect(),
                                             return_type: n.return_type.clone(),
                                         }),
-                                    },
+                                    })),
                                     args: vec![],
                                     type_: n.return_type.clone(),
                                 })),
@@ -0,0 +1,232 @@
+use crate::lowing::reference_counting::Cleanup;
+use crate::lowing::HLIR2MLIR;
+use wiz_hir::typed_decl::{TypedArgDef, TypedStruct};
+use wiz_hir::typed_type::{TypedPackage, TypedType, TypedValueType};
+use wiz_mir::expr::{
+    MLBlock, MLCall, MLCallArg, MLExpr, MLIf, MLLiteral, MLLiteralKind, MLName, MLUnaryOp,
+    MLUnaryOpKind,
+};
+use wiz_mir::ml_decl::MLVar;
+use wiz_mir::ml_type::{MLPrimitiveType, MLType, MLValueType};
+use wiz_mir::statement::{MLAssignmentStmt, MLStmt};
+
+/// Local binding which owns a struct value, with the `deinit` of the struct.
+#[derive(Debug, Clone)]
+pub(super) struct Owner {
+    pub(super) name: MLName,
+    deinit: MLName,
+}
+
+/// Deinitialization of structs which have `deinit`.
+///
+/// Each local variable and argument of such a struct owns its value together with a flag,
+/// `#live#name`, which is cleared when the value is moved out. The value is deinitialized
+/// when leaving the block of the binding, or when it is replaced by assignment, only if
+/// the flag is still set. The type checker rejects uses after move, so the flag only
+/// matters where a value is moved on some paths.
+impl<'a> HLIR2MLIR<'a> {
+    pub(super) fn has_deinit(&self, t: &TypedType) -> bool {
+        match t {
+            t if t.is_primitive() => false,
+            TypedType::Value(TypedValueType::Value(t)) => match &t.package {
+                TypedPackage::Resolved(package) => self
+                    .arena
+                    .get_type(&package.names, &t.name)
+                    .is_some_and(|rs| {
+                        rs.kind.is_struct() && rs.member_functions.contains_key(TypedStruct::DEINIT)
+                    }),
+                TypedPackage::Raw(_) => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Arguments of a function passed by value which the function owns.
+    pub(super) fn owned_arguments(&self, arg_defs: &[TypedArgDef]) -> Vec<Owner> {
+        arg_defs
+            .iter()
+            .filter(|a| self.has_deinit(&a.type_))
+            .map(|a| {
+                self.owner(
+                    MLName {
+                        name: a.name.clone(),
+                        type_: self.type_(a.type_.clone()),
+                    },
+                    &a.type_,
+                )
+            })
+            .collect()
+    }
+
+    /// `name` bound to a value of `t`, which has `deinit`.
+    pub(super) fn owner(&self, name: MLName, t: &TypedType) -> Owner {
+        Owner {
+            name,
+            deinit: self.deinit_function(t),
+        }
+    }
+
+    /// `deinit` of `t`, named the same way as other member functions.
+    pub(super) fn deinit_function(&self, t: &TypedType) -> MLName {
+        let fun_type = match t {
+            TypedType::Value(TypedValueType::Value(v)) => self
+                .arena
+                .get_type(&v.package.clone().into_resolved().names, &v.name)
+                .and_then(|rs| rs.member_functions.get(TypedStruct::DEINIT).cloned()),
+            _ => None,
+        };
+        match fun_type {
+            Some(TypedType::Function(fun_type)) => MLName {
+                name: self.member_function_name(&t.to_string(), TypedStruct::DEINIT, &fun_type),
+                type_: self.type_(TypedType::Function(fun_type)),
+            },
+            _ => panic!("{:?} has no deinit", t),
+        }
+    }
+
+    /// Makes the local binding of `owner` own its value, which is deinitialized when
+    /// leaving the current block. Returns the declaration of its flag.
+    pub(super) fn own_value(&mut self, owner: Owner) -> MLStmt {
+        let flag = Self::live_flag(&owner.name.name);
+        if let Some(cleanups) = self.cleanups.last_mut() {
+            cleanups.push(Cleanup::Deinit(owner));
+        }
+        MLStmt::Var(MLVar {
+            is_mute: true,
+            name: flag.name,
+            type_: flag.type_,
+            value: Self::bool(true),
+        })
+    }
+
+    /// Whether `name` is a local binding owning its value.
+    pub(super) fn is_owner(&self, name: &str) -> bool {
+        self.owner_of(name).is_some()
+    }
+
+    fn owner_of(&self, name: &str) -> Option<&Owner> {
+        self.cleanups.iter().flatten().find_map(|c| match c {
+            Cleanup::Deinit(o) if o.name.name == name => Some(o),
+            _ => None,
+        })
+    }
+
+    /// `value` moved out of the binding it names, if the binding is an owner.
+    pub(super) fn move_out(&self, value: MLExpr) -> MLExpr {
+        match value {
+            MLExpr::Name(n) if self.is_owner(&n.name) => Self::moved(n),
+            value => value,
+        }
+    }
+
+    /// `value` moved out of the binding it names, if it is one of `cleanups`.
+    pub(super) fn move_out_of(cleanups: &[Cleanup], value: MLExpr) -> MLExpr {
+        match value {
+            MLExpr::Name(n)
+                if cleanups
+                    .iter()
+                    .any(|c| matches!(c, Cleanup::Deinit(o) if o.name.name == n.name)) =>
+            {
+                Self::moved(n)
+            }
+            value => value,
+        }
+    }
+
+    fn moved(name: MLName) -> MLExpr {
+        MLExpr::Block(MLBlock {
+            body: vec![
+                MLStmt::Assignment(MLAssignmentStmt {
+                    target: MLExpr::Name(Self::live_flag(&name.name)),
+                    value: Self::bool(false),
+                }),
+                MLStmt::Expr(MLExpr::Name(name)),
+            ],
+        })
+    }
+
+    /// `target = value` on an owner, which deinitializes the value it held if it is live.
+    pub(super) fn reassign(&mut self, target: MLName, value: MLExpr) -> MLExpr {
+        let owner = match self.owner_of(&target.name) {
+            Some(owner) => owner.clone(),
+            None => unreachable!("{} is not an owner", target.name),
+        };
+        let name = self.temporary("new", value.type_());
+        MLExpr::Block(MLBlock {
+            body: vec![
+                MLStmt::Var(MLVar {
+                    is_mute: false,
+                    name: name.name.clone(),
+                    type_: name.type_.clone(),
+                    value,
+                }),
+                Self::deinit_if_live(owner),
+                MLStmt::Assignment(MLAssignmentStmt {
+                    target: MLExpr::Name(Self::live_flag(&target.name)),
+                    value: Self::bool(true),
+                }),
+                MLStmt::Assignment(MLAssignmentStmt {
+                    target: MLExpr::Name(target),
+                    value: MLExpr::Name(name),
+                }),
+            ],
+        })
+    }
+
+    /// Result of a call discarded by an expression statement, which is deinitialized at once.
+    pub(super) fn discard_value(&mut self, value: MLExpr, deinit: MLName) -> MLExpr {
+        let name = self.temporary("discard", value.type_());
+        MLExpr::Block(MLBlock {
+            body: vec![
+                MLStmt::Var(MLVar {
+                    is_mute: false,
+                    name: name.name.clone(),
+                    type_: name.type_.clone(),
+                    value,
+                }),
+                MLStmt::Expr(Self::deinit(name, deinit)),
+            ],
+        })
+    }
+
+    /// `if #live#name { name.deinit() }`
+    pub(super) fn deinit_if_live(owner: Owner) -> MLStmt {
+        MLStmt::Expr(MLExpr::If(MLIf {
+            condition: Box::new(MLExpr::Name(Self::live_flag(&owner.name.name))),
+            body: MLBlock {
+                body: vec![MLStmt::Expr(Self::deinit(owner.name, owner.deinit))],
+            },
+            else_body: None,
+            type_: MLValueType::Primitive(MLPrimitiveType::Unit),
+        }))
+    }
+
+    fn deinit(name: MLName, deinit: MLName) -> MLExpr {
+        let reference = MLValueType::Reference(Box::new(name.type_.clone()));
+        MLExpr::Call(MLCall {
+            target: Box::new(MLExpr::Name(deinit)),
+            args: vec![MLCallArg {
+                arg: MLExpr::PrimitiveUnaryOp(MLUnaryOp {
+                    target: Box::new(MLExpr::Name(name)),
+                    kind: MLUnaryOpKind::Ref,
+                    type_: reference,
+                }),
+            }],
+            type_: MLValueType::Primitive(MLPrimitiveType::Unit),
+        })
+    }
+
+    fn live_flag(name: &str) -> MLName {
+        MLName {
+            name: format!("#live#{}", name),
+            type_: MLType::Value(MLValueType::Primitive(MLPrimitiveType::Bool)),
+        }
+    }
+
+    fn bool(value: bool) -> MLExpr {
+        MLExpr::Literal(MLLiteral {
+            kind: MLLiteralKind::Boolean(value.to_string()),
+            type_: MLValueType::Primitive(MLPrimitiveType::Bool),
+        })
+    }
+}
@@ -0,0 +1,477 @@
+use crate::lowing::ownership::Owner;
+use crate::lowing::HLIR2MLIR;
+use wiz_hir::typed_decl::TypedStruct;
+use wiz_hir::typed_expr::{TypedExpr, TypedExprKind, TypedPrefixUnaryOperator, TypedUnaryOp};
+use wiz_hir::typed_stmt::TypedBlock;
+use wiz_hir::typed_type::{Package, TypedPackage, TypedType, TypedValueType};
+use wiz_mir::expr::{
+    MLBinOp, MLBinOpKind, MLBlock, MLCall, MLCallArg, MLExpr, MLIf, MLLiteral, MLLiteralKind,
+    MLMember, MLName, MLTypeCast, MLUnaryOp, MLUnaryOpKind,
+};
+use wiz_mir::ml_decl::{MLArgDef, MLField, MLFun, MLFunBody, MLStruct, MLVar};
+use wiz_mir::ml_type::{MLFunctionType, MLPrimitiveType, MLType, MLValueType};
+use wiz_mir::statement::{MLAssignmentStmt, MLReturn, MLStmt};
+
+/// Functions of `std::memory::allocator` which store instances of classes.
+const ALLOCATE_OBJECT: &str = "std::memory::allocator::allocateObject##_#USize";
+const DEALLOCATE_OBJECT: &str = "std::memory::allocator::deallocateObject##_#*UInt8";
+
+/// Hidden first field of classes, which counts references to the instance.
+const REFERENCE_COUNT: &str = "#refCount";
+const NEW: &str = "#new";
+const RETAIN: &str = "#retain";
+const RELEASE: &str = "#release";
+
+/// Work to be done when leaving a block.
+#[derive(Debug, Clone)]
+pub(super) enum Cleanup {
+    /// Block of `defer`.
+    Defer(TypedBlock),
+    /// Release of a local variable holding an instance of a class.
+    Release(MLName),
+    /// Deinitialization of a local binding of a struct with `deinit`, unless it is moved out.
+    Deinit(Owner),
+}
+
+/// Reference counting of instances of classes.
+///
+/// An instance is a reference to a heap allocated struct whose first field counts
+/// references to it. Variables and fields own a reference, arguments borrow one and
+/// results of calls are owned by the caller. The instance is deinitialized and
+/// deallocated when its last reference is released.
+impl<'a> HLIR2MLIR<'a> {
+    pub(super) fn is_class(&self, t: &TypedType) -> bool {
+        match t {
+            TypedType::Value(TypedValueType::Value(t)) => match &t.package {
+                TypedPackage::Resolved(package) => self
+                    .arena
+                    .get_type(&package.names, &t.name)
+                    .is_some_and(|rs| rs.kind.is_class()),
+                TypedPackage::Raw(_) => false,
+            },
+            _ => false,
+        }
+    }
+
+    pub(super) fn class_type(name: &str) -> MLValueType {
+        MLValueType::Reference(Box::new(MLType::Value(MLValueType::Struct(
+            name.to_string(),
+        ))))
+    }
+
+    /// Whether `e` results in a reference the evaluating side has to release.
+    fn is_owned(&self, e: &TypedExpr) -> bool {
+        match &e.kind {
+            TypedExprKind::Call(_) => true,
+            TypedExprKind::Subscript(s) => self.user_defined_subscript(s).is_some(),
+            TypedExprKind::UnaryOp(TypedUnaryOp::Prefix(p)) => {
+                matches!(
+                    p.operator,
+                    TypedPrefixUnaryOperator::Reference | TypedPrefixUnaryOperator::Dereference
+                ) && self.is_owned(&p.target)
+            }
+            _ => false,
+        }
+    }
+
+    /// Lowers `e` to be stored, retaining it unless the reference is already owned.
+    /// A struct value is moved out of the binding owning it.
+    pub(super) fn owned_value(&mut self, e: TypedExpr) -> MLExpr {
+        let is_borrowed = e.ty.as_ref().is_some_and(|t| self.is_class(t)) && !self.is_owned(&e);
+        let value = self.value_expr(e);
+        if !is_borrowed {
+            return self.move_out(value);
+        }
+        let (mut body, value) = match value {
+            MLExpr::Name(n) => (vec![], n),
+            value => {
+                let name = self.temporary("retain", value.type_());
+                (vec![Self::val(&name, value)], name)
+            }
+        };
+        body.push(MLStmt::Expr(Self::retain(MLExpr::Name(value.clone()))));
+        body.push(MLStmt::Expr(MLExpr::Name(value)));
+        MLExpr::Block(MLBlock { body })
+    }
+
+    /// Releases the local variable `name` when leaving the current block.
+    pub(super) fn own(&mut self, name: MLName) {
+        if let Some(cleanups) = self.cleanups.last_mut() {
+            cleanups.push(Cleanup::Release(name));
+        }
+    }
+
+    /// Expression statement whose value is an instance of a class.
+    pub(super) fn discard(&mut self, e: TypedExpr) -> MLExpr {
+        if !self.is_owned(&e) {
+            return self.expr(e);
+        }
+        let value = self.expr(e);
+        let name = self.temporary("discard", value.type_());
+        MLExpr::Block(MLBlock {
+            body: vec![
+                Self::val(&name, value),
+                MLStmt::Expr(Self::release(MLExpr::Name(name))),
+            ],
+        })
+    }
+
+    /// `target = value` on a class, which releases the instance `target` held before.
+    pub(super) fn replace(&mut self, target: TypedExpr, value: TypedExpr) -> MLExpr {
+        let value = self.owned_value(value);
+        let target = self.expr(target);
+        let name = self.temporary("new", value.type_());
+        MLExpr::Block(MLBlock {
+            body: vec![
+                Self::val(&name, value),
+                MLStmt::Expr(Self::release(target.clone())),
+                MLStmt::Assignment(MLAssignmentStmt {
+                    target,
+                    value: MLExpr::Name(name),
+                }),
+            ],
+        })
+    }
+
+    /// Argument of a call, which is stored to a temporary declared before the call and
+    /// released after it if the reference is owned.
+    pub(super) fn argument(
+        &mut self,
+        e: TypedExpr,
+        temporaries: &mut Vec<(MLName, MLExpr)>,
+    ) -> MLExpr {
+        let is_owned = e.ty.as_ref().is_some_and(|t| self.is_class(t)) && self.is_owned(&e);
+        let value = self.value_expr(e);
+        if !is_owned {
+            return value;
+        }
+        let name = self.temporary("arg", value.type_());
+        temporaries.push((name.clone(), value));
+        MLExpr::Name(name)
+    }
+
+    pub(super) fn release_temporaries(
+        &mut self,
+        call: MLExpr,
+        temporaries: Vec<(MLName, MLExpr)>,
+    ) -> MLExpr {
+        if temporaries.is_empty() {
+            return call;
+        }
+        let mut body: Vec<_> = temporaries
+            .iter()
+            .map(|(name, value)| Self::val(name, value.clone()))
+            .collect();
+        let releases = temporaries
+            .into_iter()
+            .map(|(name, _)| MLStmt::Expr(Self::release(MLExpr::Name(name))));
+        match call.type_() {
+            MLType::Value(MLValueType::Primitive(
+                MLPrimitiveType::Unit | MLPrimitiveType::Noting,
+            )) => {
+                body.push(MLStmt::Expr(call));
+                body.extend(releases);
+            }
+            type_ => {
+                let name = self.temporary("result", type_);
+                body.push(Self::val(&name, call));
+                body.extend(releases);
+                body.push(MLStmt::Expr(MLExpr::Name(name)));
+            }
+        }
+        MLExpr::Block(MLBlock { body })
+    }
+
+    pub(super) fn zero_reference_count() -> (String, MLExpr) {
+        (REFERENCE_COUNT.to_string(), Self::reference_count(0))
+    }
+
+    /// Moves the struct `value` to the heap, which results in an instance referenced once.
+    pub(super) fn new_instance(value: MLLiteral) -> MLExpr {
+        let name = match &value.type_ {
+            MLValueType::Struct(name) => name.clone(),
+            t => panic!("{:?} is not a class", t),
+        };
+        Self::class_call(
+            &name,
+            NEW,
+            vec![MLExpr::Literal(value)],
+            Self::class_type(&name),
+        )
+    }
+
+    fn retain(instance: MLExpr) -> MLExpr {
+        let name = Self::class_name(&instance);
+        Self::class_call(&name, RETAIN, vec![instance], Self::unit())
+    }
+
+    pub(super) fn release(instance: MLExpr) -> MLExpr {
+        let name = Self::class_name(&instance);
+        Self::class_call(&name, RELEASE, vec![instance], Self::unit())
+    }
+
+    /// Lowers class `c`, whose struct starts with the reference count, with the functions
+    /// which allocate, retain and release its instances.
+    pub(super) fn class(&mut self, c: TypedStruct, package: Package) -> (MLStruct, Vec<MLFun>) {
+        let deinit = c
+            .member_functions
+            .iter()
+            .find(|f| f.name == TypedStruct::DEINIT)
+            .map(|f| self.fun_arg_label_type_name_mangling(&f.arg_defs));
+        let class_fields: Vec<_> = c
+            .stored_properties
+            .iter()
+            .filter(|p| self.is_class(&p.type_))
+            .map(|p| (p.name.clone(), self.type_(p.type_.clone())))
+            .collect();
+        let (mut struct_, mut functions) = self.struct_(c, package);
+        struct_.fields.insert(
+            0,
+            MLField {
+                name: REFERENCE_COUNT.to_string(),
+                type_: MLValueType::Primitive(MLPrimitiveType::UInt64),
+            },
+        );
+        let name = struct_.name.clone();
+        let instance = MLExpr::Name(MLName {
+            name: "self".to_string(),
+            type_: MLType::Value(Self::class_type(&name)),
+        });
+        let deinit = deinit.map(|mangled_name| {
+            MLStmt::Expr(Self::class_call(
+                &name,
+                &format!("{}##{}", TypedStruct::DEINIT, mangled_name),
+                vec![instance.clone()],
+                Self::unit(),
+            ))
+        });
+        let release_fields = class_fields.into_iter().map(|(field, type_)| {
+            MLStmt::Expr(Self::release(MLExpr::Member(MLMember {
+                target: Box::new(instance.clone()),
+                name: field,
+                type_,
+            })))
+        });
+        let deallocate = MLStmt::Expr(Self::function_call(
+            DEALLOCATE_OBJECT,
+            vec![MLExpr::PrimitiveTypeCast(MLTypeCast {
+                target: Box::new(instance.clone()),
+                type_: MLValueType::Pointer(Box::new(MLType::Value(MLValueType::Primitive(
+                    MLPrimitiveType::UInt8,
+                )))),
+                location: None,
+            })],
+            Self::unit(),
+        ));
+        let destroy = deinit
+            .into_iter()
+            .chain(release_fields)
+            .chain(std::iter::once(deallocate))
+            .collect();
+        functions.extend([
+            Self::new_function(&name),
+            Self::retain_function(&name),
+            Self::release_function(&name, destroy),
+        ]);
+        (struct_, functions)
+    }
+
+    /// `fun #new(_ value: A): A` storing `value` to newly allocated memory.
+    fn new_function(name: &str) -> MLFun {
+        let class_type = Self::class_type(name);
+        let struct_type = MLValueType::Struct(name.to_string());
+        let instance = MLName {
+            name: "self".to_string(),
+            type_: MLType::Value(class_type.clone()),
+        };
+        let allocate = Self::function_call(
+            ALLOCATE_OBJECT,
+            vec![MLExpr::SizeOf(MLType::Value(struct_type.clone()))],
+            MLValueType::Pointer(Box::new(MLType::Value(MLValueType::Primitive(
+                MLPrimitiveType::UInt8,
+            )))),
+        );
+        MLFun {
+            name: format!("{}::{}", name, NEW),
+            arg_defs: vec![MLArgDef {
+                name: "value".to_string(),
+                type_: struct_type.clone(),
+            }],
+            return_type: class_type.clone(),
+            body: Some(MLFunBody {
+                body: vec![
+                    Self::val(
+                        &instance,
+                        MLExpr::PrimitiveTypeCast(MLTypeCast {
+                            target: Box::new(allocate),
+                            type_: class_type,
+                            location: None,
+                        }),
+                    ),
+                    MLStmt::Assignment(MLAssignmentStmt {
+                        target: MLExpr::PrimitiveUnaryOp(MLUnaryOp {
+                            target: Box::new(MLExpr::Name(instance.clone())),
+                            kind: MLUnaryOpKind::DeRef,
+                            type_: struct_type.clone(),
+                        }),
+                        value: MLExpr::Name(MLName {
+                            name: "value".to_string(),
+                            type_: MLType::Value(struct_type),
+                        }),
+                    }),
+                    MLStmt::Assignment(MLAssignmentStmt {
+                        target: Self::reference_count_of(&instance),
+                        value: Self::reference_count(1),
+                    }),
+                    MLStmt::Expr(MLExpr::Return(MLReturn::new(Some(MLExpr::Name(instance))))),
+                ],
+            }),
+        }
+    }
+
+    /// `fun #retain(_ self: A)` counting up references to `self`.
+    fn retain_function(name: &str) -> MLFun {
+        let instance = Self::self_name(name);
+        MLFun {
+            name: format!("{}::{}", name, RETAIN),
+            arg_defs: vec![Self::self_arg_def(name)],
+            return_type: Self::unit(),
+            body: Some(MLFunBody {
+                body: vec![Self::count(&instance, MLBinOpKind::Plus)],
+            }),
+        }
+    }
+
+    /// `fun #release(_ self: A)` counting down references to `self`, which runs `destroy`
+    /// when no references remain. Releasing zero-initialized fields does nothing.
+    fn release_function(name: &str, destroy: Vec<MLStmt>) -> MLFun {
+        let instance = Self::self_name(name);
+        let usize = MLValueType::Primitive(MLPrimitiveType::USize);
+        let bool = MLValueType::Primitive(MLPrimitiveType::Bool);
+        let is_not_null = MLExpr::PrimitiveBinOp(MLBinOp {
+            left: Box::new(MLExpr::PrimitiveTypeCast(MLTypeCast {
+                target: Box::new(MLExpr::Name(instance.clone())),
+                type_: usize,
+                location: None,
+            })),
+            kind: MLBinOpKind::NotEqual,
+            right: Box::new(Self::usize_literal(0)),
+            type_: bool.clone(),
+            location: None,
+        });
+        let is_unreferenced = MLExpr::PrimitiveBinOp(MLBinOp {
+            left: Box::new(Self::reference_count_of(&instance)),
+            kind: MLBinOpKind::Equal,
+            right: Box::new(Self::reference_count(0)),
+            type_: bool,
+            location: None,
+        });
+        MLFun {
+            name: format!("{}::{}", name, RELEASE),
+            arg_defs: vec![Self::self_arg_def(name)],
+            return_type: Self::unit(),
+            body: Some(MLFunBody {
+                body: vec![MLStmt::Expr(MLExpr::If(MLIf {
+                    condition: Box::new(is_not_null),
+                    body: MLBlock {
+                        body: vec![
+                            Self::count(&instance, MLBinOpKind::Minus),
+                            MLStmt::Expr(MLExpr::If(MLIf {
+                                condition: Box::new(is_unreferenced),
+                                body: MLBlock { body: destroy },
+                                else_body: None,
+                                type_: Self::unit(),
+                            })),
+                        ],
+                    },
+                    else_body: None,
+                    type_: Self::unit(),
+                }))],
+            }),
+        }
+    }
+
+    /// `self.#refCount = self.#refCount <op> 1`
+    fn count(instance: &MLName, kind: MLBinOpKind) -> MLStmt {
+        MLStmt::Assignment(MLAssignmentStmt {
+            target: Self::reference_count_of(instance),
+            value: MLExpr::PrimitiveBinOp(MLBinOp {
+                left: Box::new(Self::reference_count_of(instance)),
+                kind,
+                right: Box::new(Self::reference_count(1)),
+                type_: MLValueType::Primitive(MLPrimitiveType::UInt64),
+                location: None,
+            }),
+        })
+    }
+
+    fn reference_count_of(instance: &MLName) -> MLExpr {
+        MLExpr::Member(MLMember {
+            target: Box::new(MLExpr::Name(instance.clone())),
+            name: REFERENCE_COUNT.to_string(),
+            type_: MLType::Value(MLValueType::Primitive(MLPrimitiveType::UInt64)),
+        })
+    }
+
+    fn reference_count(value: u64) -> MLExpr {
+        MLExpr::Literal(MLLiteral {
+            kind: MLLiteralKind::Integer(value.to_string()),
+            type_: MLValueType::Primitive(MLPrimitiveType::UInt64),
+        })
+    }
+
+    fn self_name(name: &str) -> MLName {
+        MLName {
+            name: "self".to_string(),
+            type_: MLType::Value(Self::class_type(name)),
+        }
+    }
+
+    fn self_arg_def(name: &str) -> MLArgDef {
+        MLArgDef {
+            name: "self".to_string(),
+            type_: Self::class_type(name),
+        }
+    }
+
+    fn class_name(instance: &MLExpr) -> String {
+        match instance.type_() {
+            MLType::Value(MLValueType::Reference(t)) => match *t {
+                MLType::Value(MLValueType::Struct(name)) => name,
+                t => panic!("{:?} is not a class", t),
+            },
+            t => panic!("{:?} is not a class", t),
+        }
+    }
+
+    fn class_call(name: &str, function: &str, args: Vec<MLExpr>, type_: MLValueType) -> MLExpr {
+        Self::function_call(&format!("{}::{}", name, function), args, type_)
+    }
+
+    fn function_call(name: &str, args: Vec<MLExpr>, type_: MLValueType) -> MLExpr {
+        MLExpr::Call(MLCall {
+            target: Box::new(MLExpr::Name(MLName {
+                name: name.to_string(),
+                type_: MLType::Function(MLFunctionType {
+                    arguments: args.iter().map(|a| a.type_().into_value_type()).collect(),
+                    return_type: type_.clone(),
+                }),
+            })),
+            args: args.into_iter().map(|arg| MLCallArg { arg }).collect(),
+            type_,
+        })
+    }
+
+    fn val(name: &MLName, value: MLExpr) -> MLStmt {
+        MLStmt::Var(MLVar {
+            is_mute: false,
+            name: name.name.clone(),
+            type_: name.type_.clone(),
+            value,
+        })
+    }
+
+    fn unit() -> MLValueType {
+        MLValueType::Primitive(MLPrimitiveType::Unit)
+    }
+}